@@ -0,0 +1,50 @@
+//! The attachment table printed by the `list_files` and `extract_files`
+//! examples.
+
+use extractembedfilepdf::EmbeddedFileMetadata;
+
+const NAME_WIDTH: usize = 32;
+const MIME_WIDTH: usize = 24;
+const RULE_WIDTH: usize = NAME_WIDTH + MIME_WIDTH + 46;
+
+/// Print one line per attachment, given as filename, size column and
+/// metadata, followed by a totals line with the file count and `total`.
+pub fn print_table<'a>(
+    rows: impl IntoIterator<Item = (&'a str, String, &'a EmbeddedFileMetadata)>,
+    total: &str,
+) {
+    println!(
+        "{:<NAME_WIDTH$}  {:>10}  {:<MIME_WIDTH$}  {:<12}  {:<10}  {:>4}",
+        "NAME", "SIZE", "MIME", "RELATIONSHIP", "SOURCE", "PAGE"
+    );
+    println!("{}", "-".repeat(RULE_WIDTH));
+
+    let mut count = 0;
+    for (name, size, meta) in rows {
+        println!(
+            "{:<NAME_WIDTH$}  {:>10}  {:<MIME_WIDTH$}  {:<12}  {:<10}  {:>4}",
+            truncate(name, NAME_WIDTH),
+            size,
+            truncate(meta.mime_type.as_deref().unwrap_or("-"), MIME_WIDTH),
+            meta.af_relationship
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |r| r.to_string()),
+            meta.source.map_or("-", |s| s.as_str()),
+            meta.page.map_or_else(|| "-".to_string(), |p| p.to_string()),
+        );
+        count += 1;
+    }
+
+    println!("{}", "-".repeat(RULE_WIDTH));
+    println!("{:<NAME_WIDTH$}  {:>10}", format!("{count} file(s)"), total);
+}
+
+/// Shorten `s` to at most `width` characters, marking the cut with `…`.
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else {
+        let head: String = s.chars().take(width - 1).collect();
+        format!("{head}…")
+    }
+}
@@ -0,0 +1,67 @@
+//! CLI that compares the embedded files of two PDFs by content hash and name.
+//!
+//! Usage:
+//!   cargo run --example diff_files -- old.pdf new.pdf
+//!
+//! Exits with status 0 when the attachments are identical, 1 when they differ,
+//! and 2 on error — the same convention as `diff(1)`.
+
+use extractembedfilepdf::{compare, PdfAnalyzer};
+use std::{env, process};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 3 {
+        eprintln!("Usage: {} <old.pdf> <new.pdf>", args[0]);
+        process::exit(2);
+    }
+
+    let load = |path: &str| {
+        PdfAnalyzer::from_path(path).unwrap_or_else(|e| {
+            eprintln!("Cannot load {path}: {e}");
+            process::exit(2);
+        })
+    };
+    let old = load(&args[1]);
+    let new = load(&args[2]);
+
+    let diff = compare::diff(&old, &new).unwrap_or_else(|e| {
+        eprintln!("Cannot compare attachments: {e}");
+        process::exit(2);
+    });
+
+    for name in &diff.removed {
+        println!("- {name}");
+    }
+    for name in &diff.added {
+        println!("+ {name}");
+    }
+    for change in &diff.changed {
+        println!(
+            "~ {}  {} → {}",
+            change.filename,
+            &change.old_sha256[..12],
+            &change.new_sha256[..12]
+        );
+    }
+    for rename in &diff.renamed {
+        println!("> {} → {}", rename.old_filename, rename.new_filename);
+    }
+    for name in &diff.unchanged {
+        println!("= {name}");
+    }
+
+    println!(
+        "\n{} added, {} removed, {} changed, {} renamed, {} unchanged",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.renamed.len(),
+        diff.unchanged.len()
+    );
+
+    if !diff.is_identical() {
+        process::exit(1);
+    }
+}
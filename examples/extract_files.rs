@@ -3,37 +3,212 @@
 //! Usage:
 //!   cargo run --example extract_files -- invoice.pdf
 //!   cargo run --example extract_files -- invoice.pdf ./output
+//!   cargo run --example extract_files -- invoice.pdf --tar - | tar -x -C dest
+//!   cargo run --example extract_files -- invoice.pdf --tar attachments.tar
+//!   cargo run --example extract_files -- invoice.pdf --verify-checksums [--warn-only]
+//!   cargo run --example extract_files -- invoice.pdf ./out --name invoice.xml --name '*.csv'
+//!   cargo run --example extract_files -- invoice.pdf --junit pdfa3-report.xml
+//!
+//! With `--tar`, the attachments are packed into a tar archive instead of an
+//! output directory, so the two cannot be given together. Each one is decoded,
+//! verified if requested, and written to the archive before the next is
+//! decoded, so memory use stays at about one attachment. A failed check stops
+//! the run and leaves the archive without its end marker, which `tar` reports
+//! as truncated. Of several attachments with the same name only the first is
+//! archived. When the archive goes to stdout (`--tar -`), all status output is
+//! sent to stderr instead.
+//!
+//! With `--verify-checksums`, each file is compared against its declared
+//! `/Params/CheckSum` (MD5) and `/Params/Size`. Any mismatch makes the run exit
+//! with status 2 unless `--warn-only` is also given.
+//!
+//! `--name` may be repeated and accepts glob patterns; only matching
+//! attachments are decoded and written. `--source` (repeatable) restricts
+//! discovery to `names-tree`, `annotations`, `af` or `deep-scan`.
+//! `--allow-pageless` accepts attachment containers that have no pages.
+//! `--recover-truncated` saves what can be decoded from damaged streams, and
+//! `--fail-on-any-error` aborts instead of skipping attachments that cannot
+//! be extracted. `--max-duration <secs>` gives up on documents that take
+//! longer than that to process.
+//!
+//! `--junit <file>` also writes the validation results as JUnit XML, one test
+//! case per check and attachment, for CI pipelines.
+
+use extractembedfilepdf::{
+    format_bytes, AttachmentSource, EmbeddedFile, ExtractionReport, ExtractorConfig, PdfAnalyzer,
+    TarWriter, WarningKind,
+};
+use std::io::{self, Write};
+use std::time::Duration;
+use std::{env, fs, process};
+
+mod common;
 
-use extractembedfilepdf::{ExtractorConfig, PdfAnalyzer};
-use std::{env, process};
+/// Prints a status line to stdout, or to stderr when stdout carries the tar stream.
+macro_rules! status {
+    ($to_stderr:expr, $($arg:tt)*) => {
+        if $to_stderr {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Command-line options for this example.
+struct Options {
+    pdf_path: String,
+    output_dir: Option<String>,
+    tar_dest: Option<String>,
+    names: Vec<glob::Pattern>,
+    sources: Vec<AttachmentSource>,
+    verify_checksums: bool,
+    warn_only: bool,
+    allow_pageless: bool,
+    recover_truncated: bool,
+    fail_on_any_error: bool,
+    max_duration: Option<Duration>,
+    junit: Option<String>,
+}
+
+impl Options {
+    /// Parse `args` (including the program name); `None` on a usage error.
+    fn parse(args: &[String]) -> Option<Self> {
+        let mut positional = Vec::new();
+        let mut tar_dest = None;
+        let mut names = Vec::new();
+        let mut sources = Vec::new();
+        let mut verify_checksums = false;
+        let mut warn_only = false;
+        let mut allow_pageless = false;
+        let mut recover_truncated = false;
+        let mut fail_on_any_error = false;
+        let mut max_duration = None;
+        let mut junit = None;
+
+        let mut iter = args.iter().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--tar" => tar_dest = Some(iter.next()?.clone()),
+                "--junit" => junit = Some(iter.next()?.clone()),
+                "--name" => {
+                    let pattern = iter.next()?;
+                    match glob::Pattern::new(pattern) {
+                        Ok(p) => names.push(p),
+                        Err(e) => {
+                            eprintln!("Invalid --name pattern '{pattern}': {e}");
+                            return None;
+                        }
+                    }
+                }
+                "--source" => match iter.next()?.parse() {
+                    Ok(source) => sources.push(source),
+                    Err(e) => {
+                        eprintln!("{e}");
+                        return None;
+                    }
+                },
+                "--verify-checksums" => verify_checksums = true,
+                "--warn-only" => warn_only = true,
+                "--allow-pageless" => allow_pageless = true,
+                "--recover-truncated" => recover_truncated = true,
+                "--fail-on-any-error" => fail_on_any_error = true,
+                "--max-duration" => {
+                    let secs = iter.next()?;
+                    match secs.parse().map(Duration::try_from_secs_f64) {
+                        Ok(Ok(limit)) => max_duration = Some(limit),
+                        _ => {
+                            eprintln!("Invalid --max-duration '{secs}'");
+                            return None;
+                        }
+                    }
+                }
+                flag if flag.starts_with("--") => return None,
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let pdf_path = positional.next()?;
+        let output_dir = positional.next();
+        if positional.next().is_some() {
+            return None;
+        }
+        if output_dir.is_some() && tar_dest.is_some() {
+            eprintln!("--tar cannot be combined with an output directory");
+            return None;
+        }
+
+        Some(Self {
+            pdf_path,
+            output_dir,
+            tar_dest,
+            names,
+            sources,
+            verify_checksums,
+            warn_only,
+            allow_pageless,
+            recover_truncated,
+            fail_on_any_error,
+            max_duration,
+            junit,
+        })
+    }
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() < 2 {
-        eprintln!("Usage: {} <pdf_file> [output_dir]", args[0]);
+    let Some(opts) = Options::parse(&args) else {
+        eprintln!(
+            "Usage: {} <pdf_file> [output_dir] [--tar <archive|->] \
+             [--name <glob>]... [--source <source>]... \
+             [--verify-checksums [--warn-only]] [--allow-pageless] \
+             [--recover-truncated] [--fail-on-any-error] [--max-duration <secs>] \
+             [--junit <file>]",
+            args[0]
+        );
         process::exit(1);
-    }
+    };
 
-    let pdf_path = &args[1];
-    let output_dir = args.get(2).map(String::as_str);
+    let pdf_path = opts.pdf_path.as_str();
+    let output_dir = opts.output_dir.as_deref();
+    let quiet_stdout = opts.tar_dest.as_deref() == Some("-");
+    let verify_checksums = opts.verify_checksums;
 
+    // When verifying, write files ourselves only after the checks pass. The
+    // JUnit report analyses every attachment, so with --junit we write them
+    // ourselves as well, to save only those selected with --name.
+    let library_writes = output_dir.is_some() && !verify_checksums && opts.junit.is_none();
     let config = ExtractorConfig {
-        extract_to_disk: output_dir.is_some(),
+        extract_to_disk: library_writes,
         output_directory: output_dir.map(str::to_owned),
+        discovery_sources: (!opts.sources.is_empty()).then(|| opts.sources.clone()),
+        allow_pageless: opts.allow_pageless,
+        recover_truncated_streams: opts.recover_truncated,
+        fail_on_any_error: opts.fail_on_any_error,
+        max_duration: opts.max_duration,
         ..Default::default()
     };
 
-    println!("Analysing: {pdf_path}");
+    status!(quiet_stdout, "Analysing: {pdf_path}");
 
     let analyzer = PdfAnalyzer::with_config(pdf_path, config).unwrap_or_else(|e| {
         eprintln!("Error loading PDF: {e}");
         process::exit(1);
     });
 
+    // Write the JUnit report before any check below can exit; its extraction
+    // is reused in step 4.
+    let analysed = opts.junit.as_ref().map(|dest| {
+        let files = write_junit(&analyzer, pdf_path, dest);
+        status!(quiet_stdout, "✓ Wrote JUnit report to {dest}");
+        files
+    });
+
     // 1. Is it a valid PDF?
     match analyzer.is_pdf() {
-        Ok(true) => println!("✓ Valid PDF"),
+        Ok(true) => status!(quiet_stdout, "✓ Valid PDF"),
         Ok(false) | Err(_) => {
             eprintln!("✗ Not a valid PDF");
             process::exit(1);
@@ -46,45 +221,317 @@ fn main() {
             let level = analyzer
                 .conformance_level()
                 .unwrap_or_else(|| "PDF/A-3".into());
-            println!("✓ {level}");
+            status!(quiet_stdout, "✓ {level}");
         }
-        Ok(false) => println!("⚠ Not PDF/A-3 (proceeding anyway)"),
-        Err(e) => println!("⚠ PDF/A-3 check failed: {e}"),
+        Ok(false) => status!(quiet_stdout, "⚠ Not PDF/A-3 (proceeding anyway)"),
+        Err(e) => status!(quiet_stdout, "⚠ PDF/A-3 check failed: {e}"),
     }
 
     // 3. Are there embedded files?
-    let count = analyzer.count_embedded_files().unwrap_or(0);
+    let count = analyzer.count_embedded_files().unwrap_or_else(|e| {
+        eprintln!("Error discovering embedded files: {e}");
+        process::exit(1);
+    });
     if count == 0 {
-        println!("  No embedded files found.");
+        status!(quiet_stdout, "  No embedded files found.");
         process::exit(0);
     }
-    println!("✓ {count} embedded file(s)");
+    status!(quiet_stdout, "✓ {count} embedded file(s)");
+
+    // 4a. With --tar, decode, verify and archive one attachment at a time.
+    if let Some(dest) = &opts.tar_dest {
+        let selected =
+            |name: &str| opts.names.is_empty() || opts.names.iter().any(|p| p.matches(name));
+        let written = match analysed {
+            Some(report) => {
+                let files = report.files.into_iter().filter(|f| selected(&f.filename));
+                write_tar(dest, files.map(|f| (f.filename.clone(), Ok(f))), &opts)
+            }
+            None => {
+                let handles = analyzer.embedded_file_handles().unwrap_or_else(|e| {
+                    eprintln!("Cannot list embedded files: {e}");
+                    process::exit(1);
+                });
+                let handles = handles.into_iter().filter(|h| selected(h.filename()));
+                let files = handles.map(|h| (h.filename().to_owned(), h.decode()));
+                write_tar(dest, files, &opts)
+            }
+        };
+        match written {
+            Ok(0) => {
+                eprintln!("✗ No embedded file could be extracted");
+                process::exit(1);
+            }
+            Ok(count) => status!(quiet_stdout, "✓ Wrote {count} file(s) to tar archive"),
+            Err(e) => {
+                eprintln!("✗ Writing tar archive failed: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // 4. Extract them — all of them, or only those selected with --name.
+    // Files the library kept off the disk, as another file has their name,
+    // are not counted as saved.
+    let mut not_written = 0;
+    let files = if opts.names.is_empty() {
+        let report = match analysed {
+            Some(report) => report,
+            None => analyzer.extract_with_report().unwrap_or_else(|e| {
+                eprintln!("Extraction error: {e}");
+                process::exit(1);
+            }),
+        };
+        for warning in &report.warnings {
+            match warning.kind {
+                WarningKind::Skipped => {
+                    eprintln!("⚠ Skipped {}: {}", warning.name, warning.message)
+                }
+                WarningKind::DuplicateName if library_writes => {
+                    eprintln!("⚠ Not saved {}: {}", warning.name, warning.message);
+                    not_written += 1;
+                }
+                _ => {}
+            }
+        }
+        if report.files.is_empty() {
+            eprintln!("✗ No embedded file could be extracted");
+            process::exit(1);
+        }
+        report.files
+    } else {
+        extract_selected(&analyzer, &opts.names, analysed, quiet_stdout)
+    };
+
+    // 5. Optionally verify integrity against the declared checksum and size.
+    let integrity_ok = !verify_checksums || verify_integrity(&files, quiet_stdout);
+    if !integrity_ok && !opts.warn_only {
+        eprintln!("✗ Integrity check failed");
+        process::exit(2);
+    }
+
+    // 6. Save the files and list them as a table, with any problems below it.
+    let save_dir = output_dir.unwrap_or(".");
+    let mut saved = files.len() - not_written;
+    if !library_writes {
+        // config.extract_to_disk was false, so save manually
+        for file in &files {
+            if let Err(e) = file.save_to_disk(save_dir) {
+                eprintln!("✗ {}: save failed: {e}", file.filename);
+                saved -= 1;
+            }
+        }
+    }
+
+    println!();
+    let total = format_bytes(files.iter().map(EmbeddedFile::len).sum());
+    let rows = files
+        .iter()
+        .map(|f| (f.filename.as_str(), format_bytes(f.len()), &f.metadata));
+    common::print_table(rows, &total);
+    for file in &files {
+        for problem in problems(file) {
+            eprintln!("⚠ {}: {problem}", file.filename);
+        }
+    }
+    println!("✓ Saved {saved} file(s) to {save_dir}");
+}
+
+/// Everything about `file` that was not extracted as declared.
+fn problems(file: &EmbeddedFile) -> Vec<String> {
+    let meta = &file.metadata;
+    let mut problems = Vec::new();
+    if meta.truncated {
+        problems.push(format!(
+            "stream is damaged; only the first {} bytes were recovered",
+            file.len()
+        ));
+    }
+    if !meta.undecoded_filters.is_empty() {
+        problems.push(format!(
+            "still encoded with {}; saved as raw stream data",
+            meta.undecoded_filters.join(", ")
+        ));
+    }
+    if let Some(mismatch) = meta.size_mismatch {
+        problems.push(format!(
+            "declared size is {} bytes but {} were decoded",
+            mismatch.declared, mismatch.actual
+        ));
+    }
+    if meta.encrypted_container {
+        problems.push("file is itself encrypted; its content cannot be inspected".into());
+    }
+    problems
+}
 
-    // 4. Extract them.
-    let files = analyzer.extract_embedded_files().unwrap_or_else(|e| {
-        eprintln!("Extraction error: {e}");
+/// Extract only the attachments whose filename matches one of `patterns`,
+/// decoding each one individually unless `analysed` already holds them.
+/// Exits if nothing matches.
+fn extract_selected(
+    analyzer: &PdfAnalyzer,
+    patterns: &[glob::Pattern],
+    analysed: Option<ExtractionReport>,
+    quiet_stdout: bool,
+) -> Vec<EmbeddedFile> {
+    let names = analyzer.embedded_file_names().unwrap_or_else(|e| {
+        eprintln!("Cannot list embedded files: {e}");
         process::exit(1);
     });
 
-    let save_dir = output_dir.unwrap_or(".");
-    for (i, file) in files.iter().enumerate() {
-        println!("\n  File #{}", i + 1);
-        println!("    Name : {}", file.filename);
-        println!("    Size : {} bytes", file.data.len());
-        if let Some(ref mime) = file.metadata.mime_type {
-            println!("    MIME : {mime}");
-        }
-        if let Some(ref date) = file.metadata.modification_date {
-            println!("    Date : {date}");
-        }
-        if output_dir.is_none() {
-            // config.extract_to_disk was false, so save manually
-            match file.save_to_disk(save_dir) {
-                Ok(_) => println!("    ✓ Saved to {save_dir}/{}", file.filename),
-                Err(e) => eprintln!("    ✗ Save failed: {e}"),
+    for pattern in patterns {
+        if !names.iter().any(|n| pattern.matches(n)) {
+            eprintln!("⚠ No embedded file matches '{pattern}'");
+        }
+    }
+
+    let mut selected: Vec<&String> = Vec::new();
+    for name in names
+        .iter()
+        .filter(|n| patterns.iter().any(|p| p.matches(n)))
+    {
+        if !selected.contains(&name) {
+            selected.push(name);
+        }
+    }
+
+    if selected.is_empty() {
+        eprintln!("✗ No embedded file matched the requested names");
+        process::exit(1);
+    }
+    status!(quiet_stdout, "✓ {} file(s) selected", selected.len());
+
+    // Pick the selected files from an extraction that already ran, the first
+    // of each name like `extract_embedded_file`.
+    if let Some(report) = analysed {
+        let mut files: Vec<EmbeddedFile> = Vec::new();
+        for file in report.files {
+            if selected.contains(&&file.filename)
+                && !files.iter().any(|f| f.filename == file.filename)
+            {
+                files.push(file);
+            }
+        }
+        for warning in &report.warnings {
+            if warning.kind == WarningKind::Skipped && selected.contains(&&warning.name) {
+                eprintln!("✗ {}: {}", warning.name, warning.message);
+            }
+        }
+        return files;
+    }
+
+    selected
+        .into_iter()
+        .filter_map(|name| match analyzer.extract_embedded_file(name) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("✗ {name}: {e}");
+                None
             }
+        })
+        .collect()
+}
+
+/// Check every file against its declared `/Params/CheckSum` and `/Params/Size`,
+/// printing one line per file. Returns `false` if any file failed a check.
+fn verify_integrity(files: &[EmbeddedFile], quiet_stdout: bool) -> bool {
+    let mut all_ok = true;
+    for file in files {
+        let mut problems = Vec::new();
+
+        if file.verify_checksum() == Some(false) {
+            problems.push(format!(
+                "checksum mismatch (declared {}, actual {})",
+                file.metadata.checksum.as_deref().unwrap_or_default(),
+                file.md5_hex()
+            ));
+        }
+        if file.size_matches_declaration() == Some(false) {
+            problems.push(format!(
+                "size mismatch (declared {}, actual {})",
+                file.declared_size().unwrap_or_default(),
+                file.len()
+            ));
+        }
+
+        if problems.is_empty() {
+            let what = match (
+                file.metadata.checksum.is_some(),
+                file.declared_size().is_some(),
+            ) {
+                (false, false) => "nothing declared to verify",
+                _ => "ok",
+            };
+            status!(quiet_stdout, "  ✓ {}: {what}", file.filename);
         } else {
-            println!("    ✓ Saved to {save_dir}/{}", file.filename);
+            all_ok = false;
+            eprintln!("  ✗ {}: {}", file.filename, problems.join("; "));
+        }
+    }
+    all_ok
+}
+
+/// Write the analysis of `analyzer` as a JUnit XML report to `dest`, and
+/// return the extraction it ran.
+fn write_junit(analyzer: &PdfAnalyzer, pdf_path: &str, dest: &str) -> ExtractionReport {
+    let written = analyzer
+        .analyze()
+        .map_err(|e| e.to_string())
+        .and_then(|report| {
+            fs::write(dest, report.to_junit_xml(pdf_path)).map_err(|e| e.to_string())?;
+            Ok(ExtractionReport {
+                files: report.files,
+                warnings: report.warnings,
+                statistics: report.statistics,
+                ..Default::default()
+            })
+        });
+    written.unwrap_or_else(|e| {
+        eprintln!("✗ Writing JUnit report failed: {e}");
+        process::exit(1);
+    })
+}
+
+/// Write `files` as a tar archive to `dest`, where `-` means stdout, one at a
+/// time as the iterator yields them. Returns the number of files archived.
+fn write_tar(
+    dest: &str,
+    files: impl Iterator<Item = (String, extractembedfilepdf::Result<EmbeddedFile>)>,
+    opts: &Options,
+) -> io::Result<usize> {
+    let writer: Box<dyn Write> = if dest == "-" {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(fs::File::create(dest)?)
+    };
+
+    let mut tar = TarWriter::new(writer);
+    let mut count = 0;
+    for (name, file) in files {
+        let file = match file {
+            Ok(file) => file,
+            Err(e) if opts.fail_on_any_error => {
+                eprintln!("✗ {name}: {e}");
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("⚠ Skipped {name}: {e}");
+                continue;
+            }
+        };
+        if opts.verify_checksums
+            && !verify_integrity(std::slice::from_ref(&file), dest == "-")
+            && !opts.warn_only
+        {
+            eprintln!("✗ Integrity check failed; the archive is incomplete");
+            process::exit(2);
+        }
+        if tar.append(&file)? {
+            eprintln!("  + {} ({} bytes)", file.filename, file.data.len());
+            count += 1;
         }
     }
+    tar.finish()?.flush()?;
+    Ok(count)
 }
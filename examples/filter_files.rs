@@ -80,10 +80,7 @@ fn main() {
 
         // For XML files print a short content preview
         if file.has_extension("xml") || file.metadata.is_xml() {
-            if let Ok(text) = std::str::from_utf8(&file.data) {
-                let preview: String = text.chars().take(120).collect();
-                println!("  Preview: {preview}…");
-            }
+            println!("  Preview: {}", file.preview(120));
         }
 
         file.save_to_disk(".").unwrap_or_else(|e| {
@@ -0,0 +1,96 @@
+//! CLI that lists every embedded file as a fixed-width table for quick triage.
+//!
+//! Nothing is decoded: the sizes are the ones declared in `/Params/Size`
+//! (`-` when a file does not declare one). The total is marked `>=` when
+//! some files declare no size, and `unknown` when the declared sizes are
+//! too large to add up.
+//!
+//! Usage:
+//!   cargo run --example list_files -- invoice.pdf
+//!   cargo run --example list_files -- invoice.pdf --source annotations
+//!
+//! `--source` may be repeated to choose the discovery mechanisms
+//! (`names-tree`, `annotations`, `af`, `deep-scan`).
+
+use extractembedfilepdf::{
+    format_bytes, AttachmentSource, ExtractError, ExtractorConfig, PdfAnalyzer,
+};
+use std::{env, process};
+
+mod common;
+
+/// Parse `args` (including the program name) into the PDF path and the
+/// `--source` selection; `None` on a usage error.
+fn parse_args(args: &[String]) -> Option<(String, Vec<AttachmentSource>)> {
+    let mut pdf_path = None;
+    let mut sources = Vec::new();
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--source" => match iter.next()?.parse() {
+                Ok(source) => sources.push(source),
+                Err(e) => {
+                    eprintln!("{e}");
+                    return None;
+                }
+            },
+            flag if flag.starts_with("--") => return None,
+            _ if pdf_path.is_none() => pdf_path = Some(arg.clone()),
+            _ => return None,
+        }
+    }
+
+    Some((pdf_path?, sources))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let Some((pdf_path, sources)) = parse_args(&args) else {
+        eprintln!("Usage: {} <pdf_file> [--source <source>]...", args[0]);
+        process::exit(1);
+    };
+
+    let config = ExtractorConfig {
+        discovery_sources: (!sources.is_empty()).then_some(sources),
+        ..Default::default()
+    };
+
+    let analyzer = PdfAnalyzer::with_config(&pdf_path, config).unwrap_or_else(|e| {
+        eprintln!("Cannot load PDF: {e}");
+        process::exit(1);
+    });
+
+    let handles = match analyzer.embedded_file_handles() {
+        Ok(handles) => handles,
+        Err(ExtractError::NoEmbeddedFiles) => Vec::new(),
+        Err(e) => {
+            eprintln!("Cannot read attachments: {e}");
+            process::exit(1);
+        }
+    };
+
+    // The declared sizes come from the PDF and may be missing or absurd; a
+    // total that leaves files out is marked as a lower bound.
+    let declared: Vec<usize> = handles.iter().filter_map(|h| h.metadata().size).collect();
+    let undeclared = handles.len() - declared.len();
+    let total = declared
+        .iter()
+        .try_fold(0usize, |sum, &size| sum.checked_add(size));
+    let total = match total {
+        _ if declared.is_empty() => "-".to_string(),
+        None => "unknown".to_string(),
+        Some(total) if undeclared > 0 => format!(">= {}", format_bytes(total)),
+        Some(total) => format_bytes(total),
+    };
+    let rows = handles.iter().map(|handle| {
+        let meta = handle.metadata();
+        let size = meta.size.map_or_else(|| "-".to_string(), format_bytes);
+        (handle.filename(), size, meta)
+    });
+    common::print_table(rows, &total);
+    if undeclared > 0 && !declared.is_empty() {
+        println!("({undeclared} file(s) without a declared size are not counted)");
+    }
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (String, Vec<u8>)| {
+    let (filename, data) = input;
+    extractembedfilepdf::fuzz::fuzz_attachment(&filename, &data);
+});
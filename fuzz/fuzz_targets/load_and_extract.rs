@@ -0,0 +1,5 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| extractembedfilepdf::fuzz::fuzz_load_and_extract(data));
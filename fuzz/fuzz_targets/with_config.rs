@@ -0,0 +1,6 @@
+#![no_main]
+
+use extractembedfilepdf::fuzz::{fuzz_with_config, FuzzInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: FuzzInput| fuzz_with_config(&input));
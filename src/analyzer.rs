@@ -1,6 +1,20 @@
-use crate::{ExtractorConfig, Result};
-use lopdf::Document;
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+#[cfg(feature = "fs")]
+use crate::extraction_engine::WrittenFiles;
+use crate::file_discovery::FileSpec;
+use crate::file_structure::{self, StructureFinding};
+use crate::lazy;
+use crate::memory::MemoryBudget;
+use crate::provenance::SourceInfo;
+use crate::repair;
+use crate::validator::{PdfValidator, XmpInfo};
+use crate::{Classifier, ExtractError, ExtractorConfig, ObjectRef, PdfDict, Result};
+use lopdf::{Document, Object};
+#[cfg(feature = "fs")]
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
 // ── PdfAnalyzer ───────────────────────────────────────────────────────────────
 
@@ -24,49 +38,398 @@ use std::path::Path;
 ///     max_embedded_file_size: Some(10 * 1024 * 1024),
 ///     ..Default::default()
 /// };
-/// let a = PdfAnalyzer::with_config("invoice.pdf", cfg).unwrap();
+/// let a = PdfAnalyzer::with_config("invoice.pdf", cfg.clone()).unwrap();
+/// let a = PdfAnalyzer::from_bytes_with_config(&bytes, cfg).unwrap();
 /// ```
+///
+/// # Thread safety
+///
+/// `PdfAnalyzer` is `Send + Sync`. Every query takes `&self`, and the lazily
+/// filled caches are [`OnceLock`]s, so one loaded document can be shared
+/// through an `Arc` and serve concurrent queries from several threads.
+///
+/// Cloning is cheap: the parsed document is shared between the clones, and
+/// only the configuration and the small caches are copied. Changing the
+/// configuration of one clone does not affect the others.
+#[derive(Clone)]
 pub struct PdfAnalyzer {
-    document: Document,
+    document: Arc<Document>,
     config: ExtractorConfig,
+    /// File specifications found on first use; discovery walks the catalog,
+    /// every page, and the name tree, so it is done at most once.
+    file_specs: OnceLock<Vec<FileSpec>>,
+    /// Decoded XMP packet and PDF/A identification, or the reason it could
+    /// not be read. Validation queries reuse it instead of re-decoding.
+    xmp: OnceLock<std::result::Result<XmpInfo, String>>,
+    /// Size, digest and path of the raw document. The size is charged to
+    /// every operation's [`ExtractorConfig::max_memory_bytes`] budget.
+    source: Arc<SourceInfo>,
+    /// File-structure rule violations, checked while the raw bytes are at
+    /// hand.
+    pub(crate) structure: Vec<StructureFinding>,
+    /// Every file written to disk by any operation, so a later call cannot
+    /// overwrite a file with another attachment of the same name. Shared
+    /// with clones and with [`embedded_file_stream`](Self::embedded_file_stream).
+    #[cfg(feature = "fs")]
+    written: WrittenFiles,
 }
 
+// Sharing one analyzer across request handlers is a documented guarantee;
+// a cache that is not thread-safe must fail to compile.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PdfAnalyzer>();
+};
+
 impl PdfAnalyzer {
     // ── Constructors ──────────────────────────────────────────────────────────
 
     /// Load a PDF from the file system.
+    #[cfg(feature = "fs")]
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(Self {
-            document: Document::load(path)?,
-            config: ExtractorConfig::default(),
-        })
+        let data = std::fs::read(&path)?;
+        let document = load_document(&data, &Deadline::default())?;
+        let mut analyzer = Self::new(document, ExtractorConfig::default(), &data);
+        Arc::make_mut(&mut analyzer.source).path = Some(path.as_ref().to_path_buf());
+        Ok(analyzer)
     }
 
     /// Load a PDF from an in-memory byte slice.
+    ///
+    /// The buffer is only borrowed for the duration of the call, so a shared
+    /// buffer such as `Arc<[u8]>` or `bytes::Bytes` can be passed by reference
+    /// without copying it first. lopdf copies object data while parsing; the
+    /// analyzer keeps no reference to `data`. To extract attachments without
+    /// that copy, use a [`LazyDocument`](crate::LazyDocument), which borrows
+    /// the buffer instead.
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
-        Ok(Self {
-            document: Document::load_mem(data)?,
-            config: ExtractorConfig::default(),
-        })
+        Ok(Self::new(
+            load_document(data, &Deadline::default())?,
+            ExtractorConfig::default(),
+            data,
+        ))
+    }
+
+    /// Load a PDF from an in-memory byte slice, repairing a broken
+    /// cross-reference table if necessary.
+    ///
+    /// The document is first loaded normally. If that fails, the buffer is
+    /// scanned for object definitions and a fresh xref section is built from
+    /// them, which recovers files whose xref offsets were damaged in transit
+    /// (e.g. by line-ending conversion). If the repaired buffer cannot be
+    /// loaded either, the original error is returned.
+    ///
+    /// Encrypted documents and objects that are themselves damaged cannot be
+    /// recovered this way.
+    pub fn from_bytes_lenient(data: &[u8]) -> Result<Self> {
+        let document = match load_document(data, &Deadline::default()) {
+            Ok(document) => document,
+            Err(error) => {
+                log::warn!("loading failed ({error}); rebuilding the cross-reference table");
+                repair::rebuild_xref(data)
+                    .and_then(|repaired| Document::load_mem(&repaired).ok())
+                    .ok_or(error)?
+            }
+        };
+        Ok(Self::new(document, ExtractorConfig::default(), data))
+    }
+
+    /// Load an encrypted PDF from an in-memory byte slice, decrypting it with
+    /// `password`.
+    ///
+    /// Embedded streams are decrypted as well, including those that select a
+    /// dedicated crypt filter through a `/Crypt` decode filter. Without the
+    /// correct password such streams fail with [`ExtractError::Encrypted`].
+    pub fn from_bytes_with_password(data: &[u8], password: &str) -> Result<Self> {
+        Ok(Self::new(
+            Document::load_mem_with_password(data, password).map_err(ExtractError::parse)?,
+            ExtractorConfig::default(),
+            data,
+        ))
     }
 
     /// Load a PDF from the file system with a custom [`ExtractorConfig`].
+    #[cfg(feature = "fs")]
     pub fn with_config<P: AsRef<Path>>(path: P, config: ExtractorConfig) -> Result<Self> {
-        Ok(Self {
-            document: Document::load(path)?,
+        let mut analyzer = Self::load_bytes(&std::fs::read(&path)?, config)?;
+        Arc::make_mut(&mut analyzer.source).path = Some(path.as_ref().to_path_buf());
+        Ok(analyzer)
+    }
+
+    /// Load a PDF from an in-memory byte slice with a custom
+    /// [`ExtractorConfig`].
+    ///
+    /// A document larger than [`ExtractorConfig::max_memory_bytes`] is
+    /// rejected before parsing.
+    pub fn from_bytes_with_config(data: &[u8], config: ExtractorConfig) -> Result<Self> {
+        Self::load_bytes(data, config)
+    }
+
+    /// Parse `data` with the given configuration, reporting the load time to
+    /// the configured [`MetricsSink`](crate::MetricsSink). A document larger
+    /// than [`ExtractorConfig::max_memory_bytes`] is rejected before parsing.
+    pub(crate) fn load_bytes(data: &[u8], config: ExtractorConfig) -> Result<Self> {
+        MemoryBudget::start(config.max_memory_bytes, 0).check(data.len())?;
+        let started = config.metrics.is_some().then(Instant::now);
+        let deadline = Deadline::start(config.max_duration);
+        let document = if config.attachments_only {
+            load_attachments_only(data, &deadline)?
+        } else {
+            load_document(data, &deadline)?
+        };
+        if let (Some(metrics), Some(started)) = (&config.metrics, started) {
+            metrics.record_load(started.elapsed(), data.len());
+        }
+        Ok(Self::new(document, config, data))
+    }
+
+    /// Wrap `document`, which was loaded from `data`. The file-structure
+    /// checks run only for [`ExtractorConfig::check_file_structure`].
+    fn new(document: Document, config: ExtractorConfig, data: &[u8]) -> Self {
+        Self {
+            structure: if config.check_file_structure && !config.attachments_only {
+                file_structure::check(&document, data)
+            } else {
+                Vec::new()
+            },
+            document: Arc::new(document),
+            file_specs: OnceLock::new(),
+            xmp: OnceLock::new(),
+            source: Arc::new(SourceInfo::new(data, config_hashes_source(&config))),
             config,
-        })
+            #[cfg(feature = "fs")]
+            written: WrittenFiles::default(),
+        }
     }
 
     // ── Accessors ─────────────────────────────────────────────────────────────
 
-    /// Returns a reference to the underlying [`lopdf::Document`].
+    /// Returns a reference to the underlying `lopdf::Document`.
+    ///
+    /// Not part of the stable API: this is an escape hatch to the parser
+    /// backend, whose type may change in any release, including a patch
+    /// release. Use [`dictionary`](Self::dictionary) and [`ObjectRef`] for
+    /// reading entries the crate does not interpret.
+    #[doc(hidden)]
     pub fn document(&self) -> &Document {
         &self.document
     }
 
+    /// Returns a read-only view of the dictionary of indirect object
+    /// `object`, or of a stream's dictionary, for reading entries the
+    /// crate does not interpret itself.
+    ///
+    /// Returns `None` if the object does not exist or is not a dictionary or
+    /// stream.
+    pub fn dictionary(&self, object: ObjectRef) -> Option<PdfDict<'_>> {
+        let dict = match self.document.get_object(object.into()).ok()? {
+            lopdf::Object::Dictionary(dict) => dict,
+            lopdf::Object::Stream(stream) => &stream.dict,
+            _ => return None,
+        };
+        Some(PdfDict::new(dict, &self.document))
+    }
+
+    /// Consumes the analyzer and returns the parsed `lopdf::Document`, for
+    /// further processing such as redaction or re-saving without parsing the
+    /// PDF a second time.
+    ///
+    /// The document is copied only if a clone of the analyzer still shares
+    /// it. Not part of the stable API, like [`document`](Self::document).
+    #[doc(hidden)]
+    pub fn into_document(self) -> Document {
+        Arc::try_unwrap(self.document).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// SHA-256 hex digest of the raw bytes the document was loaded from.
+    /// It is computed while loading, and only with
+    /// [`ExtractorConfig::hash_source`](crate::ExtractorConfig::hash_source)
+    /// or [`write_sidecars`](crate::ExtractorConfig::write_sidecars);
+    /// otherwise this is `None`, because the bytes are not kept.
+    pub fn source_sha256(&self) -> Option<&str> {
+        self.source.sha256.as_deref()
+    }
+
     /// Returns a reference to the active [`ExtractorConfig`].
     pub fn config(&self) -> &ExtractorConfig {
         &self.config
     }
+
+    /// Replaces the active [`ExtractorConfig`] without reloading the
+    /// document, e.g. to count attachments first and then extract them with
+    /// a size cap.
+    ///
+    /// Discovered file specifications stay cached unless
+    /// [`ExtractorConfig::discovery_sources`] changes. The new
+    /// [`ExtractorConfig::max_memory_bytes`] applies to later operations
+    /// only; the document is not checked against it again.
+    ///
+    /// Settings that only affect loading keep the effect they had then. In
+    /// particular the file-structure checks need the raw bytes, which are
+    /// not kept, so [`structure_findings`](Self::structure_findings) stays
+    /// as it was computed at load time: turning on
+    /// [`ExtractorConfig::check_file_structure`] here does not run them.
+    pub fn set_config(&mut self, config: ExtractorConfig) {
+        if config.discovery_sources != self.config.discovery_sources {
+            self.file_specs = OnceLock::new();
+        }
+        self.config = config;
+    }
+
+    /// Returns a mutable reference to the active [`ExtractorConfig`], with
+    /// the same effect as [`set_config`](Self::set_config).
+    ///
+    /// Because any field may change, the cached file specifications are
+    /// dropped and discovery runs again on the next query. As with
+    /// `set_config`, the file-structure checks are fixed at load time, and
+    /// changing [`ExtractorConfig::check_file_structure`] through this
+    /// reference does not affect [`structure_findings`](Self::structure_findings).
+    pub fn config_mut(&mut self) -> &mut ExtractorConfig {
+        self.file_specs = OnceLock::new();
+        &mut self.config
+    }
+
+    /// Adds `classifier` to [`ExtractorConfig::classifiers`], so that it
+    /// labels every file extracted from now on. Unlike
+    /// [`config_mut`](Self::config_mut), this keeps the cached file
+    /// specifications.
+    pub fn register_classifier(&mut self, classifier: impl Classifier + 'static) {
+        self.config.classifiers.push(Arc::new(classifier));
+    }
+
+    // ── Internal ──────────────────────────────────────────────────────────────
+
+    /// Returns the discovered file specifications, running discovery on the
+    /// first call only. Discovery gives up once `deadline` expires, and a
+    /// timed-out run is not cached.
+    pub(crate) fn file_specs(&self, deadline: Deadline) -> Result<&[FileSpec]> {
+        if let Some(specs) = self.file_specs.get() {
+            return Ok(specs);
+        }
+        let started = self.config.metrics.is_some().then(Instant::now);
+        let specs = ExtractionEngine::discover(self.document.as_ref(), &self.config, deadline)?;
+        if let (Some(metrics), Some(started)) = (&self.config.metrics, started) {
+            metrics.record_discovery(started.elapsed(), specs.len());
+        }
+        Ok(self.file_specs.get_or_init(|| specs))
+    }
+
+    /// Returns the parsed XMP metadata, decoding the `/Metadata` stream on the
+    /// first call only. Failures are cached as well and reported as
+    /// [`ExtractError::NotPdfA3`].
+    pub(crate) fn xmp_info(&self) -> Result<&XmpInfo> {
+        let cached = self.xmp.get_or_init(|| {
+            PdfValidator::new(&self.document)
+                .read_xmp()
+                .map_err(|e| match e {
+                    ExtractError::NotPdfA3(msg) => msg,
+                    other => other.to_string(),
+                })
+        });
+        cached
+            .as_ref()
+            .map_err(|msg| ExtractError::NotPdfA3(msg.clone()))
+    }
+
+    /// Builds an [`ExtractionEngine`] over the cached file specifications.
+    /// The [`ExtractorConfig::max_duration`] budget starts here and covers
+    /// discovery as well as everything the engine decodes.
+    pub(crate) fn engine(&self) -> Result<ExtractionEngine<'_>> {
+        let deadline = Deadline::start(self.config.max_duration);
+        let specs = self.file_specs(deadline)?;
+        let engine = ExtractionEngine::new(self.document.as_ref(), &self.config, specs)
+            .with_deadline(deadline)
+            .with_memory(self.memory_budget());
+        #[cfg(feature = "fs")]
+        let engine = engine
+            .with_source(&self.source)
+            .with_written(self.written.clone());
+        Ok(engine)
+    }
+
+    /// The parsed document, for handing to tasks that outlive `&self`.
+    #[cfg(feature = "async")]
+    pub(crate) fn shared_document(&self) -> &Arc<Document> {
+        &self.document
+    }
+
+    /// What is known about the raw document; see [`SourceInfo`].
+    #[cfg(all(feature = "async", feature = "fs"))]
+    pub(crate) fn source(&self) -> &Arc<SourceInfo> {
+        &self.source
+    }
+
+    /// The files written to disk so far, shared with every engine.
+    #[cfg(all(feature = "async", feature = "fs"))]
+    pub(crate) fn written(&self) -> &WrittenFiles {
+        &self.written
+    }
+
+    /// Starts an [`ExtractorConfig::max_memory_bytes`] budget for one
+    /// operation, with the document's raw size already taken.
+    pub(crate) fn memory_budget(&self) -> MemoryBudget {
+        MemoryBudget::start(self.config.max_memory_bytes, self.source.len)
+    }
+}
+
+/// Whether loading with `config` hashes the raw document.
+fn config_hashes_source(config: &ExtractorConfig) -> bool {
+    #[cfg(feature = "fs")]
+    return config.hash_source || config.write_sidecars;
+    #[cfg(not(feature = "fs"))]
+    config.hash_source
+}
+
+/// Parse `data`, supplementing lopdf's handling of hybrid-reference files.
+///
+/// lopdf only follows the `/XRefStm` of a hybrid-reference file when the
+/// trailer also has a `/Prev` entry. Otherwise objects that are listed solely
+/// in the cross-reference stream — typically the compressed object streams
+/// of Acrobat "optimized" files — are silently missing. In that case the
+/// xref is rebuilt from the object definitions, and the objects it finds
+/// that the regular load missed are added; everything the real xref lists
+/// is kept as loaded.
+///
+/// lopdf cannot be interrupted while it parses, so `deadline` is checked
+/// whenever a parse returns.
+pub(crate) fn load_document(data: &[u8], deadline: &Deadline) -> Result<Document> {
+    let mut document = Document::load_mem(data).map_err(ExtractError::parse)?;
+    deadline.check()?;
+    let trailer = &document.trailer;
+    if !trailer.has(b"XRefStm") || trailer.has(b"Encrypt") {
+        return Ok(document);
+    }
+
+    let Some(rebuilt) = repair::rebuild_xref(data).and_then(|b| Document::load_mem(&b).ok()) else {
+        return Ok(document);
+    };
+    log::debug!("hybrid-reference file; filling in objects listed only in /XRefStm");
+    for (id, object) in rebuilt.objects {
+        document.objects.entry(id).or_insert(object);
+    }
+    document.max_id = document.max_id.max(rebuilt.max_id);
+    deadline.check()?;
+    Ok(document)
+}
+
+/// Parse `data` for [`ExtractorConfig::attachments_only`]: the page content
+/// streams, and the images, form XObjects and font programs the pages use,
+/// are left out while loading, so they are never parsed. Encrypted and
+/// damaged documents are parsed in full like [`load_document`]; for those,
+/// the same streams, and those the nested forms name, are emptied once the
+/// document is loaded, so the analyzer does not hold their data. Streams
+/// outside the pages' content, such as annotation appearances, are kept.
+pub(crate) fn load_attachments_only(data: &[u8], deadline: &Deadline) -> Result<Document> {
+    let mut document = match lazy::load_without_page_data(data, *deadline)? {
+        Some(document) => document,
+        None => load_document(data, deadline)?,
+    };
+    for id in lazy::page_data(&document, true) {
+        if let Some(Object::Stream(stream)) = document.objects.get_mut(&id) {
+            if !stream.dict.has_type(b"EmbeddedFile") {
+                stream.content = Vec::new();
+            }
+        }
+    }
+    Ok(document)
 }
@@ -1,5 +1,4 @@
-use crate::extraction_engine::ExtractionEngine;
-use crate::{EmbeddedFile, Result};
+use crate::{EmbeddedFile, ExtractionReport, Result};
 
 /// Extraction and file discovery functionality for PdfAnalyzer.
 impl super::PdfAnalyzer {
@@ -7,18 +6,47 @@ impl super::PdfAnalyzer {
 
     /// Returns `Ok(true)` when the document contains at least one embedded file.
     pub fn has_embedded_files(&self) -> Result<bool> {
-        let engine = ExtractionEngine::new(self.document(), self.config());
-        engine.has_files()
+        Ok(self.engine()?.has_files())
     }
 
     /// Returns the number of embedded files in the document.
     pub fn count_embedded_files(&self) -> Result<usize> {
-        let engine = ExtractionEngine::new(self.document(), self.config());
-        engine.count_files()
+        Ok(self.engine()?.count_files())
+    }
+
+    /// Returns the filename of every embedded file, in discovery order.
+    ///
+    /// Only the file specification dictionaries are read; no stream is decoded.
+    pub fn embedded_file_names(&self) -> Result<Vec<String>> {
+        Ok(self.engine()?.list_file_names())
     }
 
     // ── Extraction ────────────────────────────────────────────────────────────
 
+    /// Extract a single embedded file by filename.
+    ///
+    /// The name is compared against both the declared filename and the
+    /// name-tree key; only the matching file's stream is decoded. Size limits
+    /// and [`ExtractorConfig::extract_to_disk`](crate::ExtractorConfig::extract_to_disk)
+    /// apply as for
+    /// [`extract_embedded_files`](Self::extract_embedded_files).
+    ///
+    /// Returns [`ExtractError::FileNotFound`](crate::ExtractError::FileNotFound)
+    /// when no file matches.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let xml = analyzer.extract_embedded_file("factur-x.xml").unwrap();
+    /// println!("{} bytes", xml.data.len());
+    /// ```
+    pub fn extract_embedded_file(&self, name: &str) -> Result<EmbeddedFile> {
+        self.engine()?.extract_file(name)
+    }
+
     /// Extract every embedded file from the document.
     ///
     /// Files are decoded (decompressed) before being returned. If
@@ -41,7 +69,34 @@ impl super::PdfAnalyzer {
     /// }
     /// ```
     pub fn extract_embedded_files(&self) -> Result<Vec<EmbeddedFile>> {
-        let engine = ExtractionEngine::new(self.document(), self.config());
-        engine.extract_all_files()
+        self.engine()?.extract_all_files()
+    }
+
+    /// Extract every embedded file and report what went wrong along the way.
+    ///
+    /// Attachments that fail are listed in [`ExtractionReport::warnings`]
+    /// instead of being skipped silently, as are files returned still encoded
+    /// or truncated and files that do not match their declared checksum.
+    /// Unlike [`extract_embedded_files`](Self::extract_embedded_files), a
+    /// document whose attachments all fail yields a report without files.
+    ///
+    /// Returns [`ExtractError::NoEmbeddedFiles`](crate::ExtractError::NoEmbeddedFiles)
+    /// when no file specifications are found.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let report = analyzer.extract_with_report().unwrap();
+    /// for warning in &report.warnings {
+    ///     println!("{}: {}", warning.name, warning.message);
+    /// }
+    /// ```
+    pub fn extract_with_report(&self) -> Result<ExtractionReport> {
+        let mut report = self.engine()?.extract_report()?;
+        report.source_sha256 = self.source_sha256().map(str::to_owned);
+        Ok(report)
     }
 }
\ No newline at end of file
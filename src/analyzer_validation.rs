@@ -1,5 +1,5 @@
 use crate::validator::PdfValidator;
-use crate::Result;
+use crate::{ExtractError, PdfAConformance, Result};
 
 /// Validation functionality for PdfAnalyzer.
 impl super::PdfAnalyzer {
@@ -12,27 +12,72 @@ impl super::PdfAnalyzer {
     /// parse the file, so an `Err` here is a programming error (e.g. an empty
     /// byte slice was passed to [`from_bytes`]).
     ///
+    /// When [`ExtractorConfig::allow_pageless`] is `true`, the page check is
+    /// waived for documents that carry an `/EmbeddedFiles` name tree.
+    ///
     /// [`from_bytes`]: PdfAnalyzer::from_bytes
+    /// [`ExtractorConfig::allow_pageless`]: crate::ExtractorConfig::allow_pageless
     pub fn is_pdf(&self) -> Result<bool> {
-        PdfValidator::new(self.document()).validate_pdf_structure()
+        PdfValidator::new(self.document()).validate_pdf_structure(self.config())
     }
 
     /// Returns `Ok(true)` when the XMP metadata declares PDF/A-3 conformance.
     ///
     /// Both attribute-style (`pdfaid:part="3"`) and element-style
-    /// (`<pdfaid:part>3</pdfaid:part>`) XMP serialisations are recognised.
-    /// Conformance levels A, B, and U are accepted.
+    /// (`<pdfaid:part>3</pdfaid:part>`) XMP serialisations are recognised,
+    /// under any namespace prefix and spread over any number of
+    /// `rdf:Description` blocks, in UTF-8 or UTF-16 packets. Conformance
+    /// levels A, B, and U are accepted.
     ///
     /// When [`ExtractorConfig::strict_pdfa3_validation`] is `true`, a document
     /// that is not PDF/A-3 causes `Err(ExtractError::NotPdfA3(…))` instead of
     /// `Ok(false)`.
     pub fn is_pdfa3(&self) -> Result<bool> {
-        PdfValidator::new(self.document()).validate_pdfa3(self.config())
+        PdfValidator::validate_pdfa3(self.xmp_info()?, self.config())
     }
 
     /// Returns the PDF/A conformance level string (e.g. `"PDF/A-3B"`) when the
     /// XMP metadata declares one, or `None` otherwise.
     pub fn conformance_level(&self) -> Option<String> {
-        PdfValidator::new(self.document()).conformance_level()
+        self.conformance()
+            .map(|conformance| conformance.to_string())
+    }
+
+    /// Returns the PDF/A part and conformance level declared by the XMP
+    /// metadata, or `None` when there is no PDF/A identification.
+    pub fn conformance(&self) -> Option<PdfAConformance> {
+        self.xmp_info().ok()?.conformance
+    }
+
+    /// Fails with [`ExtractError::ConformanceMismatch`] unless the document
+    /// declares a conformance that [satisfies](PdfAConformance::satisfies)
+    /// `target`.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::{PdfAConformance, PdfAnalyzer};
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// if let Err(e) = analyzer.require_conformance(PdfAConformance::PDFA_3B) {
+    ///     eprintln!("{e}"); // e.g. "… requires PDF/A-3B but declares PDF/A-2B"
+    /// }
+    /// ```
+    pub fn require_conformance(&self, target: PdfAConformance) -> Result<()> {
+        match self.conformance() {
+            Some(found) if found.satisfies(target) => Ok(()),
+            found => Err(ExtractError::ConformanceMismatch {
+                required: target,
+                found,
+            }),
+        }
+    }
+
+    /// Returns the document's XMP packet, decompressed and decoded as UTF-8,
+    /// for reading properties beyond the PDF/A identification.
+    ///
+    /// The packet is read from the catalog's `/Metadata` stream once and
+    /// cached. When it cannot be read, `Err(ExtractError::NotPdfA3(…))`
+    /// explains why.
+    pub fn xmp_raw(&self) -> Result<String> {
+        Ok(self.xmp_info()?.packet.clone())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,133 @@
+use crate::{ErrorCode, ExtractError};
+use std::fmt;
+use std::path::PathBuf;
+
+// ── AuditSink ─────────────────────────────────────────────────────────────────
+
+/// Receives an [`AuditEvent`] for every attachment the extraction pipeline
+/// hands out, rejects or writes to disk.
+///
+/// Install a sink through [`ExtractorConfig::audit`] to build an audit trail
+/// of what left a document, e.g. by appending each event to a hash-chained
+/// log. Like [`MetricsSink`](crate::MetricsSink), the sink is shared across
+/// threads, so [`record`](Self::record) takes `&self`.
+///
+/// Files are only audited when the library extracts them; copies written
+/// later through [`EmbeddedFile::save_to_disk`](crate::EmbeddedFile::save_to_disk)
+/// are up to the caller.
+///
+/// # Example
+///
+/// ```
+/// use extractembedfilepdf::{AuditEvent, AuditSink, ExtractorConfig};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Default)]
+/// struct Trail(Mutex<Vec<AuditEvent>>);
+///
+/// impl AuditSink for Trail {
+///     fn record(&self, event: AuditEvent) {
+///         self.0.lock().unwrap().push(event);
+///     }
+/// }
+///
+/// let config = ExtractorConfig {
+///     audit: Some(Arc::new(Trail::default())),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ExtractorConfig::audit`]: crate::ExtractorConfig::audit
+pub trait AuditSink: Send + Sync {
+    /// Record one event.
+    fn record(&self, event: AuditEvent);
+}
+
+impl fmt::Debug for dyn AuditSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditSink")
+    }
+}
+
+/// Something that happened to one attachment, sent to an [`AuditSink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AuditEvent {
+    /// The attachment was decoded and handed to the caller.
+    Extracted {
+        /// The attachment's filename.
+        name: String,
+        /// Decoded size in bytes.
+        size: usize,
+        /// Lowercase SHA-256 hex digest of the decoded data.
+        sha256: String,
+    },
+
+    /// The attachment was withheld by a configured limit or policy, such as
+    /// [`ExtractorConfig::max_embedded_file_size`](crate::ExtractorConfig::max_embedded_file_size)
+    /// or a [`ContentScanner`](crate::ContentScanner).
+    Blocked {
+        /// The attachment's name-tree key or filename.
+        name: String,
+        /// Why it was withheld.
+        code: ErrorCode,
+        /// A human-readable description.
+        reason: String,
+    },
+
+    /// The attachment could not be extracted.
+    Failed {
+        /// The attachment's name-tree key or filename.
+        name: String,
+        /// The category of the failure.
+        code: ErrorCode,
+        /// A human-readable description.
+        reason: String,
+    },
+
+    /// The decoded data does not match the declared `/Params/CheckSum`.
+    ChecksumFailed {
+        /// The attachment's filename.
+        name: String,
+        /// The MD5 hex digest declared in the document.
+        declared: String,
+        /// The MD5 hex digest of the decoded data.
+        actual: String,
+    },
+
+    /// The attachment was written to disk. Follows the attachment's
+    /// [`Extracted`](Self::Extracted) event, with the same digest.
+    Written {
+        /// The attachment's filename.
+        name: String,
+        /// The file that was written.
+        path: PathBuf,
+        /// Lowercase SHA-256 hex digest of the written data.
+        sha256: String,
+    },
+}
+
+impl AuditEvent {
+    /// An [`Extracted`](Self::Extracted) event for `data`.
+    pub(crate) fn extracted(name: &str, data: &[u8]) -> Self {
+        use sha2::{Digest, Sha256};
+        AuditEvent::Extracted {
+            name: name.into(),
+            size: data.len(),
+            sha256: crate::pdf_utils::hex_encode(&Sha256::digest(data)),
+        }
+    }
+
+    /// A [`Blocked`](Self::Blocked) or [`Failed`](Self::Failed) event for an
+    /// attachment that was not extracted because of `error`.
+    pub(crate) fn rejected(name: &str, error: &ExtractError) -> Self {
+        let (name, code, reason) = (name.to_string(), error.code(), error.to_string());
+        match code {
+            ErrorCode::SizeLimit
+            | ErrorCode::ExternalReference
+            | ErrorCode::Infected
+            | ErrorCode::DangerousFile => AuditEvent::Blocked { name, code, reason },
+            _ => AuditEvent::Failed { name, code, reason },
+        }
+    }
+}
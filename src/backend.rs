@@ -0,0 +1,90 @@
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, BTreeSet};
+
+// ── PdfBackend ────────────────────────────────────────────────────────────────
+
+/// Read access to the object graph of a PDF document: the part of a parsed
+/// document that attachment discovery and extraction depend on.
+///
+/// [`lopdf::Document`] implements it, and the analyzer always extracts from
+/// one. The trait exists so that discovery and decoding can be exercised
+/// against an in-memory object graph that was never serialized, such as
+/// `test_util::ObjectGraph` with the `test-util` feature. It stays private
+/// because its signatures are lopdf types.
+pub(crate) trait PdfBackend: Sync {
+    /// The object `id`, following references, or
+    /// [`lopdf::Error::ObjectNotFound`] when there is none.
+    fn get_object(&self, id: ObjectId) -> lopdf::Result<&Object>;
+
+    /// The document catalog, the dictionary the trailer's `/Root` names.
+    fn catalog(&self) -> lopdf::Result<&Dictionary>;
+
+    /// The object id of every page, by 1-based page number.
+    ///
+    /// The default walks the `/Kids` of the catalog's `/Pages` tree in
+    /// order, visiting every node once.
+    fn get_pages(&self) -> BTreeMap<u32, ObjectId> {
+        let mut pages = BTreeMap::new();
+        let Ok(root) = self.catalog().and_then(|catalog| catalog.get(b"Pages")) else {
+            return pages;
+        };
+        let mut pending: Vec<&Object> = vec![root];
+        let mut seen = BTreeSet::new();
+        while let Some(node) = pending.pop() {
+            let Ok(id) = node.as_reference() else {
+                continue;
+            };
+            if !seen.insert(id) {
+                continue;
+            }
+            let Ok(dict) = self.get_dictionary(id) else {
+                continue;
+            };
+            match dict.get(b"Kids").and_then(Object::as_array) {
+                Ok(kids) => pending.extend(kids.iter().rev()),
+                Err(_) => {
+                    pages.insert(pages.len() as u32 + 1, id);
+                }
+            }
+        }
+        pages
+    }
+
+    /// Every indirect object, in ascending id order.
+    fn objects(&self) -> Box<dyn Iterator<Item = (ObjectId, &Object)> + '_>;
+
+    /// Whether the document is encrypted and was not decrypted on load, so
+    /// its streams hold ciphertext.
+    fn is_locked(&self) -> bool;
+
+    /// The dictionary `id`.
+    fn get_dictionary(&self, id: ObjectId) -> lopdf::Result<&Dictionary> {
+        self.get_object(id).and_then(Object::as_dict)
+    }
+}
+
+impl PdfBackend for Document {
+    fn get_object(&self, id: ObjectId) -> lopdf::Result<&Object> {
+        Document::get_object(self, id)
+    }
+
+    fn catalog(&self) -> lopdf::Result<&Dictionary> {
+        Document::catalog(self)
+    }
+
+    fn get_pages(&self) -> BTreeMap<u32, ObjectId> {
+        Document::get_pages(self)
+    }
+
+    fn objects(&self) -> Box<dyn Iterator<Item = (ObjectId, &Object)> + '_> {
+        Box::new(self.objects.iter().map(|(id, object)| (*id, object)))
+    }
+
+    fn is_locked(&self) -> bool {
+        self.trailer.has(b"Encrypt") && !self.was_encrypted()
+    }
+
+    fn get_dictionary(&self, id: ObjectId) -> lopdf::Result<&Dictionary> {
+        Document::get_dictionary(self, id)
+    }
+}
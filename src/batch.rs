@@ -0,0 +1,214 @@
+use crate::{AnalysisReport, ExtractError, ExtractorConfig, PdfAnalyzer, Result};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::thread;
+
+// ── BatchInput ────────────────────────────────────────────────────────────────
+
+/// A single document to analyse in a [`PdfBatch`].
+#[derive(Debug, Clone)]
+pub enum BatchInput {
+    /// Load the document from the file system.
+    #[cfg(feature = "fs")]
+    Path(PathBuf),
+    /// Parse the document from an in-memory buffer.
+    Bytes(Vec<u8>),
+    /// Parse the document from a buffer shared with the caller. Only the
+    /// reference count is copied when the input is queued.
+    Shared(Arc<[u8]>),
+}
+
+#[cfg(feature = "fs")]
+impl From<PathBuf> for BatchInput {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<&Path> for BatchInput {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for BatchInput {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+impl From<Arc<[u8]>> for BatchInput {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        Self::Shared(bytes)
+    }
+}
+
+// ── BatchItem ─────────────────────────────────────────────────────────────────
+
+/// The outcome for one document of a batch run.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BatchItem {
+    /// Position of the document in the input sequence.
+    pub index: usize,
+
+    /// The path of the document, or `None` for in-memory inputs.
+    pub path: Option<PathBuf>,
+
+    /// The analysis result, or the error that stopped loading or extraction.
+    /// A document whose analysis panicked yields [`ExtractError::Internal`]
+    /// carrying the panic message; the worker carries on with the next input.
+    pub report: Result<AnalysisReport>,
+}
+
+// ── PdfBatch ──────────────────────────────────────────────────────────────────
+
+/// Analyses many documents on a pool of worker threads with a shared
+/// [`ExtractorConfig`].
+///
+/// # Example
+///
+/// ```no_run
+/// use extractembedfilepdf::{ExtractorConfig, PdfBatch};
+/// use std::path::PathBuf;
+///
+/// let paths: Vec<PathBuf> = vec!["a.pdf".into(), "b.pdf".into()];
+/// for item in PdfBatch::new(ExtractorConfig::default()).run(paths) {
+///     match item.report {
+///         Ok(report) => println!("#{}: {} file(s)", item.index, report.files.len()),
+///         Err(e) => eprintln!("#{}: {e}", item.index),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PdfBatch {
+    config: Arc<ExtractorConfig>,
+    workers: usize,
+}
+
+impl PdfBatch {
+    /// Create a batch runner using one worker per available CPU.
+    pub fn new(config: ExtractorConfig) -> Self {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            config: Arc::new(config),
+            workers,
+        }
+    }
+
+    /// Set the number of worker threads (at least one).
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Analyse every input and yield one [`BatchItem`] per document.
+    ///
+    /// Items are yielded as soon as each document finishes, so they may arrive
+    /// out of input order; use [`BatchItem::index`] to correlate. Inputs are
+    /// pulled lazily and at most one finished item per worker waits to be
+    /// received, so memory stays bounded by about two documents per worker
+    /// however slowly the results are consumed.
+    pub fn run<I>(&self, inputs: I) -> BatchResults
+    where
+        I: IntoIterator,
+        I::Item: Into<BatchInput>,
+        I::IntoIter: Send + 'static,
+    {
+        let queue = Arc::new(Mutex::new(inputs.into_iter().enumerate()));
+        let (sender, receiver) = mpsc::sync_channel(self.workers);
+
+        for _ in 0..self.workers {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+            let config = Arc::clone(&self.config);
+
+            thread::spawn(move || loop {
+                // Hold the lock only while pulling the next input. A worker
+                // that panicked while pulling leaves the queue usable.
+                let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+                let Some((index, input)) = next else { break };
+
+                let item = analyse_or_catch(index, input.into(), &config);
+                if sender.send(item).is_err() {
+                    break; // receiver dropped; stop early
+                }
+            });
+        }
+
+        BatchResults { receiver }
+    }
+}
+
+/// [`analyse`] a single input, turning a panic into an error item so one
+/// malformed document cannot take a worker down.
+fn analyse_or_catch(index: usize, input: BatchInput, config: &ExtractorConfig) -> BatchItem {
+    let path = match &input {
+        #[cfg(feature = "fs")]
+        BatchInput::Path(path) => Some(path.clone()),
+        _ => None,
+    };
+
+    panic::catch_unwind(AssertUnwindSafe(|| analyse(index, input, config))).unwrap_or_else(
+        |payload| BatchItem {
+            index,
+            path,
+            report: Err(ExtractError::Internal(format!(
+                "analysis panicked: {}",
+                panic_message(&*payload)
+            ))),
+        },
+    )
+}
+
+/// The message of a panic payload, for the usual `&str` and `String` payloads.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Load and analyse a single input.
+fn analyse(index: usize, input: BatchInput, config: &ExtractorConfig) -> BatchItem {
+    let (path, analyzer) = match input {
+        #[cfg(feature = "fs")]
+        BatchInput::Path(path) => {
+            let analyzer = PdfAnalyzer::with_config(&path, config.clone());
+            (Some(path), analyzer)
+        }
+        BatchInput::Bytes(bytes) => (None, PdfAnalyzer::load_bytes(&bytes, config.clone())),
+        BatchInput::Shared(bytes) => (None, PdfAnalyzer::load_bytes(&bytes, config.clone())),
+    };
+
+    BatchItem {
+        index,
+        path,
+        report: analyzer.and_then(|a| a.analyze()),
+    }
+}
+
+// ── BatchResults ──────────────────────────────────────────────────────────────
+
+/// Iterator over the [`BatchItem`]s of a running batch, returned by
+/// [`PdfBatch::run`]. Ends once every input has been processed.
+#[derive(Debug)]
+pub struct BatchResults {
+    receiver: mpsc::Receiver<BatchItem>,
+}
+
+impl Iterator for BatchResults {
+    type Item = BatchItem;
+
+    fn next(&mut self) -> Option<BatchItem> {
+        self.receiver.recv().ok()
+    }
+}
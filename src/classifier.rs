@@ -0,0 +1,75 @@
+use crate::EmbeddedFile;
+use std::fmt;
+use std::sync::Arc;
+
+// ── Classifier ────────────────────────────────────────────────────────────────
+
+/// An organization-specific detector that runs over every extracted file and
+/// labels it, for document types the built-in
+/// [`classify`](EmbeddedFile::classify) does not know, such as internal
+/// forms or proprietary XML schemas.
+///
+/// Register classifiers in [`ExtractorConfig::classifiers`] or with
+/// [`PdfAnalyzer::register_classifier`]. Each one that returns a label adds
+/// it to [`EmbeddedFileMetadata::labels`] under its [`name`](Self::name);
+/// they run in registration order, after decoding and before the size
+/// limit, risk policy and content scanner are applied.
+///
+/// # Example
+///
+/// ```
+/// use extractembedfilepdf::{Classifier, EmbeddedFile, ExtractorConfig};
+/// use std::sync::Arc;
+///
+/// /// Recognises the purchase orders of an in-house ERP system.
+/// struct PurchaseOrders;
+///
+/// impl Classifier for PurchaseOrders {
+///     fn name(&self) -> &str {
+///         "acme"
+///     }
+///
+///     fn classify(&self, file: &EmbeddedFile) -> Option<String> {
+///         let marker: &[u8] = b"urn:acme:erp:purchase-order";
+///         file.data
+///             .windows(marker.len())
+///             .any(|w| w == marker)
+///             .then(|| "purchase-order".to_string())
+///     }
+/// }
+///
+/// let config = ExtractorConfig {
+///     classifiers: vec![Arc::new(PurchaseOrders)],
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ExtractorConfig::classifiers`]: crate::ExtractorConfig::classifiers
+/// [`EmbeddedFileMetadata::labels`]: crate::EmbeddedFileMetadata::labels
+/// [`PdfAnalyzer::register_classifier`]: crate::PdfAnalyzer::register_classifier
+pub trait Classifier: Send + Sync {
+    /// The key under which this classifier's labels are stored, e.g. the
+    /// organization or schema family it detects.
+    fn name(&self) -> &str;
+
+    /// The label for `file`, or `None` when the file is not one this
+    /// classifier recognises.
+    fn classify(&self, file: &EmbeddedFile) -> Option<String>;
+}
+
+impl fmt::Debug for dyn Classifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Classifier({})", self.name())
+    }
+}
+
+/// Label `file` with every classifier in `classifiers` that recognises it.
+pub(crate) fn annotate(classifiers: &[Arc<dyn Classifier>], file: &mut EmbeddedFile) {
+    for classifier in classifiers {
+        if let Some(label) = classifier.classify(file) {
+            file.metadata
+                .labels
+                .insert(classifier.name().to_owned(), label);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Comparing the attachments of two documents, for regression tests of
+//! invoice generators and similar pipelines.
+//!
+//! [`diff`] matches attachments by content first and by name second, so it
+//! tells renamed files from modified ones. The older [`compare_attachments`]
+//! matches by filename only and is deprecated in its favour.
+
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+use crate::{ExtractError, PdfAnalyzer, Result};
+use std::collections::{BTreeMap, HashMap};
+
+// ── AttachmentDiff ────────────────────────────────────────────────────────────
+
+/// The result of comparing the attachments of two documents by filename and
+/// SHA-256 content hash.
+///
+/// Returned by [`diff`] and [`compare_attachments`]. All lists are sorted by
+/// filename.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AttachmentDiff {
+    /// Files present only in the second document.
+    pub added: Vec<String>,
+
+    /// Files present only in the first document.
+    pub removed: Vec<String>,
+
+    /// Files present in both documents whose content differs.
+    pub changed: Vec<ChangedAttachment>,
+
+    /// Files present in both documents with identical content.
+    pub unchanged: Vec<String>,
+
+    /// Files present in both documents with identical content under different
+    /// names. Only [`diff`] detects renames.
+    pub renamed: Vec<RenamedAttachment>,
+}
+
+/// A file present in both documents under the same name but with different
+/// content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChangedAttachment {
+    /// The shared filename.
+    pub filename: String,
+
+    /// SHA-256 hex digest of the file in the first document.
+    pub old_sha256: String,
+
+    /// SHA-256 hex digest of the file in the second document.
+    pub new_sha256: String,
+}
+
+/// A file present in both documents with the same content but a different
+/// name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RenamedAttachment {
+    /// The filename in the first document.
+    pub old_filename: String,
+
+    /// The filename in the second document.
+    pub new_filename: String,
+
+    /// SHA-256 hex digest of the shared content.
+    pub sha256: String,
+}
+
+impl AttachmentDiff {
+    /// Returns `true` when both documents carry the same attachments with
+    /// identical content.
+    pub fn is_identical(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+// ── diff ──────────────────────────────────────────────────────────────────────
+
+/// Compare the embedded files of two documents, telling renamed files from
+/// modified ones.
+///
+/// Attachments are matched by SHA-256 hash first: the same content under
+/// the same name is unchanged, under another name it is renamed. The files
+/// left over are then matched by filename, and a name present in both
+/// documents is changed. Whatever still has no partner is added or removed.
+/// Every attachment is matched at most once, so two files sharing a name are
+/// compared individually rather than collapsed.
+///
+/// Attachments are decoded but never written to disk, even with
+/// [`ExtractorConfig::extract_to_disk`](crate::ExtractorConfig::extract_to_disk),
+/// nor scanned or audited. Those that cannot be decoded are left out of the
+/// comparison; use [`PdfAnalyzer::extract_with_report`] to see why. A
+/// document without embedded files has an empty attachment list; a timeout
+/// or an exhausted memory budget is returned as an error.
+///
+/// # Example
+///
+/// ```no_run
+/// use extractembedfilepdf::{compare, PdfAnalyzer};
+///
+/// let old = PdfAnalyzer::from_path("invoice-v1.pdf").unwrap();
+/// let new = PdfAnalyzer::from_path("invoice-v2.pdf").unwrap();
+///
+/// let diff = compare::diff(&old, &new).unwrap();
+/// for rename in &diff.renamed {
+///     println!("{} is now {}", rename.old_filename, rename.new_filename);
+/// }
+/// ```
+pub fn diff(a: &PdfAnalyzer, b: &PdfAnalyzer) -> Result<AttachmentDiff> {
+    let mut old = hashed_files(a)?;
+    let mut new = hashed_files(b)?;
+    let mut diff = AttachmentDiff::default();
+
+    // Same content under the same name, then under another name.
+    for same_name in [true, false] {
+        old.retain(|(old_name, old_hash)| {
+            let Some(i) = new.iter().position(|(new_name, new_hash)| {
+                new_hash == old_hash && (new_name == old_name) == same_name
+            }) else {
+                return true;
+            };
+            let (new_name, _) = new.remove(i);
+            if same_name {
+                diff.unchanged.push(new_name);
+            } else {
+                diff.renamed.push(RenamedAttachment {
+                    old_filename: old_name.clone(),
+                    new_filename: new_name,
+                    sha256: old_hash.clone(),
+                });
+            }
+            false
+        });
+    }
+
+    // Same name, different content.
+    old.retain(|(old_name, old_hash)| {
+        let Some(i) = new.iter().position(|(new_name, _)| new_name == old_name) else {
+            return true;
+        };
+        let (_, new_hash) = new.remove(i);
+        diff.changed.push(ChangedAttachment {
+            filename: old_name.clone(),
+            old_sha256: old_hash.clone(),
+            new_sha256: new_hash,
+        });
+        false
+    });
+
+    diff.removed = old.into_iter().map(|(name, _)| name).collect();
+    diff.added = new.into_iter().map(|(name, _)| name).collect();
+    diff.added.sort();
+    diff.removed.sort();
+    diff.unchanged.sort();
+    diff.changed.sort_by(|x, y| x.filename.cmp(&y.filename));
+    diff.renamed
+        .sort_by(|x, y| x.old_filename.cmp(&y.old_filename));
+    Ok(diff)
+}
+
+/// The filename and SHA-256 hex digest of every file of `analyzer` that can
+/// be decoded, in discovery order, for both comparisons. Each file is
+/// decoded for inspection only and released once it is hashed.
+fn hashed_files(analyzer: &PdfAnalyzer) -> Result<Vec<(String, String)>> {
+    let deadline = Deadline::start(analyzer.config().max_duration);
+    let specs = analyzer.file_specs(deadline)?;
+    let memory = analyzer.memory_budget();
+    let engine = ExtractionEngine::new(analyzer.document(), analyzer.config(), specs)
+        .with_deadline(deadline)
+        .with_memory(memory.clone());
+    let mut files = Vec::new();
+    for spec in specs {
+        let file = match engine.inspect_file(spec) {
+            Ok(file) => file,
+            Err(e @ (ExtractError::Timeout(_) | ExtractError::MemoryLimit(_))) => return Err(e),
+            Err(_) => continue,
+        };
+        memory.release(file.data.len());
+        let hash = file.sha256_hex();
+        files.push((file.filename, hash));
+    }
+    Ok(files)
+}
+
+// ── compare_attachments ───────────────────────────────────────────────────────
+
+/// Compare the embedded files of two documents by filename only.
+///
+/// Deprecated in favour of [`diff`], which returns the same
+/// [`AttachmentDiff`] and also fills in [`AttachmentDiff::renamed`].
+///
+/// Files are matched by filename; content is compared by SHA-256 hash, and
+/// [`AttachmentDiff::renamed`] stays empty. When a name occurs more than
+/// once, the n-th file of that name in one document is matched with the
+/// n-th in the other, so duplicates are compared individually.
+///
+/// Attachments are decoded the same way as for [`diff`]: never written to
+/// disk, those that cannot be decoded are left out, and a document without
+/// embedded files has an empty attachment list.
+///
+/// # Example
+///
+/// ```no_run
+/// # #![allow(deprecated)]
+/// use extractembedfilepdf::{compare_attachments, PdfAnalyzer};
+///
+/// let old = PdfAnalyzer::from_path("invoice-v1.pdf").unwrap();
+/// let new = PdfAnalyzer::from_path("invoice-v2.pdf").unwrap();
+///
+/// let diff = compare_attachments(&old, &new).unwrap();
+/// for change in &diff.changed {
+///     println!("{} changed", change.filename);
+/// }
+/// ```
+#[deprecated(note = "use `compare::diff`, which also detects renamed attachments")]
+pub fn compare_attachments(a: &PdfAnalyzer, b: &PdfAnalyzer) -> Result<AttachmentDiff> {
+    let old = hashes_by_name(a)?;
+    let new = hashes_by_name(b)?;
+    let mut diff = AttachmentDiff::default();
+
+    for (key, old_hash) in &old {
+        let (name, _) = key;
+        match new.get(key) {
+            None => diff.removed.push(name.clone()),
+            Some(new_hash) if new_hash == old_hash => diff.unchanged.push(name.clone()),
+            Some(new_hash) => diff.changed.push(ChangedAttachment {
+                filename: name.clone(),
+                old_sha256: old_hash.clone(),
+                new_sha256: new_hash.clone(),
+            }),
+        }
+    }
+
+    diff.added = new
+        .keys()
+        .filter(|key| !old.contains_key(*key))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    Ok(diff)
+}
+
+/// Extract all files and map (filename, occurrence) → SHA-256 hex digest.
+///
+/// The occurrence counts earlier files of the same name in discovery order,
+/// so duplicate names stay separate entries.
+fn hashes_by_name(analyzer: &PdfAnalyzer) -> Result<BTreeMap<(String, usize), String>> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    Ok(hashed_files(analyzer)?
+        .into_iter()
+        .map(|(name, hash)| {
+            let occurrence = seen.entry(name.clone()).or_default();
+            let key = (name, *occurrence);
+            *occurrence += 1;
+            (key, hash)
+        })
+        .collect())
+}
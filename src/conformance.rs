@@ -0,0 +1,85 @@
+use crate::xmp;
+
+// ── PdfAConformance ───────────────────────────────────────────────────────────
+
+/// A PDF/A part and conformance level such as PDF/A-3B, as declared by the
+/// `pdfaid:part` and `pdfaid:conformance` XMP properties.
+///
+/// Returned by [`PdfAnalyzer::conformance`](crate::PdfAnalyzer::conformance)
+/// and checked by
+/// [`PdfAnalyzer::require_conformance`](crate::PdfAnalyzer::require_conformance).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PdfAConformance {
+    /// The part of ISO 19005: 1, 2 or 3.
+    pub part: u8,
+
+    /// The conformance level within the part.
+    pub level: ConformanceLevel,
+}
+
+/// A PDF/A conformance level, ordered from the least to the most demanding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConformanceLevel {
+    /// Level B (basic): reliable visual reproduction.
+    B,
+    /// Level U (Unicode): level B plus Unicode mapping of all text. Defined
+    /// by parts 2 and 3 only.
+    U,
+    /// Level A (accessible): level U plus a tagged logical structure.
+    A,
+}
+
+impl PdfAConformance {
+    /// PDF/A-3B, the level most e-invoicing formats build on.
+    pub const PDFA_3B: Self = Self::new(3, ConformanceLevel::B);
+
+    /// The conformance PDF/A-`part` level `level`.
+    pub const fn new(part: u8, level: ConformanceLevel) -> Self {
+        Self { part, level }
+    }
+
+    /// Whether a document declaring `self` also meets `required`: the parts
+    /// must be equal and the declared level at least as demanding, so
+    /// PDF/A-3A satisfies a PDF/A-3B requirement but PDF/A-2B does not.
+    pub fn satisfies(self, required: PdfAConformance) -> bool {
+        self.part == required.part && self.level >= required.level
+    }
+
+    /// The declaration in an XMP packet: the `pdfaid:part` and
+    /// `pdfaid:conformance` properties, wherever and in whichever form the
+    /// packet sets them (see [`xmp::property`]). The level is read
+    /// case-insensitively, since some producers write `b`.
+    pub(crate) fn from_xmp(packet: &str) -> Option<Self> {
+        let pdfaid = |name| xmp::property(packet, xmp::PDFA_ID_NAMESPACE, "pdfaid", name);
+
+        let part = pdfaid("part")?
+            .parse()
+            .ok()
+            .filter(|part| (1..=3).contains(part))?;
+        let level = match pdfaid("conformance")?.to_ascii_uppercase().as_str() {
+            "A" => ConformanceLevel::A,
+            "B" => ConformanceLevel::B,
+            "U" => ConformanceLevel::U,
+            _ => return None,
+        };
+        Some(Self::new(part, level))
+    }
+}
+
+impl std::fmt::Display for PdfAConformance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PDF/A-{}{}", self.part, self.level)
+    }
+}
+
+impl std::fmt::Display for ConformanceLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConformanceLevel::B => "B",
+            ConformanceLevel::U => "U",
+            ConformanceLevel::A => "A",
+        })
+    }
+}
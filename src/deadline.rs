@@ -0,0 +1,38 @@
+use crate::{ExtractError, Result};
+use std::time::{Duration, Instant};
+
+/// The time budget of one operation, from [`ExtractorConfig::max_duration`].
+///
+/// Without a limit the clock is never read, so an unlimited deadline is free
+/// and safe on targets without `Instant`.
+///
+/// [`ExtractorConfig::max_duration`]: crate::ExtractorConfig::max_duration
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Deadline {
+    started: Option<(Instant, Duration)>,
+}
+
+impl Deadline {
+    /// Start counting `limit` from now; `None` never expires.
+    pub fn start(limit: Option<Duration>) -> Self {
+        Self {
+            started: limit.map(|limit| (Instant::now(), limit)),
+        }
+    }
+
+    /// Whether the budget is used up.
+    pub fn expired(&self) -> bool {
+        self.started
+            .is_some_and(|(started, limit)| started.elapsed() >= limit)
+    }
+
+    /// Fail with [`ExtractError::Timeout`] once the budget is used up.
+    pub fn check(&self) -> Result<()> {
+        match self.started {
+            Some((started, limit)) if started.elapsed() >= limit => {
+                Err(ExtractError::Timeout(limit))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::pdf_utils::extract_string_from_dict;
+use crate::{xmp, PdfAnalyzer, Result};
+
+// ── DocumentMetadata ──────────────────────────────────────────────────────────
+
+/// Descriptive properties of a document read from its XMP packet: the Dublin
+/// Core title, creators, languages and description, and the XMP basic
+/// dates.
+///
+/// Returned by [`PdfAnalyzer::document_metadata`]. Every field is empty when
+/// the packet does not set the property.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocumentMetadata {
+    /// `dc:title`, in the default language (`x-default`) or else the first
+    /// one given.
+    pub title: Option<String>,
+
+    /// `dc:creator`, the authors in the order they are listed.
+    pub creators: Vec<String>,
+
+    /// `dc:language`, the languages of the content as RFC 3066 tags such as
+    /// `de-CH`.
+    pub languages: Vec<String>,
+
+    /// `dc:description`, chosen by language like [`title`](Self::title).
+    pub description: Option<String>,
+
+    /// `xmp:CreateDate` as written, an ISO 8601 date such as
+    /// `2024-03-01T10:15:00+01:00`.
+    pub create_date: Option<String>,
+
+    /// `xmp:ModifyDate` as written.
+    pub modify_date: Option<String>,
+}
+
+impl DocumentMetadata {
+    /// The properties set in `packet`. XML character references in the
+    /// values are resolved.
+    pub(crate) fn from_xmp(packet: &str) -> Self {
+        let items = |name| xmp::array_items(packet, xmp::DC_NAMESPACE, "dc", name);
+        let date = |name| xmp::property(packet, xmp::XMP_NAMESPACE, "xmp", name).map(xmp::unescape);
+
+        Self {
+            title: language_default(items("title")),
+            creators: items("creator")
+                .into_iter()
+                .map(|(_, creator)| xmp::unescape(creator))
+                .collect(),
+            languages: items("language")
+                .into_iter()
+                .map(|(_, language)| xmp::unescape(language))
+                .collect(),
+            description: language_default(items("description")),
+            create_date: date("CreateDate"),
+            modify_date: date("ModifyDate"),
+        }
+    }
+}
+
+/// The `x-default` alternative of a language alternative, or the first.
+fn language_default(alternatives: Vec<(Option<&str>, &str)>) -> Option<String> {
+    alternatives
+        .iter()
+        .find(|(lang, _)| lang.is_some_and(|l| l.eq_ignore_ascii_case("x-default")))
+        .or(alternatives.first())
+        .map(|(_, text)| xmp::unescape(text))
+}
+
+impl PdfAnalyzer {
+    /// Returns the title, creators, description and dates declared in the
+    /// document's XMP packet, so that document-management integrations need
+    /// no XMP parser of their own.
+    ///
+    /// Fails with `Err(ExtractError::NotPdfA3(…))` when the packet cannot be
+    /// read, as [`xmp_raw`](Self::xmp_raw) does. The `/Info` dictionary is
+    /// not consulted.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let metadata = analyzer.document_metadata().unwrap();
+    /// println!("{}", metadata.title.as_deref().unwrap_or("(untitled)"));
+    /// println!("by {}", metadata.creators.join(", "));
+    /// ```
+    pub fn document_metadata(&self) -> Result<DocumentMetadata> {
+        Ok(DocumentMetadata::from_xmp(&self.xmp_info()?.packet))
+    }
+
+    /// Returns the document's natural language, the catalog's `/Lang` entry
+    /// (e.g. `"en-US"`), or `None` when it is missing or empty.
+    ///
+    /// PDF/A level A and PDF/UA require it. A language that contradicts the
+    /// XMP `dc:language` is reported by
+    /// [`conformance_warnings`](Self::conformance_warnings).
+    pub fn language(&self) -> Option<String> {
+        let catalog = self.document().catalog().ok()?;
+        extract_string_from_dict(catalog, b"Lang")
+            .map(|lang| lang.trim().to_owned())
+            .filter(|lang| !lang.is_empty())
+    }
+}
+
+/// Whether the language tags `a` and `b` name the same language, ignoring
+/// case. A tag also matches its more specific forms, so `de` matches
+/// `de-CH`.
+pub(crate) fn same_language(a: &str, b: &str) -> bool {
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    longer
+        .get(..shorter.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(shorter))
+        && matches!(longer.as_bytes().get(shorter.len()), None | Some(b'-'))
+}
@@ -1,3 +1,8 @@
+#[cfg(feature = "fs")]
+use crate::pdf_utils;
+use crate::probe;
+use crate::{format_bytes, ExtractorConfig, PdfAnalyzer, Result, RiskCategory};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 // ── EmbeddedFile ─────────────────────────────────────────────────────────────
@@ -5,7 +10,16 @@ use std::path::Path;
 /// A file that was embedded inside a PDF document.
 ///
 /// Returned by [`crate::PdfAnalyzer::extract_embedded_files`].
+///
+/// Equality and hashing look at the data only, as
+/// [`same_content_as`](Self::same_content_as) does: the filename and metadata
+/// are ignored, so the same content found under different names or through
+/// different [`AttachmentSource`]s collapses to one entry in a `HashSet`.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
 pub struct EmbeddedFile {
     /// The filename as declared in the PDF file specification object
     /// (Unicode name preferred over ASCII name when both are present).
@@ -21,6 +35,8 @@ pub struct EmbeddedFile {
 
 impl EmbeddedFile {
     /// Write this file into `output_dir`, creating the directory if necessary.
+    /// Only the last component of [`filename`](Self::filename) is used, so an
+    /// attachment named `../x` or `/etc/x` is written as `output_dir/x`.
     ///
     /// # Example
     ///
@@ -32,10 +48,178 @@ impl EmbeddedFile {
     ///     file.save_to_disk("./extracted").unwrap();
     /// }
     /// ```
+    #[cfg(feature = "fs")]
     pub fn save_to_disk<P: AsRef<Path>>(&self, output_dir: P) -> std::io::Result<()> {
         let dir = output_dir.as_ref();
         std::fs::create_dir_all(dir)?;
-        std::fs::write(dir.join(&self.filename), &self.data)
+        let dest = dir.join(pdf_utils::safe_filename(&self.filename));
+        std::fs::write(dest, &self.data)
+    }
+
+    /// Write this file to exactly `path`, creating its parent directories if
+    /// necessary. Unlike [`save_to_disk`](Self::save_to_disk) the embedded
+    /// filename is not used, so callers can name outputs their own way.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// for (i, file) in analyzer.extract_embedded_files().unwrap().iter().enumerate() {
+    ///     file.save_to_path(format!("./extracted/{i:03}.bin")).unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn save_to_path<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &self.data)
+    }
+
+    /// A reader over [`data`](Self::data), for APIs that take
+    /// [`std::io::Read`] such as zip writers, hashers or HTTP bodies.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, EmbeddedFileMetadata};
+    ///
+    /// let file = EmbeddedFile {
+    ///     filename: "a.txt".into(),
+    ///     data: b"hello".to_vec(),
+    ///     metadata: EmbeddedFileMetadata::default(),
+    /// };
+    /// let mut copy = Vec::new();
+    /// std::io::copy(&mut file.as_reader(), &mut copy).unwrap();
+    /// assert_eq!(copy, b"hello");
+    /// ```
+    pub fn as_reader(&self) -> std::io::Cursor<&[u8]> {
+        std::io::Cursor::new(&self.data)
+    }
+
+    /// Like [`as_reader`](Self::as_reader), but takes ownership of the data,
+    /// so the reader can outlive the file, e.g. as a `'static` request body.
+    pub fn into_reader(self) -> std::io::Cursor<Vec<u8>> {
+        std::io::Cursor::new(self.data)
+    }
+
+    /// Consumes the file and returns its data without copying it.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Consumes the file and returns its filename, data and metadata.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, EmbeddedFileMetadata};
+    ///
+    /// let file = EmbeddedFile {
+    ///     filename: "a.txt".into(),
+    ///     data: b"hello".to_vec(),
+    ///     metadata: EmbeddedFileMetadata::default(),
+    /// };
+    /// let (filename, data, _metadata) = file.into_parts();
+    /// assert_eq!((filename.as_str(), data.as_slice()), ("a.txt", &b"hello"[..]));
+    /// ```
+    pub fn into_parts(self) -> (String, Vec<u8>, EmbeddedFileMetadata) {
+        (self.filename, self.data, self.metadata)
+    }
+
+    /// Returns `true` when the data starts like a PDF document, i.e. the
+    /// `%PDF-` header appears within its first 1024 bytes. Only the header is
+    /// checked; use [`analyze`](Self::analyze) to parse it.
+    pub fn is_pdf(&self) -> bool {
+        probe::has_pdf_header(&self.data)
+    }
+
+    /// Loads the data as a PDF document, for attachments that are PDFs
+    /// themselves, e.g. to extract the files nested inside them.
+    ///
+    /// The analyzer uses the default [`ExtractorConfig`], which sets no size,
+    /// memory or time limits; use
+    /// [`analyze_with_config`](Self::analyze_with_config) to keep those of
+    /// the enclosing document.
+    pub fn analyze(&self) -> Result<PdfAnalyzer> {
+        PdfAnalyzer::from_bytes(&self.data)
+    }
+
+    /// Loads the data as a PDF document like [`analyze`](Self::analyze), with
+    /// a custom [`ExtractorConfig`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("portfolio.pdf").unwrap();
+    /// for file in analyzer.extract_embedded_files().unwrap() {
+    ///     if file.is_pdf() {
+    ///         let nested = file.analyze_with_config(analyzer.config().clone()).unwrap();
+    ///         println!("{}: {:?}", file.filename, nested.embedded_file_names());
+    ///     }
+    /// }
+    /// ```
+    pub fn analyze_with_config(&self, config: ExtractorConfig) -> Result<PdfAnalyzer> {
+        PdfAnalyzer::from_bytes_with_config(&self.data, config)
+    }
+
+    /// A printable preview of the first `max_bytes` bytes of the data: the
+    /// text itself when it is UTF-8 without control characters, otherwise a
+    /// hex dump with 16 bytes per line. A cut-off preview ends with `…`, on a
+    /// line of its own after a hex dump.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, EmbeddedFileMetadata};
+    ///
+    /// let mut file = EmbeddedFile {
+    ///     filename: "a.xml".into(),
+    ///     data: b"<invoice/>".to_vec(),
+    ///     metadata: EmbeddedFileMetadata::default(),
+    /// };
+    /// assert_eq!(file.preview(4), "<inv…");
+    ///
+    /// file.data = vec![0x50, 0x4b, 0x03, 0x04];
+    /// assert!(file.preview(64).starts_with("00000000  50 4b 03 04 "));
+    /// assert!(file.preview(64).ends_with("  |PK..|"));
+    /// ```
+    pub fn preview(&self, max_bytes: usize) -> String {
+        let head = &self.data[..self.data.len().min(max_bytes)];
+        let cut = head.len() < self.data.len();
+
+        // A multi-byte character split by the cut is dropped, not an error.
+        let text = match std::str::from_utf8(head) {
+            Ok(text) => Some(text),
+            Err(e) if cut && e.error_len().is_none() => {
+                std::str::from_utf8(&head[..e.valid_up_to()]).ok()
+            }
+            Err(_) => None,
+        };
+        let printable = |text: &&str| {
+            text.chars()
+                .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        };
+        match (text.filter(printable), cut) {
+            (Some(text), false) => text.to_string(),
+            (Some(text), true) => format!("{text}…"),
+            (None, false) => hex_dump(head),
+            (None, true) => format!("{}\n…", hex_dump(head)),
+        }
+    }
+
+    /// A one-line description: filename, size, MIME type and dates, e.g.
+    /// `"factur-x.xml (5.8 KiB, text/xml, modified D:20240101120000Z)"`.
+    /// Missing metadata is left out.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format_bytes(self.data.len())];
+        parts.extend(self.metadata.mime_type.clone());
+        if let Some(date) = &self.metadata.creation_date {
+            parts.push(format!("created {date}"));
+        }
+        if let Some(date) = &self.metadata.modification_date {
+            parts.push(format!("modified {date}"));
+        }
+        format!("{} ({})", self.filename, parts.join(", "))
     }
 
     /// Returns the file extension (lowercase), or `None` if the filename has
@@ -65,6 +249,111 @@ impl EmbeddedFile {
             .map(|e| e.eq_ignore_ascii_case(ext))
             .unwrap_or(false)
     }
+
+    /// Returns the lowercase MD5 hex digest of the decoded data, in the same
+    /// format as [`EmbeddedFileMetadata::checksum`].
+    pub fn md5_hex(&self) -> String {
+        use md5::{Digest, Md5};
+        crate::pdf_utils::hex_encode(&Md5::digest(&self.data))
+    }
+
+    /// Returns the lowercase SHA-256 hex digest of the decoded data.
+    pub fn sha256_hex(&self) -> String {
+        use sha2::{Digest, Sha256};
+        crate::pdf_utils::hex_encode(&Sha256::digest(&self.data))
+    }
+
+    /// Returns `true` when both files hold the same data, whatever their
+    /// names, e.g. to recognise an attachment that was renamed between two
+    /// documents. The same as `==`, spelled out.
+    pub fn same_content_as(&self, other: &EmbeddedFile) -> bool {
+        self.data == other.data
+    }
+
+    /// The length of the decoded data in bytes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` for a zero-byte attachment.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The uncompressed size declared in `/Params/Size`, if any.
+    pub fn declared_size(&self) -> Option<usize> {
+        self.metadata.size
+    }
+
+    /// Compares the decoded length against the declared `/Params/Size`.
+    ///
+    /// Returns `None` when the PDF declares no size, otherwise `Some(true)`
+    /// if [`len`](Self::len) matches it.
+    pub fn size_matches_declaration(&self) -> Option<bool> {
+        Some(self.declared_size()? == self.len())
+    }
+
+    /// The decoded length divided by the
+    /// [stored length](EmbeddedFileMetadata::stored_size), e.g. `4.0` for a
+    /// stream that Flate compressed to a quarter of its size. `None` when the
+    /// stored length is unknown or zero.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let stored = self.metadata.stored_size.filter(|&stored| stored > 0)?;
+        Some(self.len() as f64 / stored as f64)
+    }
+
+    /// Returns `true` when every stream filter was applied to the whole
+    /// stream, i.e. [`data`](Self::data) is the complete file rather than
+    /// still-encoded or truncated stream content.
+    pub fn is_fully_decoded(&self) -> bool {
+        self.metadata.undecoded_filters.is_empty() && !self.metadata.truncated
+    }
+
+    /// Compares the decoded data against the declared `/Params/CheckSum`.
+    ///
+    /// Returns `None` when the PDF declares no checksum, otherwise
+    /// `Some(true)` if the MD5 digest of [`data`](Self::data) matches.
+    pub fn verify_checksum(&self) -> Option<bool> {
+        let declared = self.metadata.checksum.as_deref()?;
+        Some(declared.eq_ignore_ascii_case(&self.md5_hex()))
+    }
+}
+
+/// Format `data` as `offset  hex bytes  |ASCII|` lines of 16 bytes each.
+fn hex_dump(data: &[u8]) -> String {
+    let lines: Vec<String> = data
+        .chunks(16)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  |{ascii}|", index * 16, hex.join(" "))
+        })
+        .collect();
+    lines.join("\n")
+}
+
+impl PartialEq for EmbeddedFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_content_as(other)
+    }
+}
+
+impl Eq for EmbeddedFile {}
+
+impl std::hash::Hash for EmbeddedFile {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
 }
 
 // ── EmbeddedFileMetadata ──────────────────────────────────────────────────────
@@ -74,11 +363,22 @@ impl EmbeddedFile {
 ///
 /// All fields are optional: a conforming PDF need not populate them.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
 pub struct EmbeddedFileMetadata {
     /// MIME type declared in the file specification's `/Subtype` entry
     /// (e.g. `"application/xml"`).
     pub mime_type: Option<String>,
 
+    /// MIME type recognised from the file's magic bytes, or, when neither
+    /// those nor `/Subtype` give one, looked up for the filename extension
+    /// in [`ExtractorConfig::mime_types`](crate::ExtractorConfig::mime_types).
+    /// Set for every file read from a document that either identifies.
+    pub detected_mime_type: Option<String>,
+
     /// Human-readable description from the `/Desc` entry.
     pub description: Option<String>,
 
@@ -92,8 +392,69 @@ pub struct EmbeddedFileMetadata {
     /// Uncompressed file size in bytes, from `/Params/Size`.
     pub size: Option<usize>,
 
+    /// Length of the embedded stream as stored in the PDF, before any filter
+    /// is applied. Set for every file read from a document.
+    pub stored_size: Option<usize>,
+
     /// MD5 checksum hex string from `/Params/CheckSum`, if present.
     pub checksum: Option<String>,
+
+    /// Relationship to the document from the `/AFRelationship` entry
+    /// (PDF/A-3 associated files).
+    pub af_relationship: Option<AfRelationship>,
+
+    /// Where in the document the file specification was discovered.
+    pub source: Option<AttachmentSource>,
+
+    /// 1-based page number for files attached via a page annotation.
+    pub page: Option<u32>,
+
+    /// Stream filters that could not be applied, e.g. an image codec such as
+    /// `DCTDecode` or a filter whose input is corrupt.
+    ///
+    /// When non-empty, [`EmbeddedFile::data`] holds the stream content as it
+    /// was before the first of these filters, not the file itself.
+    pub undecoded_filters: Vec<String>,
+
+    /// The stream broke off part-way and [`EmbeddedFile::data`] holds only
+    /// the bytes decoded before the damage. Only set with
+    /// [`ExtractorConfig::recover_truncated_streams`](crate::ExtractorConfig::recover_truncated_streams).
+    pub truncated: bool,
+
+    /// The file's [`RiskCategory`]. Only classified under
+    /// [`RiskPolicy::Flag`](crate::RiskPolicy::Flag); always `None` otherwise.
+    pub risk: Option<RiskCategory>,
+
+    /// The file is itself encrypted: an encrypted PDF, a ZIP archive with
+    /// encrypted entries, or a password-protected Office Open XML document.
+    /// Its content cannot be inspected without the password. Only checked
+    /// under [`RiskPolicy::Flag`](crate::RiskPolicy::Flag); always `false`
+    /// otherwise.
+    pub encrypted_container: bool,
+
+    /// Set when [`size`](Self::size) disagrees with the length of the fully
+    /// decoded data. Still-encoded and truncated data are not compared.
+    pub size_mismatch: Option<SizeMismatch>,
+
+    /// Labels from the configured [`Classifier`](crate::Classifier)s, keyed
+    /// by classifier name. Empty unless a classifier recognised the file.
+    pub labels: BTreeMap<String, String>,
+}
+
+/// The declared `/Params/Size` of an attachment and the length its stream
+/// actually decoded to; see [`EmbeddedFileMetadata::size_mismatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
+pub struct SizeMismatch {
+    /// The size from `/Params/Size`.
+    pub declared: usize,
+
+    /// The length of the decoded data.
+    pub actual: usize,
 }
 
 impl EmbeddedFileMetadata {
@@ -105,6 +466,15 @@ impl EmbeddedFileMetadata {
             .unwrap_or(false)
     }
 
+    /// The declared MIME type, or else the
+    /// [detected](Self::detected_mime_type) one, for routing attachments
+    /// whose file specification leaves `/Subtype` out.
+    pub fn content_type(&self) -> Option<&str> {
+        self.mime_type
+            .as_deref()
+            .or(self.detected_mime_type.as_deref())
+    }
+
     /// Returns `true` when the declared MIME type matches `mime_type`
     /// (case-insensitive).
     pub fn has_mime_type(&self, mime_type: &str) -> bool {
@@ -114,3 +484,173 @@ impl EmbeddedFileMetadata {
             .unwrap_or(false)
     }
 }
+
+/// Lists the fields that are set as `key: value` pairs, e.g.
+/// `mime: text/xml, size: 5.8 KiB, source: names-tree`, or `-` when none is.
+/// Flags that describe the data, such as
+/// [`undecoded_filters`](Self::undecoded_filters), are left out.
+impl std::fmt::Display for EmbeddedFileMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut fields = Vec::new();
+        if let Some(mime) = &self.mime_type {
+            fields.push(format!("mime: {mime}"));
+        }
+        if let Some(size) = self.size {
+            fields.push(format!("size: {}", format_bytes(size)));
+        }
+        if let Some(date) = &self.creation_date {
+            fields.push(format!("created: {date}"));
+        }
+        if let Some(date) = &self.modification_date {
+            fields.push(format!("modified: {date}"));
+        }
+        if let Some(relationship) = &self.af_relationship {
+            fields.push(format!("relationship: {relationship}"));
+        }
+        if let Some(description) = &self.description {
+            fields.push(format!("description: {description}"));
+        }
+        if let Some(source) = self.source {
+            fields.push(format!("source: {source}"));
+        }
+        if let Some(page) = self.page {
+            fields.push(format!("page: {page}"));
+        }
+        if fields.is_empty() {
+            return f.write_str("-");
+        }
+        f.write_str(&fields.join(", "))
+    }
+}
+
+// ── AfRelationship ────────────────────────────────────────────────────────────
+
+/// Value of a file specification's `/AFRelationship` entry (ISO 32000-2 §7.11.3).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
+pub enum AfRelationship {
+    /// Original source material for the associated content.
+    Source,
+    /// Information used to derive a visual presentation (e.g. invoice XML).
+    Data,
+    /// An alternative representation of content.
+    Alternative,
+    /// A supplemental representation of the original source or data.
+    Supplement,
+    /// An encrypted payload document.
+    EncryptedPayload,
+    /// Data associated with an AcroForm.
+    FormData,
+    /// A schema definition for the associated object.
+    Schema,
+    /// The relationship is not known or cannot be described.
+    Unspecified,
+    /// Any second-class name not defined by the specification.
+    Other(String),
+}
+
+impl AfRelationship {
+    /// Map a PDF name (without the leading `/`) to a relationship.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "Source" => Self::Source,
+            "Data" => Self::Data,
+            "Alternative" => Self::Alternative,
+            "Supplement" => Self::Supplement,
+            "EncryptedPayload" => Self::EncryptedPayload,
+            "FormData" => Self::FormData,
+            "Schema" => Self::Schema,
+            "Unspecified" => Self::Unspecified,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+
+    /// The PDF name for this relationship (without the leading `/`).
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Source => "Source",
+            Self::Data => "Data",
+            Self::Alternative => "Alternative",
+            Self::Supplement => "Supplement",
+            Self::EncryptedPayload => "EncryptedPayload",
+            Self::FormData => "FormData",
+            Self::Schema => "Schema",
+            Self::Unspecified => "Unspecified",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for AfRelationship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ── AttachmentSource ──────────────────────────────────────────────────────────
+
+/// The discovery mechanism through which an embedded file was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
+pub enum AttachmentSource {
+    /// The catalog's `/Names/EmbeddedFiles` name tree.
+    NamesTree,
+    /// A `/FileAttachment` annotation on a page.
+    Annotation,
+    /// An `/AF` associated-files array on the catalog or a page (PDF/A-3).
+    AssociatedFiles,
+    /// A scan of every object for file specifications, including ones that
+    /// nothing in the document references.
+    DeepScan,
+}
+
+impl AttachmentSource {
+    /// Sources searched when [`ExtractorConfig::discovery_sources`] is `None`.
+    /// [`AssociatedFiles`](Self::AssociatedFiles) is opt-in so that default
+    /// discovery finds the same files as before it existed, and
+    /// [`DeepScan`](Self::DeepScan) because it visits every object.
+    ///
+    /// [`ExtractorConfig::discovery_sources`]: crate::ExtractorConfig::discovery_sources
+    pub const DEFAULT: &'static [AttachmentSource] =
+        &[AttachmentSource::NamesTree, AttachmentSource::Annotation];
+
+    /// Short, stable identifier (e.g. `"names-tree"`) suitable for CLI flags.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NamesTree => "names-tree",
+            Self::Annotation => "annotation",
+            Self::AssociatedFiles => "af",
+            Self::DeepScan => "deep-scan",
+        }
+    }
+}
+
+impl std::str::FromStr for AttachmentSource {
+    type Err = String;
+
+    /// Parse the identifiers produced by [`as_str`](Self::as_str);
+    /// `"annotations"` is accepted as an alias for `"annotation"`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "names-tree" => Ok(Self::NamesTree),
+            "annotation" | "annotations" => Ok(Self::Annotation),
+            "af" => Ok(Self::AssociatedFiles),
+            "deep-scan" => Ok(Self::DeepScan),
+            other => Err(format!(
+                "unknown source '{other}' (expected names-tree, annotations, af or deep-scan)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AttachmentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::document_metadata::{same_language, DocumentMetadata};
+use crate::kind::is_invoice_filename;
+use crate::{xmp, PdfAnalyzer};
+use std::collections::BTreeSet;
+
+/// Namespaces of the XMP schemas that e-invoicing specifications define for
+/// the invoice attachment: Factur-X and ZUGFeRD 2.1, Order-X, ZUGFeRD 2.0
+/// and ZUGFeRD 1.0.
+pub(crate) const INVOICE_NAMESPACES: [&str; 4] = [
+    "urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#",
+    "urn:factur-x:pdfa:CrossIndustryDocument:1p0#",
+    "urn:zugferd:pdfa:CrossIndustryDocument:invoice:2p0#",
+    ZUGFERD_1_XMP_NAMESPACE,
+];
+
+/// Namespace of the ZUGFeRD 1.0 XMP schema (`zf`).
+pub(crate) const ZUGFERD_1_XMP_NAMESPACE: &str = "urn:ferd:pdfa:CrossIndustryDocument:invoice:1p0#";
+
+// ── ConformanceWarning ────────────────────────────────────────────────────────
+
+/// A gap in a document's PDF/A or e-invoicing declarations that validators
+/// such as veraPDF reject, although the document itself reads fine.
+///
+/// Returned by [`PdfAnalyzer::conformance_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConformanceWarning {
+    /// What is missing.
+    pub kind: ConformanceWarningKind,
+
+    /// A human-readable description.
+    pub message: String,
+}
+
+/// The category of a [`ConformanceWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConformanceWarningKind {
+    /// The XMP packet uses invoice properties (`fx:DocumentType` and the
+    /// like) but has no `pdfaExtension` schema for their namespace, which
+    /// PDF/A requires for every non-standard property.
+    MissingExtensionSchema,
+    /// The extension schema exists but does not describe a property that the
+    /// packet uses.
+    MissingPropertyDescription,
+    /// The invoice attachment's file specification has no `/AFRelationship`.
+    MissingAfRelationship,
+    /// The catalog's `/Lang` names a language that none of the XMP
+    /// `dc:language` entries matches.
+    LanguageMismatch,
+}
+
+impl PdfAnalyzer {
+    /// Checks the declarations that Factur-X and ZUGFeRD documents need
+    /// beyond the PDF/A identification:
+    ///
+    /// - the XMP packet describes the invoice schema (`fx:`) in a
+    ///   `pdfaExtension` schema, including every property it uses,
+    /// - the invoice attachment, named by `fx:DocumentFileName` or one of the
+    ///   prescribed filenames, has an `/AFRelationship`, unless the document
+    ///   is a ZUGFeRD 1.0 invoice, which predates that requirement,
+    /// - the catalog's `/Lang` agrees with the XMP `dc:language`, when both
+    ///   are given.
+    ///
+    /// A document without XMP metadata or attachments yields no warnings
+    /// from the respective check.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// for warning in analyzer.conformance_warnings() {
+    ///     println!("{:?}: {}", warning.kind, warning.message);
+    /// }
+    /// ```
+    pub fn conformance_warnings(&self) -> Vec<ConformanceWarning> {
+        let mut warnings = Vec::new();
+        let mut invoice_name = None;
+        let mut legacy_zugferd = false;
+        if let Ok(xmp) = self.xmp_info() {
+            warnings.extend(extension_schema_warnings(&xmp.packet));
+            let declared = INVOICE_NAMESPACES.iter().find_map(|ns| {
+                let name = xmp::property(&xmp.packet, ns, "fx", "DocumentFileName")?;
+                Some((name.to_owned(), *ns == ZUGFERD_1_XMP_NAMESPACE))
+            });
+            if let Some((name, legacy)) = declared {
+                invoice_name = Some(name);
+                legacy_zugferd = legacy;
+            }
+
+            let languages = DocumentMetadata::from_xmp(&xmp.packet).languages;
+            if let Some(lang) = self.language() {
+                if !languages.is_empty() && !languages.iter().any(|l| same_language(l, &lang)) {
+                    warnings.push(ConformanceWarning {
+                        kind: ConformanceWarningKind::LanguageMismatch,
+                        message: format!(
+                            "catalog /Lang is '{lang}' but XMP dc:language is '{}'",
+                            languages.join(", ")
+                        ),
+                    });
+                }
+            }
+        }
+
+        for handle in self.embedded_file_handles().unwrap_or_default() {
+            let name = handle.filename();
+            let is_invoice = invoice_name.as_deref() == Some(name) || is_invoice_filename(name);
+            if is_invoice && !legacy_zugferd && handle.metadata().af_relationship.is_none() {
+                warnings.push(ConformanceWarning {
+                    kind: ConformanceWarningKind::MissingAfRelationship,
+                    message: format!("invoice attachment '{name}' has no /AFRelationship"),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+/// Warnings for invoice properties in `packet` that no extension schema
+/// describes.
+fn extension_schema_warnings(packet: &str) -> Vec<ConformanceWarning> {
+    let schemas = xmp::property_values(
+        packet,
+        xmp::PDFA_SCHEMA_NAMESPACE,
+        "pdfaSchema",
+        "namespaceURI",
+    );
+    let properties =
+        xmp::property_values(packet, xmp::PDFA_PROPERTY_NAMESPACE, "pdfaProperty", "name");
+
+    let mut warnings = Vec::new();
+    for namespace in INVOICE_NAMESPACES {
+        let prefixes: BTreeSet<&str> = xmp::bound_prefixes(packet, namespace).into_iter().collect();
+        for prefix in prefixes {
+            let used = used_properties(packet, prefix);
+            if used.is_empty() {
+                continue;
+            }
+            if !schemas.contains(&namespace) {
+                warnings.push(ConformanceWarning {
+                    kind: ConformanceWarningKind::MissingExtensionSchema,
+                    message: format!(
+                        "XMP uses {prefix}: properties but no pdfaExtension schema describes {namespace}"
+                    ),
+                });
+                continue;
+            }
+            for name in used.into_iter().filter(|name| !properties.contains(name)) {
+                warnings.push(ConformanceWarning {
+                    kind: ConformanceWarningKind::MissingPropertyDescription,
+                    message: format!("the extension schema does not describe {prefix}:{name}"),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+/// The names of the properties `packet` sets under `prefix`, as attributes
+/// or start tags.
+fn used_properties<'a>(packet: &'a str, prefix: &str) -> BTreeSet<&'a str> {
+    let qualified = format!("{prefix}:");
+    packet
+        .match_indices(&qualified)
+        .filter(|(at, _)| {
+            packet[..*at]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c == '<' || c.is_whitespace())
+        })
+        .filter_map(|(at, _)| {
+            let rest = &packet[at + qualified.len()..];
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+                .unwrap_or(rest.len());
+            (end > 0).then(|| &rest[..end])
+        })
+        .collect()
+}
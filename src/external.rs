@@ -0,0 +1,65 @@
+use crate::{AttachmentSource, PdfAnalyzer, Result};
+
+// ── ExternalReference ─────────────────────────────────────────────────────────
+
+/// A file specification that points to a file outside the document instead
+/// of embedding it.
+///
+/// This covers plain-string file specifications (a path), dictionaries with
+/// `/FS /URL` (a URL), and dictionaries without an `/EF` entry. No data can be
+/// extracted from such a specification; extracting it yields
+/// [`ExtractError::ExternalReference`], and callers decide whether to fetch
+/// the target themselves.
+///
+/// [`ExtractError::ExternalReference`]: crate::ExtractError::ExternalReference
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExternalReference {
+    /// Name-tree key or annotation display name.
+    pub name: String,
+
+    /// Whether [`target`](Self::target) is a file path or a URL.
+    pub kind: ExternalKind,
+
+    /// The referenced file path or URL, as written in the document.
+    pub target: String,
+
+    /// The `/Desc` entry of the file specification, if any.
+    pub description: Option<String>,
+
+    /// Discovery mechanism that located the specification.
+    pub source: AttachmentSource,
+
+    /// 1-based page number for annotation-borne specifications.
+    pub page: Option<u32>,
+}
+
+/// What an [`ExternalReference`] points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExternalKind {
+    /// A file path, relative to the document or absolute.
+    File,
+    /// A uniform resource locator (`/FS /URL`).
+    Url,
+}
+
+impl PdfAnalyzer {
+    /// Returns every file specification that refers to an external file
+    /// rather than an embedded stream.
+    ///
+    /// Path strings (indirect, or inline in the names tree or an annotation),
+    /// URL specifications, and specification dictionaries without `/EF` are
+    /// reported. The configured
+    /// [`discovery_sources`](crate::ExtractorConfig::discovery_sources) apply.
+    pub fn external_references(&self) -> Result<Vec<ExternalReference>> {
+        let engine = self.engine()?;
+        let mut refs: Vec<_> = engine
+            .specs()
+            .iter()
+            .filter_map(|spec| engine.external_reference(spec))
+            .collect();
+        refs.extend(engine.inline_references());
+        Ok(refs)
+    }
+}
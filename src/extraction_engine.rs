@@ -1,128 +1,778 @@
-use crate::file_discovery::FileSpecDiscovery;
+use crate::backend::PdfBackend;
+use crate::classifier;
+use crate::deadline::Deadline;
+use crate::file_discovery::{FileSpec, FileSpecDiscovery};
 use crate::file_parsing::FileSpecParser;
-use crate::{EmbeddedFile, ExtractError, ExtractorConfig, Result};
-use lopdf::{Document, ObjectId};
+use crate::memory::MemoryBudget;
+use crate::mime;
+#[cfg(feature = "fs")]
+use crate::pdf_utils;
+use crate::progress::ProgressTracker;
+#[cfg(feature = "fs")]
+use crate::provenance::{self, SourceInfo};
+use crate::risk;
+use crate::{
+    AttachmentSource, AuditEvent, EmbeddedFile, EmbeddedFileMetadata, ExternalReference,
+    ExtractError, ExtractionReport, ExtractionStage, ExtractionStatistics, ExtractionWarning,
+    ExtractorConfig, Result, RiskPolicy, ScanPolicy, ScanVerdict, SizeMismatch,
+};
+use lopdf::Document;
+#[cfg(feature = "fs")]
+use lopdf::ObjectId;
+use std::collections::hash_map::{Entry, HashMap};
+#[cfg(feature = "fs")]
 use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+/// The outcome for one file specification: the file, with a warning if it
+/// was not written to disk because another file has its name, or why it was
+/// skipped.
+type FileOutcome =
+    std::result::Result<(EmbeddedFile, Option<ExtractionWarning>), ExtractionWarning>;
+
+/// What became of an extracted file that was to be written to disk.
+#[derive(Debug)]
+enum Written {
+    /// Nothing was written: writing is not configured, or the same content
+    /// is already on disk under the file's name.
+    Nowhere,
+    /// The file was written to this path.
+    To(PathBuf),
+    /// Another file already has the path, so this one was not written.
+    Kept(ExtractionWarning),
+}
 
 /// Central extraction engine that orchestrates the complete file extraction process.
-pub struct ExtractionEngine<'a> {
-    document: &'a Document,
+///
+/// Discovery runs once per analyzer (see [`ExtractionEngine::discover`]); the
+/// engine itself only works on the resulting file specifications.
+///
+/// The engine reads objects through [`PdfBackend`], so it runs on any object
+/// graph, not only a parsed [`Document`].
+pub struct ExtractionEngine<'a, D: PdfBackend + ?Sized = Document> {
+    document: &'a D,
     config: &'a ExtractorConfig,
+    specs: &'a [FileSpec],
+    deadline: Deadline,
+    memory: MemoryBudget,
+    /// What was written to disk under each path, so an attachment cannot
+    /// overwrite another one, or a provenance sidecar, of the same name.
+    #[cfg(feature = "fs")]
+    written: WrittenFiles,
+    /// The document the files come from, recorded in provenance sidecars.
+    #[cfg(feature = "fs")]
+    source: Option<&'a SourceInfo>,
+}
+
+/// The files written to disk so far, by path.
+///
+/// Clones share the same map. The analyzer and its streams hand one to every
+/// engine they build, so the bookkeeping spans operations: a file written by
+/// one call is not overwritten by another attachment of the same name in the
+/// next.
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WrittenFiles(Arc<Mutex<HashMap<PathBuf, Output>>>);
+
+/// The kind of file an [`ExtractionEngine`] wrote under a path.
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    /// The attachment of the file specification `spec`, whose data has the
+    /// SHA-256 digest `sha256`.
+    Attachment {
+        spec: ObjectId,
+        sha256: [u8; 32],
+    },
+    Sidecar,
 }
 
-impl<'a> ExtractionEngine<'a> {
-    pub fn new(document: &'a Document, config: &'a ExtractorConfig) -> Self {
-        Self { document, config }
+impl<'a, D: PdfBackend + ?Sized> ExtractionEngine<'a, D> {
+    pub fn new(document: &'a D, config: &'a ExtractorConfig, specs: &'a [FileSpec]) -> Self {
+        Self {
+            document,
+            config,
+            specs,
+            deadline: Deadline::default(),
+            memory: MemoryBudget::default(),
+            #[cfg(feature = "fs")]
+            written: WrittenFiles::default(),
+            #[cfg(feature = "fs")]
+            source: None,
+        }
+    }
+
+    /// Fail with [`ExtractError::Timeout`] once `deadline` expires.
+    pub(crate) fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Fail with [`ExtractError::MemoryLimit`] once decoded data outgrows
+    /// `memory`.
+    pub(crate) fn with_memory(mut self, memory: MemoryBudget) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Share the record of written files with other engines, so none of them
+    /// overwrites a file another one wrote.
+    #[cfg(feature = "fs")]
+    pub(crate) fn with_written(mut self, written: WrittenFiles) -> Self {
+        self.written = written;
+        self
+    }
+
+    /// Record `source` as the origin of the files in provenance sidecars.
+    #[cfg(feature = "fs")]
+    pub(crate) fn with_source(mut self, source: &'a SourceInfo) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Build a parser that enforces the configured size limit while decoding.
+    fn parser(&self) -> FileSpecParser<'a, D> {
+        FileSpecParser::new(
+            self.document,
+            self.config.max_embedded_file_size,
+            self.config.recover_truncated_streams,
+        )
+        .with_deadline(self.deadline)
+        .with_memory(self.memory.clone())
+    }
+
+    /// Discover all file specifications from the sources selected in `config`,
+    /// giving up with [`ExtractError::Timeout`] once `deadline` expires.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub(crate) fn discover(
+        document: &D,
+        config: &ExtractorConfig,
+        deadline: Deadline,
+    ) -> Result<Vec<FileSpec>> {
+        let specs = FileSpecDiscovery::new(document, Self::sources(config))
+            .with_deadline(deadline)
+            .collect_file_specs()?;
+        log::debug!("discovered {} file specification(s)", specs.len());
+        Ok(specs)
+    }
+
+    /// The discovery sources selected in `config`.
+    fn sources(config: &ExtractorConfig) -> &[AttachmentSource] {
+        config
+            .discovery_sources
+            .as_deref()
+            .unwrap_or(AttachmentSource::DEFAULT)
     }
 
     /// Extract all embedded files from the document.
     pub fn extract_all_files(&self) -> Result<Vec<EmbeddedFile>> {
-        let specs = self.discover_file_specs()?;
-        let files = self.parse_and_process_files(specs);
-        
-        if files.is_empty() {
+        let report = self.extract_report()?;
+
+        if report.files.is_empty() {
             return Err(ExtractError::NoEmbeddedFiles);
         }
-        
-        Ok(files)
+
+        Ok(report.files)
+    }
+
+    /// Extract all embedded files and collect a warning for every file that
+    /// was skipped or extracted with problems.
+    pub fn extract_report(&self) -> Result<ExtractionReport> {
+        if self.specs.is_empty() {
+            return Err(ExtractError::NoEmbeddedFiles);
+        }
+
+        let mut report = ExtractionReport::default();
+        // Written files are checked for clashes as they are written, against
+        // earlier calls too; otherwise, against the earlier files here.
+        let writes_to_disk = self.writes_to_disk();
+        let mut first_by_name: HashMap<String, usize> = HashMap::new();
+        for outcome in self.parse_and_process_files()? {
+            match outcome {
+                Ok((file, kept)) => {
+                    report.warnings.extend(ExtractionWarning::for_file(&file));
+                    match first_by_name.entry(file.filename.clone()) {
+                        Entry::Occupied(first)
+                            if !writes_to_disk && report.files[*first.get()].data != file.data =>
+                        {
+                            report
+                                .warnings
+                                .push(ExtractionWarning::duplicate(&file, false))
+                        }
+                        Entry::Occupied(_) => {}
+                        Entry::Vacant(entry) => {
+                            entry.insert(report.files.len());
+                        }
+                    }
+                    report.warnings.extend(kept);
+                    report.files.push(file);
+                }
+                Err(warning) => report.warnings.push(warning),
+            }
+        }
+        for warning in &report.warnings {
+            log::warn!("'{}': {}", warning.name, warning.message);
+        }
+        report.statistics = ExtractionStatistics::from_files(&report.files);
+        Ok(report)
     }
 
     /// Count embedded files in the document.
-    pub fn count_files(&self) -> Result<usize> {
-        let specs = FileSpecDiscovery::new(self.document).collect_file_specs()?;
-        Ok(specs.len())
+    pub fn count_files(&self) -> usize {
+        self.specs.len()
     }
 
     /// Check if document has embedded files.
-    pub fn has_files(&self) -> Result<bool> {
-        let specs = FileSpecDiscovery::new(self.document).collect_file_specs()?;
-        Ok(!specs.is_empty())
+    pub fn has_files(&self) -> bool {
+        !self.specs.is_empty()
     }
 
-    /// Discover all file specifications in the document.
-    fn discover_file_specs(&self) -> Result<Vec<(String, ObjectId)>> {
-        let discovery = FileSpecDiscovery::new(self.document);
-        let specs = discovery.collect_file_specs()?;
+    /// List the filename of every embedded file without decoding any stream.
+    pub fn list_file_names(&self) -> Vec<String> {
+        let parser = self.parser();
+        self.specs
+            .iter()
+            .map(|spec| parser.filename(&spec.name, spec.id))
+            .collect()
+    }
 
-        if specs.is_empty() {
-            return Err(ExtractError::NoEmbeddedFiles);
-        }
+    /// Extract the first embedded file whose filename (or name-tree key)
+    /// equals `name`. Only that file's stream is decoded.
+    pub fn extract_file(&self, name: &str) -> Result<EmbeddedFile> {
+        let parser = self.parser();
 
-        Ok(specs)
-    }
+        let spec = self
+            .specs
+            .iter()
+            .find(|spec| spec.name == name || parser.filename(&spec.name, spec.id) == name)
+            .ok_or_else(|| ExtractError::FileNotFound(name.into()))?;
 
-    /// Parse file specifications and return successfully processed files.
-    fn parse_and_process_files(&self, specs: Vec<(String, ObjectId)>) -> Vec<EmbeddedFile> {
-        let parser = FileSpecParser::new(self.document);
-        let mut results = Vec::new();
+        self.decode_file(spec)
+    }
 
-        for (name, spec_id) in specs {
-            match self.process_single_file(&parser, &name, spec_id) {
-                Some(file) => results.push(file),
-                None => continue, // Error already logged
+    /// Decode `spec` into an [`EmbeddedFile`], checking it as
+    /// [`prepare_file`](Self::prepare_file) does and writing it to disk if
+    /// configured.
+    pub fn decode_file(&self, spec: &FileSpec) -> Result<EmbeddedFile> {
+        let mut written = None;
+        let file = self.prepare_file(&self.parser(), spec).and_then(|f| {
+            match self.write_file(spec, &f) {
+                Ok(Written::To(path)) => written = Some(path),
+                Ok(Written::Kept(warning)) => log::warn!("'{}': {}", warning.name, warning.message),
+                Ok(Written::Nowhere) => {}
+                Err(e) => return Err(self.reject(f.data.len(), e)),
             }
-        }
+            Ok(f)
+        });
+        self.audit(spec, &file, written);
+        file
+    }
+
+    /// Decode `spec` into an [`EmbeddedFile`], checking it as
+    /// [`prepare_file`](Self::prepare_file) does but never writing it to
+    /// disk.
+    pub fn read_embedded_file(&self, spec: &FileSpec) -> Result<EmbeddedFile> {
+        let file = self.prepare_file(&self.parser(), spec);
+        self.audit(spec, &file, None);
+        file
+    }
+
+    /// Decode `spec` for the crate's own inspection: the size limit and
+    /// deadline apply, but the file is not scanned, risk-checked, audited or
+    /// written.
+    pub(crate) fn inspect_file(&self, spec: &FileSpec) -> Result<EmbeddedFile> {
+        let file = self.parse_spec(&self.parser(), spec)?;
+        self.validate_file_size(file.data.len())
+            .map_err(|e| self.reject(file.data.len(), e))?;
+        Ok(file)
+    }
+
+    /// Release the `bytes` a rejected file was charged while it was decoded,
+    /// and pass `error` on.
+    fn reject(&self, bytes: usize, error: ExtractError) -> ExtractError {
+        self.memory.release(bytes);
+        error
+    }
 
-        results
+    /// Read the filename and metadata of `spec` without decoding its stream.
+    pub fn file_info(&self, spec: &FileSpec) -> Result<(String, EmbeddedFileMetadata)> {
+        self.reject_external(spec)?;
+        let parser = self.parser();
+        let (filename, mut metadata) = parser.parse_file_info(&spec.name, spec.id)?;
+        metadata.source = Some(spec.source);
+        metadata.page = spec.page;
+        Ok((filename, metadata))
+    }
+
+    /// Decode the stream of `spec`, checking it as
+    /// [`prepare_file`](Self::prepare_file) does.
+    pub fn read_file(&self, spec: &FileSpec) -> Result<Vec<u8>> {
+        let data = self
+            .prepare_file(&self.parser(), spec)
+            .map(EmbeddedFile::into_data);
+        self.audit_data(spec, &data);
+        data
+    }
+
+    /// Describe `spec` as an [`ExternalReference`] if it points to a file
+    /// outside the document.
+    pub fn external_reference(&self, spec: &FileSpec) -> Option<ExternalReference> {
+        let (kind, target, description) = self.parser().external_target(spec.id)?;
+        Some(ExternalReference {
+            name: spec.name.clone(),
+            kind,
+            target,
+            description,
+            source: spec.source,
+            page: spec.page,
+        })
+    }
+
+    /// External references written inline (without an object of their own)
+    /// in the configured discovery sources.
+    pub fn inline_references(&self) -> Vec<ExternalReference> {
+        FileSpecDiscovery::new(self.document, Self::sources(self.config))
+            .collect_inline_references()
+    }
+
+    /// The file specifications this engine operates on.
+    pub fn specs(&self) -> &'a [FileSpec] {
+        self.specs
+    }
+
+    /// Parse file specifications and return, per file, either the processed
+    /// file or the warning explaining why it was skipped.
+    ///
+    /// Failures are returned as errors instead when
+    /// [`ExtractorConfig::fail_on_any_error`] is set. With the `parallel`
+    /// feature, streams are decoded concurrently; the result keeps discovery
+    /// order either way, and files are written to disk one after another in
+    /// that order, so the same file wins a name clash on every run.
+    #[cfg(not(feature = "parallel"))]
+    fn parse_and_process_files(&self) -> Result<Vec<FileOutcome>> {
+        let parser = self.parser();
+        let progress = ProgressTracker::new(self.config.progress.as_ref(), self.specs.len());
+        self.specs
+            .iter()
+            .map(|spec| self.finish_file(Some(&progress), spec, self.prepare_file(&parser, spec)))
+            .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    fn parse_and_process_files(&self) -> Result<Vec<FileOutcome>> {
+        use rayon::prelude::*;
+
+        let parser = self.parser();
+        let progress = ProgressTracker::new(self.config.progress.as_ref(), self.specs.len());
+        let prepared: Vec<_> = self
+            .specs
+            .par_iter()
+            .map(|spec| {
+                let file = self.prepare_file(&parser, spec);
+                progress.file_done(file.as_ref().map_or(0, |f| f.data.len()));
+                file
+            })
+            .collect();
+        self.specs
+            .iter()
+            .zip(prepared)
+            .map(|(spec, file)| self.finish_file(None, spec, file))
+            .collect()
+    }
+
+    /// Decode and validate a single file specification, without writing it.
+    ///
+    /// A returned file is charged to the memory budget; a rejected one is
+    /// released again.
+    fn prepare_file(&self, parser: &FileSpecParser<D>, spec: &FileSpec) -> Result<EmbeddedFile> {
+        let parsed = self.parse_spec(parser, spec);
+        let charged = parsed.as_ref().map_or(0, |f| f.data.len());
+        let file = parsed
+            .and_then(|f| self.check_fully_decoded(spec, f))
+            .and_then(|f| self.validate_and_scan(&f.filename, &f.data).map(|()| f));
+        if file.is_err() {
+            self.memory.release(charged);
+        }
+        file
     }
 
-    /// Process a single file specification with validation and optional disk writing.
-    fn process_single_file(
+    /// Write a file prepared by [`prepare_file`](Self::prepare_file) to disk
+    /// if configured, and report it to `progress` as processed, unless it was
+    /// already reported when it was decoded.
+    ///
+    /// A returned file stays charged to the memory budget, since the results
+    /// accumulate; a rejected one is released again.
+    ///
+    /// A failure becomes a [`WarningKind::Skipped`](crate::WarningKind::Skipped)
+    /// warning, or is returned as the error itself when
+    /// [`ExtractorConfig::fail_on_any_error`] is set. A timeout, an exhausted
+    /// memory budget, and a scanner hit under [`ScanPolicy::Fail`], always
+    /// abort the whole extraction.
+    fn finish_file(
         &self,
-        parser: &FileSpecParser,
-        name: &str,
-        spec_id: ObjectId,
-    ) -> Option<EmbeddedFile> {
-        // Parse the file
-        let file = match parser.parse_file_spec(name, spec_id) {
-            Ok(f) => f,
-            Err(e) => {
-                eprintln!("extractEmbedFilePDF: warning: skipping '{}': {}", name, e);
-                return None;
+        progress: Option<&ProgressTracker>,
+        spec: &FileSpec,
+        file: Result<EmbeddedFile>,
+    ) -> Result<FileOutcome> {
+        let mut written = None;
+        let mut kept = None;
+        let file = file.and_then(|f| {
+            match self.write_file(spec, &f) {
+                Ok(Written::To(path)) => written = Some(path),
+                Ok(Written::Kept(warning)) => kept = Some(warning),
+                Ok(Written::Nowhere) => {}
+                Err(e) => return Err(self.reject(f.data.len(), e)),
             }
+            Ok(f)
+        });
+        self.audit(spec, &file, written);
+        if let Some(progress) = progress {
+            progress.file_done(file.as_ref().map_or(0, |f| f.data.len()));
+        }
+
+        match file {
+            Ok(f) => Ok(Ok((f, kept))),
+            Err(e @ (ExtractError::Timeout(_) | ExtractError::MemoryLimit(_))) => Err(e),
+            Err(e @ ExtractError::Infected { .. })
+                if self.config.scan_policy == ScanPolicy::Fail =>
+            {
+                Err(e)
+            }
+            Err(e) if self.config.fail_on_any_error => Err(e),
+            Err(e) => Ok(Err(ExtractionWarning::skipped(&spec.name, &e))),
+        }
+    }
+
+    /// In strict mode, reject a file that is still encoded or truncated, or
+    /// whose decoded length differs from the declared size; it was not
+    /// extracted as declared even though decoding did not fail outright.
+    fn check_fully_decoded(&self, spec: &FileSpec, file: EmbeddedFile) -> Result<EmbeddedFile> {
+        if !self.config.fail_on_any_error
+            || (file.is_fully_decoded() && file.metadata.size_mismatch.is_none())
+        {
+            return Ok(file);
+        }
+        let reason = if let Some(mismatch) = file.metadata.size_mismatch {
+            format!(
+                "decoded to {} bytes but /Params/Size declares {}",
+                mismatch.actual, mismatch.declared
+            )
+        } else if file.metadata.truncated {
+            "stream is truncated".to_string()
+        } else {
+            format!(
+                "cannot apply filters {}",
+                file.metadata.undecoded_filters.join(", ")
+            )
         };
+        Err(
+            ExtractError::extraction(&spec.name, ExtractionStage::Decode, reason)
+                .with_spec_id(spec.id),
+        )
+    }
 
-        // Validate and process
-        if let Err(e) = self.validate_and_write_file(&file) {
-            eprintln!("extractEmbedFilePDF: error processing '{}': {}", name, e);
-            return None;
+    /// Screen the declared size, then decode `spec` and attach its discovery
+    /// source and page to the metadata.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(name = %spec.name)))]
+    fn parse_spec(&self, parser: &FileSpecParser<D>, spec: &FileSpec) -> Result<EmbeddedFile> {
+        self.deadline.check()?;
+        self.reject_external(spec)?;
+        self.screen_declared_size(parser, spec)?;
+        let started = self.config.metrics.is_some().then(Instant::now);
+        let mut file = parser.parse_file_spec(&spec.name, spec.id)?;
+        if let (Some(metrics), Some(started)) = (&self.config.metrics, started) {
+            metrics.record_decode(&spec.name, started.elapsed(), file.data.len());
         }
+        file.metadata.source = Some(spec.source);
+        file.metadata.page = spec.page;
+        file.metadata.detected_mime_type = mime::sniff(&file.data)
+            .or_else(|| match file.metadata.mime_type {
+                Some(_) => None,
+                None => self.config.mime_types.for_filename(&file.filename),
+            })
+            .map(str::to_owned);
+        if file.is_fully_decoded() && file.size_matches_declaration() == Some(false) {
+            file.metadata.size_mismatch = file.declared_size().map(|declared| SizeMismatch {
+                declared,
+                actual: file.len(),
+            });
+        }
+        if self.config.risk_policy == RiskPolicy::Flag {
+            file.metadata.risk = file.risk_category();
+            file.metadata.encrypted_container = risk::is_encrypted_container(&file.data);
+        }
+        classifier::annotate(&self.config.classifiers, &mut file);
+        Ok(file)
+    }
 
-        Some(file)
+    /// Report the outcome of extracting `spec` to the audit sink, if any,
+    /// followed by where the file was `written` to disk.
+    fn audit(&self, spec: &FileSpec, outcome: &Result<EmbeddedFile>, written: Option<PathBuf>) {
+        let Some(audit) = &self.config.audit else {
+            return;
+        };
+        let file = match outcome {
+            Ok(file) => file,
+            Err(e) => return audit.record(AuditEvent::rejected(&spec.name, e)),
+        };
+        if file.verify_checksum() == Some(false) {
+            audit.record(AuditEvent::ChecksumFailed {
+                name: file.filename.clone(),
+                declared: file.metadata.checksum.clone().unwrap_or_default(),
+                actual: file.md5_hex(),
+            });
+        }
+        let sha256 = file.sha256_hex();
+        audit.record(AuditEvent::Extracted {
+            name: file.filename.clone(),
+            size: file.data.len(),
+            sha256: sha256.clone(),
+        });
+        if let Some(path) = written {
+            audit.record(AuditEvent::Written {
+                name: file.filename.clone(),
+                path,
+                sha256,
+            });
+        }
     }
 
-    /// Validate file and optionally write to disk based on configuration.
-    fn validate_and_write_file(&self, file: &EmbeddedFile) -> Result<()> {
-        self.validate_file_size(file)?;
-        self.write_file_if_configured(file)?;
-        Ok(())
+    /// Report the outcome of reading the raw data of `spec` to the audit
+    /// sink, if any.
+    fn audit_data(&self, spec: &FileSpec, outcome: &Result<Vec<u8>>) {
+        let Some(audit) = &self.config.audit else {
+            return;
+        };
+        audit.record(match outcome {
+            Ok(data) => AuditEvent::extracted(&self.parser().filename(&spec.name, spec.id), data),
+            Err(e) => AuditEvent::rejected(&spec.name, e),
+        });
+    }
+
+    /// Fail with [`ExtractError::ExternalReference`] when `spec` has no
+    /// embedded data because it points outside the document.
+    fn reject_external(&self, spec: &FileSpec) -> Result<()> {
+        match self.external_reference(spec) {
+            Some(reference) => Err(ExtractError::ExternalReference(Box::new(reference))),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject a file before decompression when its declared or raw size
+    /// already exceeds the configured maximum, so the limit bounds memory use
+    /// rather than only the returned result.
+    fn screen_declared_size(&self, parser: &FileSpecParser<D>, spec: &FileSpec) -> Result<()> {
+        if self.config.max_embedded_file_size.is_none() {
+            return Ok(());
+        }
+        match parser.decoded_size_hint(&spec.name, spec.id)? {
+            Some(hint) => self.validate_file_size(hint),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the file to disk if configured, and return what became of it.
+    fn write_file(&self, spec: &FileSpec, file: &EmbeddedFile) -> Result<Written> {
+        #[cfg(feature = "fs")]
+        return self.write_file_if_configured(spec, file);
+        #[cfg(not(feature = "fs"))]
+        {
+            let _ = (spec, file);
+            Ok(Written::Nowhere)
+        }
+    }
+
+    /// Enforce the size limit and risk policy, then pass `data` to the
+    /// configured scanner.
+    fn validate_and_scan(&self, filename: &str, data: &[u8]) -> Result<()> {
+        self.validate_file_size(data.len())?;
+        self.check_risk(filename, data)?;
+        self.scan(filename, data)
+    }
+
+    /// Under [`RiskPolicy::Block`], reject a file that falls into a
+    /// [`RiskCategory`](crate::RiskCategory).
+    fn check_risk(&self, filename: &str, data: &[u8]) -> Result<()> {
+        if self.config.risk_policy != RiskPolicy::Block {
+            return Ok(());
+        }
+        match risk::classify(filename, data) {
+            Some(category) => Err(ExtractError::DangerousFile {
+                name: filename.into(),
+                category,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the configured [`ContentScanner`](crate::ContentScanner) over
+    /// `data`. A flagged file fails with [`ExtractError::Infected`], after
+    /// being copied to the quarantine directory if the policy asks for it.
+    fn scan(&self, filename: &str, data: &[u8]) -> Result<()> {
+        let Some(scanner) = &self.config.scanner else {
+            return Ok(());
+        };
+        let threat = match scanner.scan(filename, data)? {
+            ScanVerdict::Clean => return Ok(()),
+            ScanVerdict::Infected(threat) => threat,
+        };
+        log::warn!("'{filename}' flagged by content scanner: {threat}");
+        #[cfg(feature = "fs")]
+        if let ScanPolicy::Quarantine(dir) = &self.config.scan_policy {
+            let dest = quarantine(dir, filename, data)?;
+            log::debug!("quarantined {} bytes to {}", data.len(), dest.display());
+        }
+        Err(ExtractError::Infected {
+            name: filename.into(),
+            threat,
+        })
     }
 
     /// Validate that the file size doesn't exceed the configured maximum.
-    fn validate_file_size(&self, file: &EmbeddedFile) -> Result<()> {
+    fn validate_file_size(&self, len: usize) -> Result<()> {
         if let Some(max_size) = self.config.max_embedded_file_size {
-            if file.data.len() > max_size {
+            if len > max_size {
                 return Err(ExtractError::FileSizeExceeded);
             }
         }
         Ok(())
     }
 
+    /// Whether extracted files are written to disk.
+    fn writes_to_disk(&self) -> bool {
+        #[cfg(feature = "fs")]
+        return self.config.extract_to_disk && self.config.output_directory.is_some();
+        #[cfg(not(feature = "fs"))]
+        false
+    }
+
     /// Write the file to disk if extract_to_disk is enabled and output_directory is set.
-    fn write_file_if_configured(&self, file: &EmbeddedFile) -> Result<()> {
+    ///
+    /// The file is written under its name reduced to a single path
+    /// component, so a name such as `../x` cannot leave the output
+    /// directory. A path another attachment was already written to, by this
+    /// engine or any other sharing its [`WrittenFiles`], is not written again.
+    /// If the content differs from the file written first, by SHA-256
+    /// digest, that file is kept and the clash is returned as a
+    /// [`WarningKind::DuplicateName`](crate::WarningKind::DuplicateName)
+    /// warning, which [`extract_report`](Self::extract_report) reports. The
+    /// same attachment extracted again is rewritten. Provenance sidecars
+    /// take part in the same bookkeeping, so a sidecar and an attachment
+    /// never overwrite each other.
+    #[cfg(feature = "fs")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(name = %file.filename)))]
+    fn write_file_if_configured(&self, spec: &FileSpec, file: &EmbeddedFile) -> Result<Written> {
+        use sha2::{Digest, Sha256};
+
         if !self.config.extract_to_disk {
-            return Ok(());
+            return Ok(Written::Nowhere);
         }
 
         let output_dir = match &self.config.output_directory {
             Some(dir) => dir,
-            None => return Ok(()),
+            None => return Ok(Written::Nowhere),
         };
 
-        let dest = Path::new(output_dir).join(&file.filename);
+        let name = pdf_utils::safe_filename(&file.filename);
+        let dest = Path::new(output_dir).join(&name);
+        let mut sidecar_path = dest.clone().into_os_string();
+        sidecar_path.push(".meta.json");
+        let mut sidecar = self.config.write_sidecars;
+        let sha256 = Sha256::digest(&file.data).into();
+        {
+            let mut written = self
+                .written
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match written.entry(dest.clone()) {
+                Entry::Occupied(first) => match *first.get() {
+                    Output::Attachment { spec: id, .. } if id == spec.id => {}
+                    Output::Attachment { sha256: first, .. } if first == sha256 => {
+                        return Ok(Written::Nowhere)
+                    }
+                    Output::Attachment { .. } => {
+                        return Ok(Written::Kept(ExtractionWarning::duplicate(file, true)))
+                    }
+                    Output::Sidecar => {
+                        return Ok(Written::Kept(ExtractionWarning::named_like_sidecar(file)))
+                    }
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(Output::Attachment {
+                        spec: spec.id,
+                        sha256,
+                    });
+                }
+            }
+            if sidecar {
+                match written.entry(sidecar_path.into()) {
+                    Entry::Occupied(first) if *first.get() == Output::Sidecar => {}
+                    Entry::Occupied(_) => {
+                        log::warn!(
+                            "'{}': an attachment is named like its provenance sidecar; not writing the sidecar",
+                            file.filename
+                        );
+                        sidecar = false;
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(Output::Sidecar);
+                    }
+                }
+            }
+        }
+
+        let started = self.config.metrics.is_some().then(Instant::now);
         std::fs::create_dir_all(output_dir)?;
         std::fs::write(&dest, &file.data)?;
-        
-        Ok(())
+        if let (Some(metrics), Some(started)) = (&self.config.metrics, started) {
+            metrics.record_write(&dest, started.elapsed(), file.data.len());
+        }
+        log::debug!("wrote {} bytes to {}", file.data.len(), dest.display());
+        if sidecar {
+            let sidecar = provenance::write_sidecar(&dest, file, self.source)?;
+            log::debug!("wrote provenance to {}", sidecar.display());
+        }
+
+        Ok(Written::To(dest))
     }
-}
\ No newline at end of file
+}
+
+/// Write `data` to a new file in the quarantine directory `dir`, named after
+/// the last path component of `filename`. A name that is already taken gets
+/// a numeric suffix (`evil-1.bin`, `evil-2.bin`, …), so nothing quarantined
+/// earlier is ever overwritten.
+#[cfg(feature = "fs")]
+fn quarantine(dir: &Path, filename: &str, data: &[u8]) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+
+    std::fs::create_dir_all(dir)?;
+    let name = PathBuf::from(pdf_utils::safe_filename(filename));
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = name
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut suffix = 0u64;
+    loop {
+        let dest = match suffix {
+            0 => dir.join(&name),
+            n => dir.join(format!("{stem}-{n}{extension}")),
+        };
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&dest)
+        {
+            Ok(mut file) => {
+                file.write_all(data)?;
+                return Ok(dest);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -1,18 +1,49 @@
-use crate::{pdf_utils, Result};
+use crate::backend::PdfBackend;
+use crate::deadline::Deadline;
+use crate::{name_tree, pdf_utils, AttachmentSource, ExternalKind, ExternalReference, Result};
 use lopdf::{Document, ObjectId};
+use std::collections::HashSet;
+
+/// A discovered file specification together with where it was found.
+#[derive(Debug, Clone)]
+pub struct FileSpec {
+    /// Name-tree key or annotation display name.
+    pub name: String,
+    /// Object ID of the file specification dictionary.
+    pub id: ObjectId,
+    /// Discovery mechanism that located this specification.
+    pub source: AttachmentSource,
+    /// 1-based page number for annotation-borne attachments.
+    pub page: Option<u32>,
+}
 
 /// Handles discovery of embedded file specifications from PDF documents.
 ///
-/// This module contains logic to find file specifications from two sources:
+/// This module contains logic to find file specifications from four sources:
 /// 1. The /Names/EmbeddedFiles name tree in the document catalog
 /// 2. /FileAttachment annotations on pages
-pub struct FileSpecDiscovery<'a> {
-    document: &'a Document,
+/// 3. /AF associated-file arrays on the catalog and pages
+/// 4. A deep scan of every object for file specification dictionaries
+pub struct FileSpecDiscovery<'a, D: PdfBackend + ?Sized = Document> {
+    document: &'a D,
+    sources: &'a [AttachmentSource],
+    deadline: Deadline,
 }
 
-impl<'a> FileSpecDiscovery<'a> {
-    pub fn new(document: &'a Document) -> Self {
-        Self { document }
+impl<'a, D: PdfBackend + ?Sized> FileSpecDiscovery<'a, D> {
+    /// Create a discovery pass that searches only the given `sources`.
+    pub fn new(document: &'a D, sources: &'a [AttachmentSource]) -> Self {
+        Self {
+            document,
+            sources,
+            deadline: Deadline::default(),
+        }
+    }
+
+    /// Stop searching once `deadline` has expired.
+    pub(crate) fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
     }
 
     /// Helper to resolve a value that might be inline or a reference to a dictionary.
@@ -39,15 +70,20 @@ impl<'a> FileSpecDiscovery<'a> {
         }
     }
 
-    /// Process a names array, extracting (name, ObjectId) pairs.
-    fn process_names_array(&self, names_array: &[lopdf::Object]) -> Vec<(String, ObjectId)> {
+    /// Process a names array, extracting one [`FileSpec`] per (name, reference) pair.
+    fn process_names_array(&self, names_array: &[lopdf::Object]) -> Vec<FileSpec> {
         let mut pairs = Vec::new();
         let mut i = 0;
         while i + 1 < names_array.len() {
             if let Ok(name_bytes) = names_array[i].as_str() {
                 let name = String::from_utf8_lossy(name_bytes).into_owned();
-                if let Ok(spec_id) = names_array[i + 1].as_reference() {
-                    pairs.push((name, spec_id));
+                if let Ok(id) = names_array[i + 1].as_reference() {
+                    pairs.push(FileSpec {
+                        name,
+                        id,
+                        source: AttachmentSource::NamesTree,
+                        page: None,
+                    });
                 }
             }
             i += 2;
@@ -55,77 +91,164 @@ impl<'a> FileSpecDiscovery<'a> {
         pairs
     }
 
-    /// Collect `(name, ObjectId)` pairs for every embedded-file specification
-    /// in the document.
+    /// Collect a [`FileSpec`] for every embedded-file specification in the
+    /// document.
+    ///
+    /// The configured sources are searched in the order
+    /// names tree → annotations → `/AF` arrays → deep scan. A specification
+    /// reachable from several sources is reported once, attributed to the
+    /// first source that found it.
     ///
-    /// Two sources are searched:
-    /// 1. The `/Names/EmbeddedFiles` name tree in the document catalog.
-    /// 2. `/FileAttachment` annotations on every page.
-    pub fn collect_file_specs(&self) -> Result<Vec<(String, ObjectId)>> {
+    /// Fails with [`ExtractError::Timeout`](crate::ExtractError::Timeout)
+    /// when the deadline set by [`with_deadline`](Self::with_deadline)
+    /// expires during the search.
+    pub fn collect_file_specs(&self) -> Result<Vec<FileSpec>> {
         let mut specs = Vec::new();
         
-        specs.extend(self.collect_from_names_tree());
-        specs.extend(self.collect_from_annotations());
-        
+        if self.sources.contains(&AttachmentSource::NamesTree) {
+            specs.extend(self.collect_from_names_tree());
+            self.deadline.check()?;
+        }
+        if self.sources.contains(&AttachmentSource::Annotation) {
+            specs.extend(self.collect_from_annotations());
+            self.deadline.check()?;
+        }
+        if self.sources.contains(&AttachmentSource::AssociatedFiles) {
+            specs.extend(self.collect_from_associated_files());
+            self.deadline.check()?;
+        }
+        if self.sources.contains(&AttachmentSource::DeepScan) {
+            specs.extend(self.collect_from_deep_scan());
+            self.deadline.check()?;
+        }
+
+        let mut seen = HashSet::new();
+        specs.retain(|spec| seen.insert(spec.id));
+
         Ok(specs)
     }
 
     /// Collect file specifications from the document's names tree.
-    fn collect_from_names_tree(&self) -> Vec<(String, ObjectId)> {
+    fn collect_from_names_tree(&self) -> Vec<FileSpec> {
+        let mut specs = Vec::new();
+        self.for_each_names_array(&mut |names_array| {
+            specs.extend(self.process_names_array(names_array));
+        });
+        specs
+    }
+
+    /// Call `visit` with every leaf `/Names` array of the catalog's
+    /// `/EmbeddedFiles` name tree.
+    fn for_each_names_array(&self, visit: &mut dyn FnMut(&[lopdf::Object])) {
         let catalog = match self.document.catalog() {
             Ok(cat) => cat,
-            Err(_) => return Vec::new(),
+            Err(_) => return,
         };
 
         let names_val = match catalog.get(b"Names") {
             Ok(val) => val,
-            Err(_) => return Vec::new(),
+            Err(_) => return,
         };
 
         let names_dict = match self.resolve_dict(names_val) {
             Some(dict) => dict,
-            None => return Vec::new(),
+            None => return,
         };
 
         let ef_val = match names_dict.get(b"EmbeddedFiles") {
             Ok(val) => val,
-            Err(_) => return Vec::new(),
+            Err(_) => return,
         };
 
         if let Ok(ef_id) = ef_val.as_reference() {
-            self.walk_name_tree(ef_id)
+            name_tree::for_each_leaf(self.document, ef_id, self.deadline, visit)
         } else if let Ok(ef_dict) = ef_val.as_dict() {
             // Handle inline /EmbeddedFiles dictionary
-            self.extract_from_inline_ef_dict(ef_dict)
-        } else {
-            Vec::new()
+            if let Ok(names_array) = ef_dict.get(b"Names").and_then(|v| v.as_array()) {
+                visit(names_array);
+            }
         }
     }
 
-    /// Extract file specifications from an inline EmbeddedFiles dictionary.
-    fn extract_from_inline_ef_dict(&self, ef_dict: &lopdf::Dictionary) -> Vec<(String, ObjectId)> {
-        if let Ok(names_val) = ef_dict.get(b"Names") {
-            if let Ok(names_array) = names_val.as_array() {
-                return self.process_names_array(names_array);
+    /// Collect file specifications that are written inline as plain strings
+    /// in the names tree or in an annotation's `/FS` entry. Such a string is
+    /// the path of a file outside the document; it has no object ID and so
+    /// never appears among the [`FileSpec`]s.
+    pub fn collect_inline_references(&self) -> Vec<ExternalReference> {
+        let mut refs = Vec::new();
+
+        if self.sources.contains(&AttachmentSource::NamesTree) {
+            self.for_each_names_array(&mut |names_array| {
+                for pair in names_array.chunks_exact(2) {
+                    if let (Ok(name), Ok(target)) = (pair[0].as_str(), pair[1].as_str()) {
+                        refs.push(ExternalReference {
+                            name: String::from_utf8_lossy(name).into_owned(),
+                            kind: ExternalKind::File,
+                            target: String::from_utf8_lossy(target).into_owned(),
+                            description: None,
+                            source: AttachmentSource::NamesTree,
+                            page: None,
+                        });
+                    }
+                }
+            });
+        }
+
+        if self.sources.contains(&AttachmentSource::Annotation) {
+            for (page_number, page_id) in self.document.get_pages() {
+                let annots = self
+                    .document
+                    .get_dictionary(page_id)
+                    .and_then(|page| page.get(b"Annots"))
+                    .ok()
+                    .and_then(|annots| self.resolve_array(annots))
+                    .unwrap_or_default();
+
+                for annot in &annots {
+                    let Some(dict) = self.resolve_dict(annot) else {
+                        continue;
+                    };
+                    let is_attachment = dict
+                        .get(b"Subtype")
+                        .and_then(|s| s.as_name())
+                        .is_ok_and(|s| s == b"FileAttachment");
+                    if !is_attachment {
+                        continue;
+                    }
+                    if let Ok(target) = dict.get(b"FS").and_then(|fs| fs.as_str()) {
+                        refs.push(ExternalReference {
+                            name: Self::annotation_name(&dict),
+                            kind: ExternalKind::File,
+                            target: String::from_utf8_lossy(target).into_owned(),
+                            description: None,
+                            source: AttachmentSource::Annotation,
+                            page: Some(page_number),
+                        });
+                    }
+                }
             }
         }
-        Vec::new()
+
+        refs
     }
 
     /// Collect file specifications from page FileAttachment annotations.
-    fn collect_from_annotations(&self) -> Vec<(String, ObjectId)> {
+    fn collect_from_annotations(&self) -> Vec<FileSpec> {
         let mut specs = Vec::new();
         let pages = self.document.get_pages();
         
-        for page_id in pages.values() {
-            specs.extend(self.process_page_annotations(*page_id));
+        for (page_number, page_id) in pages {
+            if self.deadline.expired() {
+                break;
+            }
+            specs.extend(self.process_page_annotations(page_number, page_id));
         }
         
         specs
     }
 
     /// Process annotations on a single page.
-    fn process_page_annotations(&self, page_id: ObjectId) -> Vec<(String, ObjectId)> {
+    fn process_page_annotations(&self, page_number: u32, page_id: ObjectId) -> Vec<FileSpec> {
         let page_obj = match self.document.get_object(page_id) {
             Ok(obj) => obj,
             Err(_) => return Vec::new(),
@@ -146,17 +269,22 @@ impl<'a> FileSpecDiscovery<'a> {
             None => return Vec::new(),
         };
 
-        self.extract_file_attachments(&annots_array)
+        self.extract_file_attachments(&annots_array, page_number)
     }
 
     /// Extract file attachments from an annotations array.
-    fn extract_file_attachments(&self, annots: &[lopdf::Object]) -> Vec<(String, ObjectId)> {
+    fn extract_file_attachments(&self, annots: &[lopdf::Object], page_number: u32) -> Vec<FileSpec> {
         let mut specs = Vec::new();
         
         for item in annots {
             if let Ok(annot_id) = item.as_reference() {
-                if let Some((name, fs_id)) = self.process_file_attachment_annotation(annot_id) {
-                    specs.push((name, fs_id));
+                if let Some((name, id)) = self.process_file_attachment_annotation(annot_id) {
+                    specs.push(FileSpec {
+                        name,
+                        id,
+                        source: AttachmentSource::Annotation,
+                        page: Some(page_number),
+                    });
                 }
             }
         }
@@ -183,40 +311,83 @@ impl<'a> FileSpecDiscovery<'a> {
         Some((name, fs_id))
     }
 
-    /// Recursively walk a PDF name tree, collecting
-    /// `(name_string, file_spec_object_id)` pairs from leaf nodes.
-    fn walk_name_tree(&self, node_id: ObjectId) -> Vec<(String, ObjectId)> {
-        let mut out = Vec::new();
+    /// Collect file specifications referenced from `/AF` arrays on the
+    /// catalog and on every page.
+    fn collect_from_associated_files(&self) -> Vec<FileSpec> {
+        let mut specs = Vec::new();
 
-        let node_obj = match self.document.get_object(node_id) {
-            Ok(o) => o,
-            Err(_) => return out,
-        };
-        
-        let node_dict = match node_obj.as_dict() {
-            Ok(d) => d,
-            Err(_) => return out,
-        };
+        if let Ok(catalog) = self.document.catalog() {
+            specs.extend(self.process_af_array(catalog, None));
+        }
 
-        // Leaf node: has a /Names array of [key, value, key, value, …]
-        if let Ok(names_val) = node_dict.get(b"Names") {
-            if let Ok(arr) = names_val.as_array() {
-                out.extend(self.process_names_array(arr));
+        for (page_number, page_id) in self.document.get_pages() {
+            if self.deadline.expired() {
+                break;
+            }
+            if let Ok(page_dict) = self.document.get_dictionary(page_id) {
+                specs.extend(self.process_af_array(page_dict, Some(page_number)));
             }
         }
 
-        // Intermediate node: has a /Kids array of references
-        if let Ok(kids_val) = node_dict.get(b"Kids") {
-            if let Ok(kids) = kids_val.as_array() {
-                for kid in kids {
-                    if let Ok(kid_id) = kid.as_reference() {
-                        out.extend(self.walk_name_tree(kid_id));
-                    }
+        specs
+    }
+
+    /// Read the `/AF` array of `dict` and return a [`FileSpec`] for every
+    /// referenced file specification.
+    fn process_af_array(&self, dict: &lopdf::Dictionary, page: Option<u32>) -> Vec<FileSpec> {
+        let af_array = match dict.get(b"AF").ok().and_then(|v| self.resolve_array(v)) {
+            Some(array) => array,
+            None => return Vec::new(),
+        };
+
+        af_array
+            .iter()
+            .filter_map(|item| item.as_reference().ok())
+            .filter_map(|id| {
+                let spec_dict = self.document.get_dictionary(id).ok()?;
+                Some(FileSpec {
+                    name: Self::filespec_name(spec_dict),
+                    id,
+                    source: AttachmentSource::AssociatedFiles,
+                    page,
+                })
+            })
+            .collect()
+    }
+
+    /// Scan every indirect object for a dictionary that carries an `/EF`
+    /// entry, i.e. a file specification with an embedded file, regardless of
+    /// whether anything references it. File specifications without `/EF`
+    /// only point at external files and are left out.
+    fn collect_from_deep_scan(&self) -> Vec<FileSpec> {
+        self.document
+            .objects()
+            .take_while(|_| !self.deadline.expired())
+            .filter_map(|(id, obj)| {
+                let dict = obj.as_dict().ok()?;
+                if !dict.has(b"EF") {
+                    return None;
                 }
+                Some(FileSpec {
+                    name: Self::filespec_name(dict),
+                    id,
+                    source: AttachmentSource::DeepScan,
+                    page: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Extract a display name from a file specification dictionary.
+    /// Falls back to `"attachment"` if none of `/UF`, `/F`, or the legacy
+    /// `/Unix`, `/Mac`, `/DOS` keys is set.
+    fn filespec_name(dict: &lopdf::Dictionary) -> String {
+        for key in pdf_utils::FILENAME_KEYS {
+            if let Some(name) = pdf_utils::extract_string_from_dict(dict, key) {
+                return name;
             }
         }
-
-        out
+        "attachment".into()
     }
 
     /// Extract a display name from a FileAttachment annotation dictionary.
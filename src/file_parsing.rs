@@ -1,4 +1,11 @@
-use crate::{pdf_utils, EmbeddedFile, EmbeddedFileMetadata, ExtractError, Result};
+use crate::backend::PdfBackend;
+use crate::deadline::Deadline;
+use crate::filters::{self, Decoded, Filter};
+use crate::memory::MemoryBudget;
+use crate::{
+    pdf_utils, AfRelationship, EmbeddedFile, EmbeddedFileMetadata, ExternalKind, ExtractError,
+    ExtractionStage, Result,
+};
 use lopdf::{Document, ObjectId};
 
 /// Handles parsing of file specifications and extraction of embedded file data.
@@ -7,26 +14,77 @@ use lopdf::{Document, ObjectId};
 /// - Parse file specification objects
 /// - Extract and decompress stream content
 /// - Read metadata from file specifications
-pub struct FileSpecParser<'a> {
-    document: &'a Document,
+pub struct FileSpecParser<'a, D: PdfBackend + ?Sized = Document> {
+    document: &'a D,
+    max_decoded_size: Option<usize>,
+    recover_truncated: bool,
+    deadline: Deadline,
+    memory: MemoryBudget,
 }
 
-impl<'a> FileSpecParser<'a> {
-    pub fn new(document: &'a Document) -> Self {
-        Self { document }
+impl<'a, D: PdfBackend + ?Sized> FileSpecParser<'a, D> {
+    /// Create a parser. When `max_decoded_size` is set, streams are decoded
+    /// incrementally and decoding stops as soon as the limit is crossed. With
+    /// `recover_truncated`, damaged streams yield their decodable prefix.
+    pub fn new(document: &'a D, max_decoded_size: Option<usize>, recover_truncated: bool) -> Self {
+        Self {
+            document,
+            max_decoded_size,
+            recover_truncated,
+            deadline: Deadline::default(),
+            memory: MemoryBudget::default(),
+        }
+    }
+
+    /// Stop decoding with [`ExtractError::Timeout`] once `deadline` expires.
+    pub(crate) fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Stop decoding with [`ExtractError::MemoryLimit`] once a stream
+    /// outgrows what is left of `memory`.
+    pub(crate) fn with_memory(mut self, memory: MemoryBudget) -> Self {
+        self.memory = memory;
+        self
     }
 
     /// Create an extraction error with consistent formatting.
-    fn extraction_error(&self, name: &str, message: &str) -> ExtractError {
-        ExtractError::ExtractionError(name.into(), message.into())
+    fn extraction_error(&self, name: &str, stage: ExtractionStage, message: &str) -> ExtractError {
+        ExtractError::extraction(name, stage, message)
+    }
+
+    /// Resolve an object, reporting a dangling reference as an extraction
+    /// error of `stage` with the parser error as its cause.
+    fn resolve_object(
+        &self,
+        obj_id: ObjectId,
+        name: &str,
+        stage: ExtractionStage,
+    ) -> Result<&'a lopdf::Object> {
+        self.document
+            .get_object(obj_id)
+            .map_err(|cause| ExtractError::ExtractionError {
+                name: name.into(),
+                spec_id: None,
+                stage,
+                reason: format!("cannot resolve object {} {}", obj_id.0, obj_id.1),
+                cause: Some(cause.into()),
+            })
     }
 
     /// Get object from document and convert to dictionary with error context.
-    fn get_dict_object(&self, obj_id: ObjectId, name: &str, context: &str) -> Result<lopdf::Dictionary> {
-        let obj = self.document.get_object(obj_id)?;
+    ///
+    /// The dictionary is borrowed from the document; nothing is cloned.
+    fn get_dict_object(
+        &self,
+        obj_id: ObjectId,
+        name: &str,
+        context: &str,
+    ) -> Result<&'a lopdf::Dictionary> {
+        let obj = self.resolve_object(obj_id, name, ExtractionStage::FileSpec)?;
         obj.as_dict()
-            .map_err(|_| self.extraction_error(name, context))
-            .cloned()
+            .map_err(|_| self.extraction_error(name, ExtractionStage::FileSpec, context))
     }
 
     /// Parse a file-specification object and return an [`EmbeddedFile`] with content and metadata.
@@ -38,10 +96,12 @@ impl<'a> FileSpecParser<'a> {
     ///   /Type  /Filespec
     ///   /F     (ascii filename)
     ///   /UF    (unicode filename)          ← preferred
+    ///   /Unix /Mac /DOS (legacy platform-specific filenames)
     ///   /Desc  (description)
     ///   /EF    <<
     ///              /F   <stream-ref>       ← the actual data stream
     ///              /UF  <stream-ref>       ← alternative key, same stream
+    ///              /Unix /Mac /DOS         ← legacy keys, used as fallback
     ///          >>
     /// >>
     /// ```
@@ -50,63 +110,229 @@ impl<'a> FileSpecParser<'a> {
     /// of its values (`/F`, `/UF`) **is** an indirect reference to the stream
     /// object. The stream content is read and returned in the result.
     pub fn parse_file_spec(&self, name: &str, spec_id: ObjectId) -> Result<EmbeddedFile> {
-        let spec_dict = self.get_dict_object(spec_id, name, "file spec is not a dictionary")?;
-        let ef_dict = self.resolve_ef_dictionary(&spec_dict, name)?;
-        let stream = self.extract_embedded_stream(&ef_dict, name)?;
-        
-        let data = stream
-            .decompressed_content()
-            .unwrap_or_else(|_| stream.content.clone());
-
-        let filename = Self::best_filename(&spec_dict, name);
-        let metadata = Self::read_metadata(&spec_dict, &stream.dict);
+        let (spec_dict, stream) = self.locate_stream(name, spec_id)?;
+        let decoded = self.decode_stream(name, stream)?;
 
+        let mut metadata = Self::read_metadata(spec_dict, stream);
+        metadata.undecoded_filters = decoded.undecoded_filters;
+        metadata.truncated = decoded.truncated;
         Ok(EmbeddedFile {
-            filename,
-            data,
+            filename: Self::best_filename(spec_dict, name),
+            data: decoded.data,
             metadata,
         })
     }
 
+    /// Read the filename and metadata of a file specification without
+    /// decoding its embedded stream.
+    pub fn parse_file_info(
+        &self,
+        name: &str,
+        spec_id: ObjectId,
+    ) -> Result<(String, EmbeddedFileMetadata)> {
+        let (spec_dict, stream) = self.locate_stream(name, spec_id)?;
+        Ok((
+            Self::best_filename(spec_dict, name),
+            Self::read_metadata(spec_dict, stream),
+        ))
+    }
+
+    /// Lower bound on the decoded size of an embedded file, known without
+    /// decompressing it: the larger of `/Params/Size` and, for streams without
+    /// a `/Filter`, the raw stream length.
+    pub fn decoded_size_hint(&self, name: &str, spec_id: ObjectId) -> Result<Option<usize>> {
+        let (_, stream) = self.locate_stream(name, spec_id)?;
+
+        let declared = stream
+            .dict
+            .get(b"Params")
+            .and_then(|p| p.as_dict())
+            .and_then(|p| p.get(b"Size"))
+            .and_then(|v| v.as_i64())
+            .ok()
+            .and_then(|n| usize::try_from(n).ok());
+        let unfiltered = (!stream.dict.has(b"Filter")).then_some(stream.content.len());
+
+        Ok(declared.max(unfiltered))
+    }
+
+    /// Resolve spec dictionary → /EF → embedded stream, borrowing both from
+    /// the document.
+    fn locate_stream(
+        &self,
+        name: &str,
+        spec_id: ObjectId,
+    ) -> Result<(&'a lopdf::Dictionary, &'a lopdf::Stream)> {
+        let locate = || {
+            let spec_dict = self.get_dict_object(spec_id, name, "file spec is not a dictionary")?;
+            let ef_dict = self.resolve_ef_dictionary(spec_dict, name)?;
+            let stream = self.extract_embedded_stream(ef_dict, name)?;
+            Ok((spec_dict, stream))
+        };
+        locate().map_err(|e: ExtractError| e.with_spec_id(spec_id))
+    }
+
+    /// Decode a stream's content. Filters that cannot be applied are reported
+    /// in [`Decoded::undecoded_filters`], and damaged input either counts as
+    /// undecodable or, with `recover_truncated`, yields a truncated result.
+    ///
+    /// With a size limit configured, decoding fails with
+    /// [`ExtractError::FileSizeExceeded`] without ever holding much more than
+    /// `limit + 1` decoded bytes per filter.
+    ///
+    /// Streams of a document that is still encrypted are rejected with
+    /// [`ExtractError::Encrypted`] rather than returned as ciphertext.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, stream))
+    )]
+    fn decode_stream(&self, name: &str, stream: &lopdf::Stream) -> Result<Decoded> {
+        let mut chain = filters::chain(stream);
+        let crypt_filter = chain
+            .as_ref()
+            .ok()
+            .and_then(|chain| Self::crypt_filter_name(chain));
+        let is_locked = self.document.is_locked();
+        if is_locked && crypt_filter != Some(b"Identity".as_slice()) {
+            return Err(ExtractError::Encrypted(name.into()));
+        }
+        // lopdf applies the named crypt filter while decrypting the document
+        // on load, and `/Identity` needs no work, so only the remaining
+        // filters are left to decode.
+        if let Ok(chain) = &mut chain {
+            chain.retain(|filter| filter.name != b"Crypt");
+        }
+
+        filters::decode_chain(
+            &stream.content,
+            chain,
+            self.max_decoded_size,
+            self.recover_truncated,
+            &self.deadline,
+            &self.memory,
+        )
+    }
+
+    /// The crypt filter named by the `/Crypt` entry of a filter chain, or
+    /// `None` when there is none. A missing `/Name` means `/Identity`.
+    fn crypt_filter_name<'s>(chain: &[Filter<'s>]) -> Option<&'s [u8]> {
+        let crypt = chain.iter().find(|filter| filter.name == b"Crypt")?;
+        let name = crypt
+            .params
+            .and_then(|p| p.get(b"Name").and_then(|n| n.as_name()).ok());
+        Some(name.unwrap_or(b"Identity"))
+    }
+
+    /// If the file specification refers to something outside the document,
+    /// return its kind, path or URL, and description.
+    ///
+    /// That is the case for a plain string (a path), a dictionary with
+    /// `/FS /URL` (the URL is in `/F`), and a dictionary without `/EF`.
+    pub fn external_target(
+        &self,
+        spec_id: ObjectId,
+    ) -> Option<(ExternalKind, String, Option<String>)> {
+        let spec_dict = match self.document.get_object(spec_id).ok()? {
+            lopdf::Object::String(path, _) => {
+                return Some((
+                    ExternalKind::File,
+                    String::from_utf8_lossy(path).into_owned(),
+                    None,
+                ));
+            }
+            lopdf::Object::Dictionary(dict) => dict,
+            _ => return None,
+        };
+
+        let is_url = spec_dict
+            .get(b"FS")
+            .and_then(|fs| fs.as_name())
+            .is_ok_and(|fs| fs == b"URL");
+        if !is_url && spec_dict.has(b"EF") {
+            return None;
+        }
+
+        let kind = if is_url {
+            ExternalKind::Url
+        } else {
+            ExternalKind::File
+        };
+        let target = pdf_utils::FILENAME_KEYS
+            .iter()
+            .find_map(|key| pdf_utils::extract_string_from_dict(spec_dict, key))?;
+        let description = pdf_utils::extract_string_from_dict(spec_dict, b"Desc");
+        Some((kind, target, description))
+    }
+
+    /// Resolve the display filename of a file specification without touching
+    /// its embedded stream. Falls back to `name` if the spec cannot be read.
+    pub fn filename(&self, name: &str, spec_id: ObjectId) -> String {
+        match self.get_dict_object(spec_id, name, "file spec is not a dictionary") {
+            Ok(spec_dict) => Self::best_filename(spec_dict, name),
+            Err(_) => name.into(),
+        }
+    }
+
     /// Resolve the /EF dictionary, handling both inline and reference cases.
-    fn resolve_ef_dictionary(&self, spec_dict: &lopdf::Dictionary, name: &str) -> Result<lopdf::Dictionary> {
-        let ef_val = spec_dict
-            .get(b"EF")
-            .map_err(|_| self.extraction_error(name, "missing /EF entry"))?;
+    fn resolve_ef_dictionary(
+        &self,
+        spec_dict: &'a lopdf::Dictionary,
+        name: &str,
+    ) -> Result<&'a lopdf::Dictionary> {
+        let ef_val = spec_dict.get(b"EF").map_err(|_| {
+            self.extraction_error(name, ExtractionStage::FileSpec, "missing /EF entry")
+        })?;
 
         if let Ok(ef_id) = ef_val.as_reference() {
             // Some producers incorrectly store /EF as a reference — handle both.
             self.get_dict_object(ef_id, name, "/EF reference is not a dict")
         } else {
-            ef_val
-                .as_dict()
-                .map_err(|_| self.extraction_error(name, "/EF is not a dictionary"))
-                .cloned()
+            ef_val.as_dict().map_err(|_| {
+                self.extraction_error(name, ExtractionStage::FileSpec, "/EF is not a dictionary")
+            })
         }
     }
 
     /// Extract the embedded file stream from the EF dictionary.
-    fn extract_embedded_stream(&self, ef_dict: &lopdf::Dictionary, name: &str) -> Result<lopdf::Stream> {
-        // /UF preferred over /F (unicode vs. ASCII path)
-        let stream_ref = ef_dict
-            .get(b"UF")
-            .or_else(|_| ef_dict.get(b"F"))
-            .map_err(|_| self.extraction_error(name, "/EF has neither /F nor /UF"))?;
+    fn extract_embedded_stream(
+        &self,
+        ef_dict: &lopdf::Dictionary,
+        name: &str,
+    ) -> Result<&'a lopdf::Stream> {
+        // /UF preferred over /F (unicode vs. ASCII path), then legacy keys
+        let stream_ref = pdf_utils::FILENAME_KEYS
+            .iter()
+            .find_map(|key| ef_dict.get(key).ok())
+            .ok_or_else(|| {
+                self.extraction_error(
+                    name,
+                    ExtractionStage::EmbeddedStream,
+                    "/EF has none of /UF, /F, /Unix, /Mac, /DOS",
+                )
+            })?;
 
-        let stream_id = stream_ref
-            .as_reference()
-            .map_err(|_| self.extraction_error(name, "/EF stream entry is not a reference"))?;
+        let stream_id = stream_ref.as_reference().map_err(|_| {
+            self.extraction_error(
+                name,
+                ExtractionStage::EmbeddedStream,
+                "/EF stream entry is not a reference",
+            )
+        })?;
 
-        let stream_obj = self.document.get_object(stream_id)?;
-        stream_obj
-            .as_stream()
-            .map_err(|_| self.extraction_error(name, "embedded stream object is not a stream"))
-            .cloned()
+        let stream_obj = self.resolve_object(stream_id, name, ExtractionStage::EmbeddedStream)?;
+        stream_obj.as_stream().map_err(|_| {
+            self.extraction_error(
+                name,
+                ExtractionStage::EmbeddedStream,
+                "embedded stream object is not a stream",
+            )
+        })
     }
 
-    /// Return the best available filename: Unicode (/UF) > ASCII (/F) > fallback.
+    /// Return the best available filename: Unicode (/UF) > ASCII (/F) >
+    /// legacy (/Unix, /Mac, /DOS) > fallback.
     fn best_filename(spec_dict: &lopdf::Dictionary, fallback: &str) -> String {
-        for key in [b"UF" as &[u8], b"F"] {
+        for key in pdf_utils::FILENAME_KEYS {
             if let Some(name) = pdf_utils::extract_string_from_dict(spec_dict, key) {
                 return name;
             }
@@ -115,16 +341,18 @@ impl<'a> FileSpecParser<'a> {
     }
 
     /// Read optional metadata from the file specification dictionary and the
-    /// embedded stream's `/Params` sub-dictionary.
+    /// embedded stream's `/Params` sub-dictionary, and record the stream's
+    /// stored length.
     fn read_metadata(
         spec_dict: &lopdf::Dictionary,
-        stream_dict: &lopdf::Dictionary,
+        stream: &lopdf::Stream,
     ) -> EmbeddedFileMetadata {
         let mut metadata = EmbeddedFileMetadata::default();
-        
+
         Self::read_spec_metadata(spec_dict, &mut metadata);
-        Self::read_stream_params(stream_dict, &mut metadata);
-        
+        Self::read_stream_params(&stream.dict, &mut metadata);
+        metadata.stored_size = Some(stream.content.len());
+
         metadata
     }
 
@@ -142,6 +370,14 @@ impl<'a> FileSpecParser<'a> {
                 metadata.mime_type = Some(s.replace('#', "").to_ascii_lowercase());
             }
         }
+
+        // /AFRelationship — PDF/A-3 associated-file relationship name
+        if let Ok(v) = spec_dict.get(b"AFRelationship") {
+            if let Ok(name_bytes) = v.as_name() {
+                let name = String::from_utf8_lossy(name_bytes);
+                metadata.af_relationship = Some(AfRelationship::from_name(&name));
+            }
+        }
     }
 
     /// Read metadata from the stream's /Params sub-dictionary.
@@ -165,7 +401,7 @@ impl<'a> FileSpecParser<'a> {
     fn read_numeric_params(params: &lopdf::Dictionary, metadata: &mut EmbeddedFileMetadata) {
         if let Ok(v) = params.get(b"Size") {
             if let Ok(n) = v.as_i64() {
-                metadata.size = Some(n as usize);
+                metadata.size = usize::try_from(n).ok();
             }
         }
     }
@@ -174,13 +410,8 @@ impl<'a> FileSpecParser<'a> {
     fn read_checksum_param(params: &lopdf::Dictionary, metadata: &mut EmbeddedFileMetadata) {
         if let Ok(v) = params.get(b"CheckSum") {
             if let Ok(bytes) = v.as_str() {
-                metadata.checksum = Some(hex_encode(bytes));
+                metadata.checksum = Some(pdf_utils::hex_encode(bytes));
             }
         }
     }
 }
-
-/// Encode raw bytes as a lowercase hex string (used for the MD5 checksum).
-fn hex_encode(bytes: &[u8]) -> String {
-    bytes.iter().map(|b| format!("{b:02x}")).collect()
-}
\ No newline at end of file
@@ -0,0 +1,397 @@
+use crate::{ObjectRef, PdfAnalyzer};
+use lopdf::xref::XrefEntry;
+use lopdf::{Document, Object, ObjectId};
+
+// ── StructureFinding ──────────────────────────────────────────────────────────
+
+/// A violation of the file-structure rules of ISO 19005-3 (PDF/A-3), found by
+/// inspecting the raw bytes the document was loaded from.
+///
+/// Returned by [`PdfAnalyzer::structure_findings`]. The document may still
+/// load and extract fine; these are the findings that make a PDF/A validator
+/// such as veraPDF reject it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StructureFinding {
+    /// The rule that is violated.
+    pub rule: StructureRule,
+
+    /// The object the finding is about, for per-object rules.
+    pub object_id: Option<ObjectRef>,
+
+    /// A human-readable description.
+    pub message: String,
+}
+
+/// A file-structure rule of ISO 19005-3, clause 6.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum StructureRule {
+    /// The file starts with a `%PDF-n.m` header at byte 0.
+    Header,
+    /// The header is followed by a comment of at least four bytes above 127,
+    /// marking the file as binary.
+    BinaryComment,
+    /// The trailer has an `/ID` entry.
+    TrailerId,
+    /// The trailer has no `/Encrypt` entry.
+    NoEncryption,
+    /// Nothing but an end-of-line marker follows the last `%%EOF`.
+    NoDataAfterEof,
+    /// `startxref` and every cross-reference entry point at the byte where
+    /// the cross-reference section or object starts.
+    XrefOffsets,
+    /// A stream's `/Length` is the number of bytes between `stream` and
+    /// `endstream`, not counting the end-of-line markers.
+    StreamLength,
+    /// `stream` is followed by CR LF or LF, and `endstream` is preceded by an
+    /// end-of-line marker.
+    StreamKeywords,
+}
+
+impl StructureRule {
+    /// The rule's identifier in the veraPDF validation profile for PDF/A-3,
+    /// such as `"6.1.3-1"`, for matching findings against validator output.
+    pub fn id(self) -> &'static str {
+        match self {
+            StructureRule::Header => "6.1.2-1",
+            StructureRule::BinaryComment => "6.1.2-2",
+            StructureRule::TrailerId => "6.1.3-1",
+            StructureRule::NoEncryption => "6.1.3-2",
+            StructureRule::NoDataAfterEof => "6.1.3-3",
+            StructureRule::XrefOffsets => "6.1.4-1",
+            StructureRule::StreamLength => "6.1.7.1-1",
+            StructureRule::StreamKeywords => "6.1.7.1-2",
+        }
+    }
+}
+
+impl StructureFinding {
+    fn new(rule: StructureRule, object_id: Option<ObjectId>, message: String) -> Self {
+        Self {
+            rule,
+            object_id: object_id.map(ObjectRef::from),
+            message,
+        }
+    }
+}
+
+impl PdfAnalyzer {
+    /// Returns the violations of the PDF/A file-structure rules: header and
+    /// binary comment, trailer `/ID` and `/Encrypt`, data after `%%EOF`,
+    /// cross-reference offsets, and the `/Length` and keywords of every
+    /// stream.
+    ///
+    /// The checks need the raw bytes, so they run once while loading, and
+    /// only with
+    /// [`check_file_structure`](crate::ExtractorConfig::check_file_structure);
+    /// otherwise the list is empty. The bytes are not kept, so enabling the
+    /// checks later with [`set_config`](PdfAnalyzer::set_config) or
+    /// [`config_mut`](PdfAnalyzer::config_mut) has no effect. Each finding
+    /// names its rule, whose [`id`](StructureRule::id) matches the veraPDF
+    /// report.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::{ExtractorConfig, PdfAnalyzer};
+    ///
+    /// let config = ExtractorConfig {
+    ///     check_file_structure: true,
+    ///     ..Default::default()
+    /// };
+    /// let analyzer = PdfAnalyzer::with_config("invoice.pdf", config).unwrap();
+    /// for finding in analyzer.structure_findings() {
+    ///     println!("{}: {}", finding.rule.id(), finding.message);
+    /// }
+    /// ```
+    pub fn structure_findings(&self) -> &[StructureFinding] {
+        &self.structure
+    }
+}
+
+/// Check `data`, which `document` was loaded from, against every
+/// [`StructureRule`].
+pub(crate) fn check(document: &Document, data: &[u8]) -> Vec<StructureFinding> {
+    let mut findings = Vec::new();
+    header(data, &mut findings);
+    trailer(document, &mut findings);
+    end_of_file(data, &mut findings);
+    let objects = xref_offsets(document, data, &mut findings);
+    streams(document, data, &objects, &mut findings);
+    findings
+}
+
+fn header(data: &[u8], findings: &mut Vec<StructureFinding>) {
+    let version = data
+        .strip_prefix(b"%PDF-")
+        .filter(|v| matches!(v, [major, b'.', minor, ..] if major.is_ascii_digit() && minor.is_ascii_digit()));
+    let Some(version) = version else {
+        findings.push(StructureFinding::new(
+            StructureRule::Header,
+            None,
+            "file does not start with a %PDF-n.m header".into(),
+        ));
+        return;
+    };
+
+    let comment = skip_eol(&version[3..]).and_then(|line| line.strip_prefix(b"%"));
+    let binary = comment.is_some_and(|comment| {
+        let line = comment
+            .split(|&b| b == b'\r' || b == b'\n')
+            .next()
+            .unwrap_or_default();
+        line.len() >= 4 && line[..4].iter().all(|&b| b > 127)
+    });
+    if !binary {
+        findings.push(StructureFinding::new(
+            StructureRule::BinaryComment,
+            None,
+            "header is not followed by a comment of four bytes above 127".into(),
+        ));
+    }
+}
+
+fn trailer(document: &Document, findings: &mut Vec<StructureFinding>) {
+    let trailer = &document.trailer;
+    let has_id = trailer
+        .get(b"ID")
+        .and_then(Object::as_array)
+        .is_ok_and(|id| id.len() == 2);
+    if !has_id {
+        findings.push(StructureFinding::new(
+            StructureRule::TrailerId,
+            None,
+            "trailer has no /ID with two file identifiers".into(),
+        ));
+    }
+    if trailer.has(b"Encrypt") {
+        findings.push(StructureFinding::new(
+            StructureRule::NoEncryption,
+            None,
+            "trailer has an /Encrypt entry".into(),
+        ));
+    }
+}
+
+fn end_of_file(data: &[u8], findings: &mut Vec<StructureFinding>) {
+    const EOF: &[u8] = b"%%EOF";
+    let Some(at) = data.windows(EOF.len()).rposition(|w| w == EOF) else {
+        findings.push(StructureFinding::new(
+            StructureRule::NoDataAfterEof,
+            None,
+            "file has no %%EOF marker".into(),
+        ));
+        return;
+    };
+    let rest = &data[at + EOF.len()..];
+    if !matches!(rest, b"" | b"\n" | b"\r" | b"\r\n") {
+        findings.push(StructureFinding::new(
+            StructureRule::NoDataAfterEof,
+            None,
+            format!("{} bytes follow the last %%EOF", rest.len()),
+        ));
+    }
+}
+
+/// Check `startxref` and the cross-reference entries, and return the
+/// objects whose entries are right with their offsets.
+fn xref_offsets(
+    document: &Document,
+    data: &[u8],
+    findings: &mut Vec<StructureFinding>,
+) -> Vec<(ObjectId, usize)> {
+    const STARTXREF: &[u8] = b"startxref";
+    let start = data
+        .windows(STARTXREF.len())
+        .rposition(|w| w == STARTXREF)
+        .and_then(|at| {
+            let digits = trim_start(&data[at + STARTXREF.len()..]);
+            let len = digits.iter().take_while(|b| b.is_ascii_digit()).count();
+            std::str::from_utf8(&digits[..len])
+                .ok()?
+                .parse::<usize>()
+                .ok()
+        });
+    let points_at_xref = start
+        .and_then(|offset| data.get(offset..))
+        .is_some_and(|section| section.starts_with(b"xref") || object_header(section).is_some());
+    if !points_at_xref {
+        findings.push(StructureFinding::new(
+            StructureRule::XrefOffsets,
+            None,
+            "startxref does not point at a cross-reference section".into(),
+        ));
+    }
+
+    let mut located = Vec::new();
+    for (&number, entry) in &document.reference_table.entries {
+        let XrefEntry::Normal { offset, generation } = *entry else {
+            continue;
+        };
+        let id = (number, generation);
+        let offset = offset as usize;
+        if data.get(offset..).and_then(object_header) == Some(id) {
+            located.push((id, offset));
+        } else {
+            findings.push(StructureFinding::new(
+                StructureRule::XrefOffsets,
+                Some(id),
+                format!("cross-reference offset {offset} of object {number} {generation} is wrong"),
+            ));
+        }
+    }
+    located
+}
+
+/// Check the keywords and `/Length` of every stream among `objects`, the
+/// objects at their cross-reference offsets.
+///
+/// This works on the raw bytes because lopdf rejects a stream object whose
+/// direct `/Length` is wrong instead of loading it.
+fn streams(
+    document: &Document,
+    data: &[u8],
+    objects: &[(ObjectId, usize)],
+    findings: &mut Vec<StructureFinding>,
+) {
+    let mut starts: Vec<usize> = objects.iter().map(|&(_, offset)| offset).collect();
+    starts.sort_unstable();
+    for &(id, offset) in objects {
+        // An object ends at `endobj`, or at the latest where the next one
+        // starts, so a missing keyword does not make every search run to the
+        // end of the file.
+        let next = starts.partition_point(|&start| start <= offset);
+        let object = &data[offset..starts.get(next).copied().unwrap_or(data.len())];
+        let end = find(object, b"endobj").unwrap_or(object.len());
+        let Some(keyword) = stream_keyword(&object[..end]) else {
+            continue;
+        };
+        let after_keyword = &object[keyword + b"stream".len()..];
+        let content = match after_keyword {
+            [b'\r', b'\n', ..] => &after_keyword[2..],
+            [b'\n', ..] => &after_keyword[1..],
+            _ => {
+                findings.push(StructureFinding::new(
+                    StructureRule::StreamKeywords,
+                    Some(id),
+                    "stream keyword is not followed by CR LF or LF".into(),
+                ));
+                skip_eol(after_keyword).unwrap_or(after_keyword)
+            }
+        };
+
+        let Some(length) = declared_length(document, &object[..keyword]) else {
+            continue;
+        };
+        let after = content.get(length..).unwrap_or_default();
+        match skip_eol(after) {
+            Some(rest) if rest.starts_with(b"endstream") => {}
+            None if after.starts_with(b"endstream") => findings.push(StructureFinding::new(
+                StructureRule::StreamKeywords,
+                Some(id),
+                "endstream keyword is not preceded by an end-of-line marker".into(),
+            )),
+            _ => findings.push(StructureFinding::new(
+                StructureRule::StreamLength,
+                Some(id),
+                format!("/Length {length} does not end at the endstream keyword"),
+            )),
+        }
+    }
+}
+
+/// The position of the `stream` keyword that closes the dictionary of
+/// `object`, if it is a stream object.
+fn stream_keyword(object: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(found) = find(&object[pos..], b"stream") {
+        let at = pos + found;
+        if trim_end(&object[..at]).ends_with(b">>") {
+            return Some(at);
+        }
+        pos = at + 1;
+    }
+    None
+}
+
+/// The `/Length` in the raw stream dictionary `dict`, a direct integer or
+/// resolved through `document` when it is a reference.
+fn declared_length(document: &Document, dict: &[u8]) -> Option<usize> {
+    const KEY: &[u8] = b"/Length";
+    let mut pos = 0;
+    let value = loop {
+        let at = pos + find(&dict[pos..], KEY)?;
+        pos = at + KEY.len();
+        let rest = &dict[pos..];
+        // `/Length1` and the like are different keys.
+        if rest
+            .first()
+            .map_or(true, |&b| is_whitespace(b) || b"/[<(".contains(&b))
+        {
+            break trim_start(rest);
+        }
+    };
+
+    let mut rest = value;
+    let mut number = || -> Option<u32> {
+        let len = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let number = std::str::from_utf8(&rest[..len]).ok()?.parse().ok()?;
+        rest = trim_start(&rest[len..]);
+        Some(number)
+    };
+    let first = number()?;
+    let reference = number().and_then(|generation| {
+        let generation = u16::try_from(generation).ok()?;
+        rest.starts_with(b"R").then_some((first, generation))
+    });
+    match reference {
+        Some(id) => usize::try_from(document.get_object(id).ok()?.as_i64().ok()?).ok(),
+        None => usize::try_from(first).ok(),
+    }
+}
+
+/// `bytes` after its leading CR LF, LF or CR, if it starts with one.
+fn skip_eol(bytes: &[u8]) -> Option<&[u8]> {
+    bytes
+        .strip_prefix(b"\r\n")
+        .or_else(|| bytes.strip_prefix(b"\n"))
+        .or_else(|| bytes.strip_prefix(b"\r"))
+}
+
+/// The object number and generation of the `N G obj` header `bytes` start
+/// with.
+fn object_header(bytes: &[u8]) -> Option<ObjectId> {
+    let mut rest = bytes;
+    let mut number = || -> Option<u32> {
+        let len = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let value = std::str::from_utf8(&rest[..len]).ok()?.parse().ok()?;
+        rest = trim_start(&rest[len..]);
+        Some(value)
+    };
+    let id = (number()?, u16::try_from(number()?).ok()?);
+    rest.starts_with(b"obj").then_some(id)
+}
+
+/// `bytes` without leading PDF whitespace.
+fn trim_start(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().take_while(|&&b| is_whitespace(b)).count();
+    &bytes[len..]
+}
+
+/// `bytes` without trailing PDF whitespace.
+fn trim_end(bytes: &[u8]) -> &[u8] {
+    let len = bytes
+        .iter()
+        .rev()
+        .take_while(|&&b| is_whitespace(b))
+        .count();
+    &bytes[..bytes.len() - len]
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+/// The position of the first `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,563 @@
+//! Decoders for the standard PDF stream filters.
+//!
+//! lopdf decodes `/FlateDecode`, `/LZWDecode` and `/ASCII85Decode`, fails the
+//! whole chain on anything else, and quietly returns partial output when a
+//! Flate stream is corrupt. [`decode`] applies every non-image filter of
+//! ISO 32000-1 §7.4 itself, including the abbreviated names allowed in inline
+//! images, and reports the filters it could not apply (and any truncation)
+//! instead of passing raw or partial bytes off as the decoded file.
+
+use crate::deadline::Deadline;
+use crate::memory::MemoryBudget;
+use crate::{ExtractError, Result};
+use lopdf::{Dictionary, Object, Stream};
+use std::borrow::Cow;
+
+/// The most output one inflate step produces.
+const CHUNK: usize = 32 * 1024;
+
+/// How many input bytes or runs the LZW and run-length decoders process
+/// between two deadline checks.
+const DEADLINE_INTERVAL: usize = 4096;
+
+/// The output of [`decode`].
+#[derive(Debug)]
+pub(crate) struct Decoded {
+    /// The stream content after every filter up to the first undecodable one.
+    pub data: Vec<u8>,
+
+    /// The filters that were not applied, starting with the first one that
+    /// is unsupported or whose input was invalid. Empty when fully decoded.
+    pub undecoded_filters: Vec<String>,
+
+    /// A filter's input ended early or became corrupt, and `data` holds only
+    /// what could be decoded up to that point.
+    pub truncated: bool,
+}
+
+/// The result of applying one filter.
+enum Output {
+    /// The filter decoded all of its input.
+    Complete(Vec<u8>),
+    /// The input ended early or became corrupt; the bytes decoded before that.
+    Truncated(Vec<u8>),
+}
+
+impl Output {
+    /// Complete output, or truncated output if anything was decoded at all.
+    fn new(data: Vec<u8>, complete: bool) -> Option<Self> {
+        match (complete, data.is_empty()) {
+            (true, _) => Some(Self::Complete(data)),
+            (false, true) => None,
+            (false, false) => Some(Self::Truncated(data)),
+        }
+    }
+
+    fn map(self, f: impl FnOnce(Vec<u8>) -> Option<Vec<u8>>) -> Option<Self> {
+        match self {
+            Self::Complete(data) => f(data).map(Self::Complete),
+            Self::Truncated(data) => f(data).map(Self::Truncated),
+        }
+    }
+}
+
+/// One entry of a stream's `/Filter` chain with its `/DecodeParms`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Filter<'a> {
+    pub name: &'a [u8],
+    pub params: Option<&'a Dictionary>,
+}
+
+/// The filter chain of `stream`, in the order the filters are applied.
+///
+/// A `/Filter` entry that is neither a name nor an array of names cannot be
+/// decoded; its entries are returned as the error, ready to be reported as
+/// [`Decoded::undecoded_filters`].
+pub(crate) fn chain(stream: &Stream) -> std::result::Result<Vec<Filter<'_>>, Vec<String>> {
+    let names = match stream.dict.get(b"Filter") {
+        Err(_) => return Ok(Vec::new()),
+        Ok(Object::Name(name)) => vec![name.as_slice()],
+        Ok(Object::Array(entries)) => entries
+            .iter()
+            .map(Object::as_name)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| entries.iter().map(describe_filter).collect::<Vec<_>>())?,
+        Ok(other) => return Err(vec![describe_filter(other)]),
+    };
+    Ok(names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| Filter {
+            name,
+            params: decode_params(&stream.dict, index),
+        })
+        .collect())
+}
+
+/// A `/Filter` entry as it appears in a report: the filter name, or the
+/// kind of object found where a name was expected.
+fn describe_filter(entry: &Object) -> String {
+    match entry {
+        Object::Name(name) => String::from_utf8_lossy(name).into_owned(),
+        other => format!("<{}>", other.enum_variant()),
+    }
+}
+
+/// Apply the filters of `stream` in order; see [`decode_chain`].
+pub(crate) fn decode(
+    stream: &Stream,
+    limit: Option<usize>,
+    recover_truncated: bool,
+    deadline: &Deadline,
+    memory: &MemoryBudget,
+) -> Result<Decoded> {
+    decode_chain(
+        &stream.content,
+        chain(stream),
+        limit,
+        recover_truncated,
+        deadline,
+        memory,
+    )
+}
+
+/// Apply `filters` to `content` in order.
+///
+/// Decoding stops at the first filter that is unsupported (image codecs such
+/// as `/DCTDecode`) or fails on its input; that filter and all following ones
+/// are returned in [`Decoded::undecoded_filters`], as are the entries of a
+/// malformed `/Filter` (the `Err` of [`chain`]). With `recover_truncated`,
+/// a filter whose input breaks off part-way contributes what it decoded so
+/// far and the result is marked [`Decoded::truncated`] instead.
+///
+/// Fails with [`ExtractError::FileSizeExceeded`] as soon as an intermediate
+/// result grows beyond `limit`, with [`ExtractError::MemoryLimit`] when it
+/// outgrows what is left of `memory`, and with [`ExtractError::Timeout`] when
+/// `deadline` expires, which the Flate, LZW and run-length decoders also
+/// check while they run.
+///
+/// `content` is only read; it is copied only when it has to be returned as
+/// it is. Every intermediate result, and that copy, is charged to `memory`
+/// while it is held, so concurrent decodes cannot all pass the same check.
+/// The returned data stays charged; the caller releases it once it is
+/// dropped, or keeps the charge for results that accumulate.
+pub(crate) fn decode_chain(
+    content: &[u8],
+    filters: std::result::Result<Vec<Filter<'_>>, Vec<String>>,
+    limit: Option<usize>,
+    recover_truncated: bool,
+    deadline: &Deadline,
+    memory: &MemoryBudget,
+) -> Result<Decoded> {
+    let mut held = 0;
+    let decoded = apply_all(
+        content,
+        filters,
+        limit,
+        recover_truncated,
+        deadline,
+        memory,
+        &mut held,
+    );
+    if decoded.is_err() {
+        memory.release(held);
+    }
+    decoded
+}
+
+/// The filter loop of [`decode_chain`]. `held` tracks how much of `memory`
+/// the current result holds.
+fn apply_all(
+    content: &[u8],
+    filters: std::result::Result<Vec<Filter<'_>>, Vec<String>>,
+    limit: Option<usize>,
+    recover_truncated: bool,
+    deadline: &Deadline,
+    memory: &MemoryBudget,
+    held: &mut usize,
+) -> Result<Decoded> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let (filters, mut undecoded_filters) = match filters {
+        Ok(filters) => (filters, Vec::new()),
+        Err(entries) => (Vec::new(), entries),
+    };
+
+    let mut data = Cow::Borrowed(content);
+    let mut truncated = false;
+    for (index, filter) in filters.iter().enumerate() {
+        deadline.check()?;
+        let budget = limit.min(memory.remaining());
+        let output = apply(filter.name, &data, filter.params, budget, deadline);
+        // A decoder that ran out of time stops early; its output is discarded.
+        deadline.check()?;
+        let output = match output {
+            Some(Output::Complete(output)) => output,
+            Some(Output::Truncated(output)) if recover_truncated => {
+                truncated = true;
+                output
+            }
+            _ => {
+                undecoded_filters = filters[index..]
+                    .iter()
+                    .map(|f| String::from_utf8_lossy(f.name).into_owned())
+                    .collect();
+                break;
+            }
+        };
+        if output.len() > limit {
+            return Err(ExtractError::FileSizeExceeded);
+        }
+        memory.charge(output.len())?;
+        memory.release(*held);
+        *held = output.len();
+        data = Cow::Owned(output);
+    }
+
+    let data = match data {
+        Cow::Owned(data) => data,
+        Cow::Borrowed(raw) => {
+            memory.charge(raw.len())?;
+            *held = raw.len();
+            raw.to_vec()
+        }
+    };
+    Ok(Decoded {
+        data,
+        undecoded_filters,
+        truncated,
+    })
+}
+
+/// The `/DecodeParms` dictionary belonging to the filter at `index`.
+fn decode_params(dict: &Dictionary, index: usize) -> Option<&Dictionary> {
+    match dict.get(b"DecodeParms").ok()? {
+        Object::Array(params) => params.get(index)?.as_dict().ok(),
+        Object::Dictionary(params) => (index == 0).then_some(params),
+        _ => None,
+    }
+}
+
+/// Apply one filter. Returns `None` when the filter is not supported or its
+/// input is invalid from the start. Output beyond `limit` bytes, or produced
+/// after `deadline` expired, may be cut short.
+fn apply(
+    filter: &[u8],
+    input: &[u8],
+    params: Option<&Dictionary>,
+    limit: usize,
+    deadline: &Deadline,
+) -> Option<Output> {
+    match filter {
+        b"ASCIIHexDecode" | b"AHx" => ascii_hex(input).map(Output::Complete),
+        b"ASCII85Decode" | b"A85" => ascii85(input).map(Output::Complete),
+        b"LZWDecode" | b"LZW" => {
+            let early_change = int_param(params, b"EarlyChange", 1) != 0;
+            lzw(input, early_change, limit, deadline)?.map(|data| predict(data, params, limit))
+        }
+        b"FlateDecode" | b"Fl" => {
+            inflate(input, limit, deadline)?.map(|data| predict(data, params, limit))
+        }
+        b"RunLengthDecode" | b"RL" => run_length(input, limit, deadline),
+        _ => None,
+    }
+}
+
+fn int_param(params: Option<&Dictionary>, key: &[u8], default: i64) -> i64 {
+    params
+        .and_then(|p| p.get(key).ok())
+        .and_then(|v| v.as_i64().ok())
+        .unwrap_or(default)
+}
+
+fn ascii_hex(input: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() / 2);
+    let mut high = None;
+    for &byte in input {
+        let digit = match byte {
+            b'>' => break,
+            b if b.is_ascii_whitespace() || b == 0 => continue,
+            b => char::from(b).to_digit(16)? as u8,
+        };
+        match high.take() {
+            Some(h) => out.push(h << 4 | digit),
+            None => high = Some(digit),
+        }
+    }
+    // An odd final digit is padded with 0.
+    if let Some(h) = high {
+        out.push(h << 4);
+    }
+    Some(out)
+}
+
+fn ascii85(input: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 4 / 5);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+
+    for &byte in input {
+        match byte {
+            b'~' => break, // "~>" end of data
+            b'z' if len == 0 => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group[len] = byte - b'!';
+                len += 1;
+                if len == 5 {
+                    out.extend_from_slice(&ascii85_group(&group)?);
+                    len = 0;
+                }
+            }
+            b if b.is_ascii_whitespace() || b == 0 => {}
+            _ => return None,
+        }
+    }
+
+    // A final partial group of n characters encodes n - 1 bytes; pad with 'u'.
+    match len {
+        0 => {}
+        1 => return None,
+        _ => {
+            group[len..].fill(b'u' - b'!');
+            out.extend_from_slice(&ascii85_group(&group)?[..len - 1]);
+        }
+    }
+    Some(out)
+}
+
+fn ascii85_group(group: &[u8; 5]) -> Option<[u8; 4]> {
+    let value = group.iter().try_fold(0u32, |acc, &digit| {
+        acc.checked_mul(85)?.checked_add(digit.into())
+    })?;
+    Some(value.to_be_bytes())
+}
+
+/// Inflate zlib data. The stream counts as complete once the final deflate
+/// block has been seen; a missing Adler-32 checksum is tolerated, as is a
+/// missing zlib header.
+///
+/// Each step decodes at most [`CHUNK`] bytes and never more than one byte
+/// past `limit`, so the output (and its capacity) stays within `limit + 1`.
+fn inflate(input: &[u8], limit: usize, deadline: &Deadline) -> Option<Output> {
+    use flate2::{Decompress, FlushDecompress, Status};
+
+    let has_header = input.len() >= 2
+        && input[0] & 0x0F == 8
+        && (u16::from(input[0]) << 8 | u16::from(input[1])) % 31 == 0;
+    let deflated = if has_header { &input[2..] } else { input };
+    let cap = limit.saturating_add(1);
+
+    let mut decompress = Decompress::new(false);
+    let mut out = Vec::new();
+    loop {
+        if out.len() > limit || deadline.expired() {
+            return Output::new(out, true);
+        }
+        let len = out.len();
+        let step = CHUNK.min(cap - len);
+        if out.capacity() - len < step {
+            // Grow geometrically, but never past the first byte over the limit.
+            let target = (len * 2).max(len + step).min(cap);
+            out.reserve_exact(target - len);
+        }
+        out.resize(len + step, 0);
+
+        let consumed = decompress.total_in() as usize;
+        let produced = decompress.total_out();
+        let status = decompress.decompress(
+            &deflated[consumed..],
+            &mut out[len..],
+            FlushDecompress::None,
+        );
+        out.truncate(len + (decompress.total_out() - produced) as usize);
+        match status {
+            Ok(Status::StreamEnd) => return Output::new(out, true),
+            Ok(_)
+                if decompress.total_in() as usize == consumed
+                    && decompress.total_out() == produced =>
+            {
+                return Output::new(out, false); // input exhausted mid-stream
+            }
+            Ok(_) => {}
+            Err(_) => return Output::new(out, false),
+        }
+    }
+}
+
+/// LZW with the PDF variant's 9–12 bit codes, MSB first (§7.4.4).
+fn lzw(input: &[u8], early_change: bool, limit: usize, deadline: &Deadline) -> Option<Output> {
+    const CLEAR: usize = 256;
+    const EOD: usize = 257;
+
+    let mut table: Vec<Vec<u8>> = (0..=255u8).map(|b| vec![b]).collect();
+    table.extend([Vec::new(), Vec::new()]);
+    let mut width = 9;
+    let mut previous: Option<usize> = None;
+    let mut out = Vec::new();
+
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for (index, &byte) in input.iter().enumerate() {
+        if index % DEADLINE_INTERVAL == 0 && deadline.expired() {
+            return Output::new(out, true);
+        }
+        buffer = buffer << 8 | u32::from(byte);
+        bits += 8;
+        while bits >= width {
+            bits -= width;
+            let code = (buffer >> bits) as usize & ((1 << width) - 1);
+            match code {
+                CLEAR => {
+                    table.truncate(EOD + 1);
+                    width = 9;
+                    previous = None;
+                    continue;
+                }
+                EOD => return Output::new(out, true),
+                _ => {}
+            }
+
+            let entry = match (previous, table.get(code)) {
+                (_, Some(entry)) => entry.clone(),
+                (Some(prev), None) if code == table.len() => {
+                    let mut entry = table[prev].clone();
+                    entry.push(table[prev][0]);
+                    entry
+                }
+                _ => return Output::new(out, false),
+            };
+            if let Some(prev) = previous {
+                if table.len() < 4096 {
+                    let mut added = table[prev].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                }
+            }
+            out.extend_from_slice(&entry);
+            if out.len() > limit {
+                return Output::new(out, true);
+            }
+            previous = Some(code);
+
+            let switch_at = (1 << width) - usize::from(early_change);
+            if table.len() >= switch_at && width < 12 {
+                width += 1;
+            }
+        }
+    }
+    Output::new(out, true)
+}
+
+fn run_length(input: &[u8], limit: usize, deadline: &Deadline) -> Option<Output> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    let mut runs = 0usize;
+    while let Some(&length) = input.get(pos) {
+        runs += 1;
+        if runs % DEADLINE_INTERVAL == 0 && deadline.expired() {
+            break;
+        }
+        match length {
+            128 => break, // end of data
+            0..=127 => {
+                let count = usize::from(length) + 1;
+                let Some(literal) = input.get(pos + 1..pos + 1 + count) else {
+                    out.extend_from_slice(&input[pos + 1..]);
+                    return Output::new(out, false);
+                };
+                out.extend_from_slice(literal);
+                pos += 1 + count;
+            }
+            _ => {
+                let Some(&byte) = input.get(pos + 1) else {
+                    return Output::new(out, false);
+                };
+                out.resize(out.len() + 257 - usize::from(length), byte);
+                pos += 2;
+            }
+        }
+        if out.len() > limit {
+            break;
+        }
+    }
+    Output::new(out, true)
+}
+
+/// Undo the `/Predictor` of a Flate or LZW stream (§7.4.4.4).
+///
+/// The row width comes from `/DecodeParms` and is not trusted: a row wider
+/// than `data` or than `limit` is rejected before anything is allocated.
+fn predict(data: Vec<u8>, params: Option<&Dictionary>, limit: usize) -> Option<Vec<u8>> {
+    let predictor = int_param(params, b"Predictor", 1);
+    if predictor == 1 || data.is_empty() {
+        return Some(data);
+    }
+
+    let colors = usize::try_from(int_param(params, b"Colors", 1)).ok()?;
+    let bits = usize::try_from(int_param(params, b"BitsPerComponent", 8)).ok()?;
+    let columns = usize::try_from(int_param(params, b"Columns", 1)).ok()?;
+    let pixel_bits = colors.checked_mul(bits)?;
+    let pixel = (pixel_bits / 8 + usize::from(pixel_bits % 8 != 0)).max(1);
+    let row_bits = pixel_bits.checked_mul(columns)?;
+    let row = row_bits / 8 + usize::from(row_bits % 8 != 0);
+    if row == 0 || row > data.len() || row > limit {
+        return None;
+    }
+
+    match predictor {
+        2 if bits == 8 => {
+            let mut out = data;
+            for line in out.chunks_mut(row) {
+                for i in pixel..line.len() {
+                    line[i] = line[i].wrapping_add(line[i - pixel]);
+                }
+            }
+            Some(out)
+        }
+        10..=15 => png_predict(&data, row, pixel),
+        _ => None,
+    }
+}
+
+/// Reverse PNG row filters; every row starts with its filter type byte.
+fn png_predict(data: &[u8], row: usize, pixel: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / (row + 1) * row);
+    let mut prior = vec![0u8; row];
+    for line in data.chunks(row + 1) {
+        let (&filter, encoded) = line.split_first()?;
+        let mut current = encoded.to_vec();
+        current.resize(row, 0);
+        for i in 0..row {
+            let left = if i >= pixel { current[i - pixel] } else { 0 };
+            let up = prior[i];
+            let up_left = if i >= pixel { prior[i - pixel] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            };
+            current[i] = current[i].wrapping_add(predicted);
+        }
+        out.extend_from_slice(&current[..encoded.len()]);
+        prior = current;
+    }
+    Some(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
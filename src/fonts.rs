@@ -0,0 +1,134 @@
+use crate::{ObjectRef, PdfAnalyzer};
+use lopdf::{Dictionary, Document, Object};
+
+/// Font dictionary entries that hold an embedded font program.
+const FONT_FILE_KEYS: [&[u8]; 3] = [b"FontFile", b"FontFile2", b"FontFile3"];
+
+// ── FontInfo ──────────────────────────────────────────────────────────────────
+
+/// One font of the document, as listed by [`PdfAnalyzer::font_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FontInfo {
+    /// The font dictionary.
+    pub object_id: ObjectRef,
+
+    /// The `/BaseFont` name including any subset tag, such as
+    /// `"ABCDEF+Arial"`. Type 3 fonts usually have none.
+    pub name: Option<String>,
+
+    /// The font type from `/Subtype`: `Type1`, `TrueType`, `Type0`, `Type3`
+    /// or `MMType1`.
+    pub subtype: String,
+
+    /// Whether the font program is embedded. Type 3 fonts always are, since
+    /// their glyphs are content streams; for a `Type0` font the descendant
+    /// font is checked. PDF/A requires every font used for rendering to be
+    /// embedded.
+    pub embedded: bool,
+
+    /// Whether the name carries a subset tag, six capital letters and `+`,
+    /// meaning only the glyphs in use are embedded.
+    pub subset: bool,
+
+    /// The `/Encoding`: a name such as `WinAnsiEncoding` or `Identity-H`, the
+    /// `/BaseEncoding` of an encoding dictionary or the `/CMapName` of an
+    /// embedded CMap. `None` when the font uses its built-in encoding.
+    pub encoding: Option<String>,
+}
+
+impl PdfAnalyzer {
+    /// Lists every font of the document with whether it is embedded or
+    /// subsetted and how it is encoded, in object number order.
+    ///
+    /// Non-embedded fonts are the most common reason for a document to fail
+    /// PDF/A validation. Like [`security_findings`](Self::security_findings),
+    /// this scans every object, so fonts that no page uses are listed too.
+    /// The CIDFonts below a `Type0` font are reported through their parent.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// for font in analyzer.font_report().iter().filter(|f| !f.embedded) {
+    ///     println!("not embedded: {}", font.name.as_deref().unwrap_or("?"));
+    /// }
+    /// ```
+    pub fn font_report(&self) -> Vec<FontInfo> {
+        let document = self.document();
+        document
+            .objects
+            .iter()
+            .filter_map(|(&id, object)| {
+                let font = object.as_dict().ok()?;
+                if font.get(b"Type").and_then(Object::as_name).ok() != Some(b"Font") {
+                    return None;
+                }
+                let subtype = name(font, b"Subtype")?;
+                if subtype.starts_with("CIDFontType") {
+                    return None;
+                }
+                let base_font = name(font, b"BaseFont");
+                Some(FontInfo {
+                    object_id: id.into(),
+                    subset: base_font.as_deref().is_some_and(is_subset),
+                    name: base_font,
+                    embedded: subtype == "Type3" || is_embedded(document, font),
+                    encoding: encoding(document, font),
+                    subtype,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether the font program of `font`, or of its first descendant font, is
+/// embedded.
+fn is_embedded(document: &Document, font: &Dictionary) -> bool {
+    let font = match font.get(b"DescendantFonts") {
+        Ok(descendants) => {
+            let Some(first) = resolve(document, descendants)
+                .and_then(|d| d.as_array().ok()?.first())
+                .and_then(|d| resolve(document, d)?.as_dict().ok())
+            else {
+                return false;
+            };
+            first
+        }
+        Err(_) => font,
+    };
+    font.get(b"FontDescriptor")
+        .ok()
+        .and_then(|d| resolve(document, d)?.as_dict().ok())
+        .is_some_and(|descriptor| FONT_FILE_KEYS.iter().any(|key| descriptor.has(key)))
+}
+
+/// The encoding of `font`; see [`FontInfo::encoding`].
+fn encoding(document: &Document, font: &Dictionary) -> Option<String> {
+    match resolve(document, font.get(b"Encoding").ok()?)? {
+        Object::Name(encoding) => Some(String::from_utf8_lossy(encoding).into_owned()),
+        Object::Dictionary(dict) => name(dict, b"BaseEncoding"),
+        Object::Stream(cmap) => name(&cmap.dict, b"CMapName"),
+        _ => None,
+    }
+}
+
+/// Whether `name` starts with a subset tag such as `ABCDEF+`.
+fn is_subset(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() > 7 && bytes[6] == b'+' && bytes[..6].iter().all(u8::is_ascii_uppercase)
+}
+
+/// The name value of `key` in `dict`.
+fn name(dict: &Dictionary, key: &[u8]) -> Option<String> {
+    let name = dict.get(key).and_then(Object::as_name).ok()?;
+    Some(String::from_utf8_lossy(name).into_owned())
+}
+
+/// `object`, or the object it references.
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    match object {
+        Object::Reference(id) => document.get_object(*id).ok(),
+        object => Some(object),
+    }
+}
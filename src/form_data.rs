@@ -0,0 +1,326 @@
+use crate::pdf_utils::contains;
+use crate::{repair, xmp, EmbeddedFile};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+
+/// Namespace of the XFDF root element.
+const XFDF_NAMESPACE: &[u8] = b"http://ns.adobe.com/xfdf/";
+
+/// Deepest `/Kids` nesting followed in an FDF field tree.
+const MAX_FIELD_DEPTH: usize = 32;
+
+// ── FormData ──────────────────────────────────────────────────────────────────
+
+/// The form fields carried by an FDF or XFDF attachment, returned by
+/// [`EmbeddedFile::form_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FormData {
+    /// Whether the attachment is FDF or XFDF.
+    pub format: FormDataFormat,
+
+    /// The form document the data belongs to: FDF `/F` or XFDF `<f href>`.
+    pub target: Option<String>,
+
+    /// The terminal fields in document order.
+    pub fields: Vec<FormField>,
+}
+
+/// The syntax of a [`FormData`] attachment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FormDataFormat {
+    /// Forms Data Format, PDF object syntax with a `%FDF-` header.
+    Fdf,
+    /// XML Forms Data Format.
+    Xfdf,
+}
+
+/// One form field of a [`FormData`] attachment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FormField {
+    /// The fully qualified field name, with the names of its ancestors
+    /// joined by `.`, e.g. `"address.city"`.
+    pub name: String,
+
+    /// The field's values: one for text fields and buttons, several for
+    /// multiple-selection list boxes, none for a field without a value.
+    pub values: Vec<String>,
+}
+
+impl FormData {
+    /// The first value of the field with the fully qualified `name`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        let field = self.fields.iter().find(|field| field.name == name)?;
+        field.values.first().map(String::as_str)
+    }
+}
+
+impl EmbeddedFile {
+    /// Parses an FDF or XFDF attachment into the form it targets and its
+    /// field names and values, so that form data round-tripped through a
+    /// PDF can be processed further. Returns `None` for any other file,
+    /// judged by the content alone; see [`AttachmentKind::FormData`].
+    ///
+    /// [`AttachmentKind::FormData`]: crate::AttachmentKind::FormData
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, FormDataFormat};
+    ///
+    /// let file = EmbeddedFile {
+    ///     filename: "data.xfdf".into(),
+    ///     data: br#"<xfdf xmlns="http://ns.adobe.com/xfdf/"><f href="form.pdf"/>
+    ///         <fields><field name="name"><value>Jane</value></field></fields></xfdf>"#
+    ///         .to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// let form = file.form_data().unwrap();
+    /// assert_eq!(form.format, FormDataFormat::Xfdf);
+    /// assert_eq!(form.target.as_deref(), Some("form.pdf"));
+    /// assert_eq!(form.value("name"), Some("Jane"));
+    /// ```
+    pub fn form_data(&self) -> Option<FormData> {
+        match detect(&self.data)? {
+            FormDataFormat::Fdf => parse_fdf(&self.data),
+            FormDataFormat::Xfdf => Some(parse_xfdf(&String::from_utf8_lossy(&self.data))),
+        }
+    }
+}
+
+/// The form data format of `data`, by its header or root element.
+pub(crate) fn detect(data: &[u8]) -> Option<FormDataFormat> {
+    if data.starts_with(b"%FDF-") {
+        return Some(FormDataFormat::Fdf);
+    }
+    let head = &data[..data.len().min(1024)];
+    let has_root = contains(head, b"<xfdf ") || contains(head, b"<xfdf>");
+    (has_root && contains(head, XFDF_NAMESPACE)).then_some(FormDataFormat::Xfdf)
+}
+
+// ── FDF ───────────────────────────────────────────────────────────────────────
+
+/// Load FDF `data` by its PDF object syntax. FDF files rarely have a
+/// cross-reference table, so one is built for them.
+fn parse_fdf(data: &[u8]) -> Option<FormData> {
+    let mut pdf = data.to_vec();
+    pdf[..5].copy_from_slice(b"%PDF-");
+    let document = Document::load_mem(&pdf)
+        .ok()
+        .or_else(|| Document::load_mem(&repair::rebuild_xref(&pdf)?).ok())?;
+    let fdf = resolve(&document, document.catalog().ok()?.get(b"FDF").ok()?)?
+        .as_dict()
+        .ok()?;
+
+    let mut fields = Vec::new();
+    if let Ok(kids) = fdf.get(b"Fields") {
+        collect_fields(&document, kids, "", 0, &mut HashSet::new(), &mut fields);
+    }
+    Some(FormData {
+        format: FormDataFormat::Fdf,
+        target: fdf.get(b"F").ok().and_then(|f| file_name(&document, f)),
+        fields,
+    })
+}
+
+/// Add the terminal fields of the field array `kids` to `fields`, qualifying
+/// their names with `parent`. Every indirect field and field array is
+/// visited once, so fields that list themselves among their kids cannot
+/// multiply the work.
+fn collect_fields(
+    document: &Document,
+    kids: &Object,
+    parent: &str,
+    depth: usize,
+    seen: &mut HashSet<ObjectId>,
+    fields: &mut Vec<FormField>,
+) {
+    if let Object::Reference(id) = kids {
+        if !seen.insert(*id) {
+            return;
+        }
+    }
+    let Some(Ok(kids)) = resolve(document, kids).map(Object::as_array) else {
+        return;
+    };
+    for field in kids {
+        if let Object::Reference(id) = field {
+            if !seen.insert(*id) {
+                continue;
+            }
+        }
+        let Some(Ok(field)) = resolve(document, field).map(Object::as_dict) else {
+            continue;
+        };
+        let partial = field
+            .get(b"T")
+            .ok()
+            .and_then(|t| text(resolve(document, t)?));
+        let name = match (parent, partial) {
+            (parent, Some(partial)) if !parent.is_empty() => format!("{parent}.{partial}"),
+            (_, Some(partial)) => partial,
+            (parent, None) => parent.to_owned(),
+        };
+        let children = field.get(b"Kids").ok();
+        match children.filter(|kids| resolve(document, kids).is_some()) {
+            Some(kids) if depth < MAX_FIELD_DEPTH => {
+                collect_fields(document, kids, &name, depth + 1, seen, fields)
+            }
+            Some(_) => {}
+            None => fields.push(FormField {
+                values: values(document, field),
+                name,
+            }),
+        }
+    }
+}
+
+/// The `/V` of an FDF field as text.
+fn values(document: &Document, field: &Dictionary) -> Vec<String> {
+    let Some(value) = field.get(b"V").ok().and_then(|v| resolve(document, v)) else {
+        return Vec::new();
+    };
+    match value {
+        Object::Array(items) => items
+            .iter()
+            .filter_map(|item| text(resolve(document, item)?))
+            .collect(),
+        value => text(value).into_iter().collect(),
+    }
+}
+
+/// A file specification string, or the filename of a file specification
+/// dictionary.
+fn file_name(document: &Document, spec: &Object) -> Option<String> {
+    match resolve(document, spec)? {
+        Object::Dictionary(spec) => [b"UF".as_slice(), b"F"]
+            .iter()
+            .find_map(|key| text(resolve(document, spec.get(key).ok()?)?)),
+        spec => text(spec),
+    }
+}
+
+/// A string, name or number as text.
+fn text(object: &Object) -> Option<String> {
+    match object {
+        Object::String(bytes, _) => Some(text_string(bytes)),
+        Object::Name(name) => Some(String::from_utf8_lossy(name).into_owned()),
+        Object::Integer(n) => Some(n.to_string()),
+        Object::Real(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Decode a PDF text string: UTF-16BE with a byte order mark, UTF-8, or else
+/// PDFDocEncoding, read as Latin-1.
+fn text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(b"\xfe\xff") {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let utf8 = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    match std::str::from_utf8(utf8) {
+        Ok(text) => text.to_owned(),
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+/// `object`, or the object it references.
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    match object {
+        Object::Reference(id) => document.get_object(*id).ok(),
+        object => Some(object),
+    }
+}
+
+// ── XFDF ──────────────────────────────────────────────────────────────────────
+
+/// Read the `<f>` target and the nested `<field>` elements of an XFDF
+/// document.
+fn parse_xfdf(xml: &str) -> FormData {
+    let mut target = None;
+    let mut fields = Vec::new();
+    // The open <field> elements: qualified name, values, whether it has
+    // child fields.
+    let mut open: Vec<(String, Vec<String>, bool)> = Vec::new();
+
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        let after = &rest[start + end + 1..];
+        rest = after;
+
+        let closing = tag.starts_with('/');
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let name = tag
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        let local = name.rsplit(':').next().unwrap_or(name);
+
+        match (local, closing) {
+            ("f", false) => target = attribute(tag, "href").map(xmp::unescape),
+            ("field", false) => {
+                let partial = attribute(tag, "name")
+                    .map(xmp::unescape)
+                    .unwrap_or_default();
+                let qualified = match open.last_mut() {
+                    Some((parent, _, has_children)) => {
+                        *has_children = true;
+                        format!("{parent}.{partial}")
+                    }
+                    None => partial,
+                };
+                open.push((qualified, Vec::new(), false));
+                if self_closing {
+                    close_field(&mut open, &mut fields);
+                }
+            }
+            ("field", true) => close_field(&mut open, &mut fields),
+            ("value", false) if !self_closing => {
+                let text = &after[..after.find('<').unwrap_or(after.len())];
+                if let Some((_, values, _)) = open.last_mut() {
+                    values.push(xmp::unescape(text));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    FormData {
+        format: FormDataFormat::Xfdf,
+        target,
+        fields,
+    }
+}
+
+/// Close the innermost open `<field>`, keeping it when it is terminal.
+fn close_field(open: &mut Vec<(String, Vec<String>, bool)>, fields: &mut Vec<FormField>) {
+    if let Some((name, values, false)) = open.pop() {
+        fields.push(FormField { name, values });
+    }
+}
+
+/// The value of the attribute `name` in the start tag `tag`.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.match_indices(name).find_map(|(at, _)| {
+        let before = tag[..at].chars().next_back()?;
+        if !before.is_whitespace() {
+            return None;
+        }
+        let value = tag[at + name.len()..]
+            .trim_start()
+            .strip_prefix('=')?
+            .trim_start();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let value = &value[1..];
+        Some(&value[..value.find(quote)?])
+    })
+}
@@ -0,0 +1,24 @@
+// ── Human-readable sizes ──────────────────────────────────────────────────────
+
+/// Format a byte count with a binary unit suffix, e.g. `"5.8 KiB"`.
+///
+/// ```
+/// use extractembedfilepdf::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 B");
+/// assert_eq!(format_bytes(5902), "5.8 KiB");
+/// assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+/// ```
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
@@ -0,0 +1,165 @@
+//! Entry points for fuzzing the crate with
+//! [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz) or any other
+//! coverage-guided fuzzer.
+//!
+//! Each function feeds untrusted bytes through one part of the public API
+//! and discards the results; errors are expected, while a panic, hang or
+//! runaway allocation is a bug. Every run is bounded by [`TIME_LIMIT`] and
+//! [`MEMORY_LIMIT`] and never touches the filesystem.
+//!
+//! A fuzz target for the whole pipeline, driven by a fuzzed configuration:
+//!
+//! ```ignore
+//! #![no_main]
+//! use extractembedfilepdf::fuzz::{fuzz_with_config, FuzzInput};
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|input: FuzzInput| fuzz_with_config(&input));
+//! ```
+//!
+//! Available with the `fuzzing` feature.
+
+use crate::{
+    AttachmentSource, EmbeddedFile, ExtractorConfig, LazyDocument, PdfAnalyzer, RiskPolicy,
+};
+use arbitrary::Arbitrary;
+use std::time::Duration;
+
+/// The [`ExtractorConfig::max_duration`] of every fuzzed run.
+pub const TIME_LIMIT: Duration = Duration::from_secs(2);
+
+/// The [`ExtractorConfig::max_memory_bytes`] of every fuzzed run.
+pub const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+// ── FuzzConfig ────────────────────────────────────────────────────────────────
+
+/// The fuzzable part of an [`ExtractorConfig`]. Settings that write to disk
+/// or call back into user code are left at their defaults, and the time and
+/// memory budgets are always set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Arbitrary)]
+pub struct FuzzConfig {
+    /// [`ExtractorConfig::strict_pdfa3_validation`].
+    pub strict_pdfa3_validation: bool,
+    /// [`ExtractorConfig::max_embedded_file_size`].
+    pub max_embedded_file_size: Option<u32>,
+    /// [`ExtractorConfig::discovery_sources`].
+    pub discovery_sources: Option<Vec<AttachmentSource>>,
+    /// [`ExtractorConfig::allow_pageless`].
+    pub allow_pageless: bool,
+    /// [`ExtractorConfig::attachments_only`].
+    pub attachments_only: bool,
+    /// [`ExtractorConfig::check_file_structure`].
+    pub check_file_structure: bool,
+    /// [`ExtractorConfig::recover_truncated_streams`].
+    pub recover_truncated_streams: bool,
+    /// [`ExtractorConfig::fail_on_any_error`].
+    pub fail_on_any_error: bool,
+    /// [`ExtractorConfig::risk_policy`].
+    pub risk_policy: RiskPolicy,
+}
+
+impl From<FuzzConfig> for ExtractorConfig {
+    fn from(config: FuzzConfig) -> Self {
+        ExtractorConfig {
+            strict_pdfa3_validation: config.strict_pdfa3_validation,
+            max_embedded_file_size: config.max_embedded_file_size.map(|size| size as usize),
+            discovery_sources: config.discovery_sources,
+            allow_pageless: config.allow_pageless,
+            attachments_only: config.attachments_only,
+            check_file_structure: config.check_file_structure,
+            recover_truncated_streams: config.recover_truncated_streams,
+            fail_on_any_error: config.fail_on_any_error,
+            risk_policy: config.risk_policy,
+            max_duration: Some(TIME_LIMIT),
+            max_memory_bytes: Some(MEMORY_LIMIT),
+            ..Default::default()
+        }
+    }
+}
+
+/// A document and the configuration to analyze it with, for
+/// [`fuzz_with_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Arbitrary)]
+pub struct FuzzInput {
+    /// The configuration.
+    pub config: FuzzConfig,
+    /// The document bytes.
+    pub data: Vec<u8>,
+}
+
+// ── Entry points ──────────────────────────────────────────────────────────────
+
+/// Loads `data` as a PDF with the default configuration and runs the whole
+/// pipeline on it: validation, discovery, extraction, the document-level
+/// checks and every parser for the extracted files. Extraction runs through
+/// [`LazyDocument`] as well.
+pub fn fuzz_load_and_extract(data: &[u8]) {
+    run(data, FuzzConfig::default().into());
+}
+
+/// Like [`fuzz_load_and_extract`], with the configuration taken from
+/// `input`.
+pub fn fuzz_with_config(input: &FuzzInput) {
+    run(&input.data, input.config.clone().into());
+}
+
+/// Runs every parser for extracted files, such as invoice, form data and
+/// QR-bill detection, on `data` as an attachment named `filename`.
+pub fn fuzz_attachment(filename: &str, data: &[u8]) {
+    let file = EmbeddedFile {
+        filename: filename.to_owned(),
+        data: data.to_vec(),
+        metadata: Default::default(),
+    };
+    inspect(&file, &FuzzConfig::default().into());
+}
+
+/// Runs the pipeline on `data` with `config`.
+fn run(data: &[u8], config: ExtractorConfig) {
+    let _ = PdfAnalyzer::probe_bytes(data).worth_analyzing();
+    if let Ok(lazy) = LazyDocument::from_bytes_with_config(data, config.clone()) {
+        let _ = lazy.extract_with_report();
+    }
+    let Ok(analyzer) = PdfAnalyzer::from_bytes_with_config(data, config) else {
+        return;
+    };
+
+    let _ = analyzer.metadata_revisions(data);
+    let _ = analyzer.is_pdf();
+    let _ = analyzer.is_pdfa3();
+    let _ = analyzer.conformance();
+    let _ = analyzer.document_metadata();
+    let _ = analyzer.embedded_file_names();
+    let _ = analyzer.analyze();
+    let _ = analyzer.risk_report();
+    let _ = analyzer.validate_hybrid_invoice();
+    let _ = analyzer.external_references();
+    let _ = analyzer.font_report();
+    let _ = analyzer.xfa_datasets();
+    let _ = analyzer.extract_text();
+
+    if let Ok(files) = analyzer.extract_embedded_files() {
+        for file in &files {
+            inspect(file, analyzer.config());
+        }
+    }
+}
+
+/// Runs every parser for extracted files on `file`. Nested PDFs are loaded
+/// with `config`, so within the same limits, and only listed, not inspected
+/// in turn, so a fuzzed input cannot recurse.
+fn inspect(file: &EmbeddedFile, config: &ExtractorConfig) {
+    let _ = file.classify();
+    let _ = file.is_auxiliary();
+    let _ = file.risk_category();
+    let _ = file.form_data();
+    let _ = file.qr_bill();
+    let _ = file.peppol();
+    let _ = file.normalized_xml();
+    let _ = file.summary();
+    let _ = file.verify_checksum();
+    if file.is_pdf() {
+        let nested = file.analyze_with_config(config.clone());
+        let _ = nested.map(|nested| nested.embedded_file_names());
+    }
+}
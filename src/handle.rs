@@ -0,0 +1,163 @@
+use crate::file_discovery::FileSpec;
+use crate::{
+    AfRelationship, EmbeddedFile, EmbeddedFileMetadata, ObjectRef, PdfAnalyzer, PdfDict, Result,
+};
+use std::collections::BTreeMap;
+
+// ── EmbeddedFileHandle ────────────────────────────────────────────────────────
+
+/// A reference to an embedded file whose content has not been decoded yet.
+///
+/// The filename and metadata are available immediately; the embedded stream
+/// is only decompressed when [`read`](Self::read) or [`decode`](Self::decode)
+/// is called. Returned by [`PdfAnalyzer::embedded_file_handles`].
+///
+/// # Example
+///
+/// ```no_run
+/// use extractembedfilepdf::PdfAnalyzer;
+///
+/// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+/// for handle in analyzer.embedded_file_handles().unwrap() {
+///     println!("{}", handle.filename());
+///     if handle.filename().ends_with(".xml") {
+///         let bytes = handle.read().unwrap();
+///         println!("  {} bytes", bytes.len());
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct EmbeddedFileHandle<'a> {
+    analyzer: &'a PdfAnalyzer,
+    spec: &'a FileSpec,
+    filename: String,
+    metadata: EmbeddedFileMetadata,
+}
+
+impl<'a> EmbeddedFileHandle<'a> {
+    /// The filename as declared in the file specification.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Metadata read from the file specification and `/Params` dictionary.
+    pub fn metadata(&self) -> &EmbeddedFileMetadata {
+        &self.metadata
+    }
+
+    /// The indirect object holding the file specification dictionary.
+    pub fn spec_ref(&self) -> ObjectRef {
+        self.spec.id.into()
+    }
+
+    /// A read-only view of the file specification dictionary, for entries
+    /// that [`metadata`](Self::metadata) does not cover.
+    pub fn spec_dict(&self) -> Option<PdfDict<'a>> {
+        self.analyzer.dictionary(self.spec_ref())
+    }
+
+    /// Decode the embedded stream and return its bytes.
+    ///
+    /// [`ExtractorConfig::max_embedded_file_size`] is enforced; disk writing
+    /// is never triggered from a handle.
+    ///
+    /// [`ExtractorConfig::max_embedded_file_size`]: crate::ExtractorConfig::max_embedded_file_size
+    pub fn read(&self) -> Result<Vec<u8>> {
+        self.analyzer.engine()?.read_file(self.spec)
+    }
+
+    /// Decode the embedded stream and return an owned [`EmbeddedFile`].
+    ///
+    /// Unlike [`metadata`](Self::metadata), the returned metadata reports
+    /// [`undecoded_filters`](EmbeddedFileMetadata::undecoded_filters).
+    pub fn decode(&self) -> Result<EmbeddedFile> {
+        self.analyzer.engine()?.read_embedded_file(self.spec)
+    }
+}
+
+impl std::fmt::Debug for EmbeddedFileHandle<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddedFileHandle")
+            .field("filename", &self.filename)
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PdfAnalyzer {
+    /// Returns a lazy handle for every embedded file, without decoding any
+    /// stream.
+    ///
+    /// File specifications whose structure cannot be read are skipped, as in
+    /// [`extract_embedded_files`](Self::extract_embedded_files), unless
+    /// [`ExtractorConfig::fail_on_any_error`](crate::ExtractorConfig::fail_on_any_error)
+    /// is set.
+    pub fn embedded_file_handles(&self) -> Result<Vec<EmbeddedFileHandle<'_>>> {
+        let engine = self.engine()?;
+        let mut handles = Vec::with_capacity(engine.specs().len());
+        for spec in engine.specs() {
+            match engine.file_info(spec) {
+                Ok((filename, metadata)) => handles.push(EmbeddedFileHandle {
+                    analyzer: self,
+                    spec,
+                    filename,
+                    metadata,
+                }),
+                Err(e) if self.config().fail_on_any_error => return Err(e),
+                Err(e) => log::warn!("skipping '{}': {}", spec.name, e),
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Returns the [embedded file handles](Self::embedded_file_handles)
+    /// grouped by their `/AFRelationship`, each group in discovery order.
+    ///
+    /// A file specification without the entry is grouped under
+    /// [`AfRelationship::Unspecified`], its default in ISO 32000-2.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let groups = analyzer.attachments_by_relationship().unwrap();
+    /// for (relationship, files) in &groups {
+    ///     println!("{relationship}: {} file(s)", files.len());
+    /// }
+    /// ```
+    pub fn attachments_by_relationship(
+        &self,
+    ) -> Result<BTreeMap<AfRelationship, Vec<EmbeddedFileHandle<'_>>>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for handle in self.embedded_file_handles()? {
+            let relationship = handle
+                .metadata
+                .af_relationship
+                .clone()
+                .unwrap_or(AfRelationship::Unspecified);
+            groups.entry(relationship).or_default().push(handle);
+        }
+        Ok(groups)
+    }
+
+    /// The attachments declared with `/AFRelationship /Data`, such as the
+    /// invoice XML of a ZUGFeRD or Factur-X document.
+    pub fn data_files(&self) -> Result<Vec<EmbeddedFileHandle<'_>>> {
+        self.handles_with_relationship(AfRelationship::Data)
+    }
+
+    /// The attachments declared with `/AFRelationship /Source`, the original
+    /// material the document was produced from.
+    pub fn source_files(&self) -> Result<Vec<EmbeddedFileHandle<'_>>> {
+        self.handles_with_relationship(AfRelationship::Source)
+    }
+
+    fn handles_with_relationship(
+        &self,
+        relationship: AfRelationship,
+    ) -> Result<Vec<EmbeddedFileHandle<'_>>> {
+        let mut handles = self.embedded_file_handles()?;
+        handles.retain(|handle| handle.metadata.af_relationship.as_ref() == Some(&relationship));
+        Ok(handles)
+    }
+}
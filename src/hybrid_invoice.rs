@@ -0,0 +1,324 @@
+use crate::extension_schema::{INVOICE_NAMESPACES, ZUGFERD_1_XMP_NAMESPACE};
+use crate::kind::{is_invoice_filename, ZUGFERD_1_NAMESPACE};
+use crate::pdf_utils::contains;
+use crate::{xmp, AfRelationship, AttachmentKind, EmbeddedFile, ExtractError, PdfAnalyzer, Result};
+
+/// The element of a Cross Industry Invoice whose `ID` names the profile.
+const GUIDELINE_ELEMENT: &str = "GuidelineSpecifiedDocumentContextParameter";
+
+/// The `/AFRelationship` values Factur-X allows for the invoice XML.
+const INVOICE_RELATIONSHIPS: [AfRelationship; 3] = [
+    AfRelationship::Data,
+    AfRelationship::Alternative,
+    AfRelationship::Source,
+];
+
+// ── HybridInvoiceValidation ───────────────────────────────────────────────────
+
+/// The outcome of [`PdfAnalyzer::validate_hybrid_invoice`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HybridInvoiceValidation {
+    /// The guideline identifier of the invoice XML, e.g.
+    /// `urn:cen.eu:en16931:2017#compliant#urn:factur-x.eu:1p0:basic`.
+    pub profile: Option<String>,
+
+    /// The profile as an XMP conformance level, e.g. `"BASIC"` or
+    /// `"EN 16931"`, when the guideline identifier is a known one.
+    pub conformance_level: Option<String>,
+
+    /// The document is a ZUGFeRD 1.0 invoice, by its XML namespace or XMP
+    /// schema, and was checked under the rules of that version.
+    pub legacy_zugferd: bool,
+
+    /// The packaging rules the document breaks, empty when it passes.
+    pub findings: Vec<HybridInvoiceFinding>,
+}
+
+/// A packaging rule of Factur-X or ZUGFeRD that a document breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HybridInvoiceFinding {
+    /// The rule that is broken.
+    pub rule: HybridInvoiceRule,
+
+    /// A human-readable description.
+    pub message: String,
+}
+
+/// A packaging rule checked by [`PdfAnalyzer::validate_hybrid_invoice`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HybridInvoiceRule {
+    /// The document carries exactly one invoice XML attachment.
+    InvoiceCount,
+    /// The invoice XML has a prescribed filename, the one the XMP
+    /// descriptor names.
+    FileName,
+    /// The invoice XML's `/AFRelationship` is `Data`, `Alternative` or
+    /// `Source`, and `Data` for the MINIMUM and BASIC WL profiles, whose
+    /// XML is not a complete invoice. ZUGFeRD 1.0 invoices may omit it.
+    AfRelationship,
+    /// The document declares PDF/A-3.
+    PdfA3,
+    /// The XMP metadata has a complete `fx:` descriptor whose conformance
+    /// level is the profile of the invoice XML.
+    XmpDescriptor,
+}
+
+impl HybridInvoiceValidation {
+    /// Returns `true` when no rule is broken.
+    pub fn is_valid(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl PdfAnalyzer {
+    /// Checks the packaging rules that make a PDF a Factur-X or ZUGFeRD
+    /// hybrid invoice: exactly one invoice XML attachment under a prescribed
+    /// filename, an `/AFRelationship` the profile allows, a PDF/A-3
+    /// identification, and an XMP `fx:` descriptor that names the attachment
+    /// and the profile of the XML.
+    ///
+    /// The profile is read from the `GuidelineSpecifiedDocumentContextParameter`
+    /// of a Cross Industry Invoice. The invoice XML itself is not validated.
+    ///
+    /// ZUGFeRD 1.0 invoices, with a `ZUGFeRD-invoice.xml` attachment and the
+    /// `zf:` XMP schema, are recognised and held to the rules of their
+    /// version, which did not yet require an `/AFRelationship`; see
+    /// [`legacy_zugferd`](HybridInvoiceValidation::legacy_zugferd).
+    ///
+    /// # Errors
+    ///
+    /// Fails when the attachments cannot be extracted; a document without
+    /// attachments yields an [`InvoiceCount`](HybridInvoiceRule::InvoiceCount)
+    /// finding instead.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("factur-x.pdf").unwrap();
+    /// let validation = analyzer.validate_hybrid_invoice().unwrap();
+    /// for finding in &validation.findings {
+    ///     println!("{:?}: {}", finding.rule, finding.message);
+    /// }
+    /// ```
+    pub fn validate_hybrid_invoice(&self) -> Result<HybridInvoiceValidation> {
+        let files = match self.extract_embedded_files() {
+            Ok(files) => files,
+            Err(ExtractError::NoEmbeddedFiles) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut validation = HybridInvoiceValidation::default();
+        let mut finding = |rule, message: String| {
+            validation
+                .findings
+                .push(HybridInvoiceFinding { rule, message })
+        };
+
+        match self.conformance() {
+            Some(conformance) if conformance.part == 3 => {}
+            Some(conformance) => finding(
+                HybridInvoiceRule::PdfA3,
+                format!("the document declares {conformance}, not PDF/A-3"),
+            ),
+            None => finding(
+                HybridInvoiceRule::PdfA3,
+                "the document declares no PDF/A conformance".into(),
+            ),
+        }
+
+        let invoices: Vec<&EmbeddedFile> = files
+            .iter()
+            .filter(|file| {
+                file.classify_with(&self.config().mime_types) == AttachmentKind::InvoiceXml
+            })
+            .collect();
+        match invoices.as_slice() {
+            [] => finding(
+                HybridInvoiceRule::InvoiceCount,
+                "the document has no invoice XML attachment".into(),
+            ),
+            [_] => {}
+            invoices => finding(
+                HybridInvoiceRule::InvoiceCount,
+                format!(
+                    "the document has {} invoice XML attachments: {}",
+                    invoices.len(),
+                    invoices
+                        .iter()
+                        .map(|file| file.filename.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        }
+
+        let descriptor = self.xmp_info().ok().and_then(|xmp| descriptor(&xmp.packet));
+        let invoice = invoices.first().copied();
+        let profile = invoice.and_then(|file| guideline_id(&xmp::decode(&file.data)));
+        let level = profile.as_deref().and_then(conformance_level);
+        let legacy_zugferd = descriptor.as_ref().is_some_and(|d| d.legacy)
+            || invoice.is_some_and(|file| contains(&file.data, ZUGFERD_1_NAMESPACE.as_bytes()));
+
+        if let Some(invoice) = invoice {
+            let name = &invoice.filename;
+            let declared = descriptor.as_ref().and_then(|d| d.file_name.as_deref());
+            if !is_invoice_filename(name) {
+                finding(
+                    HybridInvoiceRule::FileName,
+                    format!("'{name}' is not a filename the specifications prescribe"),
+                );
+            } else if let Some(declared) = declared.filter(|declared| declared != name) {
+                finding(
+                    HybridInvoiceRule::FileName,
+                    format!(
+                        "XMP fx:DocumentFileName is '{declared}' but the invoice attachment is '{name}'"
+                    ),
+                );
+            }
+
+            let allowed: &[AfRelationship] = match level {
+                Some("MINIMUM" | "BASIC WL") => &INVOICE_RELATIONSHIPS[..1],
+                _ => &INVOICE_RELATIONSHIPS,
+            };
+            match &invoice.metadata.af_relationship {
+                None if legacy_zugferd => {}
+                None => finding(
+                    HybridInvoiceRule::AfRelationship,
+                    format!("invoice attachment '{name}' has no /AFRelationship"),
+                ),
+                Some(relationship) if !allowed.contains(relationship) => finding(
+                    HybridInvoiceRule::AfRelationship,
+                    format!(
+                        "invoice attachment '{name}' is /{relationship}, but the {} profile allows {}",
+                        level.unwrap_or("invoice"),
+                        allowed
+                            .iter()
+                            .map(|r| format!("/{r}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        match &descriptor {
+            None => finding(
+                HybridInvoiceRule::XmpDescriptor,
+                "the XMP metadata has no Factur-X or ZUGFeRD descriptor".into(),
+            ),
+            Some(descriptor) => {
+                let properties = [
+                    ("DocumentType", &descriptor.document_type),
+                    ("DocumentFileName", &descriptor.file_name),
+                    ("Version", &descriptor.version),
+                    ("ConformanceLevel", &descriptor.conformance_level),
+                ];
+                for (property, value) in properties {
+                    if value.is_none() {
+                        finding(
+                            HybridInvoiceRule::XmpDescriptor,
+                            format!("the XMP descriptor has no fx:{property}"),
+                        );
+                    }
+                }
+                if let (Some(declared), Some(level)) = (&descriptor.conformance_level, level) {
+                    if normalize(declared) != normalize(level) {
+                        finding(
+                            HybridInvoiceRule::XmpDescriptor,
+                            format!(
+                                "XMP fx:ConformanceLevel is '{declared}' but the invoice XML is {level}"
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        validation.legacy_zugferd = legacy_zugferd;
+        validation.conformance_level = level.map(str::to_owned);
+        validation.profile = profile;
+        Ok(validation)
+    }
+}
+
+/// The `fx:` properties of the invoice descriptor in the XMP metadata.
+struct Descriptor {
+    document_type: Option<String>,
+    file_name: Option<String>,
+    version: Option<String>,
+    conformance_level: Option<String>,
+    /// Whether the properties are in the ZUGFeRD 1.0 schema.
+    legacy: bool,
+}
+
+/// The invoice descriptor in `packet`, or `None` when it sets none of its
+/// properties.
+fn descriptor(packet: &str) -> Option<Descriptor> {
+    INVOICE_NAMESPACES.iter().find_map(|namespace| {
+        let property = |name| xmp::property(packet, namespace, "fx", name).map(xmp::unescape);
+        let descriptor = Descriptor {
+            document_type: property("DocumentType"),
+            file_name: property("DocumentFileName"),
+            version: property("Version"),
+            conformance_level: property("ConformanceLevel"),
+            legacy: *namespace == ZUGFERD_1_XMP_NAMESPACE,
+        };
+        let is_set = descriptor.document_type.is_some()
+            || descriptor.file_name.is_some()
+            || descriptor.version.is_some()
+            || descriptor.conformance_level.is_some();
+        is_set.then_some(descriptor)
+    })
+}
+
+/// The `ID` of the guideline context parameter of a Cross Industry Invoice.
+fn guideline_id(xml: &str) -> Option<String> {
+    let (_, mut rest) = xml.split_once(GUIDELINE_ELEMENT)?;
+    while let Some(start) = rest.find('<') {
+        let end = start + rest[start..].find('>')?;
+        let tag = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('/') {
+            if tag.ends_with(GUIDELINE_ELEMENT) {
+                return None;
+            }
+            continue;
+        }
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        if name.rsplit(':').next() == Some("ID") {
+            let id = rest[..rest.find('<')?].trim();
+            return (!id.is_empty()).then(|| xmp::unescape(id));
+        }
+    }
+    None
+}
+
+/// The XMP conformance level for the guideline identifier `guideline`.
+fn conformance_level(guideline: &str) -> Option<&'static str> {
+    let guideline = guideline.to_ascii_lowercase();
+    let last = guideline.rsplit(['#', ':']).next().unwrap_or_default();
+    let level = match last {
+        _ if guideline.contains("xrechnung") => "XRECHNUNG",
+        "minimum" => "MINIMUM",
+        "basicwl" => "BASIC WL",
+        "basic" => "BASIC",
+        "comfort" => "COMFORT",
+        "extended" => "EXTENDED",
+        _ if guideline == "urn:cen.eu:en16931:2017" => "EN 16931",
+        _ => return None,
+    };
+    Some(level)
+}
+
+/// `level` in upper case without spaces or punctuation, so that `EN 16931`
+/// matches `EN16931`.
+fn normalize(level: &str) -> String {
+    level
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
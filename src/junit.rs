@@ -0,0 +1,152 @@
+use crate::{AnalysisReport, ExtractionWarning};
+use std::fmt::Write;
+
+/// One `<testcase>` of the JUnit report.
+struct TestCase {
+    classname: &'static str,
+    name: String,
+    /// The failure type, summary and details, or `None` when the check passed.
+    failure: Option<(String, String, String)>,
+}
+
+impl AnalysisReport {
+    /// Renders the report as JUnit XML, so that conformance gates show up as
+    /// test results in CI systems such as Jenkins or GitLab.
+    ///
+    /// `suite` names the `<testsuite>`, typically the document's path. Each
+    /// check becomes one `<testcase>`:
+    ///
+    /// - `structure`: the document is a structurally valid PDF,
+    /// - `pdfa3`: the XMP metadata declares PDF/A-3,
+    /// - `attachments`: one per attachment, failing when it has warnings,
+    /// - `security`: the document carries no active content.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let report = PdfAnalyzer::from_path("invoice.pdf").unwrap().analyze().unwrap();
+    /// std::fs::write("pdfa3-report.xml", report.to_junit_xml("invoice.pdf")).unwrap();
+    /// ```
+    pub fn to_junit_xml(&self, suite: &str) -> String {
+        let cases = self.test_cases();
+        let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" errors=\"0\">",
+            escape(suite),
+            cases.len()
+        );
+        for case in &cases {
+            let _ = write!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\"",
+                case.classname,
+                escape(&case.name)
+            );
+            match &case.failure {
+                None => xml.push_str("/>\n"),
+                Some((kind, message, details)) => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>",
+                        escape(kind),
+                        escape(message),
+                        escape(details)
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    /// The checks of the report, in a stable order.
+    fn test_cases(&self) -> Vec<TestCase> {
+        let mut cases = vec![
+            TestCase {
+                classname: "structure",
+                name: "valid PDF".into(),
+                failure: (!self.is_pdf).then(|| {
+                    let message = "document is not a structurally valid PDF".to_string();
+                    ("InvalidPdf".into(), message.clone(), message)
+                }),
+            },
+            TestCase {
+                classname: "pdfa3",
+                name: "declares PDF/A-3".into(),
+                failure: (!self.is_pdfa3).then(|| {
+                    let message = match &self.conformance_level {
+                        Some(level) => format!("document declares {level}"),
+                        None => "document declares no PDF/A conformance".into(),
+                    };
+                    ("NotPdfA3".into(), message.clone(), message)
+                }),
+            },
+        ];
+
+        // One case per attachment, in discovery order, then one per name that
+        // only appears in the warnings because the file was skipped.
+        let mut names: Vec<&str> = self.files.iter().map(|f| f.filename.as_str()).collect();
+        for warning in &self.warnings {
+            if !names.contains(&warning.name.as_str()) {
+                names.push(&warning.name);
+            }
+        }
+        for name in names {
+            let warnings: Vec<&ExtractionWarning> =
+                self.warnings.iter().filter(|w| w.name == name).collect();
+            cases.push(TestCase {
+                classname: "attachments",
+                name: name.into(),
+                failure: warnings.first().map(|first| {
+                    let details = warnings
+                        .iter()
+                        .map(|w| format!("{:?}: {}", w.kind, w.message))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    (format!("{:?}", first.kind), first.message.clone(), details)
+                }),
+            });
+        }
+
+        cases.push(TestCase {
+            classname: "security",
+            name: "no active content".into(),
+            failure: (!self.security_findings.is_empty()).then(|| {
+                let details = self
+                    .security_findings
+                    .iter()
+                    .map(|f| match &f.detail {
+                        Some(detail) => format!("{:?} in {}: {detail}", f.kind, f.object_id),
+                        None => format!("{:?} in {}", f.kind, f.object_id),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let message = format!("{} active content finding(s)", self.security_findings.len());
+                ("ActiveContent".into(), message, details)
+            }),
+        });
+        cases
+    }
+}
+
+/// Escape `text` for use in XML character data and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab, newline and carriage
+            // return are not allowed in XML 1.0.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,348 @@
+use crate::pdf_utils::contains;
+use crate::risk::{self, RiskCategory};
+use crate::{form_data, mime, probe, qr_bill, AfRelationship, EmbeddedFile, MimeTable};
+
+/// Namespace of the `CrossIndustryDocument` root of ZUGFeRD 1.0 invoices,
+/// which predate the Cross Industry Invoice.
+pub(crate) const ZUGFERD_1_NAMESPACE: &str = "urn:ferd:CrossIndustryDocument:invoice:1p0";
+
+/// Text that marks an XML document as an electronic invoice: the Cross
+/// Industry Invoice root of Factur-X, ZUGFeRD and XRechnung, the ZUGFeRD 1.0
+/// namespace, and the UBL invoice and credit note namespaces.
+const INVOICE_MARKERS: [&[u8]; 4] = [
+    b"CrossIndustryInvoice",
+    ZUGFERD_1_NAMESPACE.as_bytes(),
+    b"urn:oasis:names:specification:ubl:schema:xsd:Invoice-2",
+    b"urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2",
+];
+
+/// Filenames that the Factur-X, ZUGFeRD and XRechnung specifications
+/// prescribe for the invoice XML, compared case-insensitively. ZUGFeRD 1.0
+/// spells its name `ZUGFeRD-invoice.xml`, ZUGFeRD 2.0 in lowercase.
+const INVOICE_FILENAMES: [&str; 4] = [
+    "factur-x.xml",
+    "zugferd-invoice.xml",
+    "xrechnung.xml",
+    "order-x.xml",
+];
+
+/// Extensions of files that support a document rather than carry its
+/// content: schemas, stylesheets, XMP sidecars and fonts.
+const AUXILIARY_EXTENSIONS: [&str; 13] = [
+    "xsd", "dtd", "rng", "xsl", "xslt", "xmp", "ttf", "otf", "woff", "woff2", "pfb", "pfa", "afm",
+];
+
+/// Magic bytes of font programs: WOFF and Type 1. TrueType and OpenType
+/// fonts are recognised by their table directory; see [`is_sfnt`].
+const FONT_SIGNATURES: [&[u8]; 4] = [b"wOFF", b"wOF2", b"%!PS-AdobeFont", b"%!FontType1"];
+
+/// Versions that start a TrueType or OpenType font program: TrueType,
+/// OpenType with CFF outlines, and Apple TrueType.
+const SFNT_VERSIONS: [&[u8]; 3] = [b"\x00\x01\x00\x00", b"OTTO", b"true"];
+
+/// Root elements of XML that describes other XML or the document: XML
+/// Schema, XSLT and XMP.
+const AUXILIARY_XML_MARKERS: [&[u8]; 4] = [
+    b"http://www.w3.org/2001/XMLSchema\"",
+    b"http://www.w3.org/1999/XSL/Transform",
+    b"<x:xmpmeta",
+    b"<?xpacket",
+];
+
+// ── AttachmentKind ────────────────────────────────────────────────────────────
+
+/// What an attachment is, returned by [`EmbeddedFile::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AttachmentKind {
+    /// Structured invoice XML: Factur-X, ZUGFeRD, XRechnung or UBL.
+    InvoiceXml,
+    /// An ICC colour profile, such as a PDF/A output intent.
+    IccProfile,
+    /// A PDF document.
+    Pdf,
+    /// A raster image: PNG, JPEG, GIF, TIFF, BMP or WebP.
+    Image,
+    /// An archive; see [`RiskCategory::Archive`].
+    Archive,
+    /// An Office or OpenDocument file, with or without macros.
+    Office,
+    /// Form field data in FDF or XFDF; see [`EmbeddedFile::form_data`].
+    FormData,
+    /// A Swiss QR-bill payload; see [`EmbeddedFile::qr_bill`].
+    QrBill,
+    /// Anything else.
+    Other,
+}
+
+impl AttachmentKind {
+    /// A short lowercase name, e.g. `"invoice-xml"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttachmentKind::InvoiceXml => "invoice-xml",
+            AttachmentKind::IccProfile => "icc-profile",
+            AttachmentKind::Pdf => "pdf",
+            AttachmentKind::Image => "image",
+            AttachmentKind::Archive => "archive",
+            AttachmentKind::Office => "office",
+            AttachmentKind::FormData => "form-data",
+            AttachmentKind::QrBill => "qr-bill",
+            AttachmentKind::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for AttachmentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl EmbeddedFile {
+    /// Classifies the attachment so it can be routed to the right consumer.
+    ///
+    /// The magic bytes of the data decide first. Data without a recognised
+    /// signature, such as a stream whose filters could not be applied, falls
+    /// back to the declared MIME type and then to the MIME type the built-in
+    /// [`MimeTable`] gives the filename extension.
+    /// XML counts as [`AttachmentKind::InvoiceXml`] when it holds a Cross
+    /// Industry Invoice, ZUGFeRD 1.0 or UBL root or carries a filename such
+    /// as `factur-x.xml`.
+    ///
+    /// ```
+    /// # use extractembedfilepdf::{AttachmentKind, EmbeddedFile};
+    /// let file = EmbeddedFile {
+    ///     filename: "factur-x.xml".into(),
+    ///     data: b"<?xml version=\"1.0\"?><rsm:CrossIndustryInvoice/>".to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// assert_eq!(file.classify(), AttachmentKind::InvoiceXml);
+    /// ```
+    pub fn classify(&self) -> AttachmentKind {
+        self.classify_with(mime::builtin())
+    }
+
+    /// Classifies the attachment like [`classify`](Self::classify), looking
+    /// the filename extension up in `mime_types`, such as a configured
+    /// [`ExtractorConfig::mime_types`](crate::ExtractorConfig::mime_types).
+    ///
+    /// ```
+    /// # use extractembedfilepdf::{AttachmentKind, EmbeddedFile, MimeTable};
+    /// let file = EmbeddedFile {
+    ///     filename: "scan.jfif".into(),
+    ///     data: b"\x00\x00".to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// assert_eq!(file.classify(), AttachmentKind::Other);
+    /// let table = MimeTable::default().with("jfif", "image/jpeg");
+    /// assert_eq!(file.classify_with(&table), AttachmentKind::Image);
+    /// ```
+    pub fn classify_with(&self, mime_types: &MimeTable) -> AttachmentKind {
+        by_content(&self.filename, &self.data)
+            .or_else(|| self.metadata.mime_type.as_deref().and_then(by_mime))
+            .or_else(|| mime_types.for_filename(&self.filename).and_then(by_mime))
+            .unwrap_or(AttachmentKind::Other)
+    }
+
+    /// Returns `true` for attachments that typically support the document
+    /// rather than carry a business payload: ICC profiles, XMP sidecars,
+    /// XML schemas and stylesheets, and fonts, recognised by content or
+    /// extension, as well as anything filed with `/AFRelationship /Schema`.
+    /// Extensions are looked up in the built-in [`MimeTable`]; see
+    /// [`is_auxiliary_with`](Self::is_auxiliary_with).
+    ///
+    /// Workflows that want "the business payload" can skip these with one
+    /// filter:
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let payload: Vec<_> = analyzer
+    ///     .extract_embedded_files()
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .filter(|file| !file.is_auxiliary())
+    ///     .collect();
+    /// ```
+    pub fn is_auxiliary(&self) -> bool {
+        self.is_auxiliary_with(mime::builtin())
+    }
+
+    /// Decides like [`is_auxiliary`](Self::is_auxiliary), classifying with
+    /// [`classify_with`](Self::classify_with) and also counting any extension
+    /// that `mime_types` maps to a `font/` type.
+    ///
+    /// ```
+    /// # use extractembedfilepdf::{EmbeddedFile, MimeTable};
+    /// let file = EmbeddedFile {
+    ///     filename: "glyphs.fnt".into(),
+    ///     data: vec![0; 16],
+    ///     metadata: Default::default(),
+    /// };
+    /// assert!(!file.is_auxiliary());
+    /// let table = MimeTable::default().with("fnt", "font/x-fnt");
+    /// assert!(file.is_auxiliary_with(&table));
+    /// ```
+    pub fn is_auxiliary_with(&self, mime_types: &MimeTable) -> bool {
+        if self.metadata.af_relationship == Some(AfRelationship::Schema)
+            || self.classify_with(mime_types) == AttachmentKind::IccProfile
+            || is_sfnt(&self.data)
+            || FONT_SIGNATURES
+                .iter()
+                .any(|magic| self.data.starts_with(magic))
+        {
+            return true;
+        }
+        if is_xml(&self.data) {
+            let head = &self.data[..self.data.len().min(4096)];
+            if AUXILIARY_XML_MARKERS
+                .iter()
+                .any(|marker| contains(head, marker))
+            {
+                return true;
+            }
+        }
+        AUXILIARY_EXTENSIONS
+            .iter()
+            .any(|extension| self.has_extension(extension))
+            || mime_types
+                .for_filename(&self.filename)
+                .is_some_and(|mime| mime.starts_with("font/"))
+    }
+}
+
+/// Whether `data` starts like a TrueType or OpenType font: an sfnt version
+/// followed by a table directory whose search fields agree with its table
+/// count and whose table tags are printable. Four bytes alone, such as
+/// `true`, are too common in text to count.
+fn is_sfnt(data: &[u8]) -> bool {
+    if !SFNT_VERSIONS
+        .iter()
+        .any(|version| data.starts_with(version))
+    {
+        return false;
+    }
+    let field = |at: usize| {
+        data.get(at..at + 2)
+            .map(|b| u32::from(u16::from_be_bytes([b[0], b[1]])))
+    };
+    let (Some(tables), Some(search_range), Some(entry_selector), Some(range_shift)) =
+        (field(4), field(6), field(8), field(10))
+    else {
+        return false;
+    };
+    if tables == 0 {
+        return false;
+    }
+    let log2 = 31 - tables.leading_zeros();
+    if entry_selector != log2
+        || search_range != 16 << log2
+        || range_shift != tables * 16 - search_range
+    {
+        return false;
+    }
+    let Some(records) = data.get(12..12 + 16 * tables as usize) else {
+        return false;
+    };
+    records
+        .chunks_exact(16)
+        .all(|record| record[..4].iter().all(|b| (0x20..=0x7e).contains(b)))
+}
+
+/// The kind of `data`, named `filename`, judged by its content.
+fn by_content(filename: &str, data: &[u8]) -> Option<AttachmentKind> {
+    const IMAGE: [&[u8]; 6] = [
+        b"\x89PNG\r\n\x1a\n",
+        b"\xff\xd8\xff",
+        b"GIF8",
+        b"II*\x00",
+        b"MM\x00*",
+        b"BM",
+    ];
+
+    if probe::has_pdf_header(data) {
+        return Some(AttachmentKind::Pdf);
+    }
+    if IMAGE.iter().any(|magic| data.starts_with(magic))
+        || (data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP".as_slice()))
+    {
+        return Some(AttachmentKind::Image);
+    }
+    if data.get(36..40) == Some(b"acsp".as_slice()) {
+        return Some(AttachmentKind::IccProfile);
+    }
+    if data.starts_with(risk::OLE) || (data.starts_with(b"PK\x03\x04") && risk::is_office_zip(data))
+    {
+        return Some(AttachmentKind::Office);
+    }
+    if risk::classify(filename, data) == Some(RiskCategory::Archive) {
+        return Some(AttachmentKind::Archive);
+    }
+    if form_data::detect(data).is_some() {
+        return Some(AttachmentKind::FormData);
+    }
+    if qr_bill::detect(data) {
+        return Some(AttachmentKind::QrBill);
+    }
+    if is_xml(data) {
+        let named = is_invoice_filename(filename);
+        if named || INVOICE_MARKERS.iter().any(|marker| contains(data, marker)) {
+            return Some(AttachmentKind::InvoiceXml);
+        }
+    }
+    None
+}
+
+/// Whether `filename` is one the e-invoicing specifications prescribe for the
+/// invoice XML.
+pub(crate) fn is_invoice_filename(filename: &str) -> bool {
+    INVOICE_FILENAMES
+        .iter()
+        .any(|name| filename.eq_ignore_ascii_case(name))
+}
+
+/// Whether `data` starts like an XML document, after an optional byte order
+/// mark and leading whitespace.
+pub(crate) fn is_xml(data: &[u8]) -> bool {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    data.iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'<')
+}
+
+/// The kind named by a declared MIME type such as `image/png`.
+fn by_mime(mime: &str) -> Option<AttachmentKind> {
+    let mime = mime
+        .split(';')
+        .next()
+        .unwrap_or(mime)
+        .trim()
+        .to_ascii_lowercase();
+    let kind = match mime.as_str() {
+        "application/pdf" => AttachmentKind::Pdf,
+        "application/vnd.fdf" | "application/vnd.adobe.xfdf" => AttachmentKind::FormData,
+        "application/vnd.iccprofile" => AttachmentKind::IccProfile,
+        "application/msword" => AttachmentKind::Office,
+        "application/zip"
+        | "application/gzip"
+        | "application/x-7z-compressed"
+        | "application/vnd.rar"
+        | "application/x-rar-compressed"
+        | "application/x-tar"
+        | "application/x-bzip2"
+        | "application/x-xz"
+        | "application/vnd.ms-cab-compressed" => AttachmentKind::Archive,
+        m if m.starts_with("image/") => AttachmentKind::Image,
+        m if m.starts_with("application/vnd.openxmlformats-officedocument.")
+            || m.starts_with("application/vnd.oasis.opendocument.")
+            || m.starts_with("application/vnd.ms-word")
+            || m.starts_with("application/vnd.ms-excel")
+            || m.starts_with("application/vnd.ms-powerpoint") =>
+        {
+            AttachmentKind::Office
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
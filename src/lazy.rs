@@ -0,0 +1,878 @@
+//! On-demand object loading for very large documents.
+//!
+//! [`LazyDocument`] reads nothing but the cross-reference data when it is
+//! created. Every object is parsed the first time discovery or extraction
+//! asks for it and cached from then on, so extracting the attachments of a
+//! multi-gigabyte scan parses the catalog, the page tree, the name tree and
+//! the file specifications with their streams, but never the page content or
+//! the images.
+
+use crate::backend::PdfBackend;
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+#[cfg(feature = "fs")]
+use crate::extraction_engine::WrittenFiles;
+use crate::file_discovery::FileSpec;
+use crate::filters;
+use crate::memory::MemoryBudget;
+use crate::{EmbeddedFile, ExtractError, ExtractionReport, ExtractorConfig, Result};
+use lopdf::xref::{Xref, XrefEntry, XrefType};
+use lopdf::{
+    Dictionary, Document, EncryptionState, Object, ObjectId, ObjectStream, Reader, Stream,
+};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// How far from the end of the file the `startxref` keyword may appear.
+const TRAILER_WINDOW: usize = 1024;
+
+/// How many references are followed before a chain is treated as a cycle.
+const MAX_REFERENCE_DEPTH: usize = 128;
+
+// ── LazyDocument ──────────────────────────────────────────────────────────────
+
+/// A PDF whose objects are parsed on demand, for extracting the attachments
+/// of documents too large to load in full.
+///
+/// [`PdfAnalyzer`](crate::PdfAnalyzer) parses every object up front, which
+/// for a scanned archive means copying gigabytes of image streams that
+/// attachment extraction never looks at. A `LazyDocument` only parses the
+/// cross-reference sections, following `/Prev` and `/XRefStm`, and resolves
+/// objects as discovery reaches them. An object stream is decoded once, the
+/// first time one of its objects is needed, and from then on counts against
+/// the [`ExtractorConfig::max_memory_bytes`] budget of every operation, as
+/// the loaded document does for a [`PdfAnalyzer`](crate::PdfAnalyzer). The
+/// buffer is borrowed rather than copied, so a memory-mapped file works as
+/// well as a `Vec<u8>`.
+///
+/// It offers extraction only; validation, metadata and the other document
+/// checks need the [`PdfAnalyzer`](crate::PdfAnalyzer). The
+/// [`DeepScan`](crate::AttachmentSource::DeepScan) discovery source visits
+/// every object and so parses the whole document. Byte offsets are limited
+/// to 4 GiB, as in lopdf, and a document whose cross-reference data is
+/// broken is rejected instead of repaired.
+///
+/// ```no_run
+/// use extractembedfilepdf::LazyDocument;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let bytes = std::fs::read("scanned-archive.pdf")?;
+/// let document = LazyDocument::from_bytes(&bytes)?;
+/// for file in document.extract_embedded_files()? {
+///     println!("{} — {} bytes", file.filename, file.data.len());
+/// }
+/// println!("parsed {} of {} objects", document.resolved_count(), document.object_count());
+/// # Ok(())
+/// # }
+/// ```
+pub struct LazyDocument<'a> {
+    /// Holds the buffer, the merged cross-reference table, the trailer and
+    /// the decryption state, and parses single objects.
+    reader: Reader<'a>,
+    /// One slot per object in the cross-reference table, filled on first
+    /// access; `None` when the object could not be parsed.
+    objects: BTreeMap<ObjectId, OnceLock<Option<Object>>>,
+    /// The objects of each object stream, by container number, decoded the
+    /// first time one of them is needed; `None` when the container could
+    /// not be decoded.
+    object_streams: BTreeMap<u32, OnceLock<Option<BTreeMap<ObjectId, Object>>>>,
+    config: ExtractorConfig,
+    /// The size of the decoded object streams, which stay cached; every
+    /// operation's memory budget starts with it taken.
+    cached_bytes: AtomicUsize,
+    file_specs: OnceLock<Vec<FileSpec>>,
+    /// Every file written to disk by any extraction, so a later call cannot
+    /// overwrite a file with another attachment of the same name.
+    #[cfg(feature = "fs")]
+    written: WrittenFiles,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// Read the cross-reference data of the PDF in `data`.
+    ///
+    /// An encrypted document is decrypted with the empty user password; if
+    /// that is not the password, its attachments fail with
+    /// [`ExtractError::Encrypted`].
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        Self::load(data, ExtractorConfig::default(), "")
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), decrypting with `password`.
+    pub fn from_bytes_with_password(data: &'a [u8], password: &str) -> Result<Self> {
+        Self::load(data, ExtractorConfig::default(), password)
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), with a custom
+    /// [`ExtractorConfig`].
+    pub fn from_bytes_with_config(data: &'a [u8], config: ExtractorConfig) -> Result<Self> {
+        Self::load(data, config, "")
+    }
+
+    fn load(data: &'a [u8], config: ExtractorConfig, password: &str) -> Result<Self> {
+        let start = find(data, b"%PDF-")
+            .ok_or_else(|| ExtractError::InvalidPdf("missing %PDF- header".into()))?;
+        let buffer = &data[start..];
+
+        let (xref, trailer) = read_xref_chain(buffer)?;
+        let objects = xref
+            .entries
+            .iter()
+            .filter_map(|(&number, entry)| match *entry {
+                XrefEntry::Normal { generation, .. } => Some((number, generation)),
+                XrefEntry::Compressed { .. } => Some((number, 0)),
+                XrefEntry::Free | XrefEntry::UnusableFree => None,
+            })
+            .map(|id| (id, OnceLock::new()))
+            .collect();
+        let object_streams = xref
+            .entries
+            .values()
+            .filter_map(|entry| match *entry {
+                XrefEntry::Compressed { container, .. } => Some((container, OnceLock::new())),
+                _ => None,
+            })
+            .collect();
+
+        let mut document = Document::new();
+        document.reference_table = xref;
+        document.trailer = trailer;
+        let mut reader = Reader {
+            buffer,
+            document,
+            encryption_state: None,
+            raw_objects: BTreeMap::new(),
+            password: None,
+        };
+        reader.encryption_state = decryption(&reader, password);
+        log::debug!(
+            "lazily loaded a cross-reference table of {} object(s)",
+            reader.document.reference_table.entries.len()
+        );
+
+        Ok(Self {
+            reader,
+            objects,
+            object_streams,
+            config,
+            cached_bytes: AtomicUsize::new(0),
+            file_specs: OnceLock::new(),
+            #[cfg(feature = "fs")]
+            written: WrittenFiles::default(),
+        })
+    }
+
+    /// The number of objects the cross-reference table lists.
+    pub fn object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// The number of objects parsed so far.
+    pub fn resolved_count(&self) -> usize {
+        self.objects
+            .values()
+            .filter(|slot| slot.get().is_some())
+            .count()
+    }
+
+    /// The trailer dictionary of the newest revision.
+    pub(crate) fn trailer(&self) -> &Dictionary {
+        &self.reader.document.trailer
+    }
+
+    /// Returns the filename of every embedded file, in discovery order.
+    ///
+    /// Only the file specification dictionaries are read; no stream is decoded.
+    pub fn embedded_file_names(&self) -> Result<Vec<String>> {
+        Ok(self.operation().engine()?.list_file_names())
+    }
+
+    /// Extract a single embedded file by filename, as
+    /// [`PdfAnalyzer::extract_embedded_file`](crate::PdfAnalyzer::extract_embedded_file)
+    /// does.
+    pub fn extract_embedded_file(&self, name: &str) -> Result<EmbeddedFile> {
+        self.operation().engine()?.extract_file(name)
+    }
+
+    /// Extract every embedded file, as
+    /// [`PdfAnalyzer::extract_embedded_files`](crate::PdfAnalyzer::extract_embedded_files)
+    /// does.
+    pub fn extract_embedded_files(&self) -> Result<Vec<EmbeddedFile>> {
+        self.operation().engine()?.extract_all_files()
+    }
+
+    /// Extract every embedded file and report what went wrong along the way,
+    /// as [`PdfAnalyzer::extract_with_report`](crate::PdfAnalyzer::extract_with_report)
+    /// does.
+    pub fn extract_with_report(&self) -> Result<ExtractionReport> {
+        self.operation().engine()?.extract_report()
+    }
+
+    /// Starts an operation: the [`ExtractorConfig::max_duration`] and
+    /// [`ExtractorConfig::max_memory_bytes`] budgets start here, with the
+    /// cached object streams already taken.
+    fn operation(&self) -> Operation<'_, 'a> {
+        Operation {
+            document: self,
+            deadline: Deadline::start(self.config.max_duration),
+            memory: MemoryBudget::start(
+                self.config.max_memory_bytes,
+                self.cached_bytes.load(Ordering::Relaxed),
+            ),
+        }
+    }
+
+    /// The object `id` itself, parsed on first access, without following a
+    /// reference. An object stream decoded on the way counts against
+    /// `operation`; if that runs out of time or memory, the object is not
+    /// cached, so a later operation can try again.
+    fn resolve(&self, id: ObjectId, operation: &Operation<'_, 'a>) -> lopdf::Result<&Object> {
+        let not_found = || lopdf::Error::ObjectNotFound(id);
+        let slot = self.objects.get(&id).ok_or_else(not_found)?;
+        if let Some(object) = slot.get() {
+            return object.as_ref().ok_or_else(not_found);
+        }
+
+        let container = match self.reader.document.reference_table.get(id.0) {
+            Some(XrefEntry::Compressed { container, .. }) => Some(*container),
+            _ => None,
+        };
+        let parsed = match container {
+            Some(container) => self
+                .object_stream(container, operation)
+                .map_err(|_| not_found())?
+                .and_then(|objects| objects.get(&id).cloned())
+                .ok_or_else(not_found),
+            None => self.reader.get_object(id, &mut HashSet::new()),
+        };
+        slot.get_or_init(|| {
+            parsed
+                .map_err(|e| log::debug!("cannot parse object {} {}: {e}", id.0, id.1))
+                .ok()
+        })
+        .as_ref()
+        .ok_or_else(not_found)
+    }
+
+    /// The objects stored in the object stream `container`, decoded once and
+    /// cached; `None` if it cannot be decoded. When `operation` runs out of
+    /// time or memory first, the error is returned and nothing is cached.
+    fn object_stream(
+        &self,
+        container: u32,
+        operation: &Operation<'_, 'a>,
+    ) -> Result<Option<&BTreeMap<ObjectId, Object>>> {
+        let Some(slot) = self.object_streams.get(&container) else {
+            return Ok(None);
+        };
+        if let Some(objects) = slot.get() {
+            return Ok(objects.as_ref());
+        }
+
+        // Decoding never needs a second object stream, as a container listed
+        // as compressed is refused; this keeps any path that would from
+        // recursing without end.
+        let Some(_decoding) = DecodingObjectStream::enter() else {
+            log::debug!("object stream {container} is needed while decoding another");
+            return Ok(None);
+        };
+        let decoded = self.decode_object_stream(container, operation)?;
+        let size = decoded.as_ref().map_or(0, |(_, size)| *size);
+        let mut stored = false;
+        let objects = slot.get_or_init(|| {
+            stored = true;
+            decoded.map(|(objects, _)| objects)
+        });
+        if stored {
+            self.cached_bytes.fetch_add(size, Ordering::Relaxed);
+        }
+        Ok(objects.as_ref())
+    }
+
+    /// Decode the object stream `container` and return its objects with the
+    /// decoded size. The stream is decoded like an attachment, within the
+    /// deadline and memory budget of `operation`, rather than by lopdf, which
+    /// would decode it again for every object and without a limit.
+    fn decode_object_stream(
+        &self,
+        container: u32,
+        operation: &Operation<'_, 'a>,
+    ) -> Result<Option<(BTreeMap<ObjectId, Object>, usize)>> {
+        // Object streams cannot be nested; one listed as compressed would
+        // otherwise be looked up in an object stream again, possibly itself.
+        let entry = self.reader.document.reference_table.get(container);
+        if let Some(XrefEntry::Compressed { .. }) = entry {
+            log::debug!("object stream {container} is itself in an object stream");
+            return Ok(None);
+        }
+        let Some(stream) = self
+            .resolve((container, 0), operation)
+            .ok()
+            .and_then(|object| object.as_stream().ok())
+        else {
+            return Ok(None);
+        };
+        let memory = &operation.memory;
+        let decoded = match filters::decode(stream, None, false, &operation.deadline, memory) {
+            Ok(decoded) => decoded,
+            Err(e @ (ExtractError::Timeout(_) | ExtractError::MemoryLimit(_))) => return Err(e),
+            Err(e) => {
+                log::debug!("cannot decode object stream {container}: {e}");
+                return Ok(None);
+            }
+        };
+        let size = decoded.data.len();
+        if !decoded.undecoded_filters.is_empty() {
+            log::debug!("cannot decode object stream {container}");
+            memory.release(size);
+            return Ok(None);
+        }
+        let mut dict = stream.dict.clone();
+        dict.remove(b"Filter");
+        dict.remove(b"DecodeParms");
+        let mut plain = Stream::new(dict, decoded.data);
+        match ObjectStream::new(&mut plain) {
+            Ok(objects) => Ok(Some((objects.objects, size))),
+            Err(e) => {
+                log::debug!("cannot parse object stream {container}: {e}");
+                memory.release(size);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Marks an object stream as being decoded on the current thread, so that
+/// resolving the objects it needs can never start decoding another one.
+struct DecodingObjectStream;
+
+thread_local! {
+    static DECODING_OBJECT_STREAM: Cell<bool> = const { Cell::new(false) };
+}
+
+impl DecodingObjectStream {
+    /// `None` while another object stream is being decoded on this thread.
+    fn enter() -> Option<Self> {
+        let busy = DECODING_OBJECT_STREAM.with(|flag| flag.replace(true));
+        (!busy).then_some(Self)
+    }
+}
+
+impl Drop for DecodingObjectStream {
+    fn drop(&mut self) {
+        DECODING_OBJECT_STREAM.with(|flag| flag.set(false));
+    }
+}
+
+// ── Operation ─────────────────────────────────────────────────────────────────
+
+/// One extraction call on a [`LazyDocument`], with its own deadline and
+/// memory budget. Objects are resolved through it, so an object stream is
+/// decoded within the limits of the operation that first needs it.
+struct Operation<'d, 'a> {
+    document: &'d LazyDocument<'a>,
+    deadline: Deadline,
+    memory: MemoryBudget,
+}
+
+impl<'d, 'a> Operation<'d, 'a> {
+    /// Builds an [`ExtractionEngine`] within this operation's budgets,
+    /// running discovery on the document's first operation only.
+    fn engine(&self) -> Result<ExtractionEngine<'_, Self>> {
+        let document = self.document;
+        let specs = match document.file_specs.get() {
+            Some(specs) => specs,
+            None => {
+                let specs = ExtractionEngine::discover(self, &document.config, self.deadline)?;
+                document.file_specs.get_or_init(|| specs)
+            }
+        };
+        let engine = ExtractionEngine::new(self, &document.config, specs)
+            .with_deadline(self.deadline)
+            .with_memory(self.memory.clone());
+        #[cfg(feature = "fs")]
+        let engine = engine.with_written(document.written.clone());
+        Ok(engine)
+    }
+}
+
+impl PdfBackend for Operation<'_, '_> {
+    fn get_object(&self, id: ObjectId) -> lopdf::Result<&Object> {
+        let mut id = id;
+        for _ in 0..MAX_REFERENCE_DEPTH {
+            match self.document.resolve(id, self)? {
+                Object::Reference(next) => id = *next,
+                object => return Ok(object),
+            }
+        }
+        Err(lopdf::Error::ReferenceLimit)
+    }
+
+    fn catalog(&self) -> lopdf::Result<&Dictionary> {
+        let root = self.document.trailer().get(b"Root")?.as_reference()?;
+        self.get_dictionary(root)
+    }
+
+    fn objects(&self) -> Box<dyn Iterator<Item = (ObjectId, &Object)> + '_> {
+        Box::new(self.document.objects.keys().filter_map(|&id| {
+            self.document
+                .resolve(id, self)
+                .ok()
+                .map(|object| (id, object))
+        }))
+    }
+
+    fn is_locked(&self) -> bool {
+        let document = self.document;
+        document.trailer().has(b"Encrypt") && document.reader.encryption_state.is_none()
+    }
+}
+
+// ── Attachments-only loading ──────────────────────────────────────────────────
+
+/// Parse the PDF in `data` into a [`Document`] without the page content
+/// streams, nor the images, form XObjects and font programs the page
+/// resources name, for [`ExtractorConfig::attachments_only`]. Those objects
+/// are left out of the document, so their data is never read; every other
+/// object is parsed on demand, as for a [`LazyDocument`].
+///
+/// `None` for an encrypted document and for one whose cross-reference data
+/// cannot be read as is; those are left to the full parser.
+pub(crate) fn load_without_page_data(data: &[u8], deadline: Deadline) -> Result<Option<Document>> {
+    let Ok(lazy) = LazyDocument::from_bytes(data) else {
+        return Ok(None);
+    };
+    if lazy.trailer().has(b"Encrypt") {
+        return Ok(None);
+    }
+
+    let operation = Operation {
+        document: &lazy,
+        deadline,
+        memory: MemoryBudget::default(),
+    };
+    let skipped = page_data(&operation, false);
+    for &id in lazy.objects.keys() {
+        if !skipped.contains(&id) {
+            let _ = lazy.resolve(id, &operation);
+        }
+    }
+    deadline.check()?;
+    log::debug!(
+        "loaded {} object(s), leaving out {} of page data",
+        lazy.resolved_count(),
+        skipped.len()
+    );
+
+    let version = lazy
+        .reader
+        .buffer
+        .get(b"%PDF-".len()..)
+        .map(|rest| {
+            let end = rest
+                .iter()
+                .position(|byte| byte.is_ascii_whitespace() || *byte == b'%')
+                .unwrap_or(rest.len());
+            String::from_utf8_lossy(&rest[..end]).into_owned()
+        })
+        .unwrap_or_default();
+    let mut document = lazy.reader.document;
+    document.version = version;
+    document.max_id = document.reference_table.max_id();
+    document.objects = lazy
+        .objects
+        .into_iter()
+        .filter_map(|(id, slot)| Some((id, slot.into_inner()??)))
+        .collect();
+    Ok(Some(document))
+}
+
+/// The content streams of every page, and the images, form XObjects and font
+/// programs named by its resources, including inherited ones.
+///
+/// Only dictionaries are resolved to find them; the streams themselves are
+/// not, unless `nested_forms` is set: then every form XObject found is
+/// resolved too, and the page data its own `/Resources` name is added.
+/// Objects outside the pages' content, such as annotation appearance
+/// streams, are never included.
+pub(crate) fn page_data<B: PdfBackend + ?Sized>(
+    document: &B,
+    nested_forms: bool,
+) -> HashSet<ObjectId> {
+    let mut ids = HashSet::new();
+    let mut seen = HashSet::new();
+    for page in document.get_pages().into_values() {
+        let Ok(dict) = document.get_dictionary(page) else {
+            continue;
+        };
+        match dict.get(b"Contents") {
+            Ok(Object::Reference(id)) => {
+                ids.insert(*id);
+            }
+            Ok(Object::Array(parts)) => {
+                ids.extend(parts.iter().filter_map(|part| part.as_reference().ok()));
+            }
+            _ => {}
+        }
+
+        // Resources are inherited from the page tree; each node once.
+        let mut node = Some((page, dict));
+        while let Some((id, dict)) = node.take() {
+            if !seen.insert(id) {
+                break;
+            }
+            let resources = dict.get(b"Resources").ok();
+            if let Some(resources) = resources.and_then(|r| as_dict(document, r)) {
+                resource_data(document, resources, nested_forms, &mut ids);
+            }
+            node = dict
+                .get(b"Parent")
+                .and_then(Object::as_reference)
+                .ok()
+                .and_then(|parent| Some((parent, document.get_dictionary(parent).ok()?)));
+        }
+    }
+    ids
+}
+
+/// Add the images and form XObjects, and the font programs of the fonts,
+/// that `resources` names to `ids`, and with `nested_forms` those of the
+/// forms' own resources.
+fn resource_data<B: PdfBackend + ?Sized>(
+    document: &B,
+    resources: &Dictionary,
+    nested_forms: bool,
+    ids: &mut HashSet<ObjectId>,
+) {
+    let entries = |key: &[u8]| {
+        resources
+            .get(key)
+            .ok()
+            .and_then(|entry| as_dict(document, entry))
+            .into_iter()
+            .flat_map(|dict| dict.iter().map(|(_, value)| value))
+    };
+    for xobject in entries(b"XObject").filter_map(|xobject| xobject.as_reference().ok()) {
+        // A form that was already added has had its resources visited.
+        if !ids.insert(xobject) || !nested_forms {
+            continue;
+        }
+        let Ok(form) = document.get_object(xobject).and_then(Object::as_stream) else {
+            continue;
+        };
+        let is_form = form
+            .dict
+            .get(b"Subtype")
+            .and_then(Object::as_name)
+            .is_ok_and(|s| s == b"Form");
+        let resources = form
+            .dict
+            .get(b"Resources")
+            .ok()
+            .and_then(|r| as_dict(document, r));
+        if let (true, Some(resources)) = (is_form, resources) {
+            resource_data(document, resources, nested_forms, ids);
+        }
+    }
+
+    for font in entries(b"Font").filter_map(|font| as_dict(document, font)) {
+        let descendants = font
+            .get(b"DescendantFonts")
+            .ok()
+            .and_then(|fonts| as_array(document, fonts))
+            .into_iter()
+            .flatten()
+            .filter_map(|font| as_dict(document, font));
+        for font in std::iter::once(font).chain(descendants) {
+            let Some(descriptor) = font
+                .get(b"FontDescriptor")
+                .ok()
+                .and_then(|descriptor| as_dict(document, descriptor))
+            else {
+                continue;
+            };
+            ids.extend(
+                [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+                    .iter()
+                    .filter_map(|key| descriptor.get(key).and_then(Object::as_reference).ok()),
+            );
+        }
+    }
+}
+
+/// `object` as a dictionary, resolving a reference.
+fn as_dict<'o, B: PdfBackend + ?Sized>(
+    document: &'o B,
+    object: &'o Object,
+) -> Option<&'o Dictionary> {
+    match object {
+        Object::Reference(id) => document.get_dictionary(*id).ok(),
+        object => object.as_dict().ok(),
+    }
+}
+
+/// `object` as an array, resolving a reference.
+fn as_array<'o, B: PdfBackend + ?Sized>(
+    document: &'o B,
+    object: &'o Object,
+) -> Option<&'o Vec<Object>> {
+    match object {
+        Object::Reference(id) => document.get_object(*id).and_then(Object::as_array).ok(),
+        object => object.as_array().ok(),
+    }
+}
+
+// ── Cross-reference data ──────────────────────────────────────────────────────
+
+/// Read every cross-reference section of `buffer`, newest first, and return
+/// the merged table with the newest trailer. An entry in a newer section
+/// shadows the same object number in an older one.
+fn read_xref_chain(buffer: &[u8]) -> Result<(Xref, Dictionary)> {
+    let mut offset = startxref(buffer)
+        .ok_or_else(|| ExtractError::InvalidPdf("missing or invalid startxref".into()))?;
+    let mut xref = Xref::new(0, XrefType::CrossReferenceTable);
+    let mut newest = None;
+    let mut seen = HashSet::new();
+    loop {
+        if !seen.insert(offset) {
+            log::warn!("cross-reference sections form a /Prev cycle at offset {offset}");
+            break;
+        }
+        let (mut section, trailer) = read_section(buffer, offset)?;
+        // A hybrid-reference file lists objects in object streams only in
+        // the stream named by /XRefStm, which takes precedence over free
+        // entries of the table it belongs to.
+        if let Some(stream_offset) = trailer.get(b"XRefStm").ok().and_then(as_offset) {
+            let (stream, _) = read_xref_stream(buffer, stream_offset)?;
+            for (number, entry) in stream.entries {
+                let free = matches!(
+                    section.get(number),
+                    None | Some(XrefEntry::Free | XrefEntry::UnusableFree)
+                );
+                if free {
+                    section.insert(number, entry);
+                }
+            }
+        }
+        xref.merge(section);
+        let prev = trailer.get(b"Prev").ok().and_then(as_offset);
+        newest.get_or_insert(trailer);
+        match prev {
+            Some(prev) => offset = prev,
+            None => break,
+        }
+    }
+
+    let mut trailer = newest.unwrap_or_default();
+    trailer.remove(b"Prev");
+    trailer.remove(b"XRefStm");
+    xref.size = xref.max_id().saturating_add(1);
+    Ok((xref, trailer))
+}
+
+/// The cross-reference section at `offset`: a classic table or a
+/// cross-reference stream.
+fn read_section(buffer: &[u8], offset: usize) -> Result<(Xref, Dictionary)> {
+    let section = buffer
+        .get(offset..)
+        .ok_or_else(|| ExtractError::InvalidPdf(format!("xref offset {offset} is past the end")))?;
+    if section[skip_whitespace(section, 0)..].starts_with(b"xref") {
+        read_xref_table(section)
+    } else {
+        read_xref_stream(buffer, offset)
+    }
+}
+
+/// Parse a classic `xref` table and the `trailer` dictionary after it.
+fn read_xref_table(section: &[u8]) -> Result<(Xref, Dictionary)> {
+    let invalid = || ExtractError::InvalidPdf("invalid cross-reference table".into());
+    let mut xref = Xref::new(0, XrefType::CrossReferenceTable);
+    let mut pos = skip_whitespace(section, 0) + b"xref".len();
+    loop {
+        pos = skip_whitespace(section, pos);
+        if section[pos..].starts_with(b"trailer") {
+            pos += b"trailer".len();
+            break;
+        }
+        let start = read_number(section, &mut pos).ok_or_else(invalid)?;
+        let count = read_number(section, &mut pos).ok_or_else(invalid)?;
+        for number in start..start.saturating_add(count) {
+            let offset = read_number(section, &mut pos).ok_or_else(invalid)?;
+            let generation = read_number(section, &mut pos).ok_or_else(invalid)?;
+            pos = skip_whitespace(section, pos);
+            let kind = section.get(pos).copied().ok_or_else(invalid)?;
+            pos += 1;
+            let (Ok(number), Ok(offset), Ok(generation)) = (
+                u32::try_from(number),
+                u32::try_from(offset),
+                u16::try_from(generation),
+            ) else {
+                return Err(invalid());
+            };
+            let entry = match kind {
+                b'n' => XrefEntry::Normal { offset, generation },
+                b'f' => XrefEntry::Free,
+                _ => return Err(invalid()),
+            };
+            // The first of duplicate entries wins, as in the merged table.
+            if xref.get(number).is_none() {
+                xref.insert(number, entry);
+            }
+        }
+    }
+
+    let end = find(&section[pos..], b"startxref").map_or(section.len(), |end| pos + end);
+    match parse_object(&section[pos..end]) {
+        Ok(Object::Dictionary(trailer)) => Ok((xref, trailer)),
+        Ok(_) => Err(ExtractError::InvalidPdf(
+            "trailer is not a dictionary".into(),
+        )),
+        Err(e) => Err(ExtractError::InvalidPdf(format!("invalid trailer: {e}"))),
+    }
+}
+
+/// Parse the cross-reference stream that starts at `offset`.
+fn read_xref_stream(buffer: &[u8], offset: usize) -> Result<(Xref, Dictionary)> {
+    let invalid = |e: String| ExtractError::InvalidPdf(format!("invalid xref stream: {e}"));
+    let mut pos = offset;
+    let (Some(number), Some(generation)) =
+        (read_number(buffer, &mut pos), read_number(buffer, &mut pos))
+    else {
+        return Err(invalid("no object header".into()));
+    };
+    let (Ok(number), Ok(generation), Ok(offset)) = (
+        u32::try_from(number),
+        u16::try_from(generation),
+        u32::try_from(offset),
+    ) else {
+        return Err(invalid("object header out of range".into()));
+    };
+
+    // The stream's own dictionary, including /Length, must be direct, so a
+    // reader that knows only this one object can parse it.
+    let mut document = Document::new();
+    document
+        .reference_table
+        .insert(number, XrefEntry::Normal { offset, generation });
+    let reader = Reader {
+        buffer,
+        document,
+        encryption_state: None,
+        raw_objects: BTreeMap::new(),
+        password: None,
+    };
+    let stream = reader
+        .get_object((number, generation), &mut HashSet::new())
+        .and_then(|object| object.as_stream().cloned())
+        .map_err(|e| invalid(e.to_string()))?;
+    lopdf::xref::decode_xref_stream(stream).map_err(|e| invalid(e.to_string()))
+}
+
+/// The decryption state for `password` if the document is encrypted, or
+/// `None` if it is not or the password is wrong.
+fn decryption(reader: &Reader<'_>, password: &str) -> Option<EncryptionState> {
+    let encrypt = reader
+        .document
+        .trailer
+        .get(b"Encrypt")
+        .and_then(Object::as_reference)
+        .ok()?;
+    // The /Encrypt dictionary itself is never encrypted.
+    let dictionary = reader.get_object(encrypt, &mut HashSet::new()).ok()?;
+    let mut document = Document::new();
+    document.trailer = reader.document.trailer.clone();
+    document.objects.insert(encrypt, dictionary);
+    match document
+        .authenticate_password(password)
+        .and_then(|()| EncryptionState::decode(&document, password))
+    {
+        Ok(state) => Some(state),
+        Err(e) => {
+            log::warn!("cannot decrypt the document: {e}");
+            None
+        }
+    }
+}
+
+/// Parse the direct object in `bytes`, e.g. a trailer dictionary.
+fn parse_object(bytes: &[u8]) -> lopdf::Result<Object> {
+    let mut wrapped = b"1 0 obj\n".to_vec();
+    wrapped.extend_from_slice(bytes);
+    wrapped.extend_from_slice(b"\nendobj\n");
+    let mut document = Document::new();
+    document.reference_table.insert(
+        1,
+        XrefEntry::Normal {
+            offset: 0,
+            generation: 0,
+        },
+    );
+    Reader {
+        buffer: &wrapped,
+        document,
+        encryption_state: None,
+        raw_objects: BTreeMap::new(),
+        password: None,
+    }
+    .get_object((1, 0), &mut HashSet::new())
+}
+
+/// The byte offset after the last `startxref` keyword.
+fn startxref(buffer: &[u8]) -> Option<usize> {
+    let window = buffer.len().saturating_sub(TRAILER_WINDOW);
+    let mut pos = window + rfind(&buffer[window..], b"startxref")? + b"startxref".len();
+    read_number(buffer, &mut pos).and_then(|offset| usize::try_from(offset).ok())
+}
+
+/// A non-negative integer object as a byte offset.
+fn as_offset(object: &Object) -> Option<usize> {
+    object
+        .as_i64()
+        .ok()
+        .and_then(|offset| usize::try_from(offset).ok())
+}
+
+/// Skip whitespace from `pos`, then read an unsigned decimal number and
+/// leave `pos` after it.
+fn read_number(buffer: &[u8], pos: &mut usize) -> Option<u64> {
+    let start = skip_whitespace(buffer, *pos);
+    let digits = buffer
+        .get(start..)?
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let number = std::str::from_utf8(&buffer[start..start + digits])
+        .ok()?
+        .parse()
+        .ok()?;
+    *pos = start + digits;
+    Some(number)
+}
+
+/// The position of the first byte at or after `pos` that is neither
+/// whitespace nor part of a comment.
+fn skip_whitespace(buffer: &[u8], mut pos: usize) -> usize {
+    while let Some(&byte) = buffer.get(pos) {
+        match byte {
+            b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0' => pos += 1,
+            b'%' => {
+                while buffer.get(pos).is_some_and(|&b| b != b'\n' && b != b'\r') {
+                    pos += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+    pos
+}
+
+/// Position of the first occurrence of `needle`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Position of the last occurrence of `needle`.
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
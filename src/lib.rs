@@ -12,6 +12,10 @@
 //! 4. **Extract embedded files** — reads each embedded stream and returns the
 //!    raw bytes together with filename and metadata.
 //!
+//! For documents too large to load in full, [`LazyDocument`] parses only the
+//! cross-reference data up front and resolves the objects extraction needs on
+//! demand.
+//!
 //! ## Quick example
 //!
 //! ```no_run
@@ -31,21 +35,167 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Cargo features
+//!
+//! - `fs` (default) — filesystem access: `PdfAnalyzer::from_path`,
+//!   `ExtractorConfig::extract_to_disk`, `EmbeddedFile::save_to_disk` and
+//!   `ScanPolicy::Quarantine`. Without it none of these exist, so a build
+//!   with `default-features = false` provably performs no disk I/O inside a
+//!   seccomp or WebAssembly sandbox.
+//! - `parallel` — decode embedded streams concurrently on the rayon thread pool.
+//! - `serde` — implement `serde::Serialize` for the result types
+//!   ([`EmbeddedFileMetadata`], [`AnalysisReport`], [`AttachmentDiff`], …) and
+//!   for [`ExtractError`] as a `{ kind, code, message }` report.
+//! - `async` — `PdfAnalyzer::embedded_file_stream` yields embedded files as a
+//!   `futures::Stream`, decoding each on tokio's blocking thread pool.
+//! - `wasm` — `wasm-bindgen` bindings exposing a bytes-in/bytes-out
+//!   `PdfAnalyzer` class to JavaScript.
+//! - `tracing` — `tracing` spans around discovery, parsing, decoding and disk
+//!   writes.
+//! - `signing` — `ExtractionManifest::sign` signs a manifest of extracted
+//!   files with a caller-provided Ed25519 key.
+//! - `fuzzing` — the `fuzz` module, whose entry points run the whole
+//!   pipeline on untrusted bytes under time and memory limits, with
+//!   `arbitrary::Arbitrary` inputs for cargo-fuzz.
+//! - `test-util` — the `test_util` module, which builds PDF documents with
+//!   chosen XMP metadata, name-tree layouts and attachments in memory, runs
+//!   extraction on hand-built object graphs, adds the `assert_attachments!`
+//!   regression-test macro, and provides proptest strategies and
+//!   `arbitrary::Arbitrary` impls for the public types.
+//!
+//! ## Logging
+//!
+//! The crate never prints to stdout or stderr. Diagnostics such as skipped
+//! attachments, repaired cross-reference tables and files written to disk go
+//! through the [`log`](https://docs.rs/log) facade; install any `log`
+//! implementation to see them. [`PdfAnalyzer::extract_with_report`] returns
+//! the per-file warnings as values.
+//!
+//! ## WebAssembly
+//!
+//! The crate builds for `wasm32-unknown-unknown`. Construct analyzers with
+//! [`PdfAnalyzer::from_bytes`] or [`PdfAnalyzer::from_bytes_with_config`];
+//! with the `fs` feature the path-based constructors and disk writing
+//! compile but fail at runtime there because the target has no filesystem,
+//! so disable default features to leave them out.
+//!
+//! ```text
+//! RUSTFLAGS='--cfg getrandom_backend="wasm_js"' \
+//!     cargo build --target wasm32-unknown-unknown --no-default-features --features wasm
+//! ```
 
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 mod analyzer;
 mod analyzer_extraction;
 mod analyzer_validation;
+mod audit;
+mod backend;
+mod batch;
+mod classifier;
+pub mod compare;
+mod conformance;
+mod deadline;
+mod document_metadata;
 mod embedded;
+mod extension_schema;
+mod external;
 mod extraction_engine;
 mod file_discovery;
 mod file_parsing;
+mod file_structure;
+mod filters;
+mod fonts;
+mod form_data;
+mod format;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+mod handle;
+mod hybrid_invoice;
+mod junit;
+mod kind;
+mod lazy;
+mod manifest;
+mod memory;
+mod metrics;
+mod mime;
+pub mod name_tree;
+mod object;
 mod pdf_utils;
+mod peppol;
+mod probe;
+mod progress;
+mod provenance;
+mod qr_bill;
+mod repair;
+mod report;
+mod revisions;
+mod risk;
+mod scanner;
+mod security;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod statistics;
+#[cfg(feature = "async")]
+mod stream;
+mod tar;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod text;
 mod validator;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod xfa;
+mod xml_format;
+mod xmp;
+mod zip;
 
 pub use analyzer::PdfAnalyzer;
-pub use embedded::{EmbeddedFile, EmbeddedFileMetadata};
+pub use audit::{AuditEvent, AuditSink};
+pub use batch::{BatchInput, BatchItem, BatchResults, PdfBatch};
+pub use classifier::Classifier;
+#[allow(deprecated)]
+pub use compare::{compare_attachments, AttachmentDiff, ChangedAttachment, RenamedAttachment};
+pub use conformance::{ConformanceLevel, PdfAConformance};
+pub use document_metadata::DocumentMetadata;
+pub use embedded::{
+    AfRelationship, AttachmentSource, EmbeddedFile, EmbeddedFileMetadata, SizeMismatch,
+};
+pub use extension_schema::{ConformanceWarning, ConformanceWarningKind};
+pub use external::{ExternalKind, ExternalReference};
+pub use file_structure::{StructureFinding, StructureRule};
+pub use fonts::FontInfo;
+pub use form_data::{FormData, FormDataFormat, FormField};
+pub use format::format_bytes;
+pub use handle::EmbeddedFileHandle;
+pub use hybrid_invoice::{HybridInvoiceFinding, HybridInvoiceRule, HybridInvoiceValidation};
+pub use kind::AttachmentKind;
+pub use lazy::LazyDocument;
+#[cfg(feature = "signing")]
+pub use manifest::SignedManifest;
+pub use manifest::{ChecksumMismatch, ChecksumVerification, ExtractionManifest, ManifestEntry};
+pub use metrics::MetricsSink;
+pub use mime::MimeTable;
+pub use object::{ObjectRef, PdfDict};
+pub use peppol::{ParticipantId, PeppolDocument, PeppolDocumentType};
+pub use probe::PdfProbe;
+pub use progress::{Progress, ProgressCallback};
+pub use qr_bill::{QrAddress, QrBill, QrReferenceType};
+pub use report::{AnalysisReport, ExtractionReport, ExtractionWarning, WarningKind};
+pub use revisions::MetadataRevision;
+pub use risk::{RiskCategory, RiskPolicy, RiskReport, RiskSignal};
+pub use scanner::{ContentScanner, ScanPolicy, ScanVerdict};
+pub use security::{FindingKind, SecurityFinding};
+pub use statistics::ExtractionStatistics;
+#[cfg(feature = "async")]
+pub use stream::EmbeddedFileStream;
+pub use tar::TarWriter;
+pub use text::{DocumentText, PageText};
+pub use xml_format::XmlFormat;
+
 // PdfValidator is intentionally *not* re-exported; it is an internal detail.
 // Callers use PdfAnalyzer for all operations.
 
@@ -61,21 +211,147 @@ pub struct ExtractorConfig {
     /// If set, [`PdfAnalyzer::extract_embedded_files`] returns
     /// [`ExtractError::FileSizeExceeded`] as soon as any single embedded file
     /// exceeds this byte count.
+    ///
+    /// Files whose `/Params/Size` (or, if unfiltered, raw stream length)
+    /// already exceeds the limit are rejected before being decompressed.
     pub max_embedded_file_size: Option<usize>,
 
     /// If `true` and `output_directory` is also set, each successfully extracted
     /// file is written to disk automatically inside
     /// [`PdfAnalyzer::extract_embedded_files`].
+    #[cfg(feature = "fs")]
     pub extract_to_disk: bool,
 
     /// Directory used when `extract_to_disk` is `true`.
+    #[cfg(feature = "fs")]
     pub output_directory: Option<String>,
+
+    /// When `true`, every file written by `extract_to_disk` gets a
+    /// `<name>.meta.json` sidecar next to it with the PDF-declared metadata,
+    /// the file's MD5 and SHA-256 digests, the source document's path and
+    /// SHA-256 digest, and the extraction time, so that the context survives
+    /// once the file has left the PDF. Implies
+    /// [`hash_source`](Self::hash_source).
+    ///
+    /// A sidecar is not written when an attachment already has its name, and
+    /// an attachment whose name a sidecar already has is not written either.
+    #[cfg(feature = "fs")]
+    pub write_sidecars: bool,
+
+    /// When `true`, loading computes the SHA-256 digest of the raw document
+    /// for [`PdfAnalyzer::source_sha256`], extraction reports and their ZIP
+    /// comment. Off by default, because it reads the whole input once more.
+    pub hash_source: bool,
+
+    /// Discovery mechanisms used to locate embedded files. `None` searches
+    /// [`AttachmentSource::DEFAULT`]; `/AF` arrays and deep scanning must be
+    /// requested explicitly.
+    pub discovery_sources: Option<Vec<AttachmentSource>>,
+
+    /// When `true`, [`PdfAnalyzer::is_pdf`] accepts a document without pages
+    /// as long as its catalog has an `/EmbeddedFiles` name tree. Portfolios
+    /// and other attachment containers are sometimes built this way.
+    pub allow_pageless: bool,
+
+    /// When `true`, [`PdfAnalyzer::with_config`] and
+    /// [`PdfAnalyzer::from_bytes_with_config`] load the document for
+    /// attachment work only: page content streams, and the images, form
+    /// XObjects and font programs the pages use, are not parsed, and the
+    /// file-structure checks are skipped. Validation, metadata and extraction
+    /// work as usual, and for scanned documents loading is faster and the
+    /// analyzer holds far less memory. Encrypted documents and those whose
+    /// cross-reference data needs repair are parsed in full and the page
+    /// data discarded afterwards. [`PdfAnalyzer::extract_text`] finds no
+    /// text and [`PdfAnalyzer::structure_findings`] is empty.
+    pub attachments_only: bool,
+
+    /// When `true`, loading also checks the raw bytes against the PDF/A
+    /// file-structure rules reported by [`PdfAnalyzer::structure_findings`].
+    /// The checks scan the whole file several times, which extraction does
+    /// not need, so they are off by default and skipped for
+    /// [`attachments_only`](Self::attachments_only).
+    pub check_file_structure: bool,
+
+    /// When `true`, a corrupt or truncated Flate, LZW or run-length stream
+    /// yields the data decoded up to the damage, flagged with
+    /// [`EmbeddedFileMetadata::truncated`], instead of the raw stream content.
+    /// Often enough to recover an invoice XML from a damaged file.
+    pub recover_truncated_streams: bool,
+
+    /// When `true`, [`PdfAnalyzer::extract_embedded_files`] fails with the
+    /// first error instead of skipping attachments that cannot be extracted.
+    /// Attachments that are still encoded or truncated, or whose decoded
+    /// length differs from `/Params/Size`, count as failures too, and are
+    /// also refused when extracted one at a time, through a handle or a
+    /// stream.
+    pub fail_on_any_error: bool,
+
+    /// Receives load, discovery, decode and write timings; see [`MetricsSink`].
+    pub metrics: Option<Arc<dyn MetricsSink>>,
+
+    /// Upper bound on the time one operation may take, from discovery through
+    /// decoding the last attachment. When it runs out the operation fails
+    /// with [`ExtractError::Timeout`], so a pathological document cannot stall
+    /// a worker indefinitely. Discovery is cached, so later calls only spend
+    /// their budget on decoding.
+    ///
+    /// Loading with [`PdfAnalyzer::from_bytes_with_config`] or
+    /// [`PdfAnalyzer::with_config`] has a budget of its own. The parser cannot
+    /// be interrupted, so a document that takes too long to parse fails once
+    /// parsing returns; filters are checked while they decode.
+    pub max_duration: Option<Duration>,
+
+    /// Upper bound on the memory one operation may hold: the loaded document,
+    /// the stream being decoded and the files decoded so far. When it runs
+    /// out the operation fails with [`ExtractError::MemoryLimit`]. Unlike
+    /// [`max_embedded_file_size`](Self::max_embedded_file_size) this bounds
+    /// the total, so many moderately sized attachments cannot exhaust a
+    /// shared worker either.
+    ///
+    /// The document counts with the size of its raw bytes, and a document
+    /// larger than the budget is rejected by [`PdfAnalyzer::with_config`]
+    /// before parsing.
+    pub max_memory_bytes: Option<usize>,
+
+    /// Called after each attachment processed by
+    /// [`PdfAnalyzer::extract_embedded_files`] and
+    /// [`PdfAnalyzer::extract_with_report`]; see [`ProgressCallback`].
+    pub progress: Option<ProgressCallback>,
+
+    /// Receives an [`AuditEvent`] for every attachment that is extracted,
+    /// rejected or written to disk; see [`AuditSink`].
+    pub audit: Option<Arc<dyn AuditSink>>,
+
+    /// Checks every decoded attachment before it is returned or written to
+    /// disk; see [`ContentScanner`].
+    pub scanner: Option<Arc<dyn ContentScanner>>,
+
+    /// What happens to an attachment the [`scanner`](Self::scanner) flags.
+    pub scan_policy: ScanPolicy,
+
+    /// What happens to executables, scripts, archives and macro documents;
+    /// see [`RiskCategory`]. Files are not classified under the default
+    /// [`RiskPolicy::Allow`].
+    pub risk_policy: RiskPolicy,
+
+    /// Organization-specific detectors that label every extracted file in
+    /// [`EmbeddedFileMetadata::labels`]; see [`Classifier`].
+    pub classifiers: Vec<Arc<dyn Classifier>>,
+
+    /// The extension-to-MIME table that classification falls back to for
+    /// attachments whose content is not recognised. Defaults to the
+    /// built-in table; add entries for niche formats.
+    pub mime_types: MimeTable,
 }
 
 // ── Error type ───────────────────────────────────────────────────────────────
 
 /// Every error that this crate can produce.
+///
+/// New variants may be added in minor versions, so a `match` needs a
+/// wildcard arm; [`code`](Self::code) gives a stable category to match on.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ExtractError {
     /// A filesystem I/O error occurred (e.g. when loading or saving a file).
     #[error("I/O error: {0}")]
@@ -89,21 +365,259 @@ pub enum ExtractError {
     #[error("Not PDF/A-3: {0}")]
     NotPdfA3(String),
 
+    /// The document does not declare the PDF/A conformance required by
+    /// [`PdfAnalyzer::require_conformance`].
+    #[error("Document requires {required} but declares {}", .found.map_or("no PDF/A conformance".to_string(), |found| found.to_string()))]
+    ConformanceMismatch {
+        /// The conformance that was required.
+        required: PdfAConformance,
+        /// The conformance the XMP metadata declares, if any.
+        found: Option<PdfAConformance>,
+    },
+
     /// The document was parsed successfully but contains no embedded files.
     #[error("No embedded files found in this PDF")]
     NoEmbeddedFiles,
 
-    /// An embedded file was found but its stream could not be decoded.
-    #[error("Failed to extract embedded file '{0}': {1}")]
-    ExtractionError(String, String),
+    /// An embedded file was found but could not be extracted.
+    #[error("Failed to extract embedded file '{name}': {reason}")]
+    ExtractionError {
+        /// The attachment's name-tree key or filename.
+        name: String,
+        /// The object id of the file specification, when it is an indirect
+        /// object.
+        spec_id: Option<ObjectRef>,
+        /// The step of the extraction that failed.
+        stage: ExtractionStage,
+        /// What was wrong, e.g. `"missing /EF entry"`.
+        reason: String,
+        /// The parser error that caused the failure, if any. Its type is an
+        /// implementation detail; inspect it through `Display` or
+        /// [`Error::source`](std::error::Error::source).
+        #[source]
+        cause: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
 
-    /// The underlying lopdf parser returned an error.
+    /// The document could not be parsed. The parser's error is an
+    /// implementation detail; inspect it through `Display` or
+    /// [`Error::source`](std::error::Error::source).
     #[error("PDF parse error: {0}")]
-    ParseError(#[from] lopdf::Error),
+    ParseError(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// No embedded file with the requested name exists in the document.
+    #[error("No embedded file named '{0}'")]
+    FileNotFound(String),
 
     /// An extracted file exceeds the configured `max_embedded_file_size` limit.
     #[error("Embedded file exceeds the configured maximum size")]
     FileSizeExceeded,
+
+    /// The file specification points to a file or URL outside the document, so
+    /// there is no embedded data to extract.
+    #[error("'{}' refers to an external resource: {}", .0.name, .0.target)]
+    ExternalReference(Box<ExternalReference>),
+
+    /// The embedded file's stream is encrypted and the document was not
+    /// decrypted, e.g. because no or a wrong password was supplied.
+    #[error("Embedded file '{0}' is encrypted; load the document with its password")]
+    Encrypted(String),
+
+    /// The operation ran longer than [`ExtractorConfig::max_duration`].
+    #[error("Operation exceeded the time limit of {0:?}")]
+    Timeout(Duration),
+
+    /// The operation needed more memory than
+    /// [`ExtractorConfig::max_memory_bytes`] allows.
+    #[error("Operation exceeded the memory budget of {0} bytes")]
+    MemoryLimit(usize),
+
+    /// The configured [`ContentScanner`] flagged the attachment.
+    #[error("Embedded file '{name}' was flagged by the content scanner: {threat}")]
+    Infected {
+        /// The attachment's filename.
+        name: String,
+        /// The threat reported by the scanner.
+        threat: String,
+    },
+
+    /// The attachment falls into a [`RiskCategory`] and
+    /// [`ExtractorConfig::risk_policy`] is [`RiskPolicy::Block`].
+    #[error("Embedded file '{name}' is blocked as {category}")]
+    DangerousFile {
+        /// The attachment's filename.
+        name: String,
+        /// What kind of dangerous file it is.
+        category: RiskCategory,
+    },
+
+    /// The library failed unexpectedly, e.g. [`PdfBatch`] caught a panic while
+    /// analysing a document. The message describes what went wrong; please
+    /// report it as a bug.
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ExtractError {
+    /// An [`ExtractError::ExtractionError`] without object id or cause.
+    pub(crate) fn extraction(
+        name: &str,
+        stage: ExtractionStage,
+        reason: impl Into<String>,
+    ) -> Self {
+        ExtractError::ExtractionError {
+            name: name.into(),
+            spec_id: None,
+            stage,
+            reason: reason.into(),
+            cause: None,
+        }
+    }
+
+    /// An [`ExtractError::ParseError`] wrapping the parser's `error`.
+    pub(crate) fn parse(error: lopdf::Error) -> Self {
+        ExtractError::ParseError(Box::new(error))
+    }
+
+    /// Record the file specification an extraction error belongs to.
+    pub(crate) fn with_spec_id(mut self, id: lopdf::ObjectId) -> Self {
+        if let ExtractError::ExtractionError { spec_id, .. } = &mut self {
+            spec_id.get_or_insert(id.into());
+        }
+        self
+    }
+
+    /// The stable, machine-readable code of this error.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{ErrorCode, ExtractError};
+    ///
+    /// let error = ExtractError::FileSizeExceeded;
+    /// assert_eq!(error.code(), ErrorCode::SizeLimit);
+    /// assert_eq!(error.code().as_str(), "E_SIZE_LIMIT");
+    /// ```
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ExtractError::IoError(_) => ErrorCode::Io,
+            ExtractError::InvalidPdf(_) => ErrorCode::InvalidPdf,
+            ExtractError::NotPdfA3(_) => ErrorCode::NotPdfA3,
+            ExtractError::ConformanceMismatch { .. } => ErrorCode::ConformanceMismatch,
+            ExtractError::NoEmbeddedFiles => ErrorCode::NoEmbeddedFiles,
+            ExtractError::ExtractionError { .. } => ErrorCode::BadFilespec,
+            ExtractError::ParseError(_) => ErrorCode::Parse,
+            ExtractError::FileNotFound(_) => ErrorCode::FileNotFound,
+            ExtractError::FileSizeExceeded => ErrorCode::SizeLimit,
+            ExtractError::ExternalReference(_) => ErrorCode::ExternalReference,
+            ExtractError::Encrypted(_) => ErrorCode::Encrypted,
+            ExtractError::Timeout(_) => ErrorCode::Timeout,
+            ExtractError::MemoryLimit(_) => ErrorCode::MemoryLimit,
+            ExtractError::Infected { .. } => ErrorCode::Infected,
+            ExtractError::DangerousFile { .. } => ErrorCode::DangerousFile,
+            ExtractError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// The step at which extracting an attachment failed, carried by
+/// [`ExtractError::ExtractionError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtractionStage {
+    /// Reading the file specification dictionary or its `/EF` entry.
+    FileSpec,
+    /// Resolving the embedded stream referenced from `/EF`.
+    EmbeddedStream,
+    /// Decoding the stream content.
+    Decode,
+}
+
+impl ExtractionStage {
+    /// A short lowercase name, e.g. `"file-spec"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExtractionStage::FileSpec => "file-spec",
+            ExtractionStage::EmbeddedStream => "embedded-stream",
+            ExtractionStage::Decode => "decode",
+        }
+    }
+}
+
+impl std::fmt::Display for ExtractionStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Machine-readable category of an [`ExtractError`], returned by
+/// [`ExtractError::code`].
+///
+/// The string form from [`as_str`](Self::as_str) is part of the public API:
+/// existing codes keep their spelling across releases, and new codes are only
+/// added in minor versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `E_IO` — reading or writing a file failed.
+    Io,
+    /// `E_INVALID_PDF` — the input is not a structurally valid PDF.
+    InvalidPdf,
+    /// `E_NOT_PDFA3` — the document does not declare PDF/A-3 conformance.
+    NotPdfA3,
+    /// `E_CONFORMANCE_MISMATCH` — the document does not declare the required
+    /// PDF/A part and level.
+    ConformanceMismatch,
+    /// `E_NO_EMBEDDED_FILES` — the document has no (extractable) attachments.
+    NoEmbeddedFiles,
+    /// `E_BAD_FILESPEC` — a file specification or its stream is malformed.
+    BadFilespec,
+    /// `E_PARSE` — the PDF parser rejected the document.
+    Parse,
+    /// `E_FILE_NOT_FOUND` — no attachment has the requested name.
+    FileNotFound,
+    /// `E_SIZE_LIMIT` — an attachment exceeds `max_embedded_file_size`.
+    SizeLimit,
+    /// `E_EXTERNAL_REFERENCE` — the attachment lives outside the document.
+    ExternalReference,
+    /// `E_ENCRYPTED` — the attachment is encrypted and cannot be read.
+    Encrypted,
+    /// `E_TIMEOUT` — the operation exceeded `max_duration`.
+    Timeout,
+    /// `E_MEMORY_LIMIT` — the operation exceeded `max_memory_bytes`.
+    MemoryLimit,
+    /// `E_INFECTED` — the content scanner flagged the attachment.
+    Infected,
+    /// `E_DANGEROUS_FILE` — the attachment's type is blocked by `risk_policy`.
+    DangerousFile,
+    /// `E_INTERNAL` — the library failed unexpectedly, e.g. it panicked.
+    Internal,
+}
+
+impl ErrorCode {
+    /// The stable string form, e.g. `"E_NOT_PDFA3"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Io => "E_IO",
+            ErrorCode::InvalidPdf => "E_INVALID_PDF",
+            ErrorCode::NotPdfA3 => "E_NOT_PDFA3",
+            ErrorCode::ConformanceMismatch => "E_CONFORMANCE_MISMATCH",
+            ErrorCode::NoEmbeddedFiles => "E_NO_EMBEDDED_FILES",
+            ErrorCode::BadFilespec => "E_BAD_FILESPEC",
+            ErrorCode::Parse => "E_PARSE",
+            ErrorCode::FileNotFound => "E_FILE_NOT_FOUND",
+            ErrorCode::SizeLimit => "E_SIZE_LIMIT",
+            ErrorCode::ExternalReference => "E_EXTERNAL_REFERENCE",
+            ErrorCode::Encrypted => "E_ENCRYPTED",
+            ErrorCode::Timeout => "E_TIMEOUT",
+            ErrorCode::MemoryLimit => "E_MEMORY_LIMIT",
+            ErrorCode::Infected => "E_INFECTED",
+            ErrorCode::DangerousFile => "E_DANGEROUS_FILE",
+            ErrorCode::Internal => "E_INTERNAL",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Convenience alias used throughout this crate.
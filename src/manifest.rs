@@ -0,0 +1,258 @@
+use crate::pdf_utils::hex_encode;
+use crate::{EmbeddedFile, ExtractError, PdfAnalyzer, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+// ── ExtractionManifest ────────────────────────────────────────────────────────
+
+/// A record of the files extracted from one document: their names, sizes and
+/// SHA-256 digests, tied to the SHA-256 digest of the document itself.
+///
+/// With the `signing` feature, `sign` turns it into a `SignedManifest`
+/// for provenance in evidentiary and compliance workflows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractionManifest {
+    /// SHA-256 hex digest of the source PDF.
+    pub source_sha256: String,
+
+    /// One entry per extracted file, in the order given.
+    pub files: Vec<ManifestEntry>,
+}
+
+/// One extracted file in an [`ExtractionManifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ManifestEntry {
+    /// The attachment's filename.
+    pub filename: String,
+
+    /// Size of the decoded data in bytes.
+    pub size: usize,
+
+    /// SHA-256 hex digest of the decoded data.
+    pub sha256: String,
+}
+
+impl ExtractionManifest {
+    /// Describes `files` as extracted from the PDF whose raw bytes are
+    /// `source`.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::{ExtractionManifest, PdfAnalyzer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = std::fs::read("invoice.pdf")?;
+    /// let files = PdfAnalyzer::from_bytes(&bytes)?.extract_embedded_files()?;
+    /// let manifest = ExtractionManifest::new(&bytes, &files);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(source: &[u8], files: &[EmbeddedFile]) -> Self {
+        Self {
+            source_sha256: hex_encode(&Sha256::digest(source)),
+            files: files
+                .iter()
+                .map(|file| ManifestEntry {
+                    filename: file.filename.clone(),
+                    size: file.data.len(),
+                    sha256: file.sha256_hex(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The bytes that are signed: a versioned text form with one line for
+    /// the source digest and one per file.
+    ///
+    /// ```text
+    /// extractembedfilepdf-manifest/1
+    /// source <sha256>
+    /// file <sha256> <size> <filename length>:<filename>
+    /// ```
+    ///
+    /// The filename is prefixed with its length in bytes, so names holding
+    /// spaces or newlines cannot be mistaken for other entries.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = format!(
+            "extractembedfilepdf-manifest/1\nsource {}\n",
+            self.source_sha256
+        );
+        for entry in &self.files {
+            out.push_str(&format!(
+                "file {} {} {}:{}\n",
+                entry.sha256,
+                entry.size,
+                entry.filename.len(),
+                entry.filename
+            ));
+        }
+        out.into_bytes()
+    }
+
+    /// The expected SHA-256 digest per filename, for
+    /// [`PdfAnalyzer::verify_checksums`] when the document is replayed later.
+    /// A filename listed twice keeps its last digest.
+    pub fn checksums(&self) -> BTreeMap<String, String> {
+        self.files
+            .iter()
+            .map(|entry| (entry.filename.clone(), entry.sha256.clone()))
+            .collect()
+    }
+
+    /// Signs the [`canonical_bytes`](Self::canonical_bytes) with `key`.
+    #[cfg(feature = "signing")]
+    pub fn sign(self, key: &ed25519_dalek::SigningKey) -> SignedManifest {
+        use ed25519_dalek::Signer;
+
+        let signature = key.sign(&self.canonical_bytes());
+        SignedManifest {
+            manifest: self,
+            signature,
+        }
+    }
+}
+
+// ── ChecksumVerification ──────────────────────────────────────────────────────
+
+/// The result of checking a document's attachments against expected SHA-256
+/// digests.
+///
+/// Returned by [`PdfAnalyzer::verify_checksums`]. All lists are sorted by
+/// filename.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChecksumVerification {
+    /// Expected files the document does not carry.
+    pub missing: Vec<String>,
+
+    /// Files in the document that are not expected.
+    pub unexpected: Vec<String>,
+
+    /// Expected files whose content differs.
+    pub mismatched: Vec<ChecksumMismatch>,
+
+    /// Expected files with the expected content.
+    pub verified: Vec<String>,
+}
+
+/// An attachment whose SHA-256 digest differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChecksumMismatch {
+    /// The attachment's filename.
+    pub filename: String,
+
+    /// The expected SHA-256 hex digest, in lowercase.
+    pub expected_sha256: String,
+
+    /// SHA-256 hex digest of the extracted data.
+    pub actual_sha256: String,
+}
+
+impl ChecksumVerification {
+    /// Returns `true` when every expected file is present with the expected
+    /// content and no other file is.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+impl PdfAnalyzer {
+    /// Check the embedded files against `expected`, a map from filename to
+    /// SHA-256 hex digest, e.g. from an archived
+    /// [`ExtractionManifest::checksums`].
+    ///
+    /// Digests are compared case-insensitively. A document without embedded
+    /// files is treated as having none rather than as an error, so every
+    /// expected file is reported missing.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let expected = BTreeMap::from([(
+    ///     "factur-x.xml".to_string(),
+    ///     "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+    /// )]);
+    /// let result = PdfAnalyzer::from_path("invoice.pdf")
+    ///     .unwrap()
+    ///     .verify_checksums(&expected)
+    ///     .unwrap();
+    /// for mismatch in &result.mismatched {
+    ///     println!("{} was modified", mismatch.filename);
+    /// }
+    /// ```
+    pub fn verify_checksums(
+        &self,
+        expected: &BTreeMap<String, String>,
+    ) -> Result<ChecksumVerification> {
+        let files = match self.extract_embedded_files() {
+            Ok(files) => files,
+            Err(ExtractError::NoEmbeddedFiles) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut result = ChecksumVerification::default();
+        for file in &files {
+            let Some(expected_sha256) = expected.get(&file.filename) else {
+                result.unexpected.push(file.filename.clone());
+                continue;
+            };
+            let expected_sha256 = expected_sha256.to_ascii_lowercase();
+            let actual_sha256 = file.sha256_hex();
+            if actual_sha256 == expected_sha256 {
+                result.verified.push(file.filename.clone());
+            } else {
+                result.mismatched.push(ChecksumMismatch {
+                    filename: file.filename.clone(),
+                    expected_sha256,
+                    actual_sha256,
+                });
+            }
+        }
+        result.missing = expected
+            .keys()
+            .filter(|name| !files.iter().any(|file| &file.filename == *name))
+            .cloned()
+            .collect();
+
+        result.unexpected.sort();
+        result.verified.sort();
+        result
+            .mismatched
+            .sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(result)
+    }
+}
+
+// ── SignedManifest ────────────────────────────────────────────────────────────
+
+/// An [`ExtractionManifest`] with an Ed25519 signature over its
+/// [`canonical_bytes`](ExtractionManifest::canonical_bytes).
+///
+/// Returned by [`ExtractionManifest::sign`].
+#[cfg(feature = "signing")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedManifest {
+    /// The signed manifest.
+    pub manifest: ExtractionManifest,
+
+    /// The signature over the manifest's canonical bytes.
+    pub signature: ed25519_dalek::Signature,
+}
+
+#[cfg(feature = "signing")]
+impl SignedManifest {
+    /// Returns `true` if the signature was made by the holder of `key` over
+    /// the current content of [`manifest`](Self::manifest).
+    pub fn verify(&self, key: &ed25519_dalek::VerifyingKey) -> bool {
+        key.verify_strict(&self.manifest.canonical_bytes(), &self.signature)
+            .is_ok()
+    }
+
+    /// The signature as lowercase hex, for storing next to the manifest.
+    pub fn signature_hex(&self) -> String {
+        hex_encode(&self.signature.to_bytes())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{ExtractError, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The memory budget of one operation, from
+/// [`ExtractorConfig::max_memory_bytes`].
+///
+/// Clones share one counter, so streams decoded concurrently with the
+/// `parallel` feature draw on the same budget. Without a limit nothing is
+/// counted.
+///
+/// [`ExtractorConfig::max_memory_bytes`]: crate::ExtractorConfig::max_memory_bytes
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemoryBudget {
+    tracked: Option<(usize, Arc<AtomicUsize>)>,
+}
+
+impl MemoryBudget {
+    /// Start a budget of `limit` bytes with `used` bytes already taken by the
+    /// loaded document; `None` never runs out.
+    pub fn start(limit: Option<usize>, used: usize) -> Self {
+        Self {
+            tracked: limit.map(|limit| (limit, Arc::new(AtomicUsize::new(used)))),
+        }
+    }
+
+    /// The number of bytes still available.
+    pub fn remaining(&self) -> usize {
+        match &self.tracked {
+            Some((limit, used)) => limit.saturating_sub(used.load(Ordering::Relaxed)),
+            None => usize::MAX,
+        }
+    }
+
+    /// Fail with [`ExtractError::MemoryLimit`] if `bytes` more would not fit.
+    pub fn check(&self, bytes: usize) -> Result<()> {
+        match &self.tracked {
+            Some((limit, _)) if bytes > self.remaining() => Err(ExtractError::MemoryLimit(*limit)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Take `bytes` from the budget, failing with
+    /// [`ExtractError::MemoryLimit`] when they do not fit.
+    pub fn charge(&self, bytes: usize) -> Result<()> {
+        let Some((limit, used)) = &self.tracked else {
+            return Ok(());
+        };
+        let before = used.fetch_add(bytes, Ordering::Relaxed);
+        if before.saturating_add(bytes) > *limit {
+            used.fetch_sub(bytes, Ordering::Relaxed);
+            return Err(ExtractError::MemoryLimit(*limit));
+        }
+        Ok(())
+    }
+
+    /// Return `bytes` taken by [`charge`](Self::charge) once they are freed.
+    pub fn release(&self, bytes: usize) {
+        if let Some((_, used)) = &self.tracked {
+            used.fetch_sub(bytes, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+// ── MetricsSink ───────────────────────────────────────────────────────────────
+
+/// Receives timing and throughput measurements from the extraction pipeline.
+///
+/// Install a sink through [`ExtractorConfig::metrics`]; every method has an
+/// empty default, so implementors only override the stages they export. The
+/// sink is shared across threads by [`crate::PdfBatch`] and the `parallel`
+/// feature, so methods take `&self`.
+///
+/// The clock is only read while a sink is installed; `std::time::Instant` is
+/// unavailable on `wasm32-unknown-unknown`, so do not install one there.
+///
+/// # Example
+///
+/// ```
+/// use extractembedfilepdf::{ExtractorConfig, MetricsSink};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct DecodedBytes(AtomicU64);
+///
+/// impl MetricsSink for DecodedBytes {
+///     fn record_decode(&self, _name: &str, _elapsed: Duration, bytes: usize) {
+///         self.0.fetch_add(bytes as u64, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let config = ExtractorConfig {
+///     metrics: Some(Arc::new(DecodedBytes::default())),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ExtractorConfig::metrics`]: crate::ExtractorConfig::metrics
+pub trait MetricsSink: Send + Sync {
+    /// A document of `bytes` bytes was parsed in `elapsed`.
+    fn record_load(&self, elapsed: Duration, bytes: usize) {
+        let _ = (elapsed, bytes);
+    }
+
+    /// Discovery found `files` file specifications in `elapsed`.
+    fn record_discovery(&self, elapsed: Duration, files: usize) {
+        let _ = (elapsed, files);
+    }
+
+    /// The attachment `name` was decoded to `bytes` bytes in `elapsed`.
+    fn record_decode(&self, name: &str, elapsed: Duration, bytes: usize) {
+        let _ = (name, elapsed, bytes);
+    }
+
+    /// `bytes` bytes were written to `path` in `elapsed`.
+    fn record_write(&self, path: &Path, elapsed: Duration, bytes: usize) {
+        let _ = (path, elapsed, bytes);
+    }
+}
+
+impl fmt::Debug for dyn MetricsSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsSink")
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{form_data, kind, probe, risk, FormDataFormat};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Magic bytes at the start of a file and the MIME type they identify.
+const SIGNATURES: [(&[u8], &str); 12] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF8", "image/gif"),
+    (b"II*\x00", "image/tiff"),
+    (b"MM\x00*", "image/tiff"),
+    (b"BM", "image/bmp"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"BZh", "application/x-bzip2"),
+    (risk::OLE, "application/x-ole-storage"),
+];
+
+/// The built-in extension table: lowercase extension and MIME type.
+const BUILTIN: [(&str, &str); 52] = [
+    ("7z", "application/x-7z-compressed"),
+    ("bmp", "image/bmp"),
+    ("bz2", "application/x-bzip2"),
+    ("cab", "application/vnd.ms-cab-compressed"),
+    ("csv", "text/csv"),
+    ("doc", "application/msword"),
+    ("docm", "application/vnd.ms-word.document.macroenabled.12"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("eml", "message/rfc822"),
+    ("fdf", "application/vnd.fdf"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("icc", "application/vnd.iccprofile"),
+    ("icm", "application/vnd.iccprofile"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("json", "application/json"),
+    ("md", "text/markdown"),
+    ("odp", "application/vnd.oasis.opendocument.presentation"),
+    ("ods", "application/vnd.oasis.opendocument.spreadsheet"),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("otf", "font/otf"),
+    ("p7m", "application/pkcs7-mime"),
+    ("p7s", "application/pkcs7-signature"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    (
+        "pptm",
+        "application/vnd.ms-powerpoint.presentation.macroenabled.12",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("rar", "application/vnd.rar"),
+    ("rtf", "application/rtf"),
+    ("svg", "image/svg+xml"),
+    ("tar", "application/x-tar"),
+    ("tgz", "application/gzip"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("ttf", "font/ttf"),
+    ("txt", "text/plain"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xfdf", "application/vnd.adobe.xfdf"),
+    ("xls", "application/vnd.ms-excel"),
+    ("xlsm", "application/vnd.ms-excel.sheet.macroenabled.12"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("xmp", "application/rdf+xml"),
+    ("xml", "application/xml"),
+    ("xsd", "application/xml"),
+    ("xz", "application/x-xz"),
+    ("zip", "application/zip"),
+];
+
+// ── MimeTable ─────────────────────────────────────────────────────────────────
+
+/// The mapping between filename extensions and MIME types that
+/// [`EmbeddedFile::classify_with`](crate::EmbeddedFile::classify_with) falls
+/// back to when an attachment's content is not recognised.
+///
+/// [`MimeTable::default`] holds a built-in table of common formats; add
+/// entries for niche formats through
+/// [`ExtractorConfig::mime_types`](crate::ExtractorConfig::mime_types).
+/// Extensions are matched case-insensitively and without the leading dot.
+///
+/// ```
+/// use extractembedfilepdf::{ExtractorConfig, MimeTable};
+///
+/// let config = ExtractorConfig {
+///     mime_types: MimeTable::default().with("xrechnung", "application/xml"),
+///     ..Default::default()
+/// };
+/// assert_eq!(config.mime_types.mime_type("XRECHNUNG"), Some("application/xml"));
+/// assert_eq!(config.mime_types.mime_type("pdf"), Some("application/pdf"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MimeTable {
+    types: BTreeMap<String, String>,
+}
+
+impl Default for MimeTable {
+    /// The built-in table.
+    fn default() -> Self {
+        builtin().clone()
+    }
+}
+
+impl MimeTable {
+    /// A table without any entries.
+    pub fn empty() -> Self {
+        Self {
+            types: BTreeMap::new(),
+        }
+    }
+
+    /// Maps `extension` to `mime_type`, replacing any previous entry.
+    pub fn insert(&mut self, extension: &str, mime_type: &str) {
+        self.types
+            .insert(normalize(extension), mime_type.trim().to_ascii_lowercase());
+    }
+
+    /// The table with `extension` mapped to `mime_type`, for building a
+    /// configuration in one expression.
+    pub fn with(mut self, extension: &str, mime_type: &str) -> Self {
+        self.insert(extension, mime_type);
+        self
+    }
+
+    /// Removes the entry for `extension` and returns its MIME type.
+    pub fn remove(&mut self, extension: &str) -> Option<String> {
+        self.types.remove(&normalize(extension))
+    }
+
+    /// The MIME type for `extension`, e.g. `"application/pdf"` for `"pdf"`.
+    pub fn mime_type(&self, extension: &str) -> Option<&str> {
+        self.types.get(&normalize(extension)).map(String::as_str)
+    }
+
+    /// The MIME type for the extension of `filename`.
+    pub fn for_filename(&self, filename: &str) -> Option<&str> {
+        let (_, extension) = filename.rsplit_once('.')?;
+        self.mime_type(extension)
+    }
+
+    /// The extensions mapped to `mime_type`, in alphabetical order.
+    pub fn extensions<'a>(&'a self, mime_type: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.types
+            .iter()
+            .filter(move |(_, mime)| mime.eq_ignore_ascii_case(mime_type))
+            .map(|(extension, _)| extension.as_str())
+    }
+
+    /// Every entry as `(extension, mime_type)`, ordered by extension.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.types
+            .iter()
+            .map(|(extension, mime)| (extension.as_str(), mime.as_str()))
+    }
+}
+
+/// The MIME type identified by the magic bytes of `data`.
+pub(crate) fn sniff(data: &[u8]) -> Option<&'static str> {
+    if probe::has_pdf_header(data) {
+        return Some("application/pdf");
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP".as_slice()) {
+        return Some("image/webp");
+    }
+    if data.get(36..40) == Some(b"acsp".as_slice()) {
+        return Some("application/vnd.iccprofile");
+    }
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return Some(mime);
+    }
+    match form_data::detect(data) {
+        Some(FormDataFormat::Fdf) => Some("application/vnd.fdf"),
+        Some(FormDataFormat::Xfdf) => Some("application/vnd.adobe.xfdf"),
+        None => kind::is_xml(data).then_some("application/xml"),
+    }
+}
+
+/// The built-in table, built once.
+pub(crate) fn builtin() -> &'static MimeTable {
+    static TABLE: OnceLock<MimeTable> = OnceLock::new();
+    TABLE.get_or_init(|| MimeTable {
+        types: BUILTIN
+            .iter()
+            .map(|&(extension, mime)| (extension.to_owned(), mime.to_owned()))
+            .collect(),
+    })
+}
+
+/// `extension` in lowercase without a leading dot.
+fn normalize(extension: &str) -> String {
+    extension
+        .trim()
+        .trim_start_matches('.')
+        .to_ascii_lowercase()
+}
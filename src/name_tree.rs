@@ -0,0 +1,87 @@
+//! Traversal of PDF name trees.
+//!
+//! A name tree (ISO 32000-1, 7.9.6) maps string keys to objects through
+//! intermediate nodes with `/Kids` and leaf nodes with a
+//! `[key, value, key, value, …]` `/Names` array. The catalog's `/Names`
+//! dictionary holds one per category: `/EmbeddedFiles`, `/Dests`,
+//! `/JavaScript` and others.
+
+use crate::backend::PdfBackend;
+use crate::deadline::Deadline;
+use lopdf::{Document, Object, ObjectId};
+use std::collections::HashSet;
+
+/// Collect every `(key, value)` entry of the name tree rooted at `root_id`,
+/// in tree order.
+///
+/// Damaged nodes, entries whose key is not a string and a dangling last key
+/// are skipped. Each node is visited at most once, so a `/Kids` array that
+/// points back up the tree cannot loop forever. Values are returned as
+/// written; an indirect value is still an [`Object::Reference`].
+///
+/// The types are lopdf's, as returned by `PdfAnalyzer::document`, and like
+/// that accessor they are not part of the stable API: they follow the lopdf
+/// version the crate depends on.
+///
+/// ```no_run
+/// use extractembedfilepdf::{name_tree, PdfAnalyzer};
+///
+/// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+/// let document = analyzer.document();
+/// let names = document
+///     .catalog()
+///     .and_then(|catalog| catalog.get_deref(b"Names", document))
+///     .and_then(|names| names.as_dict())
+///     .unwrap();
+/// if let Ok(root) = names.get(b"JavaScript").and_then(|o| o.as_reference()) {
+///     for (key, _) in name_tree::walk(document, root) {
+///         println!("{}", String::from_utf8_lossy(key));
+///     }
+/// }
+/// ```
+pub fn walk(document: &Document, root_id: ObjectId) -> Vec<(&[u8], &Object)> {
+    let mut entries = Vec::new();
+    for_each_leaf(document, root_id, Deadline::default(), &mut |names| {
+        entries.extend(
+            names
+                .chunks_exact(2)
+                .filter_map(|pair| Some((pair[0].as_str().ok()?, &pair[1]))),
+        );
+    });
+    entries
+}
+
+/// Call `visit` with the `/Names` array of every leaf below `root_id`,
+/// visiting each node at most once and giving up once `deadline` expires.
+pub(crate) fn for_each_leaf<'a, D: PdfBackend + ?Sized>(
+    document: &'a D,
+    root_id: ObjectId,
+    deadline: Deadline,
+    visit: &mut dyn FnMut(&'a [Object]),
+) {
+    let mut seen = HashSet::new();
+    let mut pending = vec![root_id];
+
+    while let Some(node_id) = pending.pop() {
+        if deadline.expired() {
+            return;
+        }
+        if !seen.insert(node_id) {
+            continue;
+        }
+        let Ok(node) = document.get_dictionary(node_id) else {
+            continue;
+        };
+
+        // Leaf node: has a /Names array of [key, value, key, value, …]
+        if let Ok(names) = node.get(b"Names").and_then(|v| v.as_array()) {
+            visit(names);
+        }
+
+        // Intermediate node: has a /Kids array of references. They are
+        // pushed in reverse so the leftmost child is visited first.
+        if let Ok(kids) = node.get(b"Kids").and_then(|v| v.as_array()) {
+            pending.extend(kids.iter().rev().filter_map(|kid| kid.as_reference().ok()));
+        }
+    }
+}
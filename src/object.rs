@@ -0,0 +1,137 @@
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+// ── ObjectRef ─────────────────────────────────────────────────────────────────
+
+/// A reference to an indirect PDF object: its object number and generation,
+/// written `12 0 R` in a PDF file.
+///
+/// The crate's own type for object ids in the public API, so that upgrading
+/// the PDF backend does not change it. Converts to and from an
+/// `(object number, generation)` tuple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ObjectRef {
+    /// The object number.
+    pub number: u32,
+
+    /// The generation number; `0` for objects that were never replaced.
+    pub generation: u16,
+}
+
+impl From<ObjectId> for ObjectRef {
+    fn from((number, generation): ObjectId) -> Self {
+        Self { number, generation }
+    }
+}
+
+impl From<ObjectRef> for ObjectId {
+    fn from(object: ObjectRef) -> Self {
+        (object.number, object.generation)
+    }
+}
+
+impl std::fmt::Display for ObjectRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} R", self.number, self.generation)
+    }
+}
+
+// ── PdfDict ───────────────────────────────────────────────────────────────────
+
+/// A read-only view of a dictionary in the loaded document.
+///
+/// Values that are indirect references are followed to the object they point
+/// to, and typed getters return `None` for a missing key as well as for a
+/// value of another type. Returned by [`PdfAnalyzer::dictionary`] and
+/// [`EmbeddedFileHandle::spec_dict`](crate::EmbeddedFileHandle::spec_dict).
+///
+/// [`PdfAnalyzer::dictionary`]: crate::PdfAnalyzer::dictionary
+#[derive(Clone, Copy)]
+pub struct PdfDict<'a> {
+    dict: &'a Dictionary,
+    document: &'a Document,
+}
+
+impl<'a> PdfDict<'a> {
+    pub(crate) fn new(dict: &'a Dictionary, document: &'a Document) -> Self {
+        Self { dict, document }
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.dict.len()
+    }
+
+    /// Returns `true` if the dictionary has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.dict.is_empty()
+    }
+
+    /// The keys, without the leading `/`, in file order.
+    pub fn keys(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.dict.iter().map(|(key, _)| key.as_slice())
+    }
+
+    /// Returns `true` if `key` is present, whatever its value.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.dict.has(key)
+    }
+
+    /// The string value of `key`, decoded lossily as UTF-8.
+    pub fn get_str(&self, key: &[u8]) -> Option<String> {
+        let bytes = self.get(key)?.as_str().ok()?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// The name value of `key`, without the leading `/`.
+    pub fn get_name(&self, key: &[u8]) -> Option<String> {
+        let bytes = self.get(key)?.as_name().ok()?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// The integer value of `key`.
+    pub fn get_int(&self, key: &[u8]) -> Option<i64> {
+        self.get(key)?.as_i64().ok()
+    }
+
+    /// The boolean value of `key`.
+    pub fn get_bool(&self, key: &[u8]) -> Option<bool> {
+        self.get(key)?.as_bool().ok()
+    }
+
+    /// The object `key` refers to, if its value is an indirect reference.
+    pub fn get_ref(&self, key: &[u8]) -> Option<ObjectRef> {
+        self.dict
+            .get(key)
+            .ok()?
+            .as_reference()
+            .ok()
+            .map(ObjectRef::from)
+    }
+
+    /// The dictionary value of `key`; a stream yields its dictionary.
+    pub fn get_dict(&self, key: &[u8]) -> Option<PdfDict<'a>> {
+        let dict = match self.get(key)? {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => return None,
+        };
+        Some(Self::new(dict, self.document))
+    }
+
+    /// The value of `key` with one level of indirection resolved.
+    fn get(&self, key: &[u8]) -> Option<&'a Object> {
+        match self.dict.get(key).ok()? {
+            Object::Reference(id) => self.document.get_object(*id).ok(),
+            value => Some(value),
+        }
+    }
+}
+
+impl std::fmt::Debug for PdfDict<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.keys().map(String::from_utf8_lossy))
+            .finish()
+    }
+}
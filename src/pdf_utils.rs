@@ -1,5 +1,10 @@
 //! Shared PDF parsing utilities used across multiple modules.
 
+/// Keys of a file specification (and of its `/EF` dictionary) that may name
+/// the file, in order of preference: Unicode, portable, then the legacy
+/// platform-specific keys written by older producers.
+pub const FILENAME_KEYS: [&[u8]; 5] = [b"UF", b"F", b"Unix", b"Mac", b"DOS"];
+
 /// Extract a string value from a PDF dictionary for a given key.
 ///
 /// Returns `Some(String)` if the key exists and contains a valid non-empty string,
@@ -10,4 +15,115 @@ pub fn extract_string_from_dict(dict: &lopdf::Dictionary, key: &[u8]) -> Option<
         .and_then(|v| v.as_str().ok())
         .map(|s| String::from_utf8_lossy(s).into_owned())
         .filter(|s| !s.is_empty())
-}
\ No newline at end of file
+}
+
+/// Whether `needle` occurs in `haystack`.
+pub fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// `name` reduced to a single path component that is safe to create inside
+/// an output directory or archive: everything up to the last `/` or `\\` is
+/// dropped, drive colons and control characters become `_`, and a name that
+/// is empty, `.` or `..` becomes `attachment`. Attachment names come from
+/// the PDF and must never be joined to a path as they are.
+pub fn safe_filename(name: &str) -> String {
+    let last = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let safe: String = last
+        .chars()
+        .map(|c| if c == ':' || c.is_control() { '_' } else { c })
+        .collect();
+    match safe.trim() {
+        "" | "." | ".." => "attachment".into(),
+        _ => safe,
+    }
+}
+
+/// Encode raw bytes as a lowercase hex string (used for the MD5 checksum).
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The fields of a PDF date string, `D:YYYYMMDDHHmmSSOHH'mm'` (ISO 32000-1
+/// §7.9.4).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdfDate {
+    pub year: u16,
+    pub month: u16,
+    pub day: u16,
+    pub hour: u16,
+    pub minute: u16,
+    pub second: u16,
+    /// Offset of the local time from UTC, in minutes; `0` when not given.
+    pub offset_minutes: i32,
+}
+
+impl PdfDate {
+    /// Parse `text`. Everything after the year is optional and trailing
+    /// fields default to their lowest value, as the specification allows.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_prefix("D:").unwrap_or(text);
+        let digits_end = text
+            .bytes()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(text.len());
+        let (digits, zone) = text.split_at(digits_end);
+        let field = |range: std::ops::Range<usize>, default: u16| {
+            digits.get(range).map_or(Some(default), |d| d.parse().ok())
+        };
+
+        let date = Self {
+            year: digits.get(..4)?.parse().ok()?,
+            month: field(4..6, 1)?,
+            day: field(6..8, 1)?,
+            hour: field(8..10, 0)?,
+            minute: field(10..12, 0)?,
+            second: field(12..14, 0)?,
+            offset_minutes: parse_offset(zone)?,
+        };
+        let valid = (1..=12).contains(&date.month)
+            && (1..=31).contains(&date.day)
+            && date.hour <= 23
+            && date.minute <= 59
+            && date.second <= 59;
+        valid.then_some(date)
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z.
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from civil date (Howard Hinnant's algorithm).
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let doy =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+
+        days * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.offset_minutes) * 60
+    }
+}
+
+/// The UTC offset in minutes of a PDF date's time zone part: `Z`,
+/// `+HH'mm'` or `-HH'mm'`. A missing or unknown zone counts as UTC; hours
+/// or minutes that are not a number within range give `None`, so the date
+/// is rejected like one with an invalid time.
+fn parse_offset(zone: &str) -> Option<i32> {
+    let sign = match zone.as_bytes().first() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => return Some(0),
+    };
+    let mut parts = zone[1..].split('\'').filter(|part| !part.is_empty());
+    let hours: u16 = parts.next().map_or(Some(0), |h| h.parse().ok())?;
+    let minutes: u16 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (i32::from(hours) * 60 + i32::from(minutes)))
+}
@@ -0,0 +1,179 @@
+use crate::{xmp, EmbeddedFile};
+
+/// Namespace of the UBL 2.1 invoice root element.
+const UBL_INVOICE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:Invoice-2";
+
+/// Namespace of the UBL 2.1 credit note root element.
+const UBL_CREDIT_NOTE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2";
+
+/// Namespace of the UBL basic components (`cbc`).
+const CBC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2";
+
+/// Namespace of the UBL aggregate components (`cac`).
+const CAC_NAMESPACE: &str =
+    "urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2";
+
+/// The specification identifier of Peppol BIS Billing 3.0, contained in the
+/// `CustomizationID` of the specification and of its national extensions.
+const BILLING_CUSTOMIZATION: &str = "urn:fdc:peppol.eu:2017:poacc:billing:3.0";
+
+/// Prefix of the Peppol BIS Billing business process identifiers.
+const BILLING_PROFILE_PREFIX: &str = "urn:fdc:peppol.eu:2017:poacc:billing:";
+
+// ── PeppolDocument ────────────────────────────────────────────────────────────
+
+/// The Peppol BIS Billing 3.0 identification of a UBL attachment, returned
+/// by [`EmbeddedFile::peppol`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PeppolDocument {
+    /// Whether the document is an invoice or a credit note.
+    pub document_type: PeppolDocumentType,
+
+    /// The `cbc:CustomizationID`, e.g.
+    /// `urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0`.
+    pub customization_id: String,
+
+    /// The `cbc:ProfileID`, the business process, e.g.
+    /// `urn:fdc:peppol.eu:2017:poacc:billing:01:1.0`.
+    pub profile_id: Option<String>,
+
+    /// The seller's electronic address, the `cbc:EndpointID` of the
+    /// `cac:AccountingSupplierParty`.
+    pub sender: Option<ParticipantId>,
+
+    /// The buyer's electronic address, the `cbc:EndpointID` of the
+    /// `cac:AccountingCustomerParty`.
+    pub receiver: Option<ParticipantId>,
+}
+
+/// The UBL document type of a [`PeppolDocument`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PeppolDocumentType {
+    /// A UBL `Invoice`.
+    Invoice,
+    /// A UBL `CreditNote`.
+    CreditNote,
+}
+
+/// A Peppol participant identifier: an electronic address and the code of
+/// its identifier scheme, such as `0088` for GLN or `9930` for a German VAT
+/// number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParticipantId {
+    /// The `schemeID` attribute, if present.
+    pub scheme: Option<String>,
+
+    /// The identifier itself.
+    pub id: String,
+}
+
+impl std::fmt::Display for ParticipantId {
+    /// Formats as `scheme:id`, the form used in Peppol SMP lookups.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.scheme {
+            Some(scheme) => write!(f, "{scheme}:{}", self.id),
+            None => f.write_str(&self.id),
+        }
+    }
+}
+
+impl EmbeddedFile {
+    /// Identifies a UBL invoice or credit note that follows Peppol BIS
+    /// Billing 3.0, by its `CustomizationID` or `ProfileID`, and reads the
+    /// sender and receiver participant identifiers that a Peppol access
+    /// point routes it by. Returns `None` for any other file, including UBL
+    /// documents outside the Peppol network.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, PeppolDocumentType};
+    ///
+    /// let file = EmbeddedFile {
+    ///     filename: "invoice.xml".into(),
+    ///     data: br#"<Invoice xmlns="urn:oasis:names:specification:ubl:schema:xsd:Invoice-2"
+    ///   xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2"
+    ///   xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2">
+    ///   <cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID>
+    ///   <cac:AccountingSupplierParty><cac:Party>
+    ///     <cbc:EndpointID schemeID="0088">7300010000001</cbc:EndpointID>
+    ///   </cac:Party></cac:AccountingSupplierParty>
+    /// </Invoice>"#
+    ///         .to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// let peppol = file.peppol().unwrap();
+    /// assert_eq!(peppol.document_type, PeppolDocumentType::Invoice);
+    /// assert_eq!(peppol.sender.unwrap().to_string(), "0088:7300010000001");
+    /// ```
+    pub fn peppol(&self) -> Option<PeppolDocument> {
+        let xml = xmp::decode(&self.data);
+        let document_type = if xml.contains(UBL_INVOICE_NAMESPACE) {
+            PeppolDocumentType::Invoice
+        } else if xml.contains(UBL_CREDIT_NOTE_NAMESPACE) {
+            PeppolDocumentType::CreditNote
+        } else {
+            return None;
+        };
+
+        let customization_id = xmp::property(&xml, CBC_NAMESPACE, "cbc", "CustomizationID")?;
+        let profile_id = xmp::property(&xml, CBC_NAMESPACE, "cbc", "ProfileID");
+        let is_billing = customization_id.contains(BILLING_CUSTOMIZATION)
+            || profile_id.is_some_and(|id| id.starts_with(BILLING_PROFILE_PREFIX));
+        if !is_billing {
+            return None;
+        }
+
+        Some(PeppolDocument {
+            document_type,
+            customization_id: xmp::unescape(customization_id),
+            profile_id: profile_id.map(xmp::unescape),
+            sender: endpoint(&xml, "AccountingSupplierParty"),
+            receiver: endpoint(&xml, "AccountingCustomerParty"),
+        })
+    }
+}
+
+/// The `cbc:EndpointID` inside the first `cac:{party}` element of `xml`.
+fn endpoint(xml: &str, party: &str) -> Option<ParticipantId> {
+    let cac = xmp::with_default(xmp::bound_prefixes(xml, CAC_NAMESPACE), "cac");
+    let cbc = xmp::with_default(xmp::bound_prefixes(xml, CBC_NAMESPACE), "cbc");
+    let party = element(xml, &cac, party)?.1;
+    let (tag, id) = element(party, &cbc, "EndpointID")?;
+    let id = id.trim();
+    if id.is_empty() || id.contains('<') {
+        return None;
+    }
+    let scheme = tag
+        .split_once("schemeID")
+        .and_then(|(_, rest)| xmp::quoted(rest.trim_start().strip_prefix('=')?))
+        .map(|scheme| xmp::unescape(scheme.trim()));
+    Some(ParticipantId {
+        scheme,
+        id: xmp::unescape(id),
+    })
+}
+
+/// The attributes and content of the first element of `xml` named `name`
+/// under one of `prefixes`.
+fn element<'a>(xml: &'a str, prefixes: &[&str], name: &str) -> Option<(&'a str, &'a str)> {
+    prefixes
+        .iter()
+        .filter_map(|prefix| {
+            let qualified = format!("{prefix}:{name}");
+            let (at, open) = xml
+                .match_indices(&format!("<{qualified}"))
+                .find(|(at, open)| {
+                    xml[at + open.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+                })?;
+            let (tag, content) = xml[at + open.len()..].split_once('>')?;
+            if tag.ends_with('/') {
+                return None;
+            }
+            let content = &content[..content.find(&format!("</{qualified}>"))?];
+            Some((at, (tag, content)))
+        })
+        .min_by_key(|(at, _)| *at)
+        .map(|(_, element)| element)
+}
@@ -0,0 +1,105 @@
+use crate::PdfAnalyzer;
+
+/// How far from the start of the file the `%PDF-` header may appear.
+const HEADER_WINDOW: usize = 1024;
+
+/// The marker that opens every PDF file.
+const PDF_HEADER: &[u8] = b"%PDF-";
+
+/// How far from the end of the file the `%%EOF` marker may appear.
+const TRAILER_WINDOW: usize = 1024;
+
+// ── PdfProbe ──────────────────────────────────────────────────────────────────
+
+/// The result of a quick byte-level scan of a candidate PDF.
+///
+/// Returned by [`PdfAnalyzer::probe`] and [`PdfAnalyzer::probe_bytes`]. No
+/// object graph is built, so the token checks are heuristics: a token inside
+/// a compressed object stream is not seen, and a token inside an unrelated
+/// string produces a false positive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PdfProbe {
+    /// `%PDF-` appears within the first 1024 bytes.
+    pub has_header: bool,
+
+    /// The version following `%PDF-` (e.g. `"1.7"`), if readable.
+    pub version: Option<String>,
+
+    /// `%%EOF` appears within the last 1024 bytes.
+    pub has_eof_marker: bool,
+
+    /// The `/EmbeddedFiles` token occurs anywhere in the file.
+    pub mentions_embedded_files: bool,
+
+    /// The `/Metadata` token occurs anywhere in the file.
+    pub mentions_metadata: bool,
+}
+
+impl PdfProbe {
+    /// Returns `true` when both the header and the EOF marker were found.
+    pub fn looks_like_pdf(&self) -> bool {
+        self.has_header && self.has_eof_marker
+    }
+
+    /// Returns `true` when the file looks like a PDF that may carry
+    /// attachments and is therefore worth a full analysis.
+    pub fn worth_analyzing(&self) -> bool {
+        self.looks_like_pdf() && self.mentions_embedded_files
+    }
+}
+
+impl PdfAnalyzer {
+    /// Scan a file for PDF markers without parsing it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let probe = PdfAnalyzer::probe("invoice.pdf").unwrap();
+    /// if probe.worth_analyzing() {
+    ///     let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    ///     // …
+    /// }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn probe<P: AsRef<std::path::Path>>(path: P) -> crate::Result<PdfProbe> {
+        Ok(Self::probe_bytes(&std::fs::read(path)?))
+    }
+
+    /// Scan an in-memory buffer for PDF markers without parsing it.
+    pub fn probe_bytes(data: &[u8]) -> PdfProbe {
+        let head = &data[..data.len().min(HEADER_WINDOW)];
+        let tail = &data[data.len().saturating_sub(TRAILER_WINDOW)..];
+
+        let header_pos = find(head, PDF_HEADER);
+        let version = header_pos.and_then(|pos| {
+            let rest = &data[pos + 5..];
+            let end = rest
+                .iter()
+                .position(|b| !(b.is_ascii_digit() || *b == b'.'))
+                .unwrap_or(rest.len());
+            (end > 0).then(|| String::from_utf8_lossy(&rest[..end]).into_owned())
+        });
+
+        PdfProbe {
+            has_header: header_pos.is_some(),
+            version,
+            has_eof_marker: find(tail, b"%%EOF").is_some(),
+            mentions_embedded_files: find(data, b"/EmbeddedFiles").is_some(),
+            mentions_metadata: find(data, b"/Metadata").is_some(),
+        }
+    }
+}
+
+/// Whether the `%PDF-` header appears within the first 1024 bytes of `data`,
+/// where PDF readers accept it.
+pub(crate) fn has_pdf_header(data: &[u8]) -> bool {
+    find(&data[..data.len().min(HEADER_WINDOW)], PDF_HEADER).is_some()
+}
+
+/// Position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
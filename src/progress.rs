@@ -0,0 +1,98 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+// ── Progress ──────────────────────────────────────────────────────────────────
+
+/// A progress update sent to [`ExtractorConfig::progress`] after each
+/// attachment has been processed, whether it was extracted or skipped.
+///
+/// [`ExtractorConfig::progress`]: crate::ExtractorConfig::progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of attachments processed so far, from 1 to `total`.
+    pub current: usize,
+
+    /// Number of attachments in the document.
+    pub total: usize,
+
+    /// Decoded bytes of all attachments processed so far.
+    pub bytes_decoded: u64,
+}
+
+/// A callback receiving [`Progress`] updates, installed through
+/// [`ExtractorConfig::progress`].
+///
+/// Updates arrive one at a time and in order, even with the `parallel`
+/// feature: each `current` value is reported exactly once, together with the
+/// bytes decoded by exactly those `current` attachments.
+///
+/// With the `parallel` feature, an attachment is reported as soon as it has
+/// been decoded, so `current` counts attachments in the order they finish
+/// rather than in discovery order, and an attachment may be reported before
+/// it is written to disk.
+///
+/// # Example
+///
+/// ```
+/// use extractembedfilepdf::{ExtractorConfig, ProgressCallback};
+///
+/// let config = ExtractorConfig {
+///     progress: Some(ProgressCallback::new(|p| {
+///         eprintln!("{}/{} files, {} bytes", p.current, p.total, p.bytes_decoded);
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ExtractorConfig::progress`]: crate::ExtractorConfig::progress
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wrap `callback`.
+    pub fn new(callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Counts processed attachments during one extraction and reports each to the
+/// callback, if one is installed.
+pub(crate) struct ProgressTracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    total: usize,
+    /// Attachments processed and bytes decoded so far, updated together.
+    done: Mutex<(usize, u64)>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub fn new(callback: Option<&'a ProgressCallback>, total: usize) -> Self {
+        Self {
+            callback,
+            total,
+            done: Mutex::default(),
+        }
+    }
+
+    /// Record one processed attachment that decoded to `bytes` bytes.
+    pub fn file_done(&self, bytes: usize) {
+        let Some(callback) = self.callback else {
+            return;
+        };
+        // The lock is held across the call so updates reach the callback in
+        // the order they were counted.
+        let mut done = self.done.lock().unwrap_or_else(|e| e.into_inner());
+        done.0 += 1;
+        done.1 += bytes as u64;
+        (callback.0)(Progress {
+            current: done.0,
+            total: self.total,
+            bytes_decoded: done.1,
+        });
+    }
+}
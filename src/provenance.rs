@@ -0,0 +1,159 @@
+use crate::pdf_utils::hex_encode;
+#[cfg(feature = "fs")]
+use crate::EmbeddedFile;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "fs")]
+use std::fmt::Write;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "fs")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ── SourceInfo ────────────────────────────────────────────────────────────────
+
+/// What is known about the raw document an analyzer was loaded from. The
+/// bytes themselves are not kept.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceInfo {
+    /// Size of the raw document in bytes.
+    pub len: usize,
+
+    /// SHA-256 hex digest of the raw document, when
+    /// [`ExtractorConfig::hash_source`](crate::ExtractorConfig::hash_source)
+    /// asked for it.
+    pub sha256: Option<String>,
+
+    /// The path the document was read from, for path constructors.
+    #[cfg(feature = "fs")]
+    pub path: Option<PathBuf>,
+}
+
+impl SourceInfo {
+    /// Describe `data`, hashing it only when `hash` is set.
+    pub fn new(data: &[u8], hash: bool) -> Self {
+        Self {
+            len: data.len(),
+            sha256: hash.then(|| hex_encode(&Sha256::digest(data))),
+            #[cfg(feature = "fs")]
+            path: None,
+        }
+    }
+}
+
+// ── Sidecars ──────────────────────────────────────────────────────────────────
+
+/// Write the provenance sidecar of `file`, which was just written to `dest`,
+/// as `<dest>.meta.json` and return its path.
+#[cfg(feature = "fs")]
+pub(crate) fn write_sidecar(
+    dest: &Path,
+    file: &EmbeddedFile,
+    source: Option<&SourceInfo>,
+) -> std::io::Result<PathBuf> {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".meta.json");
+    let path = PathBuf::from(name);
+    std::fs::write(&path, sidecar_json(file, source, SystemTime::now()))?;
+    Ok(path)
+}
+
+/// The sidecar document: the PDF-declared metadata and digests of `file`,
+/// the document it came from, and when it was extracted.
+#[cfg(feature = "fs")]
+fn sidecar_json(file: &EmbeddedFile, source: Option<&SourceInfo>, now: SystemTime) -> String {
+    let meta = &file.metadata;
+    let source_path = source
+        .and_then(|s| s.path.as_deref())
+        .map(|p| p.to_string_lossy());
+
+    let mut json = String::from("{\n");
+    let mut field = |key: &str, value: String| {
+        if json.len() > 2 {
+            json.push_str(",\n");
+        }
+        let _ = write!(json, "  {}: {value}", string(key));
+    };
+    field("filename", string(&file.filename));
+    field("size", file.data.len().to_string());
+    field("md5", string(&file.md5_hex()));
+    field("sha256", string(&file.sha256_hex()));
+    field("mime_type", optional(meta.mime_type.as_deref()));
+    field("description", optional(meta.description.as_deref()));
+    field("creation_date", optional(meta.creation_date.as_deref()));
+    field(
+        "modification_date",
+        optional(meta.modification_date.as_deref()),
+    );
+    field(
+        "declared_size",
+        meta.size.map_or("null".into(), |s| s.to_string()),
+    );
+    field("declared_checksum", optional(meta.checksum.as_deref()));
+    field(
+        "af_relationship",
+        optional(meta.af_relationship.as_ref().map(|r| r.as_str())),
+    );
+    field("discovered_via", optional(meta.source.map(|s| s.as_str())));
+    field("page", meta.page.map_or("null".into(), |p| p.to_string()));
+    field("source_path", optional(source_path.as_deref()));
+    field(
+        "source_sha256",
+        optional(source.and_then(|s| s.sha256.as_deref())),
+    );
+    field("extracted_at", string(&rfc3339(now)));
+    json.push_str("\n}\n");
+    json
+}
+
+/// `value` as a JSON string literal.
+#[cfg(feature = "fs")]
+fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `value` as a JSON string literal, or `null`.
+#[cfg(feature = "fs")]
+fn optional(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".into(), string)
+}
+
+/// `time` in UTC as `YYYY-MM-DDTHH:MM:SSZ`.
+#[cfg(feature = "fs")]
+fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
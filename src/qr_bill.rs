@@ -0,0 +1,214 @@
+use crate::{xmp, EmbeddedFile};
+
+/// The QR type on the first line of every payload.
+const HEADER: &str = "SPC";
+
+/// The trailer that ends the mandatory part of the payload.
+const TRAILER: &str = "EPD";
+
+/// Line index of the trailer; everything before it is mandatory.
+const TRAILER_LINE: usize = 30;
+
+// ── QrBill ────────────────────────────────────────────────────────────────────
+
+/// The payment part of a Swiss QR-bill, returned by [`EmbeddedFile::qr_bill`].
+///
+/// Fields follow the *Swiss Implementation Guidelines for the QR-bill*;
+/// empty elements are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct QrBill {
+    /// The payload version, e.g. `"0200"`.
+    pub version: String,
+
+    /// The creditor's IBAN or QR-IBAN, without spaces.
+    pub iban: String,
+
+    /// The creditor.
+    pub creditor: QrAddress,
+
+    /// The party that ultimately owes the amount, if given.
+    pub debtor: Option<QrAddress>,
+
+    /// The amount as written, e.g. `"1949.75"`; `None` when the payer fills
+    /// it in.
+    pub amount: Option<String>,
+
+    /// `CHF` or `EUR`.
+    pub currency: String,
+
+    /// The kind of [`reference`](Self::reference).
+    pub reference_type: QrReferenceType,
+
+    /// The QR reference or creditor reference, if any.
+    pub reference: Option<String>,
+
+    /// The unstructured message to the creditor.
+    pub message: Option<String>,
+
+    /// The structured billing information, such as a Swico `//S1/…` string.
+    pub billing_information: Option<String>,
+
+    /// The alternative procedure parameters, at most two.
+    pub alternative_procedures: Vec<String>,
+}
+
+/// A creditor or debtor address of a [`QrBill`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct QrAddress {
+    /// `true` for a structured address (type `S`), `false` for a combined
+    /// one (type `K`), whose two address lines are in
+    /// [`street`](Self::street) and [`building_number`](Self::building_number).
+    pub structured: bool,
+
+    /// Name or company.
+    pub name: String,
+
+    /// Street, or the first address line of a combined address.
+    pub street: Option<String>,
+
+    /// Building number, or the second address line of a combined address.
+    pub building_number: Option<String>,
+
+    /// Postal code; empty in a combined address.
+    pub postal_code: Option<String>,
+
+    /// Town; empty in a combined address.
+    pub town: Option<String>,
+
+    /// Two-letter ISO 3166 country code.
+    pub country: String,
+}
+
+/// The reference type of a [`QrBill`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum QrReferenceType {
+    /// `QRR`: a 27-digit QR reference, used with a QR-IBAN.
+    QrReference,
+    /// `SCOR`: an ISO 11649 creditor reference.
+    CreditorReference,
+    /// `NON`: no reference.
+    None,
+}
+
+impl QrBill {
+    /// The Swico invoice number (`/10/`) from the billing information.
+    pub fn invoice_number(&self) -> Option<&str> {
+        self.swico_field("10")
+    }
+
+    /// The value of the Swico syntax version 1 tag `tag`, e.g. `"11"` for
+    /// the invoice date, from the billing information.
+    pub fn swico_field(&self, tag: &str) -> Option<&str> {
+        let fields = self.billing_information.as_deref()?.strip_prefix("//S1/")?;
+        let mut parts = fields.split('/');
+        while let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            if key == tag {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl EmbeddedFile {
+    /// Parses a Swiss QR-bill payload, the `SPC` text encoded in the bill's
+    /// QR code, into its payment part. The payload may be the whole file or
+    /// the text of an XML element. Returns `None` for any other file.
+    ///
+    /// ```
+    /// use extractembedfilepdf::{EmbeddedFile, QrReferenceType};
+    ///
+    /// let payload = "SPC\n0200\n1\nCH4431999123000889012\nS\nRobert Schneider AG\n\
+    ///     Rue du Lac\n1268\n2501\nBiel\nCH\n\n\n\n\n\n\n\n1949.75\nCHF\n\n\n\n\n\n\n\n\
+    ///     QRR\n210000000003139471430009017\nOrder of 15.10.2020\nEPD\n//S1/10/10201409\n";
+    /// let file = EmbeddedFile {
+    ///     filename: "qr-bill.txt".into(),
+    ///     data: payload.as_bytes().to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// let bill = file.qr_bill().unwrap();
+    /// assert_eq!(bill.amount.as_deref(), Some("1949.75"));
+    /// assert_eq!(bill.reference_type, QrReferenceType::QrReference);
+    /// assert_eq!(bill.invoice_number(), Some("10201409"));
+    /// ```
+    pub fn qr_bill(&self) -> Option<QrBill> {
+        parse(&payload(&xmp::decode(&self.data))?)
+    }
+}
+
+/// Whether `data` holds a QR-bill payload.
+pub(crate) fn detect(data: &[u8]) -> bool {
+    let head = &data[..data.len().min(64 * 1024)];
+    payload(&String::from_utf8_lossy(head)).is_some()
+}
+
+/// The payload in `text`: all of it, or the text of the XML element that
+/// holds it.
+fn payload(text: &str) -> Option<String> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with(HEADER) {
+        return is_header(text).then(|| text.to_owned());
+    }
+    let at = text
+        .match_indices(">SPC")
+        .map(|(at, _)| at + 1)
+        .find(|&at| is_header(&text[at..]))?;
+    let end = text[at..].find('<').map_or(text.len(), |end| at + end);
+    Some(xmp::unescape(&text[at..end]))
+}
+
+/// Whether `text` starts with the QR type and a version 2 line.
+fn is_header(text: &str) -> bool {
+    let mut lines = text.lines();
+    lines.next() == Some(HEADER) && lines.next().is_some_and(|v| v.starts_with("02"))
+}
+
+/// Parse the lines of a payload.
+fn parse(payload: &str) -> Option<QrBill> {
+    let lines: Vec<&str> = payload.lines().map(str::trim).collect();
+    if lines.len() <= TRAILER_LINE || lines[0] != HEADER || lines[TRAILER_LINE] != TRAILER {
+        return None;
+    }
+    let field = |i: usize| {
+        lines
+            .get(i)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+    };
+
+    Some(QrBill {
+        version: lines[1].to_owned(),
+        iban: field(3)?.replace(' ', ""),
+        creditor: address(&lines[4..11])?,
+        debtor: address(&lines[20..27]),
+        amount: field(18),
+        currency: field(19)?,
+        reference_type: match lines[27] {
+            "QRR" => QrReferenceType::QrReference,
+            "SCOR" => QrReferenceType::CreditorReference,
+            "NON" => QrReferenceType::None,
+            _ => return None,
+        },
+        reference: field(28),
+        message: field(29),
+        billing_information: field(31),
+        alternative_procedures: (32..34).filter_map(field).collect(),
+    })
+}
+
+/// The address in the seven lines `lines`, or `None` when it is empty.
+fn address(lines: &[&str]) -> Option<QrAddress> {
+    let field = |i: usize| Some(lines[i].to_owned()).filter(|line| !line.is_empty());
+    Some(QrAddress {
+        structured: lines[0] != "K",
+        name: field(1)?,
+        street: field(2),
+        building_number: field(3),
+        postal_code: field(4),
+        town: field(5),
+        country: field(6).unwrap_or_default(),
+    })
+}
@@ -0,0 +1,149 @@
+//! Cross-reference reconstruction for damaged documents.
+//!
+//! When the xref table or `startxref` offset is broken (typically by a mail
+//! gateway rewriting line endings or truncating the file), the objects
+//! themselves are usually still intact. [`rebuild_xref`] scans the buffer for
+//! `N G obj` headers and appends a fresh xref section and trailer, producing a
+//! buffer lopdf can load normally.
+
+use std::collections::BTreeMap;
+
+/// Rebuild the cross-reference data of `data`.
+///
+/// Returns `None` when no `%PDF-` header, no object headers, or no catalog
+/// could be found. When an object number occurs more than once (incremental
+/// updates), the last definition wins.
+pub(crate) fn rebuild_xref(data: &[u8]) -> Option<Vec<u8>> {
+    let start = find(data, b"%PDF-", 0)?;
+    let body = &data[start..];
+
+    let objects = scan_objects(body);
+    if objects.is_empty() {
+        return None;
+    }
+    let root = trailer_root(body)
+        .filter(|id| objects.get(&id.0).map(|o| o.0) == Some(id.1))
+        .or_else(|| find_catalog(body, &objects))?;
+
+    let mut repaired = body.to_vec();
+    if !repaired.ends_with(b"\n") {
+        repaired.push(b'\n');
+    }
+    let xref_offset = repaired.len();
+
+    // One single-entry subsection per object keeps the table valid even when
+    // object numbers are sparse.
+    repaired.extend_from_slice(b"xref\n0 1\n0000000000 65535 f\r\n");
+    for (id, (generation, offset)) in &objects {
+        repaired
+            .extend_from_slice(format!("{id} 1\n{offset:010} {generation:05} n\r\n").as_bytes());
+    }
+    let size = objects.keys().next_back().map_or(1, |max| max + 1);
+    repaired.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {size} /Root {} {} R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            root.0, root.1
+        )
+        .as_bytes(),
+    );
+    Some(repaired)
+}
+
+/// Map every object number to the generation and byte offset of its last
+/// `N G obj` header.
+fn scan_objects(body: &[u8]) -> BTreeMap<u32, (u16, usize)> {
+    let mut objects = BTreeMap::new();
+    let mut pos = 0;
+    while let Some(found) = find(body, b"obj", pos) {
+        pos = found + 3;
+        if let Some((id, generation, offset)) = object_header_before(body, found) {
+            objects.insert(id, (generation, offset));
+        }
+    }
+    objects
+}
+
+/// Parse `N G` immediately preceding the `obj` keyword at `keyword`.
+///
+/// The header must start a line (or the buffer) so that references inside
+/// content and `endobj` keywords are not mistaken for definitions.
+fn object_header_before(body: &[u8], keyword: usize) -> Option<(u32, u16, usize)> {
+    let follows = body.get(keyword + 3).copied();
+    if follows.is_some_and(|b| !is_delimiter(b)) {
+        return None;
+    }
+
+    let mut pos = keyword;
+    let generation = read_number_backwards(body, &mut pos)?;
+    let id = read_number_backwards(body, &mut pos)?;
+    let at_line_start = pos == 0 || matches!(body[pos - 1], b'\n' | b'\r');
+    if !at_line_start {
+        return None;
+    }
+    Some((id.parse().ok()?, generation.parse().ok()?, pos))
+}
+
+/// Skip whitespace backwards from `pos`, then read the digits before it.
+/// On success `pos` is left at the first digit.
+fn read_number_backwards<'a>(body: &'a [u8], pos: &mut usize) -> Option<&'a str> {
+    let mut end = *pos;
+    while end > 0 && matches!(body[end - 1], b' ' | b'\t' | b'\r' | b'\n') {
+        end -= 1;
+    }
+    if end == *pos {
+        return None; // no separating whitespace
+    }
+    let mut begin = end;
+    while begin > 0 && body[begin - 1].is_ascii_digit() {
+        begin -= 1;
+    }
+    if begin == end {
+        return None;
+    }
+    *pos = begin;
+    std::str::from_utf8(&body[begin..end]).ok()
+}
+
+/// The `/Root N G R` reference of the last trailer dictionary, if any.
+fn trailer_root(body: &[u8]) -> Option<(u32, u16)> {
+    let trailer = rfind(body, b"trailer")?;
+    let root = find(body, b"/Root", trailer)? + b"/Root".len();
+    let mut words = std::str::from_utf8(&body[root..(root + 32).min(body.len())])
+        .ok()?
+        .split_ascii_whitespace();
+    let id = words.next()?.parse().ok()?;
+    let generation = words.next()?.parse().ok()?;
+    Some((id, generation))
+}
+
+/// The last object whose body declares `/Type /Catalog`.
+fn find_catalog(body: &[u8], objects: &BTreeMap<u32, (u16, usize)>) -> Option<(u32, u16)> {
+    objects
+        .iter()
+        .rev()
+        .find(|(_, &(_, offset))| {
+            let end = find(body, b"endobj", offset).unwrap_or(body.len());
+            let object = &body[offset..end];
+            find(object, b"/Type/Catalog", 0).is_some()
+                || find(object, b"/Type /Catalog", 0).is_some()
+        })
+        .map(|(&id, &(generation, _))| (id, generation))
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || matches!(byte, b'<' | b'[' | b'(' | b'/' | b'%')
+}
+
+/// Position of the first occurrence of `needle` at or after `from`.
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| pos + from)
+}
+
+/// Position of the last occurrence of `needle`.
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
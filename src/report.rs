@@ -0,0 +1,245 @@
+use crate::{
+    ConformanceWarning, EmbeddedFile, ExtractError, ExtractionStatistics, PdfAnalyzer, Result,
+    SecurityFinding, StructureFinding,
+};
+
+// ── ExtractionReport ──────────────────────────────────────────────────────────
+
+/// The files extracted from a document together with everything that went
+/// wrong on the way.
+///
+/// Returned by [`PdfAnalyzer::extract_with_report`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractionReport {
+    /// Every embedded file that could be extracted, in discovery order.
+    pub files: Vec<EmbeddedFile>,
+
+    /// Problems with individual attachments, in discovery order.
+    pub warnings: Vec<ExtractionWarning>,
+
+    /// Counts and sizes over [`files`](Self::files).
+    pub statistics: ExtractionStatistics,
+
+    /// SHA-256 hex digest of the source document, when it was hashed; see
+    /// [`PdfAnalyzer::source_sha256`].
+    pub source_sha256: Option<String>,
+}
+
+/// A problem with one attachment that did not stop the extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractionWarning {
+    /// The filename, or the name-tree key when the file was skipped.
+    pub name: String,
+
+    /// What kind of problem occurred.
+    pub kind: WarningKind,
+
+    /// A human-readable description.
+    pub message: String,
+}
+
+/// The category of an [`ExtractionWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum WarningKind {
+    /// The attachment could not be extracted and is missing from the files.
+    Skipped,
+    /// Some stream filters could not be applied; the data is still encoded.
+    Undecoded,
+    /// The stream is damaged; the data holds only its decodable prefix.
+    Truncated,
+    /// The data does not match the declared `/Params/CheckSum`.
+    ChecksumMismatch,
+    /// The decoded length does not match the declared `/Params/Size`.
+    SizeMismatch,
+    /// An earlier attachment has the same filename but different content,
+    /// a pattern used to pass off one file as another. When files are
+    /// written to disk, this attachment was not written; that is also the
+    /// case when a provenance sidecar already has its name.
+    DuplicateName,
+    /// The file is an executable, script, archive or macro document; see
+    /// [`RiskPolicy::Flag`](crate::RiskPolicy::Flag).
+    Dangerous,
+}
+
+impl ExtractionWarning {
+    /// A warning for an attachment that was skipped because of `error`.
+    pub(crate) fn skipped(name: &str, error: &ExtractError) -> Self {
+        Self {
+            name: name.into(),
+            kind: WarningKind::Skipped,
+            message: error.to_string(),
+        }
+    }
+
+    /// A warning for `file`, whose name an earlier file with different
+    /// content already has. With `written`, files were written to disk and
+    /// only the earlier one was kept.
+    pub(crate) fn duplicate(file: &EmbeddedFile, written: bool) -> Self {
+        let mut message =
+            "an earlier attachment has the same filename but different content".to_string();
+        if written {
+            message.push_str("; only one of them was written to disk");
+        }
+        Self {
+            name: file.filename.clone(),
+            kind: WarningKind::DuplicateName,
+            message,
+        }
+    }
+
+    /// A warning for `file`, which was not written to disk because a
+    /// provenance sidecar already has its name.
+    #[cfg(feature = "fs")]
+    pub(crate) fn named_like_sidecar(file: &EmbeddedFile) -> Self {
+        Self {
+            name: file.filename.clone(),
+            kind: WarningKind::DuplicateName,
+            message:
+                "a provenance sidecar of that name was already written; the attachment was not"
+                    .to_string(),
+        }
+    }
+
+    /// Warnings about the content of an extracted file.
+    pub(crate) fn for_file(file: &EmbeddedFile) -> Vec<Self> {
+        let mut warnings = Vec::new();
+        let mut warn = |kind, message: String| {
+            warnings.push(Self {
+                name: file.filename.clone(),
+                kind,
+                message,
+            })
+        };
+
+        if !file.metadata.undecoded_filters.is_empty() {
+            warn(
+                WarningKind::Undecoded,
+                format!(
+                    "cannot apply filters {}; returned the encoded stream data",
+                    file.metadata.undecoded_filters.join(", ")
+                ),
+            );
+        }
+        if file.metadata.truncated {
+            warn(
+                WarningKind::Truncated,
+                format!(
+                    "stream is damaged; recovered the first {} bytes",
+                    file.data.len()
+                ),
+            );
+        }
+        if file.verify_checksum() == Some(false) {
+            warn(
+                WarningKind::ChecksumMismatch,
+                format!(
+                    "MD5 {} does not match the declared checksum",
+                    file.md5_hex()
+                ),
+            );
+        }
+        if let Some(mismatch) = file.metadata.size_mismatch {
+            warn(
+                WarningKind::SizeMismatch,
+                format!(
+                    "decoded to {} bytes but /Params/Size declares {}",
+                    mismatch.actual, mismatch.declared
+                ),
+            );
+        }
+        if let Some(category) = file.metadata.risk {
+            warn(
+                WarningKind::Dangerous,
+                format!("file is classified as {category}"),
+            );
+        }
+        warnings
+    }
+}
+
+// ── AnalysisReport ────────────────────────────────────────────────────────────
+
+/// The combined result of validating a document and extracting its embedded
+/// files.
+///
+/// Returned by [`PdfAnalyzer::analyze`] and produced per document by
+/// [`crate::PdfBatch`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AnalysisReport {
+    /// Whether the document passed the structural PDF checks.
+    pub is_pdf: bool,
+
+    /// Whether the XMP metadata declares PDF/A-3 conformance.
+    pub is_pdfa3: bool,
+
+    /// The declared conformance level (e.g. `"PDF/A-3B"`), if any.
+    pub conformance_level: Option<String>,
+
+    /// The document's natural language from the catalog's `/Lang`; see
+    /// [`PdfAnalyzer::language`].
+    pub language: Option<String>,
+
+    /// Every embedded file that could be extracted.
+    pub files: Vec<EmbeddedFile>,
+
+    /// Problems with individual attachments; see [`ExtractionReport`].
+    pub warnings: Vec<ExtractionWarning>,
+
+    /// Violations of the PDF/A file-structure rules; see
+    /// [`PdfAnalyzer::structure_findings`].
+    pub structure_findings: Vec<StructureFinding>,
+
+    /// Active content in the document itself; see
+    /// [`PdfAnalyzer::security_findings`].
+    pub security_findings: Vec<SecurityFinding>,
+
+    /// Missing e-invoicing declarations; see
+    /// [`PdfAnalyzer::conformance_warnings`].
+    pub conformance_warnings: Vec<ConformanceWarning>,
+
+    /// Counts and sizes over [`files`](Self::files).
+    pub statistics: ExtractionStatistics,
+}
+
+impl PdfAnalyzer {
+    /// Run validation and extraction and collect the results in one call.
+    ///
+    /// Unlike the individual query methods, "not PDF/A-3" and "no embedded
+    /// files" are reported as values rather than errors. Errors that stop the
+    /// whole extraction, such as [`ExtractError::Timeout`], are still
+    /// returned.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let report = PdfAnalyzer::from_path("invoice.pdf").unwrap().analyze().unwrap();
+    /// println!("{:?}: {} files", report.conformance_level, report.files.len());
+    /// for warning in &report.warnings {
+    ///     println!("{}: {}", warning.name, warning.message);
+    /// }
+    /// ```
+    pub fn analyze(&self) -> Result<AnalysisReport> {
+        let extraction = match self.extract_with_report() {
+            Ok(extraction) => extraction,
+            Err(ExtractError::NoEmbeddedFiles) => ExtractionReport::default(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(AnalysisReport {
+            is_pdf: self.is_pdf().unwrap_or(false),
+            is_pdfa3: self.xmp_info().map(|x| x.declares_pdfa3).unwrap_or(false),
+            conformance_level: self.conformance_level(),
+            language: self.language(),
+            files: extraction.files,
+            warnings: extraction.warnings,
+            structure_findings: self.structure_findings().to_vec(),
+            security_findings: self.security_findings(),
+            conformance_warnings: self.conformance_warnings(),
+            statistics: extraction.statistics,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use crate::analyzer::load_document;
+use crate::deadline::Deadline;
+use crate::memory::MemoryBudget;
+use crate::validator::PdfValidator;
+use crate::{ExtractError, ObjectRef, PdfAConformance, PdfAnalyzer, Result};
+
+/// The most `%%EOF` markers, counted from the end of the file, that
+/// [`PdfAnalyzer::metadata_revisions`] loads a revision for.
+const MAX_REVISIONS: usize = 64;
+
+// ── MetadataRevision ──────────────────────────────────────────────────────────
+
+/// The document-level XMP declaration of one revision of a PDF.
+///
+/// Every incremental update appends a revision that may replace the catalog's
+/// `/Metadata` stream. An analyzer always reads the newest one; returned by
+/// [`PdfAnalyzer::metadata_revisions`] to show what earlier revisions
+/// declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MetadataRevision {
+    /// The 0-based number of the `%%EOF` marker that ends this revision;
+    /// `0` is the original document. A prefix that does not load keeps its
+    /// number, so the numbers can have gaps.
+    pub index: usize,
+
+    /// Length in bytes of the file up to and including this revision's
+    /// `%%EOF` marker.
+    pub end_offset: usize,
+
+    /// The catalog's `/Metadata` stream in this revision, if any.
+    pub metadata: Option<ObjectRef>,
+
+    /// The PDF/A conformance this revision's metadata declares, if any.
+    pub conformance: Option<PdfAConformance>,
+}
+
+impl PdfAnalyzer {
+    /// The PDF/A declaration of every revision of `data`, the PDF this
+    /// analyzer was loaded from, oldest first; the last entry is what
+    /// [`conformance`](Self::conformance) reports for the whole document.
+    ///
+    /// Each revision ends with a `%%EOF` marker and is loaded on its own, so
+    /// this parses the document once per revision. Only the last 64 markers
+    /// are considered, and every load is checked
+    /// against the analyzer's
+    /// [`max_duration`](crate::ExtractorConfig::max_duration) and
+    /// [`max_memory_bytes`](crate::ExtractorConfig::max_memory_bytes). A
+    /// prefix that does not load, such as the first-page section of a
+    /// linearized file, is skipped without renumbering the revisions after
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// [`ExtractError::Timeout`](crate::ExtractError::Timeout) or
+    /// [`ExtractError::MemoryLimit`](crate::ExtractError::MemoryLimit) when a
+    /// limit is reached.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let bytes = std::fs::read("invoice.pdf").unwrap();
+    /// let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    /// for revision in analyzer.metadata_revisions(&bytes).unwrap() {
+    ///     match revision.conformance {
+    ///         Some(c) => println!("revision {}: {c}", revision.index),
+    ///         None => println!("revision {}: no PDF/A declaration", revision.index),
+    ///     }
+    /// }
+    /// ```
+    pub fn metadata_revisions(&self, data: &[u8]) -> Result<Vec<MetadataRevision>> {
+        let deadline = Deadline::start(self.config().max_duration);
+        let memory = MemoryBudget::start(self.config().max_memory_bytes, 0);
+        let ends: Vec<usize> = revision_ends(data).collect();
+        let skip = ends.len().saturating_sub(MAX_REVISIONS);
+
+        let mut revisions = Vec::new();
+        for (index, &end_offset) in ends.iter().enumerate().skip(skip) {
+            deadline.check()?;
+            memory.check(end_offset)?;
+            let document = match load_document(&data[..end_offset], &deadline) {
+                Ok(document) => document,
+                Err(e @ ExtractError::Timeout(_)) => return Err(e),
+                Err(_) => continue,
+            };
+            let Ok(catalog) = document.catalog() else {
+                continue;
+            };
+            let metadata = catalog
+                .get(b"Metadata")
+                .and_then(|m| m.as_reference())
+                .ok()
+                .map(ObjectRef::from);
+            let conformance = PdfValidator::new(&document)
+                .read_xmp()
+                .ok()
+                .and_then(|xmp| xmp.conformance);
+            revisions.push(MetadataRevision {
+                index,
+                end_offset,
+                metadata,
+                conformance,
+            });
+        }
+        Ok(revisions)
+    }
+}
+
+/// The end of every `%%EOF` marker in `data`, including the line break that
+/// follows it.
+fn revision_ends(data: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    const EOF: &[u8] = b"%%EOF";
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let found = data[pos..].windows(EOF.len()).position(|w| w == EOF)?;
+        let mut end = pos + found + EOF.len();
+        end += match &data[end..] {
+            [b'\r', b'\n', ..] => 2,
+            [b'\r' | b'\n', ..] => 1,
+            _ => 0,
+        };
+        pos = end;
+        Some(end)
+    })
+}
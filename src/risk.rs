@@ -0,0 +1,495 @@
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+use crate::pdf_utils::contains;
+use crate::probe;
+use crate::zip;
+use crate::{AttachmentSource, EmbeddedFile, ExtractError, FindingKind, PdfAnalyzer, Result};
+use lopdf::Object;
+
+/// Magic bytes of an OLE compound file: legacy Office documents and
+/// encrypted Office Open XML packages.
+pub(crate) const OLE: &[u8] = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+
+/// File extensions of scripts that Windows or a shell runs when opened.
+/// Scripts have no magic bytes beyond an optional `#!` line.
+const SCRIPT_EXTENSIONS: [&str; 12] = [
+    "bat", "cmd", "hta", "js", "jse", "ps1", "psm1", "sh", "vbe", "vbs", "wsf", "wsh",
+];
+
+// ── RiskCategory ──────────────────────────────────────────────────────────────
+
+/// A kind of attachment that is commonly used to deliver malware, returned by
+/// [`EmbeddedFile::risk_category`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
+pub enum RiskCategory {
+    /// Native or bytecode executable: PE (`MZ`), ELF, Mach-O, Java class or
+    /// Dalvik files.
+    Executable,
+    /// A script: a `#!` line, or a script extension such as `.js`, `.vbs`,
+    /// `.ps1` or `.bat`.
+    Script,
+    /// An archive that may hide further files: ZIP (other than Office and
+    /// OpenDocument files), RAR, 7-Zip, gzip, bzip2, xz, tar or CAB.
+    Archive,
+    /// An Office or OpenDocument file that carries a macro project.
+    MacroDocument,
+}
+
+impl RiskCategory {
+    /// A short lowercase name, e.g. `"macro-document"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RiskCategory::Executable => "executable",
+            RiskCategory::Script => "script",
+            RiskCategory::Archive => "archive",
+            RiskCategory::MacroDocument => "macro-document",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What to do with attachments that fall into a [`RiskCategory`], set through
+/// [`ExtractorConfig::risk_policy`](crate::ExtractorConfig::risk_policy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    any(feature = "fuzzing", feature = "test-util"),
+    derive(arbitrary::Arbitrary)
+)]
+pub enum RiskPolicy {
+    /// Extract them like any other file, without classifying.
+    #[default]
+    Allow,
+    /// Extract them, record the category in
+    /// [`EmbeddedFileMetadata::risk`](crate::EmbeddedFileMetadata::risk) and
+    /// report a [`WarningKind::Dangerous`](crate::WarningKind::Dangerous)
+    /// warning.
+    Flag,
+    /// Withhold them with [`ExtractError::DangerousFile`](crate::ExtractError::DangerousFile).
+    Block,
+}
+
+impl EmbeddedFile {
+    /// Classifies the decoded data by its magic bytes into a
+    /// [`RiskCategory`], or `None` for ordinary documents and data.
+    ///
+    /// Office files are recognised as ZIP containers with a
+    /// `[Content_Types].xml` part or as OLE compound files, and count as
+    /// [`RiskCategory::MacroDocument`] only when they contain a VBA project or
+    /// OpenDocument Basic macros. The checks are heuristics for triage, not a
+    /// substitute for a [`ContentScanner`](crate::ContentScanner).
+    ///
+    /// ```
+    /// # use extractembedfilepdf::{EmbeddedFile, RiskCategory};
+    /// let file = EmbeddedFile {
+    ///     filename: "setup.pdf".into(),
+    ///     data: b"MZ\x90\x00".to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// assert_eq!(file.risk_category(), Some(RiskCategory::Executable));
+    /// ```
+    pub fn risk_category(&self) -> Option<RiskCategory> {
+        classify(&self.filename, &self.data)
+    }
+}
+
+/// The [`RiskCategory`] of `data`, named `filename`.
+pub(crate) fn classify(filename: &str, data: &[u8]) -> Option<RiskCategory> {
+    const EXECUTABLE: [&[u8]; 8] = [
+        b"MZ",
+        b"\x7fELF",
+        b"\xfe\xed\xfa\xce",
+        b"\xce\xfa\xed\xfe",
+        b"\xfe\xed\xfa\xcf",
+        b"\xcf\xfa\xed\xfe",
+        b"\xca\xfe\xba\xbe",
+        b"dex\n",
+    ];
+    const ARCHIVE: [&[u8]; 7] = [
+        b"Rar!\x1a\x07",
+        b"7z\xbc\xaf\x27\x1c",
+        b"\x1f\x8b",
+        b"BZh",
+        b"\xfd7zXZ\x00",
+        b"MSCF",
+        b"PK\x05\x06",
+    ];
+    if EXECUTABLE.iter().any(|magic| data.starts_with(magic)) {
+        return Some(RiskCategory::Executable);
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        return classify_zip(data);
+    }
+    if data.starts_with(OLE) {
+        // Directory entry names are UTF-16LE.
+        let vba: Vec<u8> = "_VBA_PROJECT".bytes().flat_map(|b| [b, 0]).collect();
+        return contains(data, &vba).then_some(RiskCategory::MacroDocument);
+    }
+    if ARCHIVE.iter().any(|magic| data.starts_with(magic))
+        || data.get(257..262) == Some(b"ustar".as_slice())
+    {
+        return Some(RiskCategory::Archive);
+    }
+    if data.starts_with(b"#!") || has_script_extension(filename) {
+        return Some(RiskCategory::Script);
+    }
+    None
+}
+
+/// Tell Office and OpenDocument files apart from plain ZIP archives. A
+/// document without macros carries no risk.
+fn classify_zip(data: &[u8]) -> Option<RiskCategory> {
+    if !is_office_zip(data) {
+        return Some(RiskCategory::Archive);
+    }
+    let is_odf = data.get(30..38) == Some(b"mimetype".as_slice());
+    let has_macros = contains(data, b"vbaProject.bin") || (is_odf && contains(data, b"Basic/"));
+    has_macros.then_some(RiskCategory::MacroDocument)
+}
+
+/// Whether the ZIP archive `data` is an Office Open XML or OpenDocument file,
+/// judged by the part names stored in its local file headers.
+pub(crate) fn is_office_zip(data: &[u8]) -> bool {
+    contains(data, b"[Content_Types].xml") || data.get(30..38) == Some(b"mimetype".as_slice())
+}
+
+/// Whether `data` is an encrypted PDF, a ZIP archive with encrypted entries,
+/// or an OLE container holding an encrypted Office Open XML package.
+///
+/// A PDF counts only when its trailer has an `/Encrypt` entry, and a ZIP
+/// archive when any entry of its central directory is flagged as encrypted.
+/// Legacy binary Office formats flag encryption deep inside their streams
+/// and are not recognised.
+pub(crate) fn is_encrypted_container(data: &[u8]) -> bool {
+    if probe::has_pdf_header(data) {
+        return pdf_trailer(data).is_some_and(|trailer| has_key(trailer, b"Encrypt"));
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        return zip::has_encrypted_entry(data);
+    }
+    if data.starts_with(OLE) {
+        let name: Vec<u8> = "EncryptedPackage".bytes().flat_map(|b| [b, 0]).collect();
+        return contains(data, &name);
+    }
+    false
+}
+
+/// The trailer dictionary of the PDF `data`: the one of the cross-reference
+/// section or stream that the last `startxref` points at, or else the one
+/// after the last `trailer` keyword.
+fn pdf_trailer(data: &[u8]) -> Option<&[u8]> {
+    const STARTXREF: &[u8] = b"startxref";
+    let pointed_at = rfind(data, STARTXREF).and_then(|at| {
+        let rest = &data[at + STARTXREF.len()..];
+        let digits = &rest[rest.iter().take_while(|b| b.is_ascii_whitespace()).count()..];
+        let len = digits.iter().take_while(|b| b.is_ascii_digit()).count();
+        let offset: usize = std::str::from_utf8(&digits[..len]).ok()?.parse().ok()?;
+        let section = data.get(offset..)?;
+        if section.starts_with(b"xref") {
+            dictionary_after(section, find(section, b"trailer")?)
+        } else {
+            // A cross-reference stream: its dictionary is the trailer.
+            dictionary_after(section, find(section, b"obj")?)
+        }
+    });
+    pointed_at.or_else(|| dictionary_after(data, rfind(data, b"trailer")?))
+}
+
+/// The dictionary `<< … >>` that starts after `from` in `data`, including
+/// its delimiters.
+fn dictionary_after(data: &[u8], from: usize) -> Option<&[u8]> {
+    let start = from + find(&data[from..], b"<<")?;
+    let mut depth = 0usize;
+    let mut pos = start;
+    while pos < data.len() {
+        match data[pos] {
+            b'<' if data.get(pos + 1) == Some(&b'<') => {
+                depth += 1;
+                pos += 2;
+                continue;
+            }
+            b'>' if data.get(pos + 1) == Some(&b'>') => {
+                depth -= 1;
+                pos += 2;
+                if depth == 0 {
+                    return Some(&data[start..pos]);
+                }
+                continue;
+            }
+            // Hex and literal strings may hold any bytes.
+            b'<' => pos += find(&data[pos..], b">")?,
+            b'(' => pos = skip_literal_string(data, pos)?,
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// The position of the `)` that closes the literal string opened at `open`.
+fn skip_literal_string(data: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut pos = open;
+    while pos < data.len() {
+        match data[pos] {
+            b'\\' => pos += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Whether the dictionary `dict`, as returned by [`dictionary_after`], has
+/// the key `/key` at its top level.
+fn has_key(dict: &[u8], key: &[u8]) -> bool {
+    let mut depth = 0usize;
+    let mut pos = 0;
+    while pos < dict.len() {
+        match dict[pos] {
+            b'<' if dict.get(pos + 1) == Some(&b'<') => {
+                depth += 1;
+                pos += 1;
+            }
+            b'>' if dict.get(pos + 1) == Some(&b'>') => {
+                depth = depth.saturating_sub(1);
+                pos += 1;
+            }
+            b'<' => match find(&dict[pos..], b">") {
+                Some(len) => pos += len,
+                None => return false,
+            },
+            b'(' => match skip_literal_string(dict, pos) {
+                Some(close) => pos = close,
+                None => return false,
+            },
+            b'/' if depth == 1 => {
+                let name = &dict[pos + 1..];
+                let len = name
+                    .iter()
+                    .take_while(|&&b| !b.is_ascii_whitespace() && !b"/<>[]()%".contains(&b))
+                    .count();
+                if &name[..len] == key {
+                    return true;
+                }
+                pos += len;
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    false
+}
+
+/// Position of the first occurrence of `needle`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Position of the last occurrence of `needle`.
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+fn has_script_extension(filename: &str) -> bool {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            SCRIPT_EXTENSIONS
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(ext))
+        })
+}
+
+// ── RiskReport ────────────────────────────────────────────────────────────────
+
+/// Everything about a document that suggests it may be malicious, condensed
+/// into one number. Returned by [`PdfAnalyzer::risk_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RiskReport {
+    /// The sum of the [`RiskSignal::weight`]s, capped at
+    /// [`MAX_SCORE`](Self::MAX_SCORE). `0` means nothing suspicious was
+    /// found.
+    pub score: u32,
+
+    /// Every signal that contributed to the score, at most
+    /// [`MAX_SIGNALS`](Self::MAX_SIGNALS) of them.
+    pub signals: Vec<RiskSignal>,
+}
+
+impl RiskReport {
+    /// The highest possible score.
+    pub const MAX_SCORE: u32 = 100;
+
+    /// The most signals a report lists. A document with more is scored on
+    /// the first ones only, which already reach [`MAX_SCORE`](Self::MAX_SCORE).
+    pub const MAX_SIGNALS: usize = 100;
+
+    fn new(mut signals: Vec<RiskSignal>) -> Self {
+        signals.truncate(Self::MAX_SIGNALS);
+        let score = signals.iter().map(RiskSignal::weight).sum::<u32>();
+        Self {
+            score: score.min(Self::MAX_SCORE),
+            signals,
+        }
+    }
+}
+
+/// One observation that contributes to a [`RiskReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RiskSignal {
+    /// Active content in the document itself; see
+    /// [`PdfAnalyzer::security_findings`].
+    ActiveContent(FindingKind),
+    /// Something unusual about the document's encryption, e.g. that it could
+    /// not be decrypted or that a PDF/A file is encrypted at all.
+    EncryptionAnomaly(String),
+    /// An attachment that only a deep scan finds: no name tree, annotation
+    /// or `/AF` array refers to it.
+    HiddenAttachment(String),
+    /// An attachment that is itself encrypted, so its content cannot be
+    /// inspected; see
+    /// [`EmbeddedFileMetadata::encrypted_container`](crate::EmbeddedFileMetadata::encrypted_container).
+    EncryptedAttachment(String),
+    /// An attachment that is an executable, script, archive or macro
+    /// document.
+    DangerousAttachment {
+        /// The attachment's filename.
+        name: String,
+        /// What kind of dangerous file it is.
+        category: RiskCategory,
+    },
+}
+
+impl RiskSignal {
+    /// How much this signal adds to [`RiskReport::score`].
+    pub fn weight(&self) -> u32 {
+        match self {
+            RiskSignal::ActiveContent(FindingKind::Launch) => 40,
+            RiskSignal::ActiveContent(FindingKind::JavaScript) => 30,
+            RiskSignal::ActiveContent(FindingKind::OpenAction) => 20,
+            RiskSignal::ActiveContent(FindingKind::SubmitForm | FindingKind::ImportData) => 15,
+            RiskSignal::EncryptionAnomaly(_) => 25,
+            RiskSignal::HiddenAttachment(_) => 20,
+            RiskSignal::EncryptedAttachment(_) => 25,
+            RiskSignal::DangerousAttachment { category, .. } => match category {
+                RiskCategory::Executable => 50,
+                RiskCategory::Script | RiskCategory::MacroDocument => 40,
+                RiskCategory::Archive => 15,
+            },
+        }
+    }
+}
+
+impl PdfAnalyzer {
+    /// Combines [`security_findings`](Self::security_findings), encryption
+    /// anomalies, hidden and encrypted attachments and the [`RiskCategory`]
+    /// of every attachment into a single weighted score, so a gateway can reject
+    /// documents above a threshold.
+    ///
+    /// Attachments are searched with the configured
+    /// [`ExtractorConfig::discovery_sources`](crate::ExtractorConfig::discovery_sources)
+    /// and decoded without being scanned, audited or written to disk. Hidden
+    /// attachments are only reported when those include
+    /// [`AttachmentSource::DeepScan`]. The search stops once the report holds
+    /// [`RiskReport::MAX_SIGNALS`] signals.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("upload.pdf")?;
+    /// if analyzer.risk_report()?.score >= 50 {
+    ///     println!("rejected");
+    /// }
+    /// # Ok::<(), extractembedfilepdf::ExtractError>(())
+    /// ```
+    pub fn risk_report(&self) -> Result<RiskReport> {
+        let mut signals: Vec<_> = self
+            .security_findings()
+            .into_iter()
+            .map(|finding| RiskSignal::ActiveContent(finding.kind))
+            .collect();
+        signals.extend(
+            self.encryption_anomalies()
+                .into_iter()
+                .map(RiskSignal::EncryptionAnomaly),
+        );
+
+        let deadline = Deadline::start(self.config().max_duration);
+        let specs = self.file_specs(deadline)?;
+        let memory = self.memory_budget();
+        let engine = ExtractionEngine::new(self.document(), self.config(), specs)
+            .with_deadline(deadline)
+            .with_memory(memory.clone());
+        for spec in specs {
+            if signals.len() >= RiskReport::MAX_SIGNALS {
+                break;
+            }
+            if spec.source == AttachmentSource::DeepScan {
+                signals.push(RiskSignal::HiddenAttachment(spec.name.clone()));
+            }
+            let file = match engine.inspect_file(spec) {
+                Ok(file) => file,
+                Err(e @ (ExtractError::Timeout(_) | ExtractError::MemoryLimit(_))) => {
+                    return Err(e)
+                }
+                Err(_) => continue,
+            };
+            memory.release(file.data.len());
+            if is_encrypted_container(&file.data) {
+                signals.push(RiskSignal::EncryptedAttachment(file.filename.clone()));
+            }
+            if let Some(category) = file.risk_category() {
+                signals.push(RiskSignal::DangerousAttachment {
+                    name: file.filename,
+                    category,
+                });
+            }
+        }
+
+        Ok(RiskReport::new(signals))
+    }
+
+    /// Descriptions of unusual encryption settings.
+    fn encryption_anomalies(&self) -> Vec<String> {
+        let document = self.document();
+        let Ok(encrypt) = document.trailer.get(b"Encrypt") else {
+            return Vec::new();
+        };
+
+        let mut anomalies = Vec::new();
+        if !document.was_encrypted() {
+            anomalies.push("document is encrypted and could not be decrypted".to_string());
+        }
+        if self.xmp_info().is_ok_and(|xmp| xmp.declares_pdfa3) {
+            anomalies.push("PDF/A forbids encryption".to_string());
+        }
+        let encrypt = match encrypt {
+            Object::Reference(id) => document.get_dictionary(*id).ok(),
+            encrypt => encrypt.as_dict().ok(),
+        };
+        if encrypt.is_some_and(|dict| dict.has(b"EFF")) {
+            anomalies.push("embedded files use a separate crypt filter".to_string());
+        }
+        anomalies
+    }
+}
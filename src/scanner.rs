@@ -0,0 +1,85 @@
+use std::fmt;
+
+// ── ContentScanner ────────────────────────────────────────────────────────────
+
+/// Inspects every decoded attachment before it is returned or written to
+/// disk, e.g. by handing it to a virus scanner such as clamd.
+///
+/// Install a scanner through [`ExtractorConfig::scanner`]; what happens to a
+/// positive result is decided by [`ExtractorConfig::scan_policy`]. An `Err`
+/// from [`scan`](Self::scan) rejects the attachment as well, so an unreachable
+/// scanner never lets a file through unchecked.
+///
+/// # Example
+///
+/// ```
+/// use extractembedfilepdf::{ContentScanner, ExtractorConfig, ScanVerdict};
+/// use std::sync::Arc;
+///
+/// /// Flags the EICAR test file.
+/// struct Eicar;
+///
+/// impl ContentScanner for Eicar {
+///     fn scan(&self, _name: &str, data: &[u8]) -> std::io::Result<ScanVerdict> {
+///         let signature: &[u8] = b"EICAR-STANDARD-ANTIVIRUS-TEST-FILE";
+///         Ok(if data.windows(signature.len()).any(|w| w == signature) {
+///             ScanVerdict::Infected("Eicar-Test-Signature".into())
+///         } else {
+///             ScanVerdict::Clean
+///         })
+///     }
+/// }
+///
+/// let config = ExtractorConfig {
+///     scanner: Some(Arc::new(Eicar)),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ExtractorConfig::scanner`]: crate::ExtractorConfig::scanner
+/// [`ExtractorConfig::scan_policy`]: crate::ExtractorConfig::scan_policy
+pub trait ContentScanner: Send + Sync {
+    /// Scan the decoded content `data` of the attachment `name`.
+    fn scan(&self, name: &str, data: &[u8]) -> std::io::Result<ScanVerdict>;
+}
+
+impl fmt::Debug for dyn ContentScanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ContentScanner")
+    }
+}
+
+/// The result of a [`ContentScanner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    /// Nothing was found.
+    Clean,
+    /// The scanner flagged the content; the string names the threat.
+    Infected(String),
+}
+
+/// What to do with an attachment that a [`ContentScanner`] flagged.
+///
+/// In every case the attachment is withheld: it is neither returned nor
+/// written to the output directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ScanPolicy {
+    /// Skip the attachment with an [`ExtractError::Infected`] warning, like
+    /// any other attachment that cannot be extracted.
+    ///
+    /// [`ExtractError::Infected`]: crate::ExtractError::Infected
+    #[default]
+    Skip,
+    /// Skip the attachment, but first write it to this directory for later
+    /// inspection, under the last path component of its name. A file that
+    /// is already there is never overwritten; the new one gets a numeric
+    /// suffix instead (`evil-1.bin`).
+    #[cfg(feature = "fs")]
+    Quarantine(std::path::PathBuf),
+    /// Abort the whole extraction with [`ExtractError::Infected`], even when
+    /// [`ExtractorConfig::fail_on_any_error`] is not set.
+    ///
+    /// [`ExtractError::Infected`]: crate::ExtractError::Infected
+    /// [`ExtractorConfig::fail_on_any_error`]: crate::ExtractorConfig::fail_on_any_error
+    Fail,
+}
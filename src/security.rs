@@ -0,0 +1,190 @@
+use crate::deadline::Deadline;
+use crate::memory::MemoryBudget;
+use crate::{filters, pdf_utils, ObjectRef, PdfAnalyzer};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+/// Longest script excerpt kept in [`SecurityFinding::detail`].
+const SCRIPT_EXCERPT: usize = 100;
+
+/// Largest decoded script stream an excerpt is taken from.
+const SCRIPT_DECODE_LIMIT: usize = 4 * 1024;
+
+// ── SecurityFinding ───────────────────────────────────────────────────────────
+
+/// Active content found in the carrier document by
+/// [`PdfAnalyzer::security_findings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SecurityFinding {
+    /// What was found.
+    pub kind: FindingKind,
+
+    /// The indirect object that contains the action, possibly nested inside
+    /// one of its entries.
+    pub object_id: ObjectRef,
+
+    /// The script excerpt, launched file or submission target, when known.
+    pub detail: Option<String>,
+}
+
+/// The category of a [`SecurityFinding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FindingKind {
+    /// A `/JavaScript` action.
+    JavaScript,
+    /// A `/Launch` action, which starts an application or opens a file.
+    Launch,
+    /// A `/SubmitForm` action, which sends form data to a URL.
+    SubmitForm,
+    /// An `/ImportData` action, which loads form data from a file.
+    ImportData,
+    /// The catalog's `/OpenAction` runs an action other than `/GoTo` when the
+    /// document is opened; [`detail`](SecurityFinding::detail) names its type.
+    OpenAction,
+}
+
+impl FindingKind {
+    /// The action type for `name`, the `/S` entry of an action dictionary.
+    fn from_action(name: &[u8]) -> Option<Self> {
+        match name {
+            b"JavaScript" => Some(FindingKind::JavaScript),
+            b"Launch" => Some(FindingKind::Launch),
+            b"SubmitForm" => Some(FindingKind::SubmitForm),
+            b"ImportData" => Some(FindingKind::ImportData),
+            _ => None,
+        }
+    }
+}
+
+impl PdfAnalyzer {
+    /// Returns the active content of the document itself: JavaScript,
+    /// launch, form submission and data import actions, and an `/OpenAction`
+    /// that executes on opening.
+    ///
+    /// Every object is scanned, including actions nested in annotations,
+    /// form fields, `/AA` dictionaries and the `/JavaScript` name tree, so
+    /// unreferenced actions are reported too. Attachments are not scanned.
+    ///
+    /// A script stream is decoded for its excerpt only up to 4 KiB and
+    /// within [`max_duration`](crate::ExtractorConfig::max_duration) and
+    /// [`max_memory_bytes`](crate::ExtractorConfig::max_memory_bytes); a
+    /// longer script is reported without [`detail`](SecurityFinding::detail).
+    pub fn security_findings(&self) -> Vec<SecurityFinding> {
+        let scan = Scan {
+            document: self.document(),
+            deadline: Deadline::start(self.config().max_duration),
+            memory: self.memory_budget(),
+        };
+        let mut findings = Vec::new();
+
+        if let Some(finding) = open_action(scan.document) {
+            findings.push(finding);
+        }
+        for (&id, object) in &scan.document.objects {
+            visit(&scan, id, object, &mut findings);
+        }
+        findings
+    }
+}
+
+/// The document being scanned, with the limits for decoding script streams.
+struct Scan<'a> {
+    document: &'a Document,
+    deadline: Deadline,
+    memory: MemoryBudget,
+}
+
+/// A finding for a catalog `/OpenAction` that does more than navigate.
+fn open_action(document: &Document) -> Option<SecurityFinding> {
+    let catalog_id = document.trailer.get(b"Root").ok()?.as_reference().ok()?;
+    let action = match document.catalog().ok()?.get(b"OpenAction").ok()? {
+        Object::Reference(id) => document.get_dictionary(*id).ok()?,
+        Object::Dictionary(dict) => dict,
+        _ => return None,
+    };
+    let action_type = action.get(b"S").and_then(|s| s.as_name()).ok()?;
+    (action_type != b"GoTo").then(|| SecurityFinding {
+        kind: FindingKind::OpenAction,
+        object_id: catalog_id.into(),
+        detail: Some(String::from_utf8_lossy(action_type).into_owned()),
+    })
+}
+
+/// Record every action dictionary in `object`, descending into direct
+/// dictionaries and arrays but not following references.
+fn visit(scan: &Scan, id: ObjectId, object: &Object, findings: &mut Vec<SecurityFinding>) {
+    let dict = match object {
+        Object::Dictionary(dict) => dict,
+        Object::Stream(stream) => &stream.dict,
+        Object::Array(items) => {
+            for item in items {
+                visit(scan, id, item, findings);
+            }
+            return;
+        }
+        _ => return,
+    };
+
+    let kind = dict
+        .get(b"S")
+        .and_then(|s| s.as_name())
+        .ok()
+        .and_then(FindingKind::from_action);
+    if let Some(kind) = kind {
+        findings.push(SecurityFinding {
+            kind,
+            object_id: id.into(),
+            detail: action_detail(scan, kind, dict),
+        });
+    }
+    for (_, value) in dict.iter() {
+        visit(scan, id, value, findings);
+    }
+}
+
+/// The script excerpt or target file of an action.
+fn action_detail(scan: &Scan, kind: FindingKind, action: &Dictionary) -> Option<String> {
+    let document = scan.document;
+    if kind == FindingKind::JavaScript {
+        let script = match action.get(b"JS").ok()? {
+            Object::Reference(id) => document.get_object(*id).ok()?,
+            script => script,
+        };
+        let text = match script {
+            Object::String(bytes, _) => String::from_utf8_lossy(bytes).into_owned(),
+            Object::Stream(stream) => {
+                let script = filters::decode(
+                    stream,
+                    Some(SCRIPT_DECODE_LIMIT),
+                    true,
+                    &scan.deadline,
+                    &scan.memory,
+                )
+                .ok()?;
+                scan.memory.release(script.data.len());
+                String::from_utf8_lossy(&script.data).into_owned()
+            }
+            _ => return None,
+        };
+        return Some(text.trim().chars().take(SCRIPT_EXCERPT).collect());
+    }
+
+    // A file specification: a plain string or a dictionary. `/Launch` keeps
+    // the Windows-specific target in a `/Win` dictionary.
+    let target = action
+        .get(b"F")
+        .or_else(|_| action.get(b"Win").and_then(|win| win.as_dict()?.get(b"F")))
+        .ok()?;
+    let target = match target {
+        Object::Reference(id) => document.get_object(*id).ok()?,
+        target => target,
+    };
+    match target {
+        Object::String(bytes, _) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        Object::Dictionary(spec) => pdf_utils::FILENAME_KEYS
+            .iter()
+            .find_map(|key| pdf_utils::extract_string_from_dict(spec, key)),
+        _ => None,
+    }
+}
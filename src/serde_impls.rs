@@ -0,0 +1,83 @@
+//! Hand-written `Serialize` impls for types whose derived form would not suit
+//! JSON consumers. Compiled only with the `serde` feature.
+
+use crate::{AfRelationship, AttachmentSource, EmbeddedFile, ErrorCode, ExtractError};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Serialized as filename, size, SHA-256 digest, and metadata. The raw bytes
+/// are omitted: a JSON array of numbers is rarely what a consumer wants, and
+/// the data is available on the value itself.
+impl Serialize for EmbeddedFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("EmbeddedFile", 4)?;
+        state.serialize_field("filename", &self.filename)?;
+        state.serialize_field("size", &self.data.len())?;
+        state.serialize_field("sha256", &self.sha256_hex())?;
+        state.serialize_field("metadata", &self.metadata)?;
+        state.end()
+    }
+}
+
+/// Serialized as the PDF name, e.g. `"Data"`.
+impl Serialize for AfRelationship {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Serialized as the CLI identifier, e.g. `"names-tree"`.
+impl Serialize for AttachmentSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Serialized as the stable code string, e.g. `"E_SIZE_LIMIT"`.
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Serialized as a report object `{ "kind": …, "code": …, "message": … }`,
+/// where `kind` is the variant name, `code` the stable [`ErrorCode`] string
+/// and `message` the `Display` output.
+impl Serialize for ExtractError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let kind = match self {
+            ExtractError::IoError(_) => "IoError",
+            ExtractError::InvalidPdf(_) => "InvalidPdf",
+            ExtractError::NotPdfA3(_) => "NotPdfA3",
+            ExtractError::ConformanceMismatch { .. } => "ConformanceMismatch",
+            ExtractError::NoEmbeddedFiles => "NoEmbeddedFiles",
+            ExtractError::ExtractionError { .. } => "ExtractionError",
+            ExtractError::ParseError(_) => "ParseError",
+            ExtractError::FileNotFound(_) => "FileNotFound",
+            ExtractError::FileSizeExceeded => "FileSizeExceeded",
+            ExtractError::ExternalReference(_) => "ExternalReference",
+            ExtractError::Encrypted(_) => "Encrypted",
+            ExtractError::Timeout(_) => "Timeout",
+            ExtractError::MemoryLimit(_) => "MemoryLimit",
+            ExtractError::Infected { .. } => "Infected",
+            ExtractError::DangerousFile { .. } => "DangerousFile",
+            ExtractError::Internal(_) => "Internal",
+        };
+        let mut state = serializer.serialize_struct("ExtractError", 3)?;
+        state.serialize_field("kind", kind)?;
+        state.serialize_field("code", self.code().as_str())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Serialized as `{ "manifest": …, "signature": … }` with the signature as
+/// lowercase hex.
+#[cfg(feature = "signing")]
+impl Serialize for crate::SignedManifest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SignedManifest", 2)?;
+        state.serialize_field("manifest", &self.manifest)?;
+        state.serialize_field("signature", &self.signature_hex())?;
+        state.end()
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{AttachmentSource, EmbeddedFile};
+use std::collections::BTreeMap;
+
+// ── ExtractionStatistics ──────────────────────────────────────────────────────
+
+/// Totals over the files of an extraction, available as
+/// [`ExtractionReport::statistics`](crate::ExtractionReport::statistics) and
+/// [`AnalysisReport::statistics`](crate::AnalysisReport::statistics).
+///
+/// Only files that were extracted are counted; skipped attachments appear in
+/// the report's warnings instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractionStatistics {
+    /// The number of extracted files.
+    pub file_count: usize,
+
+    /// The decoded size of all files together, in bytes.
+    pub total_bytes: usize,
+
+    /// The stored size of all embedded streams together, in bytes. Files
+    /// whose stored size is unknown count with their decoded size.
+    pub stored_bytes: usize,
+
+    /// The filename of the largest file; the first one on a tie.
+    pub largest_file: Option<String>,
+
+    /// The decoded size of [`largest_file`](Self::largest_file), in bytes.
+    pub largest_file_size: usize,
+
+    /// File count per declared MIME type, in lowercase. Files without a
+    /// declared type are counted under `"-"`.
+    pub by_mime_type: BTreeMap<String, usize>,
+
+    /// File count per discovery mechanism.
+    pub by_source: BTreeMap<AttachmentSource, usize>,
+}
+
+impl ExtractionStatistics {
+    /// Computes the statistics of `files`.
+    pub fn from_files(files: &[EmbeddedFile]) -> Self {
+        let mut stats = Self {
+            file_count: files.len(),
+            ..Self::default()
+        };
+        for file in files {
+            stats.total_bytes += file.len();
+            stats.stored_bytes += file.metadata.stored_size.unwrap_or(file.len());
+            if stats.largest_file.is_none() || file.len() > stats.largest_file_size {
+                stats.largest_file = Some(file.filename.clone());
+                stats.largest_file_size = file.len();
+            }
+            let mime = file
+                .metadata
+                .mime_type
+                .as_deref()
+                .map_or_else(|| "-".to_string(), str::to_ascii_lowercase);
+            *stats.by_mime_type.entry(mime).or_default() += 1;
+            if let Some(source) = file.metadata.source {
+                *stats.by_source.entry(source).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// [`total_bytes`](Self::total_bytes) divided by
+    /// [`stored_bytes`](Self::stored_bytes): how much the embedded streams
+    /// were compressed overall. `None` when nothing is stored.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.stored_bytes > 0).then(|| self.total_bytes as f64 / self.stored_bytes as f64)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+#[cfg(feature = "fs")]
+use crate::extraction_engine::WrittenFiles;
+use crate::file_discovery::FileSpec;
+use crate::memory::MemoryBudget;
+#[cfg(feature = "fs")]
+use crate::provenance::SourceInfo;
+use crate::{EmbeddedFile, ExtractError, ExtractionStage, ExtractorConfig, PdfAnalyzer, Result};
+use futures_core::Stream;
+use lopdf::Document;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::task::JoinHandle;
+
+// ── EmbeddedFileStream ────────────────────────────────────────────────────────
+
+/// An asynchronous stream of the embedded files of a document, returned by
+/// [`PdfAnalyzer::embedded_file_stream`].
+///
+/// Each file is decoded on tokio's blocking thread pool. Decoding of the next
+/// file starts only when the stream is polled again, so at most one decoded
+/// file is held in memory ahead of the consumer.
+///
+/// Unlike [`PdfAnalyzer::extract_embedded_files`], a file that fails to decode
+/// is yielded as an `Err` item rather than skipped; the stream continues with
+/// the next file.
+///
+/// Since the consumer sets the pace, [`ExtractorConfig::max_duration`] applies
+/// to each file separately rather than to the whole stream.
+///
+/// The stream must be polled from within a tokio runtime.
+pub struct EmbeddedFileStream {
+    document: Arc<Document>,
+    config: Arc<ExtractorConfig>,
+    memory: MemoryBudget,
+    #[cfg(feature = "fs")]
+    source: Arc<SourceInfo>,
+    #[cfg(feature = "fs")]
+    written: WrittenFiles,
+    pending: VecDeque<FileSpec>,
+    decoding: Option<JoinHandle<Result<EmbeddedFile>>>,
+}
+
+impl EmbeddedFileStream {
+    /// Start decoding the next queued specification, if any.
+    fn spawn_next(&mut self) -> Option<&mut JoinHandle<Result<EmbeddedFile>>> {
+        let spec = self.pending.pop_front()?;
+        let document = Arc::clone(&self.document);
+        let config = Arc::clone(&self.config);
+        let memory = self.memory.clone();
+        #[cfg(feature = "fs")]
+        let source = Arc::clone(&self.source);
+        #[cfg(feature = "fs")]
+        let written = self.written.clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let specs = std::slice::from_ref(&spec);
+            let deadline = Deadline::start(config.max_duration);
+            let engine = ExtractionEngine::new(document.as_ref(), &config, specs)
+                .with_deadline(deadline)
+                .with_memory(memory.clone());
+            #[cfg(feature = "fs")]
+            let engine = engine.with_source(&source).with_written(written);
+            let file = engine.decode_file(&spec);
+            // The consumer owns the file from here on; the budget bounds
+            // what is being decoded, not what the consumer keeps.
+            if let Ok(file) = &file {
+                memory.release(file.data.len());
+            }
+            file
+        });
+        Some(self.decoding.insert(task))
+    }
+}
+
+impl Stream for EmbeddedFileStream {
+    type Item = Result<EmbeddedFile>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let task = match this.decoding.as_mut() {
+            Some(task) => task,
+            None => match this.spawn_next() {
+                Some(task) => task,
+                None => return Poll::Ready(None),
+            },
+        };
+
+        let outcome = match Pin::new(task).poll(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(outcome) => outcome,
+        };
+        this.decoding = None;
+
+        let item = outcome.unwrap_or_else(|e| {
+            Err(ExtractError::extraction(
+                "<stream>",
+                ExtractionStage::Decode,
+                format!("decode task failed: {e}"),
+            ))
+        });
+        Poll::Ready(Some(item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.pending.len() + usize::from(self.decoding.is_some());
+        (remaining, Some(remaining))
+    }
+}
+
+impl std::fmt::Debug for EmbeddedFileStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddedFileStream")
+            .field("remaining", &self.size_hint().0)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PdfAnalyzer {
+    /// Returns a [`Stream`] that decodes and yields the embedded files one at a
+    /// time, for pipelines that forward attachments (e.g. to object storage)
+    /// as they are produced.
+    ///
+    /// Discovery runs immediately; decoding happens as the stream is polled.
+    /// The stream owns a copy of the document, so it is `'static` and can be
+    /// moved into a spawned task. [`ExtractorConfig`] limits and disk writing
+    /// apply as in [`extract_embedded_files`](Self::extract_embedded_files).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn run() -> extractembedfilepdf::Result<()> {
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf")?;
+    /// let mut files = analyzer.embedded_file_stream()?;
+    /// while let Some(file) = files.next().await {
+    ///     let file = file?;
+    ///     println!("{} — {} bytes", file.filename, file.data.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn embedded_file_stream(&self) -> Result<EmbeddedFileStream> {
+        let specs = self.engine()?.specs();
+        if specs.is_empty() {
+            return Err(ExtractError::NoEmbeddedFiles);
+        }
+
+        Ok(EmbeddedFileStream {
+            document: Arc::clone(self.shared_document()),
+            config: Arc::new(self.config().clone()),
+            memory: self.memory_budget(),
+            #[cfg(feature = "fs")]
+            source: Arc::clone(self.source()),
+            #[cfg(feature = "fs")]
+            written: self.written().clone(),
+            pending: specs.iter().cloned().collect(),
+            decoding: None,
+        })
+    }
+}
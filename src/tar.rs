@@ -0,0 +1,185 @@
+use crate::pdf_utils::{safe_filename, PdfDate};
+use crate::{EmbeddedFile, ExtractionReport};
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// Size of a tar header and the unit all content is padded to.
+const BLOCK: usize = 512;
+/// Longest filename that fits the ustar `name` field.
+const NAME_LEN: usize = 100;
+
+impl ExtractionReport {
+    /// Writes the [`files`](Self::files) to `out` as a POSIX tar archive, the
+    /// streaming-friendly sibling of [`to_zip_bytes`](Self::to_zip_bytes) for
+    /// piping into further processing on Unix systems.
+    ///
+    /// Entries have mode `0644` and the attachment's `/Params/ModDate` as
+    /// modification time (or the Unix epoch without one). Entry names are
+    /// reduced to their last path component, so unpacking the archive cannot
+    /// write outside the target directory; names longer than 100 bytes are
+    /// carried in a PAX extended header. Of several files with the same name
+    /// only the first is written, as when extracting to disk. Files of 8 GiB
+    /// and more have their size in the GNU base-256 form.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let report = PdfAnalyzer::from_path("invoice.pdf")
+    ///     .unwrap()
+    ///     .extract_with_report()
+    ///     .unwrap();
+    /// report.write_tar(&mut std::io::stdout().lock()).unwrap();
+    /// ```
+    pub fn write_tar<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut tar = TarWriter::new(out);
+        for file in &self.files {
+            tar.append(file)?;
+        }
+        tar.finish().map(drop)
+    }
+
+    /// The [`write_tar`](Self::write_tar) archive as bytes in memory.
+    pub fn to_tar_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_tar(&mut out)
+            .expect("writing to a Vec<u8> cannot fail");
+        out
+    }
+}
+
+// ── TarWriter ─────────────────────────────────────────────────────────────────
+
+/// Writes a tar archive one file at a time, so only the file being appended
+/// has to be in memory.
+///
+/// Entries are written as by [`ExtractionReport::write_tar`], including its
+/// handling of names: of several files with the same name only the first is
+/// written. The archive is only complete once [`finish`](Self::finish) has
+/// written its end marker.
+///
+/// ```no_run
+/// use extractembedfilepdf::{PdfAnalyzer, TarWriter};
+///
+/// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+/// let mut tar = TarWriter::new(std::io::stdout().lock());
+/// for handle in analyzer.embedded_file_handles().unwrap() {
+///     tar.append(&handle.decode().unwrap()).unwrap();
+/// }
+/// tar.finish().unwrap();
+/// ```
+pub struct TarWriter<W: Write> {
+    out: W,
+    written: HashSet<String>,
+}
+
+impl<W: Write> TarWriter<W> {
+    /// Start an archive written to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            written: HashSet::new(),
+        }
+    }
+
+    /// Append `file` as the next entry. Returns `false`, without writing
+    /// anything, when a file of the same name was already appended.
+    pub fn append(&mut self, file: &EmbeddedFile) -> io::Result<bool> {
+        let name = safe_filename(&file.filename);
+        if self.written.contains(&name) {
+            return Ok(false);
+        }
+        write_entry(&mut self.out, &name, file)?;
+        self.written.insert(name);
+        Ok(true)
+    }
+
+    /// Write the end marker and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        // Two zero blocks mark the end of the archive.
+        self.out.write_all(&[0; 2 * BLOCK])?;
+        Ok(self.out)
+    }
+}
+
+/// Write the header and padded content of one file as `name`.
+fn write_entry<W: Write>(out: &mut W, name: &str, file: &EmbeddedFile) -> io::Result<()> {
+    let mtime = file
+        .metadata
+        .modification_date
+        .as_deref()
+        .and_then(PdfDate::parse)
+        .map_or(0, |date| date.unix_timestamp().max(0) as u64);
+
+    if name.len() > NAME_LEN {
+        let record = pax_record("path", name);
+        out.write_all(&header(b"././@PaxHeader", record.len() as u64, mtime, b'x'))?;
+        write_padded(out, &record)?;
+    }
+    let size = file.data.len() as u64;
+    out.write_all(&header(name.as_bytes(), size, mtime, b'0'))?;
+    write_padded(out, &file.data)
+}
+
+/// A ustar header block. `name` is cut to 100 bytes; the full name travels
+/// in a preceding PAX header.
+fn header(name: &[u8], size: u64, mtime: u64, kind: u8) -> [u8; BLOCK] {
+    let mut block = [0; BLOCK];
+    let name = &name[..name.len().min(NAME_LEN)];
+    block[..name.len()].copy_from_slice(name);
+    octal(&mut block[100..108], 0o644);
+    octal(&mut block[108..116], 0); // uid
+    octal(&mut block[116..124], 0); // gid
+    number(&mut block[124..136], size);
+    number(&mut block[136..148], mtime);
+    block[156] = kind;
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field filled with spaces.
+    block[148..156].fill(b' ');
+    let checksum: u64 = block.iter().map(|&b| u64::from(b)).sum();
+    octal(&mut block[148..155], checksum);
+    block
+}
+
+/// Write `value` into `field` in octal, or in the GNU base-256 form when it
+/// has too many digits: the high bit of the first byte set and the value
+/// big-endian in the rest.
+fn number(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    if value < 1 << (3 * digits) {
+        octal(field, value);
+    } else {
+        field.fill(0);
+        field[0] = 0x80;
+        let bytes = value.to_be_bytes();
+        let at = field.len() - bytes.len();
+        field[at..].copy_from_slice(&bytes);
+    }
+}
+
+/// Write `value` into `field` as zero-padded octal digits followed by NUL.
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+    let digits = &digits.as_bytes()[digits.len() - (field.len() - 1)..];
+    field[..digits.len()].copy_from_slice(digits);
+    field[digits.len()] = 0;
+}
+
+/// A PAX extended header record: `"<length> <key>=<value>\n"`, where the
+/// length counts the whole record including its own digits.
+fn pax_record(key: &str, value: &str) -> Vec<u8> {
+    let body = format!(" {key}={value}\n");
+    let mut len = body.len() + 1;
+    while len.to_string().len() + body.len() != len {
+        len = len.to_string().len() + body.len();
+    }
+    format!("{len}{body}").into_bytes()
+}
+
+/// Write `data` and pad it with zeros to a whole number of blocks.
+fn write_padded<W: Write>(out: &mut W, data: &[u8]) -> io::Result<()> {
+    out.write_all(data)?;
+    let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+    out.write_all(&[0; BLOCK][..padding])
+}
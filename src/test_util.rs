@@ -0,0 +1,1015 @@
+//! Builders for small PDF documents to test against, so that tests need no
+//! binary fixture files.
+//!
+//! [`PdfBuilder`] writes a document with any number of blank pages, an
+//! optional XMP metadata stream, and attachments placed in the
+//! `/EmbeddedFiles` name tree, in `FileAttachment` annotations or only in
+//! the catalog's `/AF` array. The name tree can be written in each of the
+//! layouts real producers use, see [`NameTreeLayout`].
+//!
+//! [`ObjectGraph`] skips serialization altogether: it is a bare set of
+//! objects, for testing discovery and decoding against object graphs no writer would produce. Run the
+//! extraction pipeline on one with [`extract_report`].
+//!
+//! For regression tests of code that produces PDFs, such as invoice
+//! generators, [`assert_attachments!`](crate::assert_attachments) checks in
+//! one line that a document carries exactly the expected attachments, by
+//! name and optionally by size, SHA-256 digest and MIME type; see
+//! [`ExpectedManifest`].
+//!
+//! For property tests of code that wraps this crate, the module also has
+//! [proptest](https://docs.rs/proptest) strategies for filenames, including
+//! [hostile ones](hostile_filename), [`metadata`],
+//! [extracted files](embedded_file), [attachments](attachment) and
+//! [configurations](config). The public data types also implement
+//! `arbitrary::Arbitrary`.
+//!
+//! Available with the `test-util` feature.
+//!
+//! ```
+//! use extractembedfilepdf::test_util::{Attachment, NameTreeLayout, PdfBuilder};
+//! use extractembedfilepdf::{AfRelationship, PdfAnalyzer};
+//!
+//! let bytes = PdfBuilder::new()
+//!     .pdfa(3, "B")
+//!     .name_tree(NameTreeLayout::Nested { leaf_size: 1 })
+//!     .attachment(Attachment {
+//!         mime_type: Some("text/xml".into()),
+//!         af_relationship: Some(AfRelationship::Alternative),
+//!         ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+//!     })
+//!     .attachment(Attachment::new("notes.txt", "hello"))
+//!     .build();
+//!
+//! let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+//! assert!(analyzer.is_pdfa3().unwrap());
+//! assert_eq!(analyzer.embedded_file_names().unwrap(), ["factur-x.xml", "notes.txt"]);
+//! ```
+
+use crate::backend::PdfBackend;
+use crate::deadline::Deadline;
+use crate::extraction_engine::ExtractionEngine;
+use crate::memory::MemoryBudget;
+use crate::{
+    AfRelationship, AttachmentSource, EmbeddedFile, EmbeddedFileMetadata, ExtractError,
+    ExtractionReport, ExtractorConfig, PdfAnalyzer, Result, RiskCategory, RiskPolicy, SizeMismatch,
+};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+
+// ── Attachment ────────────────────────────────────────────────────────────────
+
+/// A file to embed with [`PdfBuilder`].
+///
+/// Build one with [`Attachment::new`] and override fields with struct
+/// update syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// The filename, written as both `/F` and `/UF`.
+    pub name: String,
+
+    /// The file content.
+    pub data: Vec<u8>,
+
+    /// The MIME type, written as `/Subtype` on the file specification and
+    /// the embedded stream.
+    pub mime_type: Option<String>,
+
+    /// The `/Desc` of the file specification.
+    pub description: Option<String>,
+
+    /// The `/AFRelationship` of the file specification. Attachments in the
+    /// name tree that have one are also listed in the catalog's `/AF`
+    /// array, as PDF/A-3 requires.
+    pub af_relationship: Option<AfRelationship>,
+
+    /// Compress the stream with `/FlateDecode`.
+    pub compress: bool,
+
+    /// Write `/Params` with the `/Size` and MD5 `/CheckSum` of the data.
+    pub params: bool,
+}
+
+impl Attachment {
+    /// An uncompressed attachment with `/Params` and no other metadata.
+    pub fn new(name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            data: data.into(),
+            mime_type: None,
+            description: None,
+            af_relationship: None,
+            compress: false,
+            params: true,
+        }
+    }
+}
+
+/// How [`PdfBuilder`] writes the `/EmbeddedFiles` name tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameTreeLayout {
+    /// A single leaf node, referenced from the catalog's `/Names`
+    /// dictionary.
+    #[default]
+    Flat,
+    /// A single leaf node written directly into the catalog's `/Names`
+    /// dictionary.
+    Inline,
+    /// A root node whose `/Kids` are leaf nodes of at most `leaf_size`
+    /// entries each, with `/Limits`.
+    Nested {
+        /// The number of entries per leaf; at least one.
+        leaf_size: usize,
+    },
+}
+
+/// Where an attachment is placed in the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placement {
+    NameTree,
+    Annotation(usize),
+    AssociatedFile,
+}
+
+// ── PdfBuilder ────────────────────────────────────────────────────────────────
+
+/// Builds a PDF document in memory. See the [module documentation](self).
+///
+/// Attachments appear in the document in the order they were added.
+#[derive(Debug, Clone)]
+pub struct PdfBuilder {
+    version: String,
+    pages: usize,
+    xmp: Option<Vec<u8>>,
+    language: Option<String>,
+    name_tree: NameTreeLayout,
+    attachments: Vec<(Attachment, Placement)>,
+}
+
+impl Default for PdfBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfBuilder {
+    /// A PDF 1.7 document with one blank A4 page and nothing else.
+    pub fn new() -> Self {
+        Self {
+            version: "1.7".to_string(),
+            pages: 1,
+            xmp: None,
+            language: None,
+            name_tree: NameTreeLayout::default(),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Sets the version in the file header, e.g. `"1.4"`.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Sets the number of blank pages; `0` writes a page tree without
+    /// pages.
+    pub fn pages(mut self, pages: usize) -> Self {
+        self.pages = pages;
+        self
+    }
+
+    /// Sets the catalog's `/Metadata` stream to `packet`, verbatim.
+    pub fn xmp(mut self, packet: impl Into<Vec<u8>>) -> Self {
+        self.xmp = Some(packet.into());
+        self
+    }
+
+    /// Sets the catalog's `/Metadata` stream to an XMP packet declaring
+    /// PDF/A conformance `part` and `conformance`, e.g. `3` and `"B"`.
+    pub fn pdfa(self, part: u8, conformance: &str) -> Self {
+        self.xmp(pdfa_xmp(part, conformance))
+    }
+
+    /// Sets the catalog's `/Lang`, e.g. `"de-DE"`.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Sets the layout of the `/EmbeddedFiles` name tree.
+    pub fn name_tree(mut self, layout: NameTreeLayout) -> Self {
+        self.name_tree = layout;
+        self
+    }
+
+    /// Adds `attachment` to the `/EmbeddedFiles` name tree.
+    pub fn attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push((attachment, Placement::NameTree));
+        self
+    }
+
+    /// Adds `attachment` in a `FileAttachment` annotation on the 1-based
+    /// `page`, which must exist when the document is built.
+    pub fn annotation(mut self, page: usize, attachment: Attachment) -> Self {
+        self.attachments
+            .push((attachment, Placement::Annotation(page)));
+        self
+    }
+
+    /// Adds `attachment` to the catalog's `/AF` array only, outside the
+    /// name tree. Extraction finds it only when
+    /// [`ExtractorConfig::discovery_sources`] includes
+    /// [`AttachmentSource::AssociatedFiles`].
+    pub fn associated_file(mut self, attachment: Attachment) -> Self {
+        self.attachments
+            .push((attachment, Placement::AssociatedFile));
+        self
+    }
+
+    /// Writes the document.
+    ///
+    /// # Panics
+    ///
+    /// Panics when an annotation is placed on a page that does not exist.
+    pub fn build(&self) -> Vec<u8> {
+        let mut doc = Document::with_version(self.version.as_str());
+        let pages_id = doc.new_object_id();
+        let page_ids: Vec<ObjectId> = (0..self.pages)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                })
+            })
+            .collect();
+
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if let Some(packet) = &self.xmp {
+            let metadata_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                packet.clone(),
+            ));
+            catalog.set("Metadata", metadata_id);
+        }
+        if let Some(language) = &self.language {
+            catalog.set("Lang", Object::string_literal(language.as_str()));
+        }
+
+        let mut tree = Vec::new();
+        let mut associated = Vec::new();
+        for (attachment, placement) in &self.attachments {
+            let spec_id = file_spec(&mut doc, attachment);
+            match placement {
+                Placement::NameTree => {
+                    tree.push((attachment.name.as_str(), spec_id));
+                    if attachment.af_relationship.is_some() {
+                        associated.push(spec_id.into());
+                    }
+                }
+                Placement::AssociatedFile => associated.push(spec_id.into()),
+                Placement::Annotation(page) => {
+                    let page_id = page
+                        .checked_sub(1)
+                        .and_then(|i| page_ids.get(i))
+                        .unwrap_or_else(|| panic!("the document has no page {page}"));
+                    let annotation_id = doc.add_object(dictionary! {
+                        "Type" => "Annot",
+                        "Subtype" => "FileAttachment",
+                        "Rect" => vec![10.into(), 10.into(), 30.into(), 30.into()],
+                        "Contents" => Object::string_literal(attachment.name.as_str()),
+                        "FS" => spec_id,
+                    });
+                    if let Ok(page) = doc.get_dictionary_mut(*page_id) {
+                        match page.get_mut(b"Annots") {
+                            Ok(Object::Array(annots)) => annots.push(annotation_id.into()),
+                            _ => page.set("Annots", vec![annotation_id.into()]),
+                        }
+                    }
+                }
+            }
+        }
+
+        if !tree.is_empty() {
+            let embedded_files = self.name_tree_root(&mut doc, &tree);
+            catalog.set("Names", dictionary! { "EmbeddedFiles" => embedded_files });
+        }
+        if !associated.is_empty() {
+            catalog.set("AF", associated);
+        }
+
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => page_ids.iter().map(|&id| id.into()).collect::<Vec<Object>>(),
+                "Count" => self.pages as i64,
+            }),
+        );
+        let catalog_id = doc.add_object(catalog);
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    /// The `/EmbeddedFiles` value for the `(name, file spec)` pairs `tree`.
+    fn name_tree_root(&self, doc: &mut Document, tree: &[(&str, ObjectId)]) -> Object {
+        match self.name_tree {
+            NameTreeLayout::Flat => doc.add_object(leaf(tree, false)).into(),
+            NameTreeLayout::Inline => Object::Dictionary(leaf(tree, false)),
+            NameTreeLayout::Nested { leaf_size } => {
+                let kids: Vec<Object> = tree
+                    .chunks(leaf_size.max(1))
+                    .map(|chunk| doc.add_object(leaf(chunk, true)).into())
+                    .collect();
+                doc.add_object(dictionary! { "Kids" => kids }).into()
+            }
+        }
+    }
+}
+
+/// An XMP packet that declares PDF/A conformance `part` and `conformance`.
+pub fn pdfa_xmp(part: u8, conformance: &str) -> String {
+    format!(
+        r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/">
+      <pdfaid:part>{part}</pdfaid:part>
+      <pdfaid:conformance>{conformance}</pdfaid:conformance>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#
+    )
+}
+
+/// Add the embedded stream and file specification for `attachment` to
+/// `doc` and return the file specification's id.
+fn file_spec(doc: &mut Document, attachment: &Attachment) -> ObjectId {
+    let mut stream_dict = dictionary! { "Type" => "EmbeddedFile" };
+    let mut spec = dictionary! {
+        "Type" => "Filespec",
+        "F" => Object::string_literal(attachment.name.as_str()),
+        "UF" => Object::string_literal(attachment.name.as_str()),
+    };
+    if let Some(mime_type) = &attachment.mime_type {
+        stream_dict.set("Subtype", Object::Name(mime_type.as_bytes().to_vec()));
+        spec.set("Subtype", Object::Name(mime_type.as_bytes().to_vec()));
+    }
+    if let Some(description) = &attachment.description {
+        spec.set("Desc", Object::string_literal(description.as_str()));
+    }
+    if let Some(relationship) = &attachment.af_relationship {
+        spec.set(
+            "AFRelationship",
+            Object::Name(relationship.as_str().as_bytes().to_vec()),
+        );
+    }
+    if attachment.params {
+        use md5::{Digest, Md5};
+        stream_dict.set(
+            "Params",
+            dictionary! {
+                "Size" => attachment.data.len() as i64,
+                "CheckSum" => Object::String(
+                    Md5::digest(&attachment.data).to_vec(),
+                    StringFormat::Hexadecimal,
+                ),
+            },
+        );
+    }
+
+    let data = if attachment.compress {
+        stream_dict.set("Filter", "FlateDecode");
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&attachment.data)
+            .and_then(|()| encoder.finish())
+            .expect("compressing into a Vec cannot fail")
+    } else {
+        attachment.data.clone()
+    };
+    let stream_id = doc.add_object(Stream::new(stream_dict, data).with_compression(false));
+    spec.set("EF", dictionary! { "F" => stream_id });
+    doc.add_object(spec)
+}
+
+/// A name tree leaf node for `entries`, with `/Limits` if `limits`.
+fn leaf(entries: &[(&str, ObjectId)], limits: bool) -> Dictionary {
+    let names: Vec<Object> = entries
+        .iter()
+        .flat_map(|&(name, id)| [Object::string_literal(name), id.into()])
+        .collect();
+    let mut node = dictionary! { "Names" => names };
+    if let (true, Some(first), Some(last)) = (limits, entries.first(), entries.last()) {
+        node.set(
+            "Limits",
+            vec![
+                Object::string_literal(first.0),
+                Object::string_literal(last.0),
+            ],
+        );
+    }
+    node
+}
+
+// ── ObjectGraph ───────────────────────────────────────────────────────────────
+
+/// An in-memory object graph: numbered objects and a catalog, without a
+/// file around them, which [`extract_report`] runs on directly.
+///
+/// ```
+/// use extractembedfilepdf::test_util::{extract_report, ObjectGraph};
+/// use extractembedfilepdf::ExtractorConfig;
+/// use lopdf::{dictionary, Object, Stream};
+///
+/// let mut graph = ObjectGraph::new();
+/// let stream = graph.add(Stream::new(dictionary! {}, b"hello".to_vec()));
+/// let spec = graph.add(dictionary! {
+///     "Type" => "Filespec",
+///     "UF" => Object::string_literal("notes.txt"),
+///     "EF" => dictionary! { "F" => stream },
+/// });
+/// let catalog = graph.add(dictionary! {
+///     "Type" => "Catalog",
+///     "Names" => dictionary! {
+///         "EmbeddedFiles" => dictionary! {
+///             "Names" => vec![Object::string_literal("notes.txt"), spec.into()],
+///         },
+///     },
+/// });
+/// graph.set_root(catalog);
+///
+/// let report = extract_report(&graph, &ExtractorConfig::default()).unwrap();
+/// assert_eq!(report.files[0].data, b"hello");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ObjectGraph {
+    objects: BTreeMap<ObjectId, Object>,
+    root: Option<ObjectId>,
+    locked: bool,
+}
+
+impl ObjectGraph {
+    /// An empty graph without a catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `object` under the next free object number and return its id.
+    pub fn add(&mut self, object: impl Into<Object>) -> ObjectId {
+        let number = self.objects.keys().next_back().map_or(1, |id| id.0 + 1);
+        let id = (number, 0);
+        self.objects.insert(id, object.into());
+        id
+    }
+
+    /// Store `object` as `id`, replacing any object already there.
+    pub fn insert(&mut self, id: ObjectId, object: impl Into<Object>) {
+        self.objects.insert(id, object.into());
+    }
+
+    /// Remove the object `id`, leaving every reference to it dangling.
+    pub fn remove(&mut self, id: ObjectId) -> Option<Object> {
+        self.objects.remove(&id)
+    }
+
+    /// Use the dictionary `id` as the catalog.
+    pub fn set_root(&mut self, id: ObjectId) {
+        self.root = Some(id);
+    }
+
+    /// Report the graph as encrypted and not decrypted, so its streams are
+    /// treated as ciphertext.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    /// The object id of every page, by 1-based page number, as extraction
+    /// numbers them.
+    pub fn get_pages(&self) -> BTreeMap<u32, ObjectId> {
+        PdfBackend::get_pages(self)
+    }
+}
+
+impl From<Document> for ObjectGraph {
+    /// The objects and catalog of `document`.
+    fn from(document: Document) -> Self {
+        let locked = document.is_locked();
+        let root = document
+            .trailer
+            .get(b"Root")
+            .and_then(Object::as_reference)
+            .ok();
+        Self {
+            objects: document.objects,
+            root,
+            locked,
+        }
+    }
+}
+
+impl PdfBackend for ObjectGraph {
+    fn get_object(&self, id: ObjectId) -> lopdf::Result<&Object> {
+        let mut id = id;
+        // Follow reference chains like lopdf does, but give up on cycles.
+        for _ in 0..=self.objects.len() {
+            match self.objects.get(&id) {
+                Some(Object::Reference(next)) => id = *next,
+                Some(object) => return Ok(object),
+                None => break,
+            }
+        }
+        Err(lopdf::Error::ObjectNotFound(id))
+    }
+
+    fn catalog(&self) -> lopdf::Result<&Dictionary> {
+        let root = self.root.ok_or(lopdf::Error::DictKey("Root".to_owned()))?;
+        self.get_dictionary(root)
+    }
+
+    fn objects(&self) -> Box<dyn Iterator<Item = (ObjectId, &Object)> + '_> {
+        Box::new(self.objects.iter().map(|(id, object)| (*id, object)))
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+/// Discover and extract the attachments of `document` the way
+/// [`PdfAnalyzer::extract_with_report`](crate::PdfAnalyzer::extract_with_report)
+/// does, for an in-memory [`ObjectGraph`]. The time and memory budgets of
+/// `config` apply; document validation does not run.
+pub fn extract_report(graph: &ObjectGraph, config: &ExtractorConfig) -> Result<ExtractionReport> {
+    let deadline = Deadline::start(config.max_duration);
+    let specs = ExtractionEngine::discover(graph, config, deadline)?;
+    ExtractionEngine::new(graph, config, &specs)
+        .with_deadline(deadline)
+        .with_memory(MemoryBudget::start(config.max_memory_bytes, 0))
+        .extract_report()
+}
+
+// ── ExpectedManifest ──────────────────────────────────────────────────────────
+
+/// One attachment a document is expected to carry, for [`ExpectedManifest`].
+/// Only the properties that are set are compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedAttachment {
+    /// The filename.
+    pub name: String,
+    /// The decoded size in bytes.
+    pub size: Option<usize>,
+    /// The SHA-256 hex digest of the decoded data, in either case.
+    pub sha256: Option<String>,
+    /// The MIME type.
+    pub mime_type: Option<String>,
+}
+
+impl ExpectedAttachment {
+    /// An attachment named `name`, with any content.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Expect `size` decoded bytes.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Expect data with the SHA-256 hex digest `sha256`.
+    pub fn sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into());
+        self
+    }
+
+    /// Expect the MIME type `mime_type`.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// How `file`, which has the expected name, differs from this
+    /// expectation.
+    fn mismatches(&self, file: &EmbeddedFile) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let Some(size) = self.size.filter(|&size| size != file.data.len()) {
+            mismatches.push(format!(
+                "'{}': expected {size} bytes, found {}",
+                self.name,
+                file.data.len()
+            ));
+        }
+        if let Some(sha256) = &self.sha256 {
+            let actual = file.sha256_hex();
+            if !sha256.eq_ignore_ascii_case(&actual) {
+                mismatches.push(format!(
+                    "'{}': expected SHA-256 {sha256}, found {actual}",
+                    self.name
+                ));
+            }
+        }
+        if let Some(mime_type) = &self.mime_type {
+            let actual = file.metadata.mime_type.as_deref();
+            if actual != Some(mime_type.as_str()) {
+                mismatches.push(format!(
+                    "'{}': expected MIME type {mime_type}, found {}",
+                    self.name,
+                    actual.unwrap_or("none")
+                ));
+            }
+        }
+        mismatches
+    }
+}
+
+/// The attachments a document is expected to carry, in any order, and no
+/// others. Usually built by [`assert_attachments!`](crate::assert_attachments).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedManifest {
+    /// The expected attachments.
+    pub attachments: Vec<ExpectedAttachment>,
+}
+
+impl ExpectedManifest {
+    /// Expect exactly `attachments`.
+    pub fn new(attachments: impl IntoIterator<Item = ExpectedAttachment>) -> Self {
+        Self {
+            attachments: attachments.into_iter().collect(),
+        }
+    }
+
+    /// Expect exactly the names, sizes, digests and MIME types of `files`,
+    /// e.g. to record the attachments of a known-good document.
+    pub fn from_files(files: &[EmbeddedFile]) -> Self {
+        Self::new(files.iter().map(|file| ExpectedAttachment {
+            name: file.filename.clone(),
+            size: Some(file.data.len()),
+            sha256: Some(file.sha256_hex()),
+            mime_type: file.metadata.mime_type.clone(),
+        }))
+    }
+
+    /// How `files` differ from the expectation, one description per
+    /// difference; empty when they match.
+    pub fn mismatches(&self, files: &[EmbeddedFile]) -> Vec<String> {
+        let mut remaining: Vec<&EmbeddedFile> = files.iter().collect();
+        let mut mismatches = Vec::new();
+        for expected in &self.attachments {
+            match remaining.iter().position(|f| f.filename == expected.name) {
+                Some(index) => mismatches.extend(expected.mismatches(remaining.remove(index))),
+                None => mismatches.push(format!("'{}': missing", expected.name)),
+            }
+        }
+        mismatches.extend(
+            remaining
+                .iter()
+                .map(|file| format!("'{}': not expected", file.filename)),
+        );
+        mismatches
+    }
+
+    /// Extract the attachments of the PDF `pdf` and describe how they differ
+    /// from the expectation. A document without attachments has none rather
+    /// than failing.
+    pub fn check(&self, pdf: &[u8]) -> Result<Vec<String>> {
+        let files = match PdfAnalyzer::from_bytes(pdf)?.extract_embedded_files() {
+            Ok(files) => files,
+            Err(ExtractError::NoEmbeddedFiles) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(self.mismatches(&files))
+    }
+
+    /// Like [`check`](Self::check), but panic with every difference, or
+    /// with the error when `pdf` cannot be read.
+    #[track_caller]
+    pub fn assert_matches(&self, pdf: impl AsRef<[u8]>) {
+        match self.check(pdf.as_ref()) {
+            Ok(mismatches) if mismatches.is_empty() => {}
+            Ok(mismatches) => panic!(
+                "attachments do not match the expected manifest:\n  {}",
+                mismatches.join("\n  ")
+            ),
+            Err(e) => panic!("cannot extract attachments: {e}"),
+        }
+    }
+}
+
+/// Assert that a PDF, given as bytes, carries exactly the listed
+/// attachments, in any order. Each attachment is a filename, optionally
+/// followed by `=>` and the properties to compare: `size`, `sha256` and
+/// `mime_type`, as in [`ExpectedAttachment`].
+///
+/// Panics with every difference found. Available with the `test-util`
+/// feature.
+///
+/// ```
+/// use extractembedfilepdf::assert_attachments;
+/// use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+///
+/// let pdf = PdfBuilder::new()
+///     .attachment(Attachment {
+///         mime_type: Some("text/xml".into()),
+///         ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+///     })
+///     .attachment(Attachment::new("notes.txt", "hello"))
+///     .build();
+///
+/// assert_attachments!(pdf, [
+///     "factur-x.xml" => { size: 27, mime_type: "text/xml" },
+///     "notes.txt" => {
+///         sha256: "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+///     },
+/// ]);
+/// ```
+#[macro_export]
+macro_rules! assert_attachments {
+    ($pdf:expr, [$($name:expr $(=> { $($property:ident: $value:expr),* $(,)? })?),* $(,)?]) => {
+        $crate::test_util::ExpectedManifest::new([
+            $($crate::test_util::ExpectedAttachment::new($name)$($(.$property($value))*)?),*
+        ])
+        .assert_matches($pdf)
+    };
+}
+
+// ── Strategies ────────────────────────────────────────────────────────────────
+
+/// Filenames as well-behaved producers write them, e.g. `factur-x.xml`.
+pub fn plain_filename() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9_-]{1,24}\\.(xml|pdf|txt|csv|png|jpg|xlsx|docx|zip)"
+}
+
+/// Filenames crafted to escape an output directory or confuse the code
+/// that writes or displays them: `..` components, absolute and UNC paths,
+/// drive letters, Windows device names, control characters, bidirectional
+/// overrides, trailing dots and spaces, and very long names.
+///
+/// ```
+/// use extractembedfilepdf::test_util::hostile_filename;
+/// use proptest::prelude::*;
+///
+/// proptest!(|(name in hostile_filename())| {
+///     let path = std::path::Path::new("out").join(&name);
+///     // Code that writes attachments must not trust `path` to stay in `out`.
+///     let _ = path;
+/// });
+/// ```
+pub fn hostile_filename() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ("(\\.\\./){1,4}|(\\.\\.\\\\){1,4}", plain_filename()).prop_map(|(up, name)| up + &name),
+        select(vec![
+            "/etc/passwd",
+            "/tmp/factur-x.xml",
+            "C:\\Windows\\win.ini",
+            "C:factur-x.xml",
+            "\\\\server\\share\\factur-x.xml",
+            "\\\\?\\C:\\factur-x.xml",
+        ])
+        .prop_map(str::to_owned),
+        select(vec![
+            "CON", "NUL", "AUX", "PRN", "COM1", "LPT1", "con.xml", "nul.txt"
+        ])
+        .prop_map(str::to_owned),
+        select(vec![
+            "",
+            ".",
+            "..",
+            " ",
+            "factur-x.xml.",
+            "factur-x.xml ",
+            "a/b.xml",
+            "a\\b.xml"
+        ])
+        .prop_map(str::to_owned),
+        (
+            plain_filename(),
+            "[\\x00-\\x1f\\x7f]",
+            any::<prop::sample::Index>()
+        )
+            .prop_map(|(name, control, at)| {
+                let at = at.index(name.len() + 1);
+                format!("{}{control}{}", &name[..at], &name[at..])
+            }),
+        "[a-z]{1,12}".prop_map(|name| format!("{name}\u{202e}lmx.exe")),
+        "[a-z]{256,1024}\\.xml",
+    ]
+}
+
+/// Filenames of either kind, mostly plain ones.
+pub fn filename() -> impl Strategy<Value = String> {
+    prop_oneof![3 => plain_filename(), 1 => hostile_filename()]
+}
+
+/// Any `/AFRelationship`, including an unknown `Other` name.
+pub fn af_relationship() -> impl Strategy<Value = AfRelationship> {
+    prop_oneof![
+        select(vec![
+            AfRelationship::Source,
+            AfRelationship::Data,
+            AfRelationship::Alternative,
+            AfRelationship::Supplement,
+            AfRelationship::EncryptedPayload,
+            AfRelationship::FormData,
+            AfRelationship::Schema,
+            AfRelationship::Unspecified,
+        ]),
+        "[A-Z][A-Za-z]{0,15}".prop_map(|name| AfRelationship::from_name(&name)),
+    ]
+}
+
+/// Any [`AttachmentSource`].
+pub fn attachment_source() -> impl Strategy<Value = AttachmentSource> {
+    select(vec![
+        AttachmentSource::NamesTree,
+        AttachmentSource::Annotation,
+        AttachmentSource::AssociatedFiles,
+        AttachmentSource::DeepScan,
+    ])
+}
+
+/// Metadata as the extractor fills it in: MIME types from the built-in
+/// table, PDF date strings and hex checksums. Fields are independent, so a
+/// value need not be consistent with any file's data.
+pub fn metadata() -> impl Strategy<Value = EmbeddedFileMetadata> {
+    let mime_types: Vec<String> = crate::mime::builtin()
+        .iter()
+        .map(|(_, mime)| mime.to_owned())
+        .collect();
+    let described = (
+        option::of(select(mime_types.clone())),
+        option::of(select(mime_types)),
+        option::of("[ -~]{0,40}"),
+        option::of(pdf_date()),
+        option::of(pdf_date()),
+        option::of("[0-9A-F]{32}"),
+    );
+    let located = (
+        option::of(0..1usize << 20),
+        option::of(0..1usize << 20),
+        option::of(af_relationship()),
+        option::of(attachment_source()),
+        option::of(1..500u32),
+    );
+    let decoded = (
+        vec(select(vec!["JBIG2Decode", "JPXDecode", "Crypt"]), 0..2),
+        any::<bool>(),
+        option::of(select(vec![
+            RiskCategory::Executable,
+            RiskCategory::Script,
+            RiskCategory::Archive,
+            RiskCategory::MacroDocument,
+        ])),
+        any::<bool>(),
+        option::of((0..1usize << 20, 0..1usize << 20)),
+        btree_map("[a-z]{1,8}", "[a-z-]{1,16}", 0..3),
+    );
+    (described, located, decoded).prop_map(
+        |(
+            (
+                mime_type,
+                detected_mime_type,
+                description,
+                modification_date,
+                creation_date,
+                checksum,
+            ),
+            (size, stored_size, af_relationship, source, page),
+            (undecoded_filters, truncated, risk, encrypted_container, size_mismatch, labels),
+        )| EmbeddedFileMetadata {
+            mime_type,
+            detected_mime_type,
+            description,
+            modification_date,
+            creation_date,
+            size,
+            stored_size,
+            checksum,
+            af_relationship,
+            source,
+            page: page.filter(|_| source == Some(AttachmentSource::Annotation)),
+            undecoded_filters: undecoded_filters.into_iter().map(str::to_owned).collect(),
+            truncated,
+            risk,
+            encrypted_container,
+            size_mismatch: size_mismatch
+                .map(|(declared, actual)| SizeMismatch { declared, actual }),
+            labels,
+        },
+    )
+}
+
+/// Extracted files with a [`filename`], up to 4 KiB of data and
+/// [`metadata`].
+pub fn embedded_file() -> impl Strategy<Value = EmbeddedFile> {
+    (filename(), vec(any::<u8>(), 0..4096), metadata()).prop_map(|(filename, data, metadata)| {
+        EmbeddedFile {
+            filename,
+            data,
+            metadata,
+        }
+    })
+}
+
+/// Attachments for [`PdfBuilder`] with a [`plain_filename`] and up to 4 KiB
+/// of data.
+pub fn attachment() -> impl Strategy<Value = Attachment> {
+    (
+        plain_filename(),
+        vec(any::<u8>(), 0..4096),
+        option::of("(application|text)/[a-z]{1,12}"),
+        option::of("[ -~]{0,40}"),
+        option::of(af_relationship()),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(name, data, mime_type, description, af_relationship, compress, params)| Attachment {
+                name,
+                data,
+                mime_type,
+                description,
+                af_relationship,
+                compress,
+                params,
+            },
+        )
+}
+
+/// Configurations that never write to disk, with a time limit of at most
+/// five seconds.
+pub fn config() -> impl Strategy<Value = ExtractorConfig> {
+    (
+        any::<bool>(),
+        option::of(0..1usize << 24),
+        option::of(vec(attachment_source(), 0..4)),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        select(vec![RiskPolicy::Allow, RiskPolicy::Flag, RiskPolicy::Block]),
+        option::of((1..5000u64).prop_map(Duration::from_millis)),
+        option::of(1usize << 16..1usize << 28),
+    )
+        .prop_map(
+            |(
+                strict_pdfa3_validation,
+                max_embedded_file_size,
+                discovery_sources,
+                allow_pageless,
+                attachments_only,
+                check_file_structure,
+                recover_truncated_streams,
+                fail_on_any_error,
+                risk_policy,
+                max_duration,
+                max_memory_bytes,
+            )| ExtractorConfig {
+                strict_pdfa3_validation,
+                max_embedded_file_size,
+                discovery_sources,
+                allow_pageless,
+                attachments_only,
+                check_file_structure,
+                recover_truncated_streams,
+                fail_on_any_error,
+                risk_policy,
+                max_duration,
+                max_memory_bytes,
+                ..Default::default()
+            },
+        )
+}
+
+/// PDF date strings such as `D:20240131120000+01'00'`.
+fn pdf_date() -> impl Strategy<Value = String> {
+    (
+        1990..2100u32,
+        1..=12u32,
+        1..=28u32,
+        0..24u32,
+        0..60u32,
+        0..60u32,
+        select(vec!["Z", "+01'00'", "-05'00'", ""]),
+    )
+        .prop_map(|(year, month, day, hour, minute, second, zone)| {
+            format!("D:{year}{month:02}{day:02}{hour:02}{minute:02}{second:02}{zone}")
+        })
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use lopdf::content::Content;
+use lopdf::{Document, Encoding, Object, ObjectId};
+
+use crate::deadline::Deadline;
+use crate::filters;
+use crate::memory::MemoryBudget;
+use crate::{PdfAnalyzer, Result};
+
+/// Separates the pages in [`DocumentText::full_text`], as `pdftotext` does.
+const PAGE_SEPARATOR: char = '\u{c}';
+
+// ── DocumentText ──────────────────────────────────────────────────────────────
+
+/// The text of every page, returned by [`PdfAnalyzer::extract_text`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocumentText {
+    /// One entry per page, in page order.
+    pub pages: Vec<PageText>,
+}
+
+/// The text of one page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PageText {
+    /// The 1-based page number.
+    pub page: u32,
+
+    /// The shown strings in content-stream order, with a line break after
+    /// each text object.
+    pub text: String,
+}
+
+impl DocumentText {
+    /// The text of the whole document, the pages separated by a form feed.
+    pub fn full_text(&self) -> String {
+        let mut text = String::new();
+        for (i, page) in self.pages.iter().enumerate() {
+            if i > 0 {
+                text.push(PAGE_SEPARATOR);
+            }
+            text.push_str(&page.text);
+        }
+        text
+    }
+
+    /// The text of the page with the 1-based number `page`.
+    pub fn page(&self, page: u32) -> Option<&str> {
+        let page = self.pages.iter().find(|p| p.page == page)?;
+        Some(&page.text)
+    }
+}
+
+impl PdfAnalyzer {
+    /// Extracts the text shown on each page, so the human-readable totals of
+    /// an invoice can be cross-checked against its embedded XML without
+    /// parsing the document a second time.
+    ///
+    /// The text comes from the `Tj` and `TJ` operators of the page content
+    /// streams, decoded through each font's encoding or `/ToUnicode` map. It
+    /// is in content-stream order, which is not always reading order, and
+    /// carries no layout. Strings in a font whose encoding cannot be read are
+    /// left out rather than failing the page; a page without decodable
+    /// content has empty text.
+    ///
+    /// # Errors
+    ///
+    /// [`ExtractError::Timeout`](crate::ExtractError::Timeout) when the
+    /// document takes longer than
+    /// [`max_duration`](crate::ExtractorConfig::max_duration),
+    /// [`ExtractError::FileSizeExceeded`](crate::ExtractError::FileSizeExceeded)
+    /// when a content stream decodes to more than
+    /// [`max_embedded_file_size`](crate::ExtractorConfig::max_embedded_file_size)
+    /// and [`ExtractError::MemoryLimit`](crate::ExtractError::MemoryLimit)
+    /// when a page's content outgrows
+    /// [`max_memory_bytes`](crate::ExtractorConfig::max_memory_bytes).
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("invoice.pdf").unwrap();
+    /// let text = analyzer.extract_text().unwrap();
+    /// println!("page 1: {}", text.page(1).unwrap_or_default());
+    /// assert!(text.full_text().contains("Total"));
+    /// ```
+    pub fn extract_text(&self) -> Result<DocumentText> {
+        let document = self.document();
+        let deadline = Deadline::start(self.config().max_duration);
+        let memory = self.memory_budget();
+        let mut pages = Vec::new();
+        for (page, page_id) in document.get_pages() {
+            deadline.check()?;
+            let text = self.page_text(page_id, &deadline, &memory)?;
+            pages.push(PageText { page, text });
+        }
+        Ok(DocumentText { pages })
+    }
+
+    /// The shown text of one page. Its content streams are decoded within
+    /// [`max_embedded_file_size`](crate::ExtractorConfig::max_embedded_file_size),
+    /// the memory budget and the deadline; a stream that cannot be decoded
+    /// is skipped.
+    fn page_text(
+        &self,
+        page_id: ObjectId,
+        deadline: &Deadline,
+        memory: &MemoryBudget,
+    ) -> Result<String> {
+        let document = self.document();
+        let config = self.config();
+        let mut data = Vec::new();
+        for id in document.get_page_contents(page_id) {
+            let Ok(stream) = document.get_object(id).and_then(Object::as_stream) else {
+                continue;
+            };
+            let mut decoded = filters::decode(
+                stream,
+                config.max_embedded_file_size,
+                false,
+                deadline,
+                memory,
+            )?;
+            if decoded.undecoded_filters.is_empty() {
+                data.append(&mut decoded.data);
+            } else {
+                memory.release(decoded.data.len());
+            }
+        }
+        let content = Content::decode(&data);
+        memory.release(data.len());
+        let Ok(content) = content else {
+            return Ok(String::new());
+        };
+        Ok(shown_text(document, page_id, &content))
+    }
+}
+
+/// The strings shown by the `Tj` and `TJ` operators of `content`, decoded
+/// through the encoding of the font selected by the last `Tf`.
+fn shown_text(document: &Document, page_id: ObjectId, content: &Content) -> String {
+    let encodings: BTreeMap<Vec<u8>, Encoding> = document
+        .get_page_fonts(page_id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, font)| Some((name, font.get_font_encoding(document).ok()?)))
+        .collect();
+
+    let mut text = String::new();
+    let mut encoding = None;
+    for operation in &content.operations {
+        match operation.operator.as_str() {
+            "Tf" => {
+                let font = operation
+                    .operands
+                    .first()
+                    .and_then(|font| font.as_name().ok());
+                encoding = font.and_then(|font| encodings.get(font));
+            }
+            "Tj" | "TJ" => {
+                if let Some(encoding) = encoding {
+                    push_strings(&mut text, encoding, &operation.operands);
+                }
+            }
+            "ET" if !text.ends_with('\n') => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Append the strings among `operands`, with a space after each `TJ` array
+/// and for each kerning gap wide enough to separate words.
+fn push_strings(text: &mut String, encoding: &Encoding, operands: &[Object]) {
+    for operand in operands {
+        match operand {
+            Object::String(bytes, _) => {
+                if let Ok(string) = Document::decode_text(encoding, bytes) {
+                    text.push_str(&string);
+                }
+            }
+            Object::Array(array) => {
+                push_strings(text, encoding, array);
+                text.push(' ');
+            }
+            Object::Integer(gap) if *gap < -100 => text.push(' '),
+            _ => {}
+        }
+    }
+}
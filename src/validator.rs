@@ -1,6 +1,31 @@
-use crate::{ExtractError, ExtractorConfig, Result};
+use crate::{xmp, ExtractError, ExtractorConfig, PdfAConformance, Result};
 use lopdf::Document;
 
+// ── XmpInfo ───────────────────────────────────────────────────────────────────
+
+/// The PDF/A identification parsed from the XMP packet. Built once per
+/// analyzer and cached.
+#[derive(Debug, Clone)]
+pub(crate) struct XmpInfo {
+    /// Whether the packet declares PDF/A-3 with level A, B, or U.
+    pub(crate) declares_pdfa3: bool,
+    /// The declared part and conformance level, if any.
+    pub(crate) conformance: Option<PdfAConformance>,
+    /// The decompressed packet itself.
+    pub(crate) packet: String,
+}
+
+impl XmpInfo {
+    fn parse(packet: String) -> Self {
+        let conformance = PdfAConformance::from_xmp(&packet);
+        Self {
+            declares_pdfa3: conformance.is_some_and(|c| c.part == 3),
+            conformance,
+            packet,
+        }
+    }
+}
+
 // ── PdfValidator ──────────────────────────────────────────────────────────────
 //
 // This is an internal type.  Callers use PdfAnalyzer, which delegates here.
@@ -19,14 +44,20 @@ impl<'a> PdfValidator<'a> {
     /// Returns `Ok(true)` when the parsed document looks structurally valid.
     /// We rely on lopdf having already parsed the cross-reference table and
     /// object graph; here we just assert the mandatory elements are present.
-    pub(crate) fn validate_pdf_structure(&self) -> Result<bool> {
+    ///
+    /// With `allow_pageless` enabled, a document without pages is accepted
+    /// when its catalog carries an `/EmbeddedFiles` name tree.
+    pub(crate) fn validate_pdf_structure(&self, config: &ExtractorConfig) -> Result<bool> {
         // Catalog must exist
-        self.document
+        let catalog = self
+            .document
             .catalog()
             .map_err(|e| ExtractError::InvalidPdf(format!("missing or invalid catalog: {e}")))?;
 
-        // At least one page must exist
-        if self.document.get_pages().is_empty() {
+        // At least one page must exist, unless this is an attachment container
+        if self.document.get_pages().is_empty()
+            && !(config.allow_pageless && self.has_embedded_files_tree(catalog))
+        {
             return Err(ExtractError::InvalidPdf("document has no pages".into()));
         }
 
@@ -40,37 +71,47 @@ impl<'a> PdfValidator<'a> {
         Ok(true)
     }
 
+    /// Whether the catalog's `/Names` dictionary has an `/EmbeddedFiles` entry.
+    fn has_embedded_files_tree(&self, catalog: &lopdf::Dictionary) -> bool {
+        catalog
+            .get(b"Names")
+            .and_then(|names| match names {
+                lopdf::Object::Reference(id) => self.document.get_dictionary(*id),
+                other => other.as_dict(),
+            })
+            .is_ok_and(|names| names.has(b"EmbeddedFiles"))
+    }
+
     // ── PDF/A-3 conformance ───────────────────────────────────────────────────
 
-    /// Returns `Ok(true)` when the document's XMP metadata stream declares
-    /// PDF/A-3 conformance.
+    /// Returns `Ok(true)` when the parsed XMP metadata declares PDF/A-3
+    /// conformance.
     ///
-    /// All lopdf errors that arise from navigating the catalog → Metadata path
-    /// are mapped to `ExtractError::NotPdfA3` so the caller gets a clear
-    /// diagnostic rather than a raw parse error.
-    pub(crate) fn validate_pdfa3(&self, config: &ExtractorConfig) -> Result<bool> {
-        let xmp = self.read_xmp_metadata()?;
-        let is_pdfa3 = Self::xmp_declares_pdfa3(&xmp);
-
-        if config.strict_pdfa3_validation && !is_pdfa3 {
+    /// With `strict_pdfa3_validation` enabled, a document that does not declare
+    /// PDF/A-3 yields `ExtractError::NotPdfA3` instead of `Ok(false)`.
+    pub(crate) fn validate_pdfa3(xmp: &XmpInfo, config: &ExtractorConfig) -> Result<bool> {
+        if config.strict_pdfa3_validation && !xmp.declares_pdfa3 {
             return Err(ExtractError::NotPdfA3(
                 "document XMP does not declare PDF/A-3 conformance".into(),
             ));
         }
 
-        Ok(is_pdfa3)
+        Ok(xmp.declares_pdfa3)
     }
 
-    /// Returns the conformance level string (e.g. `"PDF/A-3B"`) when the XMP
-    /// metadata declares one, otherwise `None`.
-    pub(crate) fn conformance_level(&self) -> Option<String> {
-        let xmp = self.read_xmp_metadata().ok()?;
-        Self::extract_conformance_level(&xmp)
+    /// Read and parse the document's XMP metadata stream.
+    ///
+    /// All lopdf errors that arise from navigating the catalog → Metadata path
+    /// are mapped to `ExtractError::NotPdfA3` so the caller gets a clear
+    /// diagnostic rather than a raw parse error.
+    pub(crate) fn read_xmp(&self) -> Result<XmpInfo> {
+        self.read_xmp_metadata().map(XmpInfo::parse)
     }
 
     // ── Private helpers ───────────────────────────────────────────────────────
 
-    /// Walk catalog → /Metadata → stream → decompressed bytes → UTF-8 string.
+    /// Walk catalog → /Metadata → stream → decompressed bytes → string, see
+    /// [`xmp::decode`].
     fn read_xmp_metadata(&self) -> Result<String> {
         let catalog = self
             .document
@@ -102,61 +143,6 @@ impl<'a> PdfValidator<'a> {
             }
         };
 
-        Ok(String::from_utf8_lossy(&bytes).into_owned())
-    }
-
-    /// Parse the XMP string for `pdfaid:part` = 3 and a valid
-    /// `pdfaid:conformance` level (A, B, or U).
-    ///
-    /// XMP allows two serialisation forms:
-    /// - attribute syntax  : `pdfaid:part="3"`
-    /// - element syntax    : `<pdfaid:part>3</pdfaid:part>`
-    fn xmp_declares_pdfa3(xmp: &str) -> bool {
-        let has_part3 =
-            xmp.contains(r#"pdfaid:part="3""#) || xmp.contains("<pdfaid:part>3</pdfaid:part>");
-
-        if !has_part3 {
-            return false;
-        }
-
-        // Conformance level must be A, B, or U (case-sensitive per the spec)
-        for level in ["A", "B", "U"] {
-            let attr = format!(r#"pdfaid:conformance="{level}""#);
-            let elem = format!("<pdfaid:conformance>{level}</pdfaid:conformance>");
-            if xmp.contains(&attr) || xmp.contains(&elem) {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    /// Extract a human-readable conformance level string such as `"PDF/A-3B"`.
-    fn extract_conformance_level(xmp: &str) -> Option<String> {
-        // Determine part
-        let part = if xmp.contains(r#"pdfaid:part="3""#)
-            || xmp.contains("<pdfaid:part>3</pdfaid:part>")
-        {
-            "3"
-        } else if xmp.contains(r#"pdfaid:part="2""#) || xmp.contains("<pdfaid:part>2</pdfaid:part>")
-        {
-            "2"
-        } else if xmp.contains(r#"pdfaid:part="1""#) || xmp.contains("<pdfaid:part>1</pdfaid:part>")
-        {
-            "1"
-        } else {
-            return None;
-        };
-
-        // Determine conformance level
-        for level in ["A", "B", "U"] {
-            let attr = format!(r#"pdfaid:conformance="{level}""#);
-            let elem = format!("<pdfaid:conformance>{level}</pdfaid:conformance>");
-            if xmp.contains(&attr) || xmp.contains(&elem) {
-                return Some(format!("PDF/A-{part}{level}"));
-            }
-        }
-
-        None
+        Ok(xmp::decode(&bytes))
     }
 }
@@ -0,0 +1,79 @@
+//! JavaScript bindings generated with `wasm-bindgen`.
+//!
+//! Compiled only with the `wasm` feature. Everything is bytes-in/bytes-out:
+//! the browser supplies the PDF as a `Uint8Array` and receives attachments
+//! the same way, so no filesystem access is involved.
+
+use crate::{ExtractorConfig, PdfAnalyzer};
+use wasm_bindgen::prelude::*;
+
+/// A parsed PDF, exposed to JavaScript as `PdfAnalyzer`.
+///
+/// ```js
+/// const analyzer = new PdfAnalyzer(new Uint8Array(await file.arrayBuffer()));
+/// if (analyzer.isPdfA3()) {
+///     for (const name of analyzer.fileNames()) {
+///         console.log(name, analyzer.readText(name));
+///     }
+/// }
+/// ```
+#[wasm_bindgen(js_name = PdfAnalyzer)]
+pub struct WasmAnalyzer {
+    inner: PdfAnalyzer,
+}
+
+#[wasm_bindgen(js_class = PdfAnalyzer)]
+impl WasmAnalyzer {
+    /// Parse `bytes` as a PDF document.
+    ///
+    /// `maxFileSize`, if given, is applied as
+    /// [`ExtractorConfig::max_embedded_file_size`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8], max_file_size: Option<usize>) -> Result<WasmAnalyzer, JsError> {
+        let config = ExtractorConfig {
+            max_embedded_file_size: max_file_size,
+            ..Default::default()
+        };
+        Ok(Self {
+            inner: PdfAnalyzer::load_bytes(bytes, config)?,
+        })
+    }
+
+    /// Whether the document is a structurally valid PDF.
+    #[wasm_bindgen(js_name = isPdf)]
+    pub fn is_pdf(&self) -> Result<bool, JsError> {
+        Ok(self.inner.is_pdf()?)
+    }
+
+    /// Whether the XMP metadata declares PDF/A-3 conformance.
+    #[wasm_bindgen(js_name = isPdfA3)]
+    pub fn is_pdfa3(&self) -> Result<bool, JsError> {
+        Ok(self.inner.is_pdfa3()?)
+    }
+
+    /// The declared conformance level (e.g. `"PDF/A-3B"`), or `undefined`.
+    #[wasm_bindgen(js_name = conformanceLevel)]
+    pub fn conformance_level(&self) -> Option<String> {
+        self.inner.conformance_level()
+    }
+
+    /// The filenames of all embedded files, without decoding their streams.
+    #[wasm_bindgen(js_name = fileNames)]
+    pub fn file_names(&self) -> Result<Vec<String>, JsError> {
+        Ok(self.inner.embedded_file_names()?)
+    }
+
+    /// Decode the embedded file `name` and return its bytes.
+    #[wasm_bindgen(js_name = readFile)]
+    pub fn read_file(&self, name: &str) -> Result<Vec<u8>, JsError> {
+        Ok(self.inner.extract_embedded_file(name)?.data)
+    }
+
+    /// Decode the embedded file `name` as UTF-8 text (e.g. an invoice XML
+    /// for preview). Invalid sequences are replaced with `U+FFFD`.
+    #[wasm_bindgen(js_name = readText)]
+    pub fn read_text(&self, name: &str) -> Result<String, JsError> {
+        let file = self.inner.extract_embedded_file(name)?;
+        Ok(String::from_utf8_lossy(&file.data).into_owned())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::deadline::Deadline;
+use crate::{filters, EmbeddedFile, EmbeddedFileMetadata, PdfAnalyzer, Result};
+use lopdf::{Document, Object};
+
+/// Filename given to the datasets packet returned by
+/// [`PdfAnalyzer::xfa_datasets`].
+const DATASETS_FILENAME: &str = "xfa-datasets.xml";
+
+impl PdfAnalyzer {
+    /// Returns `true` when the document has an XFA form, an `/XFA` entry in
+    /// the catalog's `/AcroForm` dictionary.
+    pub fn has_xfa(&self) -> bool {
+        xfa(self.document()).is_some()
+    }
+
+    /// Returns the `datasets` packet of the document's XFA form as a
+    /// pseudo-attachment named `xfa-datasets.xml`, or `None` when there is
+    /// no XFA form or it has no datasets.
+    ///
+    /// Many legacy e-invoices carry their structured data as XFA form data
+    /// rather than as an embedded file, so this is where to look when
+    /// [`extract_embedded_files`](Self::extract_embedded_files) finds
+    /// nothing. The data is the `<xfa:datasets>` element, from its own
+    /// stream or cut out of a single-stream XDP document. It is not part of
+    /// any other extraction result.
+    ///
+    /// The stream is decoded under the analyzer's size, time and memory
+    /// limits. A stream that cannot be fully decoded is returned as far as
+    /// it was, with [`undecoded_filters`](EmbeddedFileMetadata::undecoded_filters)
+    /// set.
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let analyzer = PdfAnalyzer::from_path("form.pdf").unwrap();
+    /// if let Some(datasets) = analyzer.xfa_datasets().unwrap() {
+    ///     println!("{}", String::from_utf8_lossy(&datasets.data));
+    /// }
+    /// ```
+    pub fn xfa_datasets(&self) -> Result<Option<EmbeddedFile>> {
+        let document = self.document();
+        // Either one stream per packet, or the whole XDP document in one.
+        let (stream, whole_xdp) = match xfa(document) {
+            Some(Object::Array(packets)) => {
+                let datasets = packets
+                    .chunks_exact(2)
+                    .find(|packet| packet[0].as_str().ok() == Some(b"datasets"))
+                    .and_then(|packet| resolve(document, &packet[1])?.as_stream().ok());
+                match datasets {
+                    Some(stream) => (stream, false),
+                    None => return Ok(None),
+                }
+            }
+            Some(Object::Stream(xdp)) => (xdp, true),
+            _ => return Ok(None),
+        };
+
+        let config = self.config();
+        let decoded = filters::decode(
+            stream,
+            config.max_embedded_file_size,
+            config.recover_truncated_streams,
+            &Deadline::start(config.max_duration),
+            &self.memory_budget(),
+        )?;
+        let mut data = decoded.data;
+        if whole_xdp && decoded.undecoded_filters.is_empty() {
+            match datasets_element(&data) {
+                Some(range) => data = data[range].to_vec(),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(EmbeddedFile {
+            filename: DATASETS_FILENAME.into(),
+            metadata: EmbeddedFileMetadata {
+                mime_type: Some("application/xml".into()),
+                description: Some("XFA form data".into()),
+                stored_size: Some(stream.content.len()),
+                undecoded_filters: decoded.undecoded_filters,
+                truncated: decoded.truncated,
+                ..Default::default()
+            },
+            data,
+        }))
+    }
+}
+
+/// The catalog's `/AcroForm/XFA` entry, resolved.
+fn xfa(document: &Document) -> Option<&Object> {
+    let acro_form = resolve(document, document.catalog().ok()?.get(b"AcroForm").ok()?)?;
+    resolve(document, acro_form.as_dict().ok()?.get(b"XFA").ok()?)
+}
+
+/// `object`, or the object it references.
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    match object {
+        Object::Reference(id) => document.get_object(*id).ok(),
+        object => Some(object),
+    }
+}
+
+/// The byte range of the `<xfa:datasets>` element in an XDP document.
+fn datasets_element(xdp: &[u8]) -> Option<std::ops::Range<usize>> {
+    let start = find(xdp, b"<xfa:datasets")?;
+    let end = find(&xdp[start..], b"</xfa:datasets>")? + start + b"</xfa:datasets>".len();
+    Some(start..end)
+}
+
+/// The position of the first `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,373 @@
+use crate::{xmp, EmbeddedFile};
+
+// ── XmlFormat ─────────────────────────────────────────────────────────────────
+
+/// How [`EmbeddedFile::normalized_xml`] lays out an XML attachment, so that
+/// two invoices generated by different tools, or by the same tool on
+/// different days, compare and hash equal when only their formatting
+/// differs.
+///
+/// Normalization always decodes the document to UTF-8, converts line
+/// endings to `\n`, drops whitespace between elements, writes elements
+/// without content as `<name/>`, puts attributes in double quotes separated
+/// by a single space, and indents every element on its own line. Element
+/// text, attribute values, CDATA sections and entity references are kept
+/// as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct XmlFormat {
+    /// The indentation of each nesting level. An empty string writes the
+    /// document on a single line.
+    ///
+    /// Default: two spaces.
+    pub indent: String,
+
+    /// Write an `<?xml version="1.0" encoding="UTF-8"?>` declaration,
+    /// keeping the original version and `standalone` value. When `false`,
+    /// the declaration is dropped.
+    ///
+    /// Default: `true`.
+    pub declaration: bool,
+
+    /// Order each element's namespace declarations, then its other
+    /// attributes, by name, as XML canonicalization does.
+    ///
+    /// Default: `true`.
+    pub sort_attributes: bool,
+
+    /// Keep comments. When `false`, they are dropped.
+    ///
+    /// Default: `true`.
+    pub comments: bool,
+}
+
+impl Default for XmlFormat {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            declaration: true,
+            sort_attributes: true,
+            comments: true,
+        }
+    }
+}
+
+impl XmlFormat {
+    /// Normalizes the XML document `data`, which may be UTF-8 or UTF-16
+    /// with or without a byte order mark. Returns `None` when `data` is not
+    /// well-formed enough to lay out: not XML, an unterminated construct, or
+    /// mismatched tags.
+    pub fn normalize(&self, data: &[u8]) -> Option<String> {
+        let xml = xmp::decode(data).replace("\r\n", "\n").replace('\r', "\n");
+        let tokens = tokenize(xml.trim_start_matches('\u{feff}'))?;
+        self.write(&tokens)
+    }
+
+    /// Lay out `tokens`, checking that start and end tags match.
+    fn write(&self, tokens: &[Token<'_>]) -> Option<String> {
+        let mut out = String::new();
+        let mut open: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let depth = open.len();
+            match &tokens[i] {
+                Token::Declaration(attributes) => {
+                    if self.declaration && out.is_empty() {
+                        out.push_str(&declaration(attributes));
+                    }
+                }
+                Token::Instruction(text) => self.line(&mut out, depth, text),
+                Token::Comment(text) if self.comments => self.line(&mut out, depth, text),
+                Token::Comment(_) => {}
+                Token::Doctype(text) => self.line(&mut out, depth, text),
+                Token::Text(text) => {
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        if open.is_empty() {
+                            return None;
+                        }
+                        self.line(&mut out, depth, text);
+                    }
+                }
+                Token::CData(text) => self.line(&mut out, depth, text),
+                Token::Start {
+                    name,
+                    attributes,
+                    empty,
+                } => {
+                    let tag = self.start_tag(name, attributes);
+                    let content = leaf_content(&tokens[i + 1..]);
+                    if *empty {
+                        self.line(&mut out, depth, &format!("{tag}/>"));
+                    } else if let Some((content, len)) = content {
+                        match tokens.get(i + 1 + len) {
+                            Some(Token::End(end)) if end == name => {}
+                            _ => return None,
+                        }
+                        let element = if content.is_empty() {
+                            format!("{tag}/>")
+                        } else {
+                            format!("{tag}>{content}</{name}>")
+                        };
+                        self.line(&mut out, depth, &element);
+                        i += len + 1;
+                    } else {
+                        self.line(&mut out, depth, &format!("{tag}>"));
+                        open.push(name);
+                    }
+                }
+                Token::End(name) => {
+                    if open.pop() != Some(*name) {
+                        return None;
+                    }
+                    self.line(&mut out, depth - 1, &format!("</{name}>"));
+                }
+            }
+            i += 1;
+        }
+        if !open.is_empty() || out.is_empty() {
+            return None;
+        }
+        if !self.indent.is_empty() {
+            out.push('\n');
+        }
+        Some(out)
+    }
+
+    /// `<name` followed by `attributes`, in order if so configured.
+    fn start_tag(&self, name: &str, attributes: &[(&str, String)]) -> String {
+        let mut attributes: Vec<&(&str, String)> = attributes.iter().collect();
+        if self.sort_attributes {
+            attributes.sort_by_key(|(name, _)| (!is_namespace_declaration(name), *name));
+        }
+        let mut tag = format!("<{name}");
+        for (name, value) in attributes {
+            tag.push_str(&format!(" {name}=\"{value}\""));
+        }
+        tag
+    }
+
+    /// Append `text` to `out` on a new line indented to `depth`.
+    fn line(&self, out: &mut String, depth: usize, text: &str) {
+        if !out.is_empty() && !self.indent.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&self.indent.repeat(depth));
+        out.push_str(text);
+    }
+}
+
+impl EmbeddedFile {
+    /// The attachment's XML laid out by [`XmlFormat::default`], or `None`
+    /// when it is not XML. See [`normalized_xml_with`](Self::normalized_xml_with).
+    ///
+    /// ```
+    /// use extractembedfilepdf::EmbeddedFile;
+    ///
+    /// let file = EmbeddedFile {
+    ///     filename: "factur-x.xml".into(),
+    ///     data: b"<?xml version='1.0' encoding='ISO-8859-1'?>\r\n<a  z='1' y=\"2\"><b>x</b><c></c></a>"
+    ///         .to_vec(),
+    ///     metadata: Default::default(),
+    /// };
+    /// assert_eq!(
+    ///     file.normalized_xml().unwrap(),
+    ///     "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<a y=\"2\" z=\"1\">\n  <b>x</b>\n  <c/>\n</a>\n"
+    /// );
+    /// ```
+    pub fn normalized_xml(&self) -> Option<String> {
+        self.normalized_xml_with(&XmlFormat::default())
+    }
+
+    /// The attachment's XML laid out by `format`, or `None` when it is not
+    /// XML, for storing or hashing invoices so that regenerated versions
+    /// differ only where their content does.
+    pub fn normalized_xml_with(&self, format: &XmlFormat) -> Option<String> {
+        format.normalize(&self.data)
+    }
+
+    /// A copy of this file whose data is its XML laid out by `format`, ready
+    /// for [`save_to_disk`](Self::save_to_disk) or
+    /// [`sha256_hex`](Self::sha256_hex); files that are not XML are copied
+    /// unchanged. The metadata is copied as read from the PDF, so its
+    /// `size` and `checksum` describe the original data.
+    pub fn with_normalized_xml(&self, format: &XmlFormat) -> EmbeddedFile {
+        let mut file = self.clone();
+        if let Some(xml) = self.normalized_xml_with(format) {
+            file.data = xml.into_bytes();
+        }
+        file
+    }
+}
+
+/// A lexical unit of an XML document.
+#[derive(Debug)]
+enum Token<'a> {
+    /// The attributes of the `<?xml …?>` declaration.
+    Declaration(&'a str),
+    /// A processing instruction, verbatim.
+    Instruction(&'a str),
+    /// A comment, verbatim.
+    Comment(&'a str),
+    /// A document type declaration, verbatim.
+    Doctype(&'a str),
+    /// A CDATA section, verbatim.
+    CData(&'a str),
+    /// Character data between markup.
+    Text(&'a str),
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End(&'a str),
+}
+
+/// Split `xml` into tokens, or `None` when a construct is unterminated.
+fn tokenize(xml: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let Some(at) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if at > 0 {
+            tokens.push(Token::Text(&rest[..at]));
+        }
+        rest = &rest[at..];
+        let (token, len) = if rest.starts_with("<?xml ") || rest.starts_with("<?xml?") {
+            let end = rest.find("?>")?;
+            (Token::Declaration(rest[5..end].trim()), end + 2)
+        } else if rest.starts_with("<?") {
+            let end = rest.find("?>")? + 2;
+            (Token::Instruction(&rest[..end]), end)
+        } else if rest.starts_with("<!--") {
+            let end = rest.find("-->")? + 3;
+            (Token::Comment(&rest[..end]), end)
+        } else if rest.starts_with("<![CDATA[") {
+            let end = rest.find("]]>")? + 3;
+            (Token::CData(&rest[..end]), end)
+        } else if rest.starts_with("<!") {
+            let end = doctype_end(rest)?;
+            (Token::Doctype(&rest[..end]), end)
+        } else if let Some(name) = rest.strip_prefix("</") {
+            let end = name.find('>')?;
+            (Token::End(name[..end].trim_end()), end + 3)
+        } else {
+            let end = tag_end(rest)?;
+            let tag = &rest[1..end];
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            let name = &tag[..name_end];
+            if name.is_empty() {
+                return None;
+            }
+            let token = Token::Start {
+                name,
+                attributes: attributes(&tag[name_end..])?,
+                empty,
+            };
+            (token, end + 1)
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    Some(tokens)
+}
+
+/// The offset of the `>` that closes the start tag at the beginning of
+/// `xml`, skipping any inside quoted attribute values.
+fn tag_end(xml: &str) -> Option<usize> {
+    let mut quote = None;
+    for (at, c) in xml.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(at),
+            (None, '<') if at > 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The offset just past a `<!DOCTYPE …>` at the beginning of `xml`,
+/// including an internal subset in brackets.
+fn doctype_end(xml: &str) -> Option<usize> {
+    let mut nesting = 0usize;
+    for (at, c) in xml.char_indices() {
+        match c {
+            '[' => nesting += 1,
+            ']' => nesting = nesting.checked_sub(1)?,
+            '>' if nesting == 0 => return Some(at + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `name="value"` pairs in `text`, with values in the form to write
+/// between double quotes.
+fn attributes(text: &str) -> Option<Vec<(&str, String)>> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let (name, value) = rest.split_once('=')?;
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = value[1..].find(quote)? + 1;
+        attributes.push((name.trim(), value[1..end].replace('"', "&quot;")));
+        rest = value[end + 1..].trim_start();
+    }
+    Some(attributes)
+}
+
+/// The text of an element whose content starts `tokens` and holds no child
+/// elements, and the number of tokens it spans, or `None` when the element
+/// has children or mixed content.
+fn leaf_content(tokens: &[Token<'_>]) -> Option<(String, usize)> {
+    let mut content = String::new();
+    for (len, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Text(text) => content.push_str(text),
+            Token::CData(text) => content.push_str(text),
+            Token::End(_) => {
+                if content.trim().is_empty() {
+                    content.clear();
+                }
+                return Some((content, len));
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// The normalized declaration for the declaration attributes `attributes`.
+fn declaration(attributes: &str) -> String {
+    let attributes = self::attributes(attributes).unwrap_or_default();
+    let value = |name: &str| {
+        attributes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let mut declaration = format!(
+        "<?xml version=\"{}\" encoding=\"UTF-8\"",
+        value("version").unwrap_or("1.0")
+    );
+    if let Some(standalone) = value("standalone") {
+        declaration.push_str(&format!(" standalone=\"{standalone}\""));
+    }
+    declaration.push_str("?>");
+    declaration
+}
+
+/// Whether the attribute `name` declares a namespace.
+fn is_namespace_declaration(name: &str) -> bool {
+    name == "xmlns" || name.starts_with("xmlns:")
+}
@@ -0,0 +1,260 @@
+//! Tolerant reading of XMP packets.
+//!
+//! XMP is RDF/XML, but a full XML parser is not needed to find a handful of
+//! simple properties. Real producers differ in the encoding, the namespace
+//! prefix, the quoting, and whether properties are written as attributes or
+//! elements of one or several `rdf:Description` blocks; the functions here
+//! accept all of those.
+
+/// The namespace of the PDF/A identification schema (`pdfaid`).
+pub(crate) const PDFA_ID_NAMESPACE: &str = "http://www.aiim.org/pdfa/ns/id/";
+
+/// The namespace of the Dublin Core schema (`dc`).
+pub(crate) const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// The namespace of the XMP basic schema (`xmp`).
+pub(crate) const XMP_NAMESPACE: &str = "http://ns.adobe.com/xap/1.0/";
+
+/// The namespace of RDF itself (`rdf`).
+const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// The namespace of PDF/A schema descriptions (`pdfaSchema`).
+pub(crate) const PDFA_SCHEMA_NAMESPACE: &str = "http://www.aiim.org/pdfa/ns/schema#";
+
+/// The namespace of PDF/A property descriptions (`pdfaProperty`).
+pub(crate) const PDFA_PROPERTY_NAMESPACE: &str = "http://www.aiim.org/pdfa/ns/property#";
+
+/// Decode a metadata stream to text.
+///
+/// UTF-8 and both byte orders of UTF-16 are recognised by the byte order
+/// mark, or without one by the byte pattern of the leading `<`. Invalid
+/// sequences are replaced rather than rejected.
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    let (utf16, big_endian, body) = match bytes {
+        [0xef, 0xbb, 0xbf, rest @ ..] => (false, false, rest),
+        [0xfe, 0xff, rest @ ..] => (true, true, rest),
+        [0xff, 0xfe, rest @ ..] => (true, false, rest),
+        [0, b'<', ..] => (true, true, bytes),
+        [b'<', 0, ..] => (true, false, bytes),
+        _ => (false, false, bytes),
+    };
+    if !utf16 {
+        return String::from_utf8_lossy(body).into_owned();
+    }
+    let units: Vec<u16> = body
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if big_endian {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// The value of the simple property `name` of the schema `namespace`, from
+/// the first place it is set anywhere in `packet`, trimmed.
+///
+/// The property is looked up under every prefix the packet binds to
+/// `namespace`, plus `default_prefix` for packets that omit the declaration.
+/// Both the attribute form (`pdfaid:part="3"` or `pdfaid:part='3'`) and the
+/// element form (`<pdfaid:part>3</pdfaid:part>`) are recognised.
+pub(crate) fn property<'a>(
+    packet: &'a str,
+    namespace: &str,
+    default_prefix: &str,
+    name: &str,
+) -> Option<&'a str> {
+    property_values(packet, namespace, default_prefix, name)
+        .into_iter()
+        .next()
+}
+
+/// Every value the property `name` of `namespace` is set to in `packet`,
+/// trimmed, looked up as in [`property`].
+pub(crate) fn property_values<'a>(
+    packet: &'a str,
+    namespace: &str,
+    default_prefix: &str,
+    name: &str,
+) -> Vec<&'a str> {
+    with_default(bound_prefixes(packet, namespace), default_prefix)
+        .into_iter()
+        .flat_map(|prefix| {
+            let qualified = format!("{prefix}:{name}");
+            packet
+                .match_indices(&qualified)
+                .filter_map(|(at, _)| value_at(packet, at, qualified.len()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The items of the array property `name` of `namespace` (an `rdf:Seq`,
+/// `rdf:Bag` or `rdf:Alt`) with their `xml:lang`, from the first place it
+/// is set in `packet`, trimmed and in document order.
+///
+/// A property written as plain text instead of an array, as some producers
+/// do for `dc:title`, yields that text as its only item.
+pub(crate) fn array_items<'a>(
+    packet: &'a str,
+    namespace: &str,
+    default_prefix: &str,
+    name: &str,
+) -> Vec<(Option<&'a str>, &'a str)> {
+    let mut rdf = bound_prefixes(packet, RDF_NAMESPACE);
+    if !rdf.contains(&"rdf") {
+        rdf.push("rdf");
+    }
+
+    with_default(bound_prefixes(packet, namespace), default_prefix)
+        .into_iter()
+        .flat_map(|prefix| {
+            let qualified = format!("{prefix}:{name}");
+            let close = format!("</{qualified}>");
+            packet
+                .match_indices(&format!("<{qualified}"))
+                .filter_map(|(at, open)| {
+                    let rest = &packet[at + open.len()..];
+                    let (tag, content) = rest.split_once('>')?;
+                    let is_start_tag = tag.is_empty() || tag.starts_with(char::is_whitespace);
+                    if !is_start_tag || tag.ends_with('/') {
+                        return None;
+                    }
+                    Some(&content[..content.find(&close)?])
+                })
+                .collect::<Vec<_>>()
+        })
+        .map(|content| {
+            let items: Vec<_> = rdf
+                .iter()
+                .flat_map(|prefix| list_items(content, prefix))
+                .collect();
+            match content.trim() {
+                text if items.is_empty() && !text.is_empty() && !text.contains('<') => {
+                    vec![(None, text)]
+                }
+                _ => items,
+            }
+        })
+        .find(|items| !items.is_empty())
+        .unwrap_or_default()
+}
+
+/// The non-empty `<rdf:li>` items in `content`, with their `xml:lang`.
+fn list_items<'a>(content: &'a str, rdf: &str) -> Vec<(Option<&'a str>, &'a str)> {
+    let open = format!("<{rdf}:li");
+    content
+        .match_indices(&open)
+        .filter_map(|(at, _)| {
+            let rest = &content[at + open.len()..];
+            let (tag, text) = rest.split_once('>')?;
+            if !(tag.is_empty() || tag.starts_with(char::is_whitespace)) || tag.ends_with('/') {
+                return None;
+            }
+            let lang = tag
+                .split_once("xml:lang")
+                .and_then(|(_, value)| quoted(value.trim_start().strip_prefix('=')?));
+            let value = text[..text.find('<')?].trim();
+            (!value.is_empty()).then_some((lang, value))
+        })
+        .collect()
+}
+
+/// Replace the predefined XML entities and character references in `text`.
+/// Unknown or malformed references are kept as they are.
+pub(crate) fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let replacement = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match replacement {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `prefixes` plus `default_prefix`, for packets that omit the declaration.
+pub(crate) fn with_default<'a>(
+    mut prefixes: Vec<&'a str>,
+    default_prefix: &'a str,
+) -> Vec<&'a str> {
+    if !prefixes.contains(&default_prefix) {
+        prefixes.push(default_prefix);
+    }
+    prefixes
+}
+
+/// The prefixes declared for `namespace` by `xmlns:prefix="namespace"`.
+pub(crate) fn bound_prefixes<'a>(packet: &'a str, namespace: &str) -> Vec<&'a str> {
+    packet
+        .match_indices("xmlns:")
+        .filter_map(|(at, token)| {
+            let rest = &packet[at + token.len()..];
+            let end = rest.find(|c: char| c == '=' || c.is_whitespace())?;
+            let (prefix, rest) = rest.split_at(end);
+            (quoted(rest.trim_start().strip_prefix('=')?)? == namespace).then_some(prefix)
+        })
+        .collect()
+}
+
+/// The value of the property whose qualified name occupies
+/// `packet[at..at + len]`, if that occurrence sets it.
+fn value_at(packet: &str, at: usize, len: usize) -> Option<&str> {
+    let before = packet[..at].chars().next_back()?;
+    let rest = &packet[at + len..];
+    if before == '<' {
+        // Element form: the text between the start tag and the next tag.
+        let (tag, content) = rest.split_once('>')?;
+        let is_start_tag = tag.is_empty() || tag.starts_with(char::is_whitespace);
+        if !is_start_tag || tag.ends_with('/') {
+            return None;
+        }
+        let value = content[..content.find('<')?].trim();
+        (!value.is_empty()).then_some(value)
+    } else if before.is_whitespace() {
+        // Attribute form.
+        let value = quoted(rest.trim_start().strip_prefix('=')?)?.trim();
+        (!value.is_empty()).then_some(value)
+    } else {
+        None
+    }
+}
+
+/// The content of the quoted string `text` starts with, after whitespace.
+pub(crate) fn quoted(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    let quote = text.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let text = &text[1..];
+    Some(&text[..text.find(quote)?])
+}
@@ -0,0 +1,245 @@
+use crate::pdf_utils::{safe_filename, PdfDate};
+use crate::{EmbeddedFile, ExtractionReport};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// `PK\x03\x04`: local file header.
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+/// `PK\x01\x02`: central directory file header.
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+/// `PK\x05\x06`: end of central directory record.
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+/// Version 2.0, the first to support Deflate.
+const VERSION: u16 = 20;
+/// General purpose flag bit 11: the filename is UTF-8.
+const UTF8_NAME: u16 = 1 << 11;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+/// 1980-01-01 00:00, the earliest time a ZIP entry can carry.
+const DOS_EPOCH: (u16, u16) = (0, (1 << 5) | 1);
+
+impl ExtractionReport {
+    /// Packs the [`files`](Self::files) into a ZIP archive in memory, so that
+    /// a web service can return all attachments as one download without
+    /// touching the disk.
+    ///
+    /// Each entry is Deflate-compressed unless that would make it larger, and
+    /// dated by the attachment's `/Params/ModDate` (or 1980-01-01 without
+    /// one). The archive comment holds the SHA-256 digest of the source
+    /// document. Entry names are reduced to their last path component, so
+    /// unpacking the archive cannot write outside the target directory, and
+    /// of several files with the same name only the first is packed, as when
+    /// extracting to disk.
+    ///
+    /// ZIP64 is not supported: an archive of 4 GiB or more, or of more than
+    /// 65535 files, fails with [`io::ErrorKind::InvalidInput`].
+    ///
+    /// ```no_run
+    /// use extractembedfilepdf::PdfAnalyzer;
+    ///
+    /// let report = PdfAnalyzer::from_path("invoice.pdf")
+    ///     .unwrap()
+    ///     .extract_with_report()
+    ///     .unwrap();
+    /// std::fs::write("attachments.zip", report.to_zip_bytes().unwrap()).unwrap();
+    /// ```
+    pub fn to_zip_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut directory = Vec::new();
+        let mut packed = HashSet::new();
+        let mut entries: u16 = 0;
+
+        for file in &self.files {
+            let name = safe_filename(&file.filename);
+            if !packed.insert(name.clone()) {
+                continue;
+            }
+            let offset = fit(out.len())?;
+            let entry = Entry::new(file, name)?;
+            entry.write_header(&mut out, LOCAL_HEADER, None)?;
+            out.extend_from_slice(&entry.content);
+            entry.write_header(&mut directory, CENTRAL_HEADER, Some(offset))?;
+            entries = entries.checked_add(1).ok_or_else(too_large)?;
+        }
+
+        let directory_offset = fit(out.len())?;
+        let directory_len = fit(directory.len())?;
+        out.extend_from_slice(&directory);
+        let comment = match &self.source_sha256 {
+            Some(sha256) => format!("source sha256 {sha256}"),
+            None => String::new(),
+        };
+        put_u32(&mut out, END_OF_DIRECTORY);
+        put_u16(&mut out, 0); // this disk
+        put_u16(&mut out, 0); // disk with the central directory
+        put_u16(&mut out, entries);
+        put_u16(&mut out, entries);
+        put_u32(&mut out, directory_len);
+        put_u32(&mut out, directory_offset);
+        put_u16(&mut out, fit(comment.len())?);
+        out.extend_from_slice(comment.as_bytes());
+        Ok(out)
+    }
+}
+
+/// One archive member, compressed and ready to be written.
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    size: u32,
+    content: Vec<u8>,
+    time: u16,
+    date: u16,
+}
+
+impl Entry {
+    fn new(file: &EmbeddedFile, name: String) -> io::Result<Self> {
+        let mut crc = Crc::new();
+        crc.update(&file.data);
+
+        // Nothing compresses an empty file.
+        let deflated = (!file.data.is_empty()).then(|| deflate(&file.data));
+        let (method, content) = match deflated.flatten() {
+            Some(deflated) if deflated.len() < file.data.len() => (DEFLATED, deflated),
+            _ => (STORED, file.data.clone()),
+        };
+        let (time, date) = file
+            .metadata
+            .modification_date
+            .as_deref()
+            .and_then(dos_time)
+            .unwrap_or(DOS_EPOCH);
+
+        Ok(Self {
+            name,
+            method,
+            crc: crc.sum(),
+            size: fit(file.data.len())?,
+            content,
+            time,
+            date,
+        })
+    }
+
+    /// Write a local header, or a central directory header when the local
+    /// header's `offset` is given.
+    fn write_header(
+        &self,
+        out: &mut Vec<u8>,
+        signature: u32,
+        offset: Option<u32>,
+    ) -> io::Result<()> {
+        put_u32(out, signature);
+        if offset.is_some() {
+            put_u16(out, VERSION); // version made by
+        }
+        put_u16(out, VERSION); // version needed to extract
+        put_u16(out, UTF8_NAME);
+        put_u16(out, self.method);
+        put_u16(out, self.time);
+        put_u16(out, self.date);
+        put_u32(out, self.crc);
+        put_u32(out, fit(self.content.len())?);
+        put_u32(out, self.size);
+        put_u16(out, fit(self.name.len())?);
+        put_u16(out, 0); // extra field length
+        if let Some(offset) = offset {
+            put_u16(out, 0); // comment length
+            put_u16(out, 0); // disk number
+            put_u16(out, 0); // internal attributes
+            put_u32(out, 0); // external attributes
+            put_u32(out, offset);
+        }
+        out.extend_from_slice(self.name.as_bytes());
+        Ok(())
+    }
+}
+
+// ── Reading ───────────────────────────────────────────────────────────────────
+
+/// General purpose flag bit 0: the entry is encrypted.
+const ENCRYPTED: u16 = 1;
+
+/// Whether any entry of the ZIP archive `data` is encrypted, judged by the
+/// flags of its central directory. An archive whose directory cannot be
+/// read, e.g. because it was cut short, is judged by its first local header.
+pub(crate) fn has_encrypted_entry(data: &[u8]) -> bool {
+    central_directory_encrypted(data)
+        .unwrap_or_else(|| get_u16(data, 6).is_some_and(|flags| flags & ENCRYPTED != 0))
+}
+
+/// Walk the central directory; `None` if it cannot be found or read.
+fn central_directory_encrypted(data: &[u8]) -> Option<bool> {
+    // The end record is 22 bytes, followed by a comment of at most 64 KiB.
+    let search_from = data.len().saturating_sub(22 + usize::from(u16::MAX));
+    let end = search_from
+        + data[search_from..]
+            .windows(4)
+            .rposition(|w| w == END_OF_DIRECTORY.to_le_bytes())?;
+    let count = get_u16(data, end + 10)?;
+    let mut pos = usize::try_from(get_u32(data, end + 16)?).ok()?;
+    for _ in 0..count {
+        if get_u32(data, pos)? != CENTRAL_HEADER {
+            return None;
+        }
+        if get_u16(data, pos + 8)? & ENCRYPTED != 0 {
+            return Some(true);
+        }
+        let name = usize::from(get_u16(data, pos + 28)?);
+        let extra = usize::from(get_u16(data, pos + 30)?);
+        let comment = usize::from(get_u16(data, pos + 32)?);
+        pos += 46 + name + extra + comment;
+    }
+    Some(false)
+}
+
+fn get_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn get_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// `value` as a ZIP header field, or an error when it needs ZIP64.
+fn fit<T: TryFrom<usize>>(value: usize) -> io::Result<T> {
+    T::try_from(value).map_err(|_| too_large())
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "too large for a ZIP archive without ZIP64",
+    )
+}
+
+/// Raw Deflate-compress `data`.
+fn deflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).ok()?;
+    encoder.finish().ok()
+}
+
+/// Convert a PDF date to MS-DOS `(time, date)`. DOS times are local, so the
+/// time zone is ignored.
+fn dos_time(pdf_date: &str) -> Option<(u16, u16)> {
+    let date = PdfDate::parse(pdf_date)?;
+    if !(1980..=2107).contains(&date.year) {
+        return None;
+    }
+    Some((
+        (date.hour << 11) | (date.minute << 5) | (date.second / 2),
+        ((date.year - 1980) << 9) | (date.month << 5) | date.day,
+    ))
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,76 @@
+// Peak-memory checks for the stream decoders.
+//
+// These tests run in their own binary with a counting global allocator, so
+// the numbers they see are not disturbed by tests running in parallel.
+
+use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+use extractembedfilepdf::{ExtractorConfig, PdfAnalyzer};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(live, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    // Count a reallocation as growing the block in place, as the system
+    // allocator does for large blocks, so only what is held shows up.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            let grown = new_size - layout.size();
+            let live = LIVE.fetch_add(grown, Ordering::Relaxed) + grown;
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        } else {
+            LIVE.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[test]
+fn inflate_never_holds_more_than_limit_plus_one() {
+    const LIMIT: usize = 1_048_577;
+
+    // 4 MiB of zeros compress to a few KiB; without /Params/Size nothing
+    // screens the stream before it is inflated.
+    let pdf = PdfBuilder::new()
+        .attachment(Attachment {
+            compress: true,
+            params: false,
+            ..Attachment::new("bomb.bin", vec![0u8; 4 << 20])
+        })
+        .build();
+    let config = ExtractorConfig {
+        max_embedded_file_size: Some(LIMIT),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&pdf, config).unwrap();
+    drop(pdf);
+
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let files = analyzer.extract_embedded_files();
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+
+    assert!(files.map_or(true, |files| files.is_empty()));
+    // The output buffer may reach LIMIT + 1 bytes; the rest is decoder state.
+    assert!(
+        peak <= LIMIT + 1 + (256 << 10),
+        "peak {peak} bytes for a limit of {LIMIT}"
+    );
+}
@@ -5,7 +5,10 @@
 // in a `tests/fixtures/` directory and are marked `#[ignore]` so the CI pass
 // even without those files.
 
-use extractembedfilepdf::{EmbeddedFile, EmbeddedFileMetadata, ExtractError, ExtractorConfig};
+use extractembedfilepdf::{
+    AfRelationship, AttachmentSource, EmbeddedFile, EmbeddedFileMetadata, ExtractError,
+    ExtractorConfig,
+};
 
 // ── ExtractorConfig ───────────────────────────────────────────────────────────
 
@@ -16,6 +19,8 @@ fn default_config_is_permissive() {
     assert!(cfg.max_embedded_file_size.is_none());
     assert!(!cfg.extract_to_disk);
     assert!(cfg.output_directory.is_none());
+    assert!(cfg.discovery_sources.is_none());
+    assert!(!cfg.allow_pageless);
 }
 
 #[test]
@@ -25,6 +30,7 @@ fn custom_config_round_trips() {
         max_embedded_file_size: Some(1024),
         extract_to_disk: true,
         output_directory: Some("./out".into()),
+        ..Default::default()
     };
     assert!(cfg.strict_pdfa3_validation);
     assert_eq!(cfg.max_embedded_file_size, Some(1024));
@@ -66,10 +72,224 @@ fn save_to_disk_creates_file() {
 
     let written = std::fs::read(dir.path().join("test.txt")).unwrap();
     assert_eq!(written, b"hello world");
+
+    let inner = dir.path().join("inner");
+    for name in ["../up.txt", "/abs/up.txt", r"C:\Windows\up.txt"] {
+        make_file(name, b"x").save_to_disk(&inner).unwrap();
+        std::fs::remove_file(inner.join("up.txt")).unwrap();
+        assert!(!dir.path().join("up.txt").exists(), "{name}");
+    }
+    make_file("..", b"x").save_to_disk(&inner).unwrap();
+    assert!(inner.join("attachment").exists());
+}
+
+#[test]
+fn save_to_path_writes_the_exact_destination() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = make_file("test.txt", b"hello world");
+    let dest = dir.path().join("nested/out/renamed.bin");
+    file.save_to_path(&dest).unwrap();
+
+    assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+    assert!(!dir.path().join("nested/out/test.txt").exists());
+}
+
+#[test]
+fn size_accessors_compare_against_declaration() {
+    let mut file = make_file("test.txt", b"hello");
+    assert_eq!((file.len(), file.is_empty()), (5, false));
+    assert_eq!(file.declared_size(), None);
+    assert_eq!(file.size_matches_declaration(), None);
+
+    file.metadata.size = Some(5);
+    assert_eq!(file.size_matches_declaration(), Some(true));
+    file.metadata.size = Some(6);
+    assert_eq!(file.size_matches_declaration(), Some(false));
+
+    assert!(make_file("empty.txt", b"").is_empty());
+}
+
+#[test]
+fn verify_checksum_matches_md5_of_data() {
+    let mut file = make_file("test.txt", b"hello world");
+    assert_eq!(file.verify_checksum(), None);
+
+    file.metadata.checksum = Some("5EB63BBBE01EEED093CB22BB8F5ACDC3".into());
+    assert_eq!(file.verify_checksum(), Some(true));
+
+    file.data.push(b'!');
+    assert_eq!(file.verify_checksum(), Some(false));
+}
+
+#[test]
+fn readers_yield_the_data() {
+    use std::io::Read;
+
+    let file = make_file("test.txt", b"hello world");
+    let mut head = [0; 5];
+    file.as_reader().read_exact(&mut head).unwrap();
+    assert_eq!(&head, b"hello");
+
+    let mut all = String::new();
+    file.into_reader().read_to_string(&mut all).unwrap();
+    assert_eq!(all, "hello world");
+}
+
+#[test]
+fn pdf_attachments_can_be_analyzed() {
+    let inner = pdf_with_attachment("inner.xml", b"<a/>", lopdf::Dictionary::new());
+    let outer = pdf_with_attachment("inner.pdf", &inner, lopdf::Dictionary::new());
+
+    let files = extractembedfilepdf::PdfAnalyzer::from_bytes(&outer)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    assert!(files[0].is_pdf());
+    let nested = files[0].analyze().unwrap();
+    assert_eq!(nested.embedded_file_names().unwrap(), ["inner.xml"]);
+
+    assert!(!make_file("a.xml", b"<a/>").is_pdf());
+    assert!(make_file("a.pdf", b"not a pdf").analyze().is_err());
+
+    // The limits of the enclosing document can be carried over.
+    let limited = ExtractorConfig {
+        max_memory_bytes: Some(inner.len() - 1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        files[0].analyze_with_config(limited),
+        Err(ExtractError::MemoryLimit(_))
+    ));
+}
+
+#[test]
+fn preview_shows_text_or_hex() {
+    assert_eq!(
+        make_file("a.txt", b"hello world").preview(100),
+        "hello world"
+    );
+    assert_eq!(make_file("a.txt", "héllo".as_bytes()).preview(2), "h…");
+
+    let binary: Vec<u8> = (0..20).collect();
+    let preview = make_file("a.bin", &binary).preview(18);
+    let lines: Vec<_> = preview.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("00000000  00 01 02"));
+    assert!(lines[1].starts_with("00000010  10 11 "));
+    assert_eq!(lines[2], "…");
+}
+
+#[test]
+fn into_data_and_into_parts_hand_over_the_buffer() {
+    let file = make_file("a.txt", b"hello");
+    let pointer = file.data.as_ptr();
+    let data = file.clone().into_data();
+    assert_eq!(data, b"hello");
+
+    let (filename, data, metadata) = file.into_parts();
+    assert_eq!(filename, "a.txt");
+    assert_eq!(data.as_ptr(), pointer);
+    assert!(metadata.mime_type.is_none());
+}
+
+#[test]
+fn equality_compares_content_only() {
+    use std::collections::HashSet;
+
+    let a = make_file("a.xml", b"<a/>");
+    let mut found_twice = a.clone();
+    found_twice.metadata.page = Some(2);
+    let renamed = make_file("b.xml", b"<a/>");
+
+    let changed = make_file("a.xml", b"<b/>");
+
+    assert_eq!(a, found_twice);
+    assert_eq!(a, renamed);
+    assert_ne!(a, changed);
+    assert!(a.same_content_as(&renamed));
+    assert!(!a.same_content_as(&changed));
+
+    let unique: HashSet<_> = [a, found_twice, renamed, changed].into_iter().collect();
+    assert_eq!(unique.len(), 2);
+}
+
+#[test]
+fn classify_combines_content_mime_and_name() {
+    use extractembedfilepdf::{AttachmentKind, PdfAnalyzer};
+
+    let cii = b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<rsm:CrossIndustryInvoice/>";
+    assert_eq!(
+        make_file("invoice.xml", cii).classify(),
+        AttachmentKind::InvoiceXml
+    );
+    assert_eq!(
+        make_file("factur-x.xml", b"<a/>").classify(),
+        AttachmentKind::InvoiceXml
+    );
+    assert_eq!(
+        make_file("notes.xml", b"<a/>").classify(),
+        AttachmentKind::Other
+    );
+
+    let mut icc = vec![0; 128];
+    icc[36..40].copy_from_slice(b"acsp");
+    assert_eq!(
+        make_file("sRGB", &icc).classify(),
+        AttachmentKind::IccProfile
+    );
+    assert_eq!(
+        make_file("a.bin", b"%PDF-1.7\n").classify(),
+        AttachmentKind::Pdf
+    );
+    assert_eq!(
+        make_file("a.pdf", b"\x89PNG\r\n\x1a\n").classify(),
+        AttachmentKind::Image
+    );
+    assert_eq!(
+        make_file("a", b"PK\x03\x04....a.txt").classify(),
+        AttachmentKind::Archive
+    );
+    assert_eq!(
+        make_file("a.zip", b"PK\x03\x04[Content_Types].xml").classify(),
+        AttachmentKind::Office
+    );
+
+    // Without a known signature the declared MIME type, then the name, decide.
+    let mut encoded = make_file("photo.dat", b"x\x9c...");
+    encoded.metadata.mime_type = Some("image/jpeg".into());
+    assert_eq!(encoded.classify(), AttachmentKind::Image);
+    assert_eq!(
+        make_file("Report.DOCX", b"x\x9c...").classify(),
+        AttachmentKind::Office
+    );
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    for file in analyzer.extract_embedded_files().unwrap() {
+        assert_eq!(file.classify(), AttachmentKind::Office, "{}", file.filename);
+    }
 }
 
 // ── EmbeddedFileMetadata ──────────────────────────────────────────────────────
 
+#[test]
+fn metadata_display_lists_set_fields() {
+    assert_eq!(EmbeddedFileMetadata::default().to_string(), "-");
+
+    let mut file = make_file("factur-x.xml", &[b'x'; 5902]);
+    file.metadata.mime_type = Some("text/xml".into());
+    file.metadata.size = Some(5902);
+    file.metadata.modification_date = Some("D:20240101120000Z".into());
+    assert_eq!(
+        file.metadata.to_string(),
+        "mime: text/xml, size: 5.8 KiB, modified: D:20240101120000Z"
+    );
+    assert_eq!(
+        file.summary(),
+        "factur-x.xml (5.8 KiB, text/xml, modified D:20240101120000Z)"
+    );
+    assert_eq!(make_file("a.bin", b"ab").summary(), "a.bin (2 B)");
+}
+
 #[test]
 fn metadata_is_xml_true_for_xml_mime() {
     let m = EmbeddedFileMetadata {
@@ -93,16 +313,65 @@ fn metadata_has_mime_type_case_insensitive() {
     assert!(m.has_mime_type("application/xml"));
 }
 
+#[test]
+fn af_relationship_round_trips_through_pdf_name() {
+    for name in ["Source", "Data", "Alternative", "Supplement", "Unspecified"] {
+        assert_eq!(AfRelationship::from_name(name).as_str(), name);
+    }
+    assert_eq!(
+        AfRelationship::from_name("XYZ_Custom"),
+        AfRelationship::Other("XYZ_Custom".into())
+    );
+}
+
+#[test]
+fn attachment_source_display_is_cli_friendly() {
+    assert_eq!(AttachmentSource::NamesTree.to_string(), "names-tree");
+    assert_eq!(AttachmentSource::Annotation.to_string(), "annotation");
+}
+
+#[test]
+fn attachment_source_parses_cli_identifiers() {
+    for source in [
+        AttachmentSource::NamesTree,
+        AttachmentSource::Annotation,
+        AttachmentSource::AssociatedFiles,
+        AttachmentSource::DeepScan,
+    ] {
+        assert_eq!(source.as_str().parse::<AttachmentSource>(), Ok(source));
+    }
+    assert_eq!("annotations".parse(), Ok(AttachmentSource::Annotation));
+    assert!("bogus".parse::<AttachmentSource>().is_err());
+}
+
 // ── ExtractError display ──────────────────────────────────────────────────────
 
 #[test]
 fn error_display_is_non_empty() {
+    use extractembedfilepdf::ExtractionStage;
+
     let errors: &[ExtractError] = &[
         ExtractError::InvalidPdf("test".into()),
         ExtractError::NotPdfA3("test".into()),
         ExtractError::NoEmbeddedFiles,
-        ExtractError::ExtractionError("f".into(), "reason".into()),
+        ExtractError::ExtractionError {
+            name: "f".into(),
+            spec_id: None,
+            stage: ExtractionStage::Decode,
+            reason: "reason".into(),
+            cause: None,
+        },
+        ExtractError::FileNotFound("f".into()),
         ExtractError::FileSizeExceeded,
+        ExtractError::Timeout(std::time::Duration::from_secs(1)),
+        ExtractError::Infected {
+            name: "f".into(),
+            threat: "t".into(),
+        },
+        ExtractError::DangerousFile {
+            name: "f".into(),
+            category: extractembedfilepdf::RiskCategory::Script,
+        },
     ];
     for e in errors {
         assert!(!e.to_string().is_empty(), "empty display for {e:?}");
@@ -123,27 +392,4810 @@ fn from_bytes_rejects_non_pdf() {
     assert!(PdfAnalyzer::from_bytes(b"not a pdf").is_err());
 }
 
-// ── Fixture-based tests (ignored without real PDFs) ───────────────────────────
+// ── Bundled example PDFs ──────────────────────────────────────────────────────
+
+fn example_pdf(name: &str) -> String {
+    format!("{}/examples/{name}", env!("CARGO_MANIFEST_DIR"))
+}
 
-/// To run: place a valid PDF/A-3 with embedded files at
-/// `tests/fixtures/sample_pdfa3.pdf` and run with `--include-ignored`.
 #[test]
-#[ignore]
-fn fixture_pdfa3_roundtrip() {
+fn embedded_file_names_lists_without_decoding() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let names = analyzer.embedded_file_names().unwrap();
+    assert_eq!(names, ["Excel_embed1.xlsx", "Word_embed.docx"]);
+}
+
+#[test]
+fn extract_embedded_file_by_name() {
     use extractembedfilepdf::PdfAnalyzer;
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
 
-    let bytes = std::fs::read("tests/fixtures/sample_pdfa3.pdf")
-        .expect("place tests/fixtures/sample_pdfa3.pdf to run this test");
+    let file = analyzer.extract_embedded_file("Word_embed.docx").unwrap();
+    assert_eq!(file.filename, "Word_embed.docx");
+    assert_eq!(file.data.len(), 5308);
+
+    assert!(matches!(
+        analyzer.extract_embedded_file("missing.xml"),
+        Err(ExtractError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn handles_expose_metadata_and_decode_on_demand() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+
+    let handles = analyzer.embedded_file_handles().unwrap();
+    let names: Vec<_> = handles.iter().map(|h| h.filename()).collect();
+    assert_eq!(names, ["Excel_embed1.xlsx", "Word_embed.docx"]);
+    assert_eq!(
+        handles[1].metadata().source,
+        Some(AttachmentSource::NamesTree)
+    );
+
+    let file = handles[1].decode().unwrap();
+    assert_eq!(file.data, handles[1].read().unwrap());
+    assert_eq!(file.data.len(), 5308);
+}
+
+#[test]
+fn attachments_group_by_af_relationship() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
 
+    let bytes = build_pdf(|doc, catalog| {
+        let mut names = Vec::new();
+        for (name, relationship) in [
+            ("invoice.xml", Some("Data")),
+            ("scan.tiff", None),
+            ("order.xml", Some("Data")),
+            ("invoice.docx", Some("Source")),
+        ] {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, b"x".to_vec()));
+            let mut spec = dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(name),
+                "EF" => dictionary! { "F" => stream_id },
+            };
+            if let Some(relationship) = relationship {
+                spec.set("AFRelationship", Object::Name(relationship.into()));
+            }
+            names.push(Object::string_literal(name));
+            names.push(doc.add_object(spec).into());
+        }
+        let tree_id = doc.add_object(dictionary! { "Names" => names });
+        catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+    });
     let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
-    assert!(analyzer.is_pdf().unwrap());
-    assert!(analyzer.is_pdfa3().unwrap());
-    assert!(analyzer.has_embedded_files().unwrap());
 
+    let groups = analyzer.attachments_by_relationship().unwrap();
+    let grouped: Vec<(String, Vec<&str>)> = groups
+        .iter()
+        .map(|(r, files)| (r.to_string(), files.iter().map(|f| f.filename()).collect()))
+        .collect();
+    assert_eq!(
+        grouped,
+        [
+            ("Source".to_string(), vec!["invoice.docx"]),
+            ("Data".to_string(), vec!["invoice.xml", "order.xml"]),
+            ("Unspecified".to_string(), vec!["scan.tiff"]),
+        ]
+    );
+
+    let data: Vec<_> = analyzer.data_files().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[0].read().unwrap(), b"x");
+    assert_eq!(
+        analyzer.source_files().unwrap()[0].filename(),
+        "invoice.docx"
+    );
+}
+
+#[test]
+fn object_refs_and_dict_views_hide_the_backend() {
+    use extractembedfilepdf::{ObjectRef, PdfAnalyzer};
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+
+    let handle = &analyzer.embedded_file_handles().unwrap()[0];
+    let spec = handle.spec_dict().unwrap();
+    assert_eq!(spec.get_name(b"Type").as_deref(), Some("Filespec"));
+    assert!(spec.keys().any(|key| key == b"EF"));
+    let stream = spec.get_dict(b"EF").unwrap().get_dict(b"F").unwrap();
+    assert!(stream.get_int(b"Length").is_some_and(|length| length > 0));
+    assert!(spec.get_str(b"Missing").is_none());
+
+    let object = handle.spec_ref();
+    assert_eq!(ObjectRef::from((object.number, object.generation)), object);
+    assert_eq!(
+        ObjectRef {
+            number: 12,
+            generation: 0
+        }
+        .to_string(),
+        "12 0 R"
+    );
+    assert!(analyzer
+        .dictionary(ObjectRef {
+            number: u32::MAX,
+            generation: 0
+        })
+        .is_none());
+}
+
+#[test]
+fn analyze_bundles_validation_and_extraction() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let report = analyzer.analyze().unwrap();
+    assert!(report.is_pdf);
+    assert_eq!(report.files.len(), 2);
+    assert!(report.warnings.is_empty());
+    assert!(report.security_findings.is_empty());
+
+    let empty = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {})).unwrap();
+    let report = empty.analyze().unwrap();
+    assert!(report.is_pdf);
+    assert!(!report.is_pdfa3);
+    assert!(report.conformance_level.is_none());
+    assert!(report.files.is_empty());
+
+    let broken = PdfAnalyzer::from_bytes(&pdf_with_broken_attachment()).unwrap();
+    let report = broken.analyze().unwrap();
+    assert_eq!(
+        report.warnings[0].kind,
+        extractembedfilepdf::WarningKind::Skipped
+    );
+}
+
+#[test]
+fn extraction_statistics_summarise_the_files() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let stats = analyzer.extract_with_report().unwrap().statistics;
+    assert_eq!(stats.file_count, 2);
+    assert_eq!(stats.total_bytes, 5902 + 5308);
+    assert_eq!(stats.largest_file.as_deref(), Some("Excel_embed1.xlsx"));
+    assert_eq!(stats.largest_file_size, 5902);
+    assert_eq!(stats.by_mime_type.values().sum::<usize>(), 2);
+    assert_eq!(stats.by_source[&AttachmentSource::NamesTree], 2);
+    assert!(stats.stored_bytes > 0);
+    assert_eq!(analyzer.analyze().unwrap().statistics, stats);
+
+    let untyped = PdfAnalyzer::from_bytes(&pdf_with_attachment(
+        "a.bin",
+        b"abcd",
+        lopdf::Dictionary::new(),
+    ))
+    .unwrap();
+    let report = untyped.extract_with_report().unwrap();
+    assert_eq!(report.statistics.by_mime_type["-"], 1);
+    assert_eq!(report.files[0].metadata.stored_size, Some(4));
+    assert_eq!(report.files[0].compression_ratio(), Some(1.0));
+    assert_eq!(report.statistics.compression_ratio(), Some(1.0));
+}
+
+#[test]
+fn zip_bytes_pack_every_file_with_its_date() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::dictionary;
+    use std::io::Read;
+
+    let config = ExtractorConfig {
+        hash_source: true,
+        ..Default::default()
+    };
+    let path = example_pdf("format_a3_withFiles.pdf");
+    let analyzer = PdfAnalyzer::with_config(path, config).unwrap();
+    let report = analyzer.extract_with_report().unwrap();
+    let bytes = report.to_zip_bytes().unwrap();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(archive.len(), 2);
+    assert_eq!(
+        std::str::from_utf8(archive.comment()).unwrap(),
+        format!("source sha256 {}", analyzer.source_sha256().unwrap())
+    );
+    for file in &report.files {
+        let mut entry = archive.by_name(&file.filename).unwrap();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).unwrap();
+        assert_eq!(data, file.data);
+    }
+
+    let xml = b"<invoice>".repeat(100);
+    let params =
+        dictionary! { "ModDate" => lopdf::Object::string_literal("D:20240315103042+01'00'") };
+    let dated = PdfAnalyzer::from_bytes(&pdf_with_attachment("ü.xml", &xml, params)).unwrap();
+    let bytes = dated.extract_with_report().unwrap().to_zip_bytes().unwrap();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let entry = archive.by_index(0).unwrap();
+    assert_eq!(entry.name(), "ü.xml");
+    assert_eq!(entry.compression(), zip::CompressionMethod::Deflated);
+    assert!(entry.compressed_size() < entry.size());
+    let modified = entry.last_modified();
+    assert_eq!(
+        (modified.year(), modified.month(), modified.day()),
+        (2024, 3, 15)
+    );
+    assert_eq!(
+        (modified.hour(), modified.minute(), modified.second()),
+        (10, 30, 42)
+    );
+
+    // Entry names cannot escape the directory the archive is unpacked into.
+    let names = [
+        "../evil.txt",
+        "/etc/passwd",
+        r"C:\Windows\run.bat",
+        "evil.txt",
+    ];
+    let hostile = extractembedfilepdf::ExtractionReport {
+        files: names.iter().map(|name| make_file(name, b"x")).collect(),
+        ..Default::default()
+    };
+    let bytes = hostile.to_zip_bytes().unwrap();
+    let archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let names: Vec<_> = archive.file_names().collect();
+    assert_eq!(names.len(), 3);
+    for name in ["evil.txt", "passwd", "run.bat"] {
+        assert!(names.contains(&name), "{names:?}");
+    }
+
+    // More entries than the ZIP format can count need ZIP64.
+    let crowded = extractembedfilepdf::ExtractionReport {
+        files: (0..=u16::MAX as u32)
+            .map(|i| make_file(&i.to_string(), b""))
+            .collect(),
+        ..Default::default()
+    };
+    let error = crowded.to_zip_bytes().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn tar_bytes_pack_every_file_with_its_date() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::dictionary;
+    use std::io::Read;
+
+    let long_name = format!("{}.xml", "n".repeat(120));
+    let params =
+        dictionary! { "ModDate" => lopdf::Object::string_literal("D:20240315103042+01'00'") };
+    let analyzer =
+        PdfAnalyzer::from_bytes(&pdf_with_attachment(&long_name, b"<a/>", params)).unwrap();
+    let report = analyzer.extract_with_report().unwrap();
+    let bytes = report.to_tar_bytes();
+    assert_eq!(bytes.len() % 512, 0);
+
+    let mut archive = tar::Archive::new(bytes.as_slice());
+    let mut entries = archive.entries().unwrap();
+    let mut entry = entries.next().unwrap().unwrap();
+    assert_eq!(entry.path().unwrap().to_str(), Some(long_name.as_str()));
+    assert_eq!(entry.header().mode().unwrap(), 0o644);
+    // 2024-03-15T10:30:42+01:00
+    assert_eq!(entry.header().mtime().unwrap(), 1_710_495_042);
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"<a/>");
+    drop(entry);
+    assert!(entries.next().is_none());
+
+    let example = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let report = example.extract_with_report().unwrap();
+    let mut streamed = Vec::new();
+    report.write_tar(&mut streamed).unwrap();
+    let mut archive = tar::Archive::new(streamed.as_slice());
+    let sizes: Vec<u64> = archive
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().size())
+        .collect();
+    assert_eq!(sizes, [5902, 5308]);
+
+    // Entry names cannot escape the directory the archive is unpacked into.
+    let hostile = extractembedfilepdf::ExtractionReport {
+        files: vec![
+            make_file("../../evil.txt", b"x"),
+            make_file("/etc/passwd", b"y"),
+        ],
+        ..Default::default()
+    };
+    let bytes = hostile.to_tar_bytes();
+    let mut archive = tar::Archive::new(bytes.as_slice());
+    let paths: Vec<String> = archive
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().path().unwrap().display().to_string())
+        .collect();
+    assert_eq!(paths, ["evil.txt", "passwd"]);
+
+    // A time zone out of range makes the date unusable instead of overflowing.
+    let mut overflow = make_file("overflow.txt", b"z");
+    overflow.metadata.modification_date = Some("D:20240101000000+2147483647'00'".into());
+    let report_with_overflow = extractembedfilepdf::ExtractionReport {
+        files: vec![overflow],
+        ..Default::default()
+    };
+    let bytes = report_with_overflow.to_tar_bytes();
+    let mut archive = tar::Archive::new(bytes.as_slice());
+    let entry = archive.entries().unwrap().next().unwrap().unwrap();
+    assert_eq!(entry.header().mtime().unwrap(), 0);
+    assert!(report_with_overflow.to_zip_bytes().is_ok());
+
+    // Files appended one at a time give the same archive, first name wins.
+    let mut tar = extractembedfilepdf::TarWriter::new(Vec::new());
+    for file in &report.files {
+        assert!(tar.append(file).unwrap());
+    }
+    let spoof = make_file(&report.files[0].filename, b"spoof");
+    assert!(!tar.append(&spoof).unwrap());
+    assert_eq!(tar.finish().unwrap(), streamed);
+}
+
+#[test]
+fn junit_report_has_one_case_per_check() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let xmp = r#"<rdf:Description pdfaid:part="3" pdfaid:conformance="B"/>"#;
+    let valid = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+    let xml = valid.analyze().unwrap().to_junit_xml("a&b.pdf");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert!(xml.contains(r#"<testsuite name="a&amp;b.pdf" tests="3" failures="0""#));
+    assert!(!xml.contains("<failure"));
+
+    let broken = PdfAnalyzer::from_bytes(&pdf_with_broken_attachment()).unwrap();
+    let xml = broken.analyze().unwrap().to_junit_xml("broken.pdf");
+    assert!(xml.contains(r#"tests="5" failures="2""#), "{xml}");
+    assert!(xml.contains(r#"<testcase classname="attachments" name="broken.txt">"#));
+    assert!(xml.contains(r#"<failure type="Skipped""#));
+    assert!(xml.contains(r#"<failure type="NotPdfA3""#));
+}
+
+#[test]
+#[allow(deprecated)]
+fn compare_attachments_reports_added_and_unchanged() {
+    use extractembedfilepdf::{compare_attachments, PdfAnalyzer};
+    let one = PdfAnalyzer::from_path(example_pdf("format_a3_withFile.pdf")).unwrap();
+    let two = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+
+    let diff = compare_attachments(&one, &two).unwrap();
+    assert_eq!(diff.added, ["Word_embed.docx"]);
+    assert_eq!(diff.unchanged, ["Excel_embed1.xlsx"]);
+    assert!(diff.removed.is_empty() && diff.changed.is_empty());
+    assert!(!diff.is_identical());
+
+    let none = PdfAnalyzer::from_path(example_pdf("simplepdf.pdf")).unwrap();
+    let diff = compare_attachments(&one, &none).unwrap();
+    assert_eq!(diff.removed, ["Excel_embed1.xlsx"]);
+    assert!(compare_attachments(&one, &one).unwrap().is_identical());
+}
+
+#[test]
+#[allow(deprecated)]
+fn compare_attachments_keeps_duplicate_names_apart() {
+    use extractembedfilepdf::{compare_attachments, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let load = |contents: &[&str]| {
+        let bytes = build_pdf(|doc, catalog| {
+            let mut names = Vec::new();
+            for (i, content) in contents.iter().enumerate() {
+                let stream_id =
+                    doc.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+                let spec_id = doc.add_object(dictionary! {
+                    "Type" => "Filespec",
+                    "F" => Object::string_literal("data.txt"),
+                    "EF" => dictionary! { "F" => stream_id },
+                });
+                names.extend([Object::string_literal(format!("{i}")), spec_id.into()]);
+            }
+            catalog.set(
+                "Names",
+                dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+            );
+        });
+        PdfAnalyzer::from_bytes(&bytes).unwrap()
+    };
+
+    let diff = compare_attachments(&load(&["one", "two"]), &load(&["one", "2", "three"])).unwrap();
+    assert_eq!(diff.unchanged, ["data.txt"]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].filename, "data.txt");
+    assert_eq!(diff.added, ["data.txt"]);
+    assert!(diff.removed.is_empty());
+
+    // A document whose only attachment cannot be extracted compares as having
+    // none, the same way in both comparisons.
+    let broken = PdfAnalyzer::from_bytes(&build_pdf(|doc, catalog| {
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("lost.txt"),
+            "EF" => dictionary! { "F" => (999, 0) },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![Object::string_literal("0"), spec_id.into()],
+            }},
+        );
+    }))
+    .unwrap();
+    let one = load(&["one"]);
+    let by_name = compare_attachments(&broken, &one).unwrap();
+    assert_eq!(by_name.added, ["data.txt"]);
+    assert_eq!(
+        by_name,
+        extractembedfilepdf::compare::diff(&broken, &one).unwrap()
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn diff_tells_renamed_attachments_from_modified_ones() {
+    use extractembedfilepdf::{compare, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let load = |files: &[(&str, &str)], extra: (&str, &str)| {
+        let bytes = build_pdf(|doc, catalog| {
+            let mut names = Vec::new();
+            for (i, (name, content)) in files.iter().chain([&extra]).enumerate() {
+                let stream_id =
+                    doc.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+                let spec_id = doc.add_object(dictionary! {
+                    "Type" => "Filespec",
+                    "F" => Object::string_literal(*name),
+                    "EF" => dictionary! { "F" => stream_id },
+                });
+                names.extend([Object::string_literal(format!("{i}")), spec_id.into()]);
+            }
+            catalog.set(
+                "Names",
+                dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+            );
+        });
+        PdfAnalyzer::from_bytes(&bytes).unwrap()
+    };
+    let old = load(
+        &[
+            ("invoice.xml", "<Invoice/>"),
+            ("logo.png", "png"),
+            ("terms.txt", "v1"),
+            ("old.txt", "gone"),
+        ],
+        ("terms.txt", "appendix"),
+    );
+    let new = load(
+        &[
+            ("factur-x.xml", "<Invoice/>"),
+            ("logo.png", "png"),
+            ("terms.txt", "v2"),
+            ("new.txt", "fresh"),
+        ],
+        ("terms.txt", "appendix"),
+    );
+
+    let diff = compare::diff(&old, &new).unwrap();
+    // Identical: same content, same name, including both copies of a
+    // duplicated name.
+    assert_eq!(diff.unchanged, ["logo.png", "terms.txt"]);
+    // Renamed: same content, different name.
+    assert_eq!(diff.renamed.len(), 1);
+    assert_eq!(diff.renamed[0].old_filename, "invoice.xml");
+    assert_eq!(diff.renamed[0].new_filename, "factur-x.xml");
+    // Modified: same name, different content.
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].filename, "terms.txt");
+    assert_ne!(diff.changed[0].old_sha256, diff.changed[0].new_sha256);
+    // Added and removed: no partner by content or name.
+    assert_eq!(diff.added, ["new.txt"]);
+    assert_eq!(diff.removed, ["old.txt"]);
+    assert!(!diff.is_identical());
+
+    assert!(compare::diff(&old, &old).unwrap().is_identical());
+    // Filename matching alone reports the rename as a removal plus an addition.
+    let by_name = extractembedfilepdf::compare_attachments(&old, &new).unwrap();
+    assert!(by_name.renamed.is_empty());
+    assert!(by_name.added.contains(&"factur-x.xml".to_owned()));
+
+    // A document that fails to extract is an error, not an empty list.
+    let mut failing = old.clone();
+    failing.config_mut().max_duration = Some(std::time::Duration::ZERO);
+    assert!(matches!(
+        compare::diff(&failing, &new),
+        Err(extractembedfilepdf::ExtractError::Timeout(_))
+    ));
+    let empty = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {})).unwrap();
+    assert_eq!(compare::diff(&empty, &new).unwrap().added.len(), 5);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+#[allow(deprecated)]
+fn comparing_never_writes_attachments_to_disk() {
+    use extractembedfilepdf::compare;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = ExtractorConfig {
+        extract_to_disk: true,
+        output_directory: Some(dir.path().to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let one = std::fs::read(example_pdf("format_a3_withFile.pdf")).unwrap();
+    let two = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let one = analyzer_with_config(&one, config.clone());
+    let two = analyzer_with_config(&two, config);
+
+    assert_eq!(
+        compare::diff(&one, &two).unwrap().added,
+        ["Word_embed.docx"]
+    );
+    assert_eq!(
+        extractembedfilepdf::compare_attachments(&one, &two)
+            .unwrap()
+            .added,
+        ["Word_embed.docx"]
+    );
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn discovery_sources_restrict_where_files_are_found() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let path = example_pdf("format_a3_withFiles.pdf");
+
+    let only_annotations = ExtractorConfig {
+        discovery_sources: Some(vec![AttachmentSource::Annotation]),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::with_config(&path, only_annotations).unwrap();
+    assert_eq!(analyzer.count_embedded_files().unwrap(), 0);
+
+    let deep_scan = ExtractorConfig {
+        discovery_sources: Some(vec![AttachmentSource::DeepScan]),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::with_config(&path, deep_scan).unwrap();
     let files = analyzer.extract_embedded_files().unwrap();
-    assert!(!files.is_empty());
-    for f in &files {
-        assert!(!f.filename.is_empty());
-        assert!(!f.data.is_empty());
+    assert_eq!(files.len(), 2);
+    assert!(files
+        .iter()
+        .all(|f| f.metadata.source == Some(AttachmentSource::DeepScan)));
+}
+
+#[test]
+fn batch_reports_every_input() {
+    use extractembedfilepdf::{BatchInput, PdfBatch};
+    use std::path::PathBuf;
+
+    let inputs = vec![
+        BatchInput::from(PathBuf::from(example_pdf("format_a3_withFiles.pdf"))),
+        BatchInput::from(PathBuf::from(example_pdf("simplepdf.pdf"))),
+        BatchInput::from(b"not a pdf".to_vec()),
+    ];
+
+    let mut items: Vec<_> = PdfBatch::new(ExtractorConfig::default())
+        .workers(2)
+        .run(inputs)
+        .collect();
+    items.sort_by_key(|item| item.index);
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].report.as_ref().unwrap().files.len(), 2);
+    assert!(items[1].report.as_ref().unwrap().files.is_empty());
+    assert!(items[2].report.is_err());
+    assert!(items[2].path.is_none());
+}
+
+#[test]
+fn batch_accepts_shared_buffers() {
+    use extractembedfilepdf::{BatchInput, PdfBatch};
+    use std::sync::Arc;
+
+    let shared: Arc<[u8]> = std::fs::read(example_pdf("format_a3_withFiles.pdf"))
+        .unwrap()
+        .into();
+    let inputs = vec![
+        BatchInput::from(Arc::clone(&shared)),
+        BatchInput::from(shared),
+    ];
+
+    for item in PdfBatch::new(ExtractorConfig::default()).run(inputs) {
+        assert_eq!(item.report.unwrap().files.len(), 2);
+    }
+}
+
+#[test]
+fn batch_pulls_inputs_only_as_results_are_consumed() {
+    use extractembedfilepdf::PdfBatch;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let bytes = std::fs::read(example_pdf("simplepdf.pdf")).unwrap();
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&pulled);
+    let inputs = (0..20).map(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        bytes.clone()
+    });
+
+    let mut results = PdfBatch::new(ExtractorConfig::default())
+        .workers(1)
+        .run(inputs);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    // One item waits in the channel, the worker blocks on sending the next.
+    assert!(pulled.load(Ordering::SeqCst) <= 2, "{pulled:?}");
+    assert_eq!(results.by_ref().count(), 20);
+}
+
+#[test]
+fn batch_survives_a_panicking_input_iterator() {
+    use extractembedfilepdf::PdfBatch;
+
+    let bytes = std::fs::read(example_pdf("simplepdf.pdf")).unwrap();
+    let mut calls = 0;
+    let inputs = std::iter::from_fn(move || {
+        calls += 1;
+        match calls {
+            3 => panic!("input source failed"),
+            1..=5 => Some(bytes.clone()),
+            _ => None,
+        }
+    });
+
+    let items: Vec<_> = PdfBatch::new(ExtractorConfig::default())
+        .workers(2)
+        .run(inputs)
+        .collect();
+    assert_eq!(items.len(), 4);
+}
+
+#[test]
+fn batch_reports_a_panicking_analysis_as_an_error() {
+    use extractembedfilepdf::{Classifier, EmbeddedFile, ErrorCode, ExtractError, PdfBatch};
+    use std::sync::Arc;
+
+    struct Panics;
+    impl Classifier for Panics {
+        fn name(&self) -> &str {
+            "panics"
+        }
+        fn classify(&self, file: &EmbeddedFile) -> Option<String> {
+            panic!("cannot classify {}", file.filename)
+        }
+    }
+
+    let config = ExtractorConfig {
+        classifiers: vec![Arc::new(Panics)],
+        ..Default::default()
+    };
+    let with_files = std::fs::read(example_pdf("format_a3_withFile.pdf")).unwrap();
+    let without = std::fs::read(example_pdf("simplepdf.pdf")).unwrap();
+
+    let mut items: Vec<_> = PdfBatch::new(config)
+        .workers(1)
+        .run([with_files.clone(), without, with_files])
+        .collect();
+    items.sort_by_key(|item| item.index);
+    assert_eq!(items.len(), 3);
+    for i in [0, 2] {
+        match &items[i].report {
+            Err(error @ ExtractError::Internal(message)) => {
+                assert!(message.contains("cannot classify"), "{message}");
+                assert_eq!(error.code(), ErrorCode::Internal);
+            }
+            other => panic!("expected a caught panic, got {other:?}"),
+        }
     }
+    assert!(items[1].report.is_ok());
+}
+
+#[test]
+fn lenient_loading_recovers_a_broken_xref() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let mut bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    // Point startxref past the end of the file, as a truncated upload would.
+    let pos = bytes.windows(9).rposition(|w| w == b"startxref").unwrap();
+    bytes.truncate(pos);
+    bytes.extend_from_slice(b"startxref\n99999999\n%%EOF\n");
+
+    assert!(PdfAnalyzer::from_bytes(&bytes).is_err());
+    let analyzer = PdfAnalyzer::from_bytes_lenient(&bytes).unwrap();
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 2);
+}
+
+#[test]
+fn lenient_loading_still_rejects_garbage() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    assert!(PdfAnalyzer::from_bytes_lenient(b"not a pdf").is_err());
+}
+
+#[test]
+fn probe_detects_markers_without_parsing() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let probe = PdfAnalyzer::probe(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    assert!(probe.looks_like_pdf());
+    assert!(probe.worth_analyzing());
+    assert!(probe.version.is_some());
+
+    let probe = PdfAnalyzer::probe_bytes(b"not a pdf");
+    assert!(!probe.has_header && !probe.has_eof_marker);
+    assert!(!probe.worth_analyzing());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn analysis_report_serializes_to_json() {
+    use extractembedfilepdf::{BatchInput, PdfBatch};
+    use std::path::PathBuf;
+
+    let input = BatchInput::from(PathBuf::from(example_pdf("format_a3_withFiles.pdf")));
+    let item = PdfBatch::new(ExtractorConfig::default())
+        .run(vec![input])
+        .next()
+        .unwrap();
+    let json = serde_json::to_value(&item).unwrap();
+
+    let files = &json["report"]["Ok"]["files"];
+    assert_eq!(files[0]["filename"], "Excel_embed1.xlsx");
+    assert_eq!(files[0]["size"], 5902);
+    assert_eq!(files[0]["metadata"]["source"], "names-tree");
+
+    let error = serde_json::to_value(ExtractError::NoEmbeddedFiles).unwrap();
+    assert_eq!(error["kind"], "NoEmbeddedFiles");
+    assert_eq!(error["code"], "E_NO_EMBEDDED_FILES");
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_extraction_keeps_discovery_order() {
+    use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+    use extractembedfilepdf::PdfAnalyzer;
+
+    // The first attachments are the largest, so they finish decoding last.
+    let mut builder = PdfBuilder::new();
+    for i in 0..24u8 {
+        let size = (24 - usize::from(i)) * 64 * 1024;
+        builder = builder.attachment(Attachment {
+            compress: true,
+            ..Attachment::new(format!("file-{i:02}.bin"), vec![i; size])
+        });
+    }
+    let analyzer = PdfAnalyzer::from_bytes(&builder.build()).unwrap();
+
+    let parallel = analyzer.extract_embedded_files().unwrap();
+    let sequential: Vec<_> = analyzer
+        .embedded_file_handles()
+        .unwrap()
+        .iter()
+        .map(|handle| handle.decode().unwrap())
+        .collect();
+    let names = |files: &[extractembedfilepdf::EmbeddedFile]| {
+        files.iter().map(|f| f.filename.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&parallel), analyzer.embedded_file_names().unwrap());
+    assert_eq!(names(&parallel), names(&sequential));
+    assert_eq!(parallel, sequential);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn embedded_file_stream_yields_every_file() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use futures_util::{Stream, StreamExt};
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let stream = analyzer.embedded_file_stream().unwrap();
+    assert_eq!(stream.size_hint(), (2, Some(2)));
+    drop(analyzer); // the stream owns its document
+
+    let files: Vec<_> = stream.map(|file| file.unwrap()).collect().await;
+    let names: Vec<_> = files.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(names, ["Excel_embed1.xlsx", "Word_embed.docx"]);
+    assert_eq!(files[0].data.len(), 5902);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn embedded_file_stream_yields_errors_and_continues() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use futures_util::StreamExt;
+    use lopdf::{dictionary, Object, Stream};
+
+    // The middle file specification points at a stream that does not exist.
+    let bytes = build_pdf(|doc, catalog| {
+        let mut names = Vec::new();
+        for (key, data) in [("1", Some("first")), ("2", None), ("3", Some("third"))] {
+            let stream_id = match data {
+                Some(data) => doc.add_object(Stream::new(dictionary! {}, data.into())),
+                None => (999, 0),
+            };
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(format!("{key}.txt")),
+                "EF" => dictionary! { "F" => stream_id },
+            });
+            names.extend([Object::string_literal(key), spec_id.into()]);
+        }
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+        );
+    });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+
+    let items: Vec<_> = analyzer.embedded_file_stream().unwrap().collect().await;
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].as_ref().unwrap().data, b"first");
+    assert!(matches!(
+        &items[1],
+        Err(ExtractError::ExtractionError { .. })
+    ));
+    assert_eq!(items[2].as_ref().unwrap().data, b"third");
+
+    // Limits apply to every item; a timed-out file does not end the stream.
+    // `set_config` keeps the specifications discovered above.
+    let mut expired = analyzer.clone();
+    expired.set_config(ExtractorConfig {
+        max_duration: Some(std::time::Duration::ZERO),
+        ..Default::default()
+    });
+    let items: Vec<_> = expired.embedded_file_stream().unwrap().collect().await;
+    assert_eq!(items.len(), 3);
+    assert!(items
+        .iter()
+        .all(|item| matches!(item, Err(ExtractError::Timeout(_)))));
+
+    let none = PdfAnalyzer::from_path(example_pdf("simplepdf.pdf")).unwrap();
+    assert!(matches!(
+        none.embedded_file_stream(),
+        Err(ExtractError::NoEmbeddedFiles)
+    ));
+}
+
+#[cfg(all(feature = "async", feature = "fs"))]
+#[tokio::test]
+async fn embedded_file_stream_stops_decoding_when_dropped() {
+    use futures_util::{Stream, StreamExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = ExtractorConfig {
+        extract_to_disk: true,
+        output_directory: Some(dir.path().to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let analyzer = analyzer_with_config(&bytes, config);
+
+    // Nothing is decoded ahead of the consumer, so dropping the stream after
+    // the first file leaves the second one untouched.
+    let mut stream = analyzer.embedded_file_stream().unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.filename, "Excel_embed1.xlsx");
+    assert_eq!(stream.size_hint(), (1, Some(1)));
+    drop(stream);
+    assert!(dir.path().join("Excel_embed1.xlsx").exists());
+    assert!(!dir.path().join("Word_embed.docx").exists());
+
+    // Dropping it with a decode in flight detaches that task; the analyzer
+    // and a new stream are unaffected.
+    let mut stream = analyzer.embedded_file_stream().unwrap();
+    let _ = futures_util::poll!(stream.next());
+    drop(stream);
+    let names: Vec<_> = analyzer
+        .embedded_file_stream()
+        .unwrap()
+        .map(|file| file.unwrap().filename)
+        .collect()
+        .await;
+    assert_eq!(names, ["Excel_embed1.xlsx", "Word_embed.docx"]);
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 2);
+}
+
+#[cfg(all(feature = "async", feature = "fs"))]
+#[tokio::test]
+async fn embedded_file_stream_does_not_overwrite_a_file_of_the_same_name() {
+    use futures_util::StreamExt;
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let mut names = Vec::new();
+        for (key, data) in [("1", "<real/>"), ("2", "<spoof/>")] {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, data.into()));
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal("invoice.xml"),
+                "EF" => dictionary! { "F" => stream_id },
+            });
+            names.extend([Object::string_literal(key), spec_id.into()]);
+        }
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+        );
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let analyzer = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        },
+    );
+
+    let files: Vec<_> = analyzer.embedded_file_stream().unwrap().collect().await;
+    assert_eq!(files.len(), 2);
+    let written = std::fs::read(dir.path().join("invoice.xml")).unwrap();
+    assert_eq!(written, b"<real/>");
+}
+
+// ── In-memory PDFs ────────────────────────────────────────────────────────────
+
+/// Build a one-page PDF, letting `fill_catalog` add entries (and objects) to
+/// the catalog before it is written.
+fn build_pdf(fill_catalog: impl FnOnce(&mut lopdf::Document, &mut lopdf::Dictionary)) -> Vec<u8> {
+    use lopdf::{dictionary, Document, Object};
+
+    let mut doc = Document::with_version("1.7");
+    let pages_id = doc.new_object_id();
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+    fill_catalog(&mut doc, &mut catalog);
+    let catalog_id = doc.add_object(catalog);
+    doc.trailer.set("Root", catalog_id);
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+    bytes
+}
+
+/// Build a one-page PDF whose catalog carries `xmp` as its `/Metadata` stream.
+fn pdf_with_xmp(xmp: impl AsRef<[u8]>) -> Vec<u8> {
+    extractembedfilepdf::test_util::PdfBuilder::new()
+        .xmp(xmp.as_ref())
+        .build()
+}
+
+/// Build a one-page PDF with a single unfiltered attachment in the
+/// `/EmbeddedFiles` name tree. `params` becomes the stream's `/Params`.
+fn pdf_with_attachment(name: &str, data: &[u8], params: lopdf::Dictionary) -> Vec<u8> {
+    use lopdf::{dictionary, Stream};
+
+    pdf_with_stream(
+        name,
+        Stream::new(
+            dictionary! { "Type" => "EmbeddedFile", "Params" => params },
+            data.to_vec(),
+        ),
+    )
+}
+
+/// Build a one-page PDF whose `/EmbeddedFiles` name tree holds `stream` as
+/// the attachment `name`.
+fn pdf_with_stream(name: &str, stream: lopdf::Stream) -> Vec<u8> {
+    use lopdf::{dictionary, Object};
+
+    build_pdf(|doc, catalog| {
+        let stream_id = doc.add_object(stream);
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(name),
+            "UF" => Object::string_literal(name),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        let tree_id = doc.add_object(dictionary! {
+            "Names" => vec![Object::string_literal(name), spec_id.into()],
+        });
+        catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+    })
+}
+
+#[test]
+fn pageless_container_needs_allow_pageless() {
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let stream_id = doc.add_object(Stream::new(dictionary! {}, b"data".to_vec()));
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("a.txt"),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![Object::string_literal("a.txt"), spec_id.into()],
+            }},
+        );
+        catalog.remove(b"Pages");
+    });
+
+    let strict = analyzer_with_config(&bytes, ExtractorConfig::default());
+    assert!(strict.is_pdf().is_err());
+
+    let lenient = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            allow_pageless: true,
+            ..Default::default()
+        },
+    );
+    assert!(lenient.is_pdf().unwrap());
+    assert_eq!(lenient.extract_embedded_files().unwrap()[0].data, b"data");
+}
+
+/// Build a hybrid-reference PDF in the style of Acrobat "optimized" files:
+/// the classic xref table lists objects 1–4, while the name tree (5) and the
+/// file specification (6) live in an object stream (7) that only the
+/// `/XRefStm` cross-reference stream (8) knows about.
+fn hybrid_reference_pdf() -> Vec<u8> {
+    let mut pdf = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(body);
+        pdf.len() - body.len()
+    };
+
+    object(
+        &mut pdf,
+        b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R /Names 5 0 R >>\nendobj\n",
+    );
+    object(
+        &mut pdf,
+        b"2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n",
+    );
+    object(
+        &mut pdf,
+        b"3 0 obj\n<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] >>\nendobj\n",
+    );
+    object(
+        &mut pdf,
+        b"4 0 obj\n<< /Type /EmbeddedFile /Length 5 >>\nstream\nhello\nendstream\nendobj\n",
+    );
+
+    let tree = "<< /EmbeddedFiles << /Names [(hello.txt) 6 0 R] >> >>";
+    let spec = "<< /Type /Filespec /F (hello.txt) /EF << /F 4 0 R >> >>";
+    let index = format!("5 0 6 {} ", tree.len() + 1);
+    let content = format!("{index}{tree} {spec}");
+    let obj_stm = object(
+        &mut pdf,
+        format!(
+            "7 0 obj\n<< /Type /ObjStm /N 2 /First {} /Length {} >>\nstream\n{content}\nendstream\nendobj\n",
+            index.len(),
+            content.len()
+        )
+        .as_bytes(),
+    );
+
+    // W [1 4 2]: type, field 2 (offset or stream number), field 3.
+    let mut entries = Vec::new();
+    for (kind, field2, field3) in [(2u8, 7u32, 0u16), (2, 7, 1), (1, obj_stm as u32, 0)] {
+        entries.push(kind);
+        entries.extend_from_slice(&field2.to_be_bytes());
+        entries.extend_from_slice(&field3.to_be_bytes());
+    }
+    let mut xref_stream = format!(
+        "8 0 obj\n<< /Type /XRef /Size 9 /W [1 4 2] /Index [5 3] /Length {} >>\nstream\n",
+        entries.len()
+    )
+    .into_bytes();
+    xref_stream.extend_from_slice(&entries);
+    xref_stream.extend_from_slice(b"\nendstream\nendobj\n");
+    let xref_stm = object(&mut pdf, &xref_stream);
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(b"xref\n0 5\n0000000000 65535 f\r\n");
+    for offset in &offsets[..4] {
+        pdf.extend_from_slice(format!("{offset:010} 00000 n\r\n").as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size 9 /Root 1 0 R /XRefStm {xref_stm} >>\nstartxref\n{xref}\n%%EOF\n"
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+#[test]
+fn hybrid_reference_objects_are_found() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let analyzer = PdfAnalyzer::from_bytes(&hybrid_reference_pdf()).unwrap();
+    assert!(analyzer.is_pdf().unwrap());
+
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].filename, "hello.txt");
+    assert_eq!(files[0].data, b"hello");
+
+    // A stale definition after the real one does not replace an object the
+    // xref table lists.
+    let mut pdf = hybrid_reference_pdf();
+    let xref = pdf.windows(9).rposition(|w| w == b"xref\n0 5\n").unwrap();
+    let stale = b"4 0 obj\n<< /Type /EmbeddedFile /Length 5 >>\nstream\nstale\nendstream\nendobj\n";
+    pdf.splice(xref..xref, stale.iter().copied());
+    let startxref = pdf.windows(10).rposition(|w| w == b"startxref\n").unwrap() + 10;
+    pdf.truncate(startxref);
+    pdf.extend_from_slice(format!("{}\n%%EOF\n", xref + stale.len()).as_bytes());
+
+    let analyzer = PdfAnalyzer::from_bytes(&pdf).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].data, b"hello");
+}
+
+#[test]
+fn lazy_documents_resolve_only_what_extraction_needs() {
+    use extractembedfilepdf::test_util::{Attachment, NameTreeLayout, PdfBuilder};
+    use extractembedfilepdf::{LazyDocument, PdfAnalyzer};
+    use lopdf::{dictionary, Document, Stream};
+
+    let built = PdfBuilder::new()
+        .pages(10)
+        .name_tree(NameTreeLayout::Nested { leaf_size: 1 })
+        .attachment(Attachment {
+            compress: true,
+            ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+        })
+        .attachment(Attachment::new("notes.txt", "hello"))
+        .annotation(7, Attachment::new("scan.txt", "page seven"))
+        .build();
+
+    // Give every page a content stream and a large image, as in a scan.
+    let mut doc = Document::load_mem(&built).unwrap();
+    for page in doc.get_pages().into_values() {
+        let content = doc.add_object(Stream::new(dictionary! {}, b"q Q".to_vec()));
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image" },
+            vec![0; 64 * 1024],
+        ));
+        let page = doc.get_dictionary_mut(page).unwrap();
+        page.set("Contents", content);
+        page.set(
+            "Resources",
+            dictionary! { "XObject" => dictionary! { "Im0" => image } },
+        );
+    }
+    let mut classic = Vec::new();
+    doc.save_to(&mut classic).unwrap();
+    let mut modern = Vec::new();
+    doc.save_modern(&mut modern).unwrap();
+
+    let expected = PdfAnalyzer::from_bytes(&classic)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    for bytes in [&classic, &modern] {
+        let lazy = LazyDocument::from_bytes(bytes).unwrap();
+        assert_eq!(lazy.resolved_count(), 0);
+        assert_eq!(
+            lazy.embedded_file_names().unwrap(),
+            ["factur-x.xml", "notes.txt", "scan.txt"]
+        );
+        let files = lazy.extract_embedded_files().unwrap();
+        assert_eq!(files, expected);
+
+        // Catalog, page tree, name tree, specifications and their streams.
+        assert!(lazy.object_count() > 40, "{}", lazy.object_count());
+        assert!(lazy.resolved_count() < 25, "{}", lazy.resolved_count());
+    }
+
+    // Object streams are decoded within the memory budget.
+    let tight = ExtractorConfig {
+        max_memory_bytes: Some(64),
+        ..Default::default()
+    };
+    let lazy = LazyDocument::from_bytes_with_config(&modern, tight).unwrap();
+    assert!(lazy.extract_embedded_files().is_err());
+    // Every extraction has a budget of its own, so the tightest limit one
+    // extraction fits in holds for any number of them.
+    let fitting = (64..)
+        .step_by(64)
+        .find_map(|limit| {
+            let config = ExtractorConfig {
+                max_memory_bytes: Some(limit),
+                ..Default::default()
+            };
+            let lazy = LazyDocument::from_bytes_with_config(&modern, config).unwrap();
+            lazy.extract_embedded_files().is_ok().then_some(lazy)
+        })
+        .unwrap();
+    for _ in 0..100 {
+        assert_eq!(fitting.extract_embedded_files().unwrap(), expected);
+    }
+
+    let hybrid = hybrid_reference_pdf();
+    let lazy = LazyDocument::from_bytes(&hybrid).unwrap();
+    let files = lazy.extract_embedded_files().unwrap();
+    assert_eq!(files[0].filename, "hello.txt");
+    assert_eq!(files[0].data, b"hello");
+
+    // Encrypted with a user password.
+    let id = lopdf::Object::String(
+        b"0123456789abcdef".to_vec(),
+        lopdf::StringFormat::Hexadecimal,
+    );
+    doc.trailer.set("ID", vec![id.clone(), id]);
+    let state = lopdf::EncryptionState::try_from(lopdf::EncryptionVersion::V2 {
+        document: &doc,
+        owner_password: "owner",
+        user_password: "secret",
+        key_length: 128,
+        permissions: lopdf::Permissions::all(),
+    })
+    .unwrap();
+    doc.encrypt(&state).unwrap();
+    let mut encrypted = Vec::new();
+    doc.save_to(&mut encrypted).unwrap();
+    let lazy = LazyDocument::from_bytes_with_password(&encrypted, "secret").unwrap();
+    assert_eq!(lazy.extract_embedded_files().unwrap(), expected);
+    let locked = LazyDocument::from_bytes(&encrypted).unwrap();
+    assert!(locked.extract_with_report().unwrap().files.is_empty());
+
+    assert!(LazyDocument::from_bytes(b"not a pdf").is_err());
+    let truncated = &classic[..classic.len() / 2];
+    assert!(LazyDocument::from_bytes(truncated).is_err());
+    // The largest object number must not overflow the table size.
+    let last = b"%PDF-1.4\nxref\n4294967295 1\n0000000000 00000 n \ntrailer\n<< /Root 1 0 R >>\nstartxref\n9\n%%EOF\n";
+    if let Ok(lazy) = LazyDocument::from_bytes(last) {
+        assert!(lazy.extract_with_report().is_err());
+    }
+}
+
+/// Build a PDF whose only object is an object stream (1) that its
+/// cross-reference stream (2) lists as compressed inside itself, which
+/// ISO 32000 forbids.
+fn self_nested_object_stream_pdf() -> Vec<u8> {
+    let mut pdf = b"%PDF-1.5\n".to_vec();
+    let content = "1 0 << >>";
+    pdf.extend_from_slice(
+        format!(
+            "1 0 obj\n<< /Type /ObjStm /N 1 /First 4 /Length {} >>\nstream\n{content}\nendstream\nendobj\n",
+            content.len()
+        )
+        .as_bytes(),
+    );
+
+    // W [1 4 2]: object 0 is free, 1 is the first object of stream 1, and
+    // 2 is the cross-reference stream itself.
+    let xref = pdf.len();
+    let mut entries = Vec::new();
+    for (kind, field2, field3) in [(0u8, 0u32, 65535u16), (2, 1, 0), (1, xref as u32, 0)] {
+        entries.push(kind);
+        entries.extend_from_slice(&field2.to_be_bytes());
+        entries.extend_from_slice(&field3.to_be_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "2 0 obj\n<< /Type /XRef /Size 3 /W [1 4 2] /Root 1 0 R /Length {} >>\nstream\n",
+            entries.len()
+        )
+        .as_bytes(),
+    );
+    pdf.extend_from_slice(&entries);
+    pdf.extend_from_slice(format!("\nendstream\nendobj\nstartxref\n{xref}\n%%EOF\n").as_bytes());
+    pdf
+}
+
+#[test]
+fn an_object_stream_nested_in_itself_is_not_decoded() {
+    use extractembedfilepdf::LazyDocument;
+
+    let pdf = self_nested_object_stream_pdf();
+    let lazy = LazyDocument::from_bytes(&pdf).unwrap();
+    // The catalog cannot be read, so there is nothing to find.
+    assert!(lazy.embedded_file_names().unwrap().is_empty());
+    assert!(lazy.extract_with_report().is_err());
+}
+
+#[test]
+fn attachments_only_loading_survives_an_object_stream_nested_in_itself() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let config = ExtractorConfig {
+        attachments_only: true,
+        ..Default::default()
+    };
+    let pdf = self_nested_object_stream_pdf();
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&pdf, config).unwrap();
+    assert!(matches!(
+        analyzer.extract_embedded_files(),
+        Err(ExtractError::NoEmbeddedFiles)
+    ));
+}
+
+#[test]
+fn attachments_only_loading_never_parses_page_content_and_images() {
+    use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+    use extractembedfilepdf::{ExtractorConfig, PdfAnalyzer};
+    use lopdf::{dictionary, Document, Object, Stream};
+
+    let built = PdfBuilder::new()
+        .pages(3)
+        .pdfa(3, "B")
+        .attachment(Attachment {
+            compress: true,
+            ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+        })
+        .annotation(2, Attachment::new("scan.txt", "page two"))
+        .build();
+
+    let mut doc = Document::load_mem(&built).unwrap();
+    let mut page_data = Vec::new();
+    for page in doc.get_pages().into_values() {
+        let content = doc.add_object(Stream::new(dictionary! {}, b"BT (Hi) Tj ET".to_vec()));
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image" },
+            vec![0; 64 * 1024],
+        ));
+        let program = doc.add_object(Stream::new(
+            dictionary! { "Length1" => 4 },
+            b"font".to_vec(),
+        ));
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "FontDescriptor" => dictionary! { "FontFile2" => program },
+        });
+        page_data.extend([content, image, program]);
+        let page = doc.get_dictionary_mut(page).unwrap();
+        page.set("Contents", content);
+        page.set(
+            "Resources",
+            dictionary! {
+                "XObject" => dictionary! { "Im0" => image },
+                "Font" => dictionary! { "F0" => font },
+            },
+        );
+    }
+    // An annotation's appearance is a form XObject, but not page content.
+    let first_page = doc.get_pages()[&1];
+    let appearance = doc.add_object(Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        },
+        b"0 0 10 10 re f".to_vec(),
+    ));
+    let annotation = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Square",
+        "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        "AP" => dictionary! { "N" => appearance },
+    });
+    doc.get_dictionary_mut(first_page)
+        .unwrap()
+        .set("Annots", vec![annotation.into()]);
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+
+    let full = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let config = ExtractorConfig {
+        attachments_only: true,
+        ..Default::default()
+    };
+    let lean = PdfAnalyzer::from_bytes_with_config(&bytes, config).unwrap();
+
+    assert!(lean.is_pdf().unwrap());
+    assert!(lean.is_pdfa3().unwrap());
+    assert!(lean.structure_findings().is_empty());
+    assert_eq!(
+        lean.extract_embedded_files().unwrap(),
+        full.extract_embedded_files().unwrap()
+    );
+    // The page data is left out of the document; everything else is there.
+    for &id in &page_data {
+        assert!(!lean.document().objects.contains_key(&id), "{id:?}");
+        assert!(!full
+            .document()
+            .get_object(id)
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .content
+            .is_empty());
+    }
+    assert_eq!(
+        lean.document().objects.len() + page_data.len(),
+        full.document().objects.len()
+    );
+    assert_eq!(lean.document().version, full.document().version);
+    let kept = |analyzer: &PdfAnalyzer| {
+        analyzer
+            .document()
+            .get_object(appearance)
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .content
+            .clone()
+    };
+    assert_eq!(kept(&lean), b"0 0 10 10 re f");
+
+    // Encrypted with an empty user password, which lopdf opens on load. Such
+    // a document is parsed in full and the page data dropped afterwards.
+    let id = Object::String(
+        b"0123456789abcdef".to_vec(),
+        lopdf::StringFormat::Hexadecimal,
+    );
+    doc.trailer.set("ID", vec![id.clone(), id]);
+    let state = lopdf::EncryptionState::try_from(lopdf::EncryptionVersion::V2 {
+        document: &doc,
+        owner_password: "owner",
+        user_password: "",
+        key_length: 128,
+        permissions: lopdf::Permissions::all(),
+    })
+    .unwrap();
+    doc.encrypt(&state).unwrap();
+    let mut encrypted = Vec::new();
+    doc.save_to(&mut encrypted).unwrap();
+
+    let config = ExtractorConfig {
+        attachments_only: true,
+        ..Default::default()
+    };
+    let lean = PdfAnalyzer::from_bytes_with_config(&encrypted, config).unwrap();
+    assert_eq!(
+        lean.extract_embedded_files().unwrap(),
+        full.extract_embedded_files().unwrap()
+    );
+    for id in page_data {
+        let stream = lean.document().get_object(id).unwrap().as_stream().unwrap();
+        assert!(stream.content.is_empty());
+    }
+    assert_eq!(kept(&lean), b"0 0 10 10 re f");
+}
+
+#[test]
+fn legacy_platform_keys_are_used_as_fallback() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let stream_id = doc.add_object(Stream::new(dictionary! {}, b"legacy".to_vec()));
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "DOS" => Object::string_literal("README.TXT"),
+            "EF" => dictionary! { "DOS" => stream_id },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![Object::string_literal("readme"), spec_id.into()],
+            }},
+        );
+    });
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert_eq!(files[0].filename, "README.TXT");
+    assert_eq!(files[0].data, b"legacy");
+}
+
+#[test]
+fn string_file_specs_are_external_references() {
+    use extractembedfilepdf::{ExternalKind, ExternalReference, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let path_id = doc.add_object(Object::string_literal("report.pdf"));
+        let stream_id = doc.add_object(Stream::new(dictionary! {}, b"data".to_vec()));
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("data.txt"),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![
+                    Object::string_literal("data"), spec_id.into(),
+                    Object::string_literal("notes"), Object::string_literal("notes.txt"),
+                    Object::string_literal("report"), path_id.into(),
+                ],
+            }},
+        );
+    });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+
+    let targets: Vec<_> = analyzer
+        .external_references()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.target)
+        .collect();
+    assert_eq!(targets, ["report.pdf", "notes.txt"]);
+
+    match analyzer.extract_embedded_file("report") {
+        Err(ExtractError::ExternalReference(reference)) => {
+            assert_eq!(
+                *reference,
+                ExternalReference {
+                    name: "report".into(),
+                    kind: ExternalKind::File,
+                    target: "report.pdf".into(),
+                    description: None,
+                    source: AttachmentSource::NamesTree,
+                    page: None,
+                }
+            );
+        }
+        other => panic!("expected an external reference, got {other:?}"),
+    }
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 1);
+}
+
+#[test]
+fn url_file_specs_are_external_references() {
+    use extractembedfilepdf::{ExternalKind, PdfAnalyzer};
+    use lopdf::{dictionary, Object};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "FS" => "URL",
+            "F" => Object::string_literal("https://example.com/invoice.xml"),
+            "Desc" => Object::string_literal("Invoice data"),
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![Object::string_literal("invoice"), spec_id.into()],
+            }},
+        );
+    });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+
+    let refs = analyzer.external_references().unwrap();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].kind, ExternalKind::Url);
+    assert_eq!(refs[0].target, "https://example.com/invoice.xml");
+    assert_eq!(refs[0].description.as_deref(), Some("Invoice data"));
+    assert!(matches!(
+        analyzer.extract_embedded_file("invoice"),
+        Err(ExtractError::ExternalReference(_))
+    ));
+}
+
+#[test]
+fn pdfa3_identification_is_stable_across_calls() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let xmp = r#"<rdf:Description pdfaid:part="3" pdfaid:conformance="B"/>"#;
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+
+    for _ in 0..2 {
+        assert!(analyzer.is_pdfa3().unwrap());
+        assert_eq!(analyzer.conformance_level().as_deref(), Some("PDF/A-3B"));
+    }
+}
+
+#[test]
+fn pdfa2_is_not_pdfa3() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let xmp = "<pdfaid:part>2</pdfaid:part><pdfaid:conformance>U</pdfaid:conformance>";
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+
+    assert!(!analyzer.is_pdfa3().unwrap());
+    assert_eq!(analyzer.conformance_level().as_deref(), Some("PDF/A-2U"));
+}
+
+#[test]
+fn xmp_variants_from_real_producers_are_recognised() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let conformance = |xmp: &[u8]| {
+        PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp))
+            .unwrap()
+            .conformance_level()
+    };
+
+    // Wrapped in xpacket, properties split across two descriptions, element
+    // form with surrounding whitespace.
+    let split = "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+        <x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF>\n\
+        <rdf:Description rdf:about=\"\" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n\
+        <pdfaid:part>\n  3\n</pdfaid:part></rdf:Description>\n\
+        <rdf:Description rdf:about=\"\" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n\
+        <pdfaid:conformance>B</pdfaid:conformance></rdf:Description>\n\
+        </rdf:RDF></x:xmpmeta>\n<?xpacket end=\"w\"?>";
+    assert_eq!(conformance(split.as_bytes()).as_deref(), Some("PDF/A-3B"));
+
+    // UTF-8 BOM, single quotes, spaces around `=`, another prefix.
+    let quoted = "\u{feff}<rdf:Description xmlns:id='http://www.aiim.org/pdfa/ns/id/' \
+        id:part = '3' id:conformance='u'/>";
+    assert_eq!(conformance(quoted.as_bytes()).as_deref(), Some("PDF/A-3U"));
+
+    // UTF-16 with either byte order.
+    let packet = r#"<rdf:Description pdfaid:part="3" pdfaid:conformance="A"/>"#;
+    let utf16 = |to_bytes: fn(u16) -> [u8; 2], bom: [u8; 2]| {
+        let mut bytes = bom.to_vec();
+        bytes.extend(packet.encode_utf16().flat_map(to_bytes));
+        bytes
+    };
+    let le = utf16(u16::to_le_bytes, [0xff, 0xfe]);
+    let be = utf16(u16::to_be_bytes, [0xfe, 0xff]);
+    assert_eq!(conformance(&le).as_deref(), Some("PDF/A-3A"));
+    assert_eq!(conformance(&be).as_deref(), Some("PDF/A-3A"));
+    assert_eq!(conformance(&le[2..]).as_deref(), Some("PDF/A-3A"));
+
+    // Look-alike names and empty elements do not count.
+    let decoys = r#"<pdfaid:partNumber>3</pdfaid:partNumber><pdfaid:part/>
+        <rdf:Description xpdfaid:part="3" pdfaid:conformance="B"/>"#;
+    assert_eq!(conformance(decoys.as_bytes()), None);
+}
+
+#[test]
+fn incremental_update_to_pdfa3_validates_and_keeps_history() {
+    use extractembedfilepdf::{ConformanceLevel, PdfAConformance, PdfAnalyzer};
+
+    let mut bytes = pdf_with_xmp(r#"<rdf:Description pdfaid:part="2" pdfaid:conformance="B"/>"#);
+    let original = lopdf::Document::load_mem(&bytes).unwrap();
+    let catalog = original.catalog().unwrap();
+    let metadata_id = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+    let root_id = original
+        .trailer
+        .get(b"Root")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let tail = text.rsplit("startxref").next().unwrap();
+    let prev: usize = tail.split_whitespace().next().unwrap().parse().unwrap();
+
+    // Append a revision that replaces the metadata stream in place.
+    let xmp = r#"<rdf:Description pdfaid:part="3" pdfaid:conformance="B"/>"#;
+    let offset = bytes.len();
+    bytes.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Type /Metadata /Subtype /XML /Length {} >>\nstream\n{xmp}\nendstream\nendobj\n",
+            metadata_id.0,
+            xmp.len()
+        )
+        .as_bytes(),
+    );
+    let xref = bytes.len();
+    bytes.extend_from_slice(
+        format!(
+            "xref\n0 1\n0000000000 65535 f\r\n{} 1\n{offset:010} 00000 n\r\n\
+             trailer\n<< /Size {} /Root {} 0 R /Prev {prev} >>\nstartxref\n{xref}\n%%EOF\n",
+            metadata_id.0,
+            original.max_id + 1,
+            root_id.0
+        )
+        .as_bytes(),
+    );
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert!(analyzer.is_pdfa3().unwrap());
+
+    let revisions = analyzer.metadata_revisions(&bytes).unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(
+        revisions[0].conformance,
+        Some(PdfAConformance::new(2, ConformanceLevel::B))
+    );
+    assert_eq!(revisions[1].conformance, Some(PdfAConformance::PDFA_3B));
+    assert_eq!(revisions[1].index, 1);
+    assert_eq!(revisions[1].end_offset, bytes.len());
+    assert_eq!(revisions[0].metadata, revisions[1].metadata);
+    assert_eq!(revisions[1].conformance, analyzer.conformance());
+
+    // A prefix that does not load keeps its number: the binary-mark comment
+    // after the header becomes an early `%%EOF`.
+    let mut marked = bytes.clone();
+    let mark = marked.iter().position(|&b| b == b'\n').unwrap() + 1;
+    let mark_end = mark + marked[mark..].iter().position(|&b| b == b'\n').unwrap() + 1;
+    assert_eq!(mark_end - mark, 6, "binary mark expected");
+    marked[mark..mark_end].copy_from_slice(b"%%EOF\n");
+    let revisions = analyzer.metadata_revisions(&marked).unwrap();
+    let indices: Vec<_> = revisions.iter().map(|r| r.index).collect();
+    assert_eq!(indices, [1, 2]);
+
+    // Only the last markers are loaded, within the analyzer's limits.
+    let mut flooded = bytes.clone();
+    let trailer = format!("startxref\n{xref}\n%%EOF\n");
+    flooded.extend_from_slice(trailer.repeat(1_000).as_bytes());
+    let revisions = analyzer.metadata_revisions(&flooded).unwrap();
+    assert_eq!(revisions.len(), 64);
+    assert_eq!(revisions.last().unwrap().index, 1_001);
+    let tight = PdfAnalyzer::from_bytes_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_memory_bytes: Some(bytes.len() - 1),
+            ..Default::default()
+        },
+    );
+    assert!(tight.is_err());
+    let tight = PdfAnalyzer::from_bytes_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_memory_bytes: Some(bytes.len()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(matches!(
+        tight.metadata_revisions(&flooded),
+        Err(ExtractError::MemoryLimit(_))
+    ));
+}
+
+#[test]
+fn conformance_warnings_flag_missing_extension_schema_and_af_relationship() {
+    use extractembedfilepdf::{ConformanceWarningKind, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let fx = r#"<rdf:Description rdf:about="" xmlns:fx="urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#">
+        <fx:DocumentType>INVOICE</fx:DocumentType>
+        <fx:DocumentFileName>factur-x.xml</fx:DocumentFileName>
+    </rdf:Description>"#;
+    let schema = r#"<rdf:Description rdf:about=""
+            xmlns:pdfaExtension="http://www.aiim.org/pdfa/ns/extension/"
+            xmlns:pdfaSchema="http://www.aiim.org/pdfa/ns/schema#"
+            xmlns:pdfaProperty="http://www.aiim.org/pdfa/ns/property#">
+        <pdfaExtension:schemas><rdf:Bag><rdf:li rdf:parseType="Resource">
+            <pdfaSchema:namespaceURI>urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#</pdfaSchema:namespaceURI>
+            <pdfaSchema:prefix>fx</pdfaSchema:prefix>
+            <pdfaSchema:property><rdf:Seq><rdf:li rdf:parseType="Resource">
+                <pdfaProperty:name>DocumentFileName</pdfaProperty:name>
+            </rdf:li></rdf:Seq></pdfaSchema:property>
+        </rdf:li></rdf:Bag></pdfaExtension:schemas>
+    </rdf:Description>"#;
+    let kinds = |xmp: String, relationship: Option<&str>| {
+        let bytes = build_pdf(|doc, catalog| {
+            let metadata_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                xmp.into_bytes(),
+            ));
+            catalog.set("Metadata", metadata_id);
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, b"<a/>".to_vec()));
+            let mut spec = dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal("factur-x.xml"),
+                "EF" => dictionary! { "F" => stream_id },
+            };
+            if let Some(relationship) = relationship {
+                spec.set("AFRelationship", Object::Name(relationship.into()));
+            }
+            let spec_id = doc.add_object(spec);
+            let names = vec![Object::string_literal("factur-x.xml"), spec_id.into()];
+            let tree_id = doc.add_object(dictionary! { "Names" => names });
+            catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+        });
+        let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+        let warnings = analyzer.conformance_warnings();
+        assert_eq!(analyzer.analyze().unwrap().conformance_warnings, warnings);
+        warnings.into_iter().map(|w| w.kind).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        kinds(fx.to_string(), None),
+        [
+            ConformanceWarningKind::MissingExtensionSchema,
+            ConformanceWarningKind::MissingAfRelationship
+        ]
+    );
+    assert_eq!(
+        kinds(format!("{fx}{schema}"), Some("Data")),
+        [ConformanceWarningKind::MissingPropertyDescription]
+    );
+    let complete = schema.replace(
+        "<pdfaProperty:name>DocumentFileName</pdfaProperty:name>",
+        "<pdfaProperty:name>DocumentFileName</pdfaProperty:name>\
+         <pdfaProperty:name>DocumentType</pdfaProperty:name>",
+    );
+    assert!(kinds(format!("{fx}{complete}"), Some("Alternative")).is_empty());
+}
+
+#[test]
+fn document_metadata_reads_dublin_core_and_dates() {
+    use extractembedfilepdf::{DocumentMetadata, PdfAnalyzer};
+
+    let xmp = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+        <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <dc:title><rdf:Alt>
+                <rdf:li xml:lang="de">Rechnung 42</rdf:li>
+                <rdf:li xml:lang="x-default">Invoice 42 &amp; terms</rdf:li>
+            </rdf:Alt></dc:title>
+            <dc:creator><rdf:Seq>
+                <rdf:li>Jane Doe</rdf:li>
+                <rdf:li>ACME Billing</rdf:li>
+            </rdf:Seq></dc:creator>
+            <dc:description><rdf:Alt>
+                <rdf:li xml:lang="en">Monthly statement</rdf:li>
+            </rdf:Alt></dc:description>
+        </rdf:Description>
+        <rdf:Description rdf:about="" xmlns:xap="http://ns.adobe.com/xap/1.0/"
+            xap:CreateDate="2024-03-01T10:15:00+01:00">
+            <xap:ModifyDate>2024-03-02T08:00:00Z</xap:ModifyDate>
+        </rdf:Description>
+    </rdf:RDF>"#;
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+    assert_eq!(
+        analyzer.document_metadata().unwrap(),
+        DocumentMetadata {
+            title: Some("Invoice 42 & terms".into()),
+            creators: vec!["Jane Doe".into(), "ACME Billing".into()],
+            languages: vec![],
+            description: Some("Monthly statement".into()),
+            create_date: Some("2024-03-01T10:15:00+01:00".into()),
+            modify_date: Some("2024-03-02T08:00:00Z".into()),
+        }
+    );
+
+    let plain = pdf_with_xmp("<rdf:Description><dc:title>Plain</dc:title></rdf:Description>");
+    let metadata = PdfAnalyzer::from_bytes(&plain)
+        .unwrap()
+        .document_metadata()
+        .unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Plain"));
+    assert!(metadata.creators.is_empty() && metadata.create_date.is_none());
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    assert!(analyzer.document_metadata().is_err());
+}
+
+#[test]
+fn language_is_read_from_catalog_and_checked_against_xmp() {
+    use extractembedfilepdf::{ConformanceWarningKind, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let analyzer = |lang: Option<&str>, dc_language: &str| {
+        let xmp = format!(
+            r#"<rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:language><rdf:Bag>{dc_language}</rdf:Bag></dc:language>
+            </rdf:Description>"#
+        );
+        let bytes = build_pdf(|doc, catalog| {
+            let metadata_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                xmp.into_bytes(),
+            ));
+            catalog.set("Metadata", metadata_id);
+            if let Some(lang) = lang {
+                catalog.set("Lang", Object::string_literal(lang));
+            }
+        });
+        PdfAnalyzer::from_bytes(&bytes).unwrap()
+    };
+    let mismatches = |analyzer: &PdfAnalyzer| {
+        analyzer
+            .conformance_warnings()
+            .iter()
+            .filter(|w| w.kind == ConformanceWarningKind::LanguageMismatch)
+            .count()
+    };
+
+    let consistent = analyzer(Some("de-CH"), "<rdf:li>en</rdf:li><rdf:li>DE</rdf:li>");
+    assert_eq!(consistent.language().as_deref(), Some("de-CH"));
+    assert_eq!(
+        consistent.document_metadata().unwrap().languages,
+        ["en", "DE"]
+    );
+    assert_eq!(
+        consistent.analyze().unwrap().language.as_deref(),
+        Some("de-CH")
+    );
+    assert_eq!(mismatches(&consistent), 0);
+
+    let regional = analyzer(Some("fr"), "<rdf:li>fr-CA</rdf:li><rdf:li>en-US</rdf:li>");
+    assert_eq!(mismatches(&regional), 0);
+    let contradicting = analyzer(Some("fr"), "<rdf:li>fry</rdf:li>");
+    assert_eq!(mismatches(&contradicting), 1);
+
+    let missing = analyzer(None, "<rdf:li>en</rdf:li>");
+    assert_eq!(missing.language(), None);
+    assert_eq!(mismatches(&missing), 0);
+}
+
+#[test]
+fn font_report_lists_embedding_subsetting_and_encoding() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Stream};
+
+    let bytes = build_pdf(|doc, _| {
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let program = doc.add_object(Stream::new(dictionary! {}, b"glyf".to_vec()));
+        let descriptor = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "ABCDEF+Arial",
+            "FontFile2" => program,
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "BaseFont" => "ABCDEF+Arial",
+            "FontDescriptor" => descriptor,
+            "Encoding" => dictionary! { "BaseEncoding" => "MacRomanEncoding" },
+        });
+        let descendant = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => "Noto",
+            "FontDescriptor" => descriptor,
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "Noto",
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![descendant.into()],
+        });
+    });
+
+    let fonts = PdfAnalyzer::from_bytes(&bytes).unwrap().font_report();
+    let summary: Vec<_> = fonts
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_deref().unwrap(),
+                f.subtype.as_str(),
+                f.embedded,
+                f.subset,
+                f.encoding.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("Helvetica", "Type1", false, false, Some("WinAnsiEncoding")),
+            (
+                "ABCDEF+Arial",
+                "TrueType",
+                true,
+                true,
+                Some("MacRomanEncoding")
+            ),
+            ("Noto", "Type0", true, false, Some("Identity-H")),
+        ]
+    );
+}
+
+#[test]
+fn structure_findings_report_file_structure_rules() {
+    use extractembedfilepdf::{ExtractorConfig, PdfAnalyzer, StructureRule};
+    use lopdf::{dictionary, Object, Stream};
+
+    let checked = |bytes: &[u8]| {
+        let config = ExtractorConfig {
+            check_file_structure: true,
+            ..Default::default()
+        };
+        PdfAnalyzer::from_bytes_with_config(bytes, config).unwrap()
+    };
+    let rules = |bytes: &[u8]| {
+        let analyzer = checked(bytes);
+        assert_eq!(
+            analyzer.analyze().unwrap().structure_findings,
+            analyzer.structure_findings()
+        );
+        let rules: Vec<_> = analyzer
+            .structure_findings()
+            .iter()
+            .map(|f| f.rule)
+            .collect();
+        rules
+    };
+
+    let with_id = build_pdf(|doc, _| {
+        doc.add_object(Stream::new(dictionary! {}, b"stream data".to_vec()));
+        let id = Object::string_literal("0123456789abcdef");
+        doc.trailer.set("ID", vec![id.clone(), id]);
+    });
+    assert!(rules(&with_id).is_empty());
+    assert_eq!(rules(&build_pdf(|_, _| {})), [StructureRule::TrailerId]);
+
+    let mut damaged = with_id.clone();
+    let length = damaged
+        .windows(10)
+        .position(|w| w == b"Length 11>")
+        .unwrap();
+    damaged[length + 8] = b'0';
+    damaged.extend_from_slice(b"junk\n");
+    let found = checked(&damaged);
+    let found: Vec<_> = found
+        .structure_findings()
+        .iter()
+        .map(|f| (f.rule.id(), f.object_id.is_some()))
+        .collect();
+    assert_eq!(found, [("6.1.3-3", false), ("6.1.7.1-1", true)]);
+
+    // The checks are opt-in.
+    let unchecked = PdfAnalyzer::from_bytes(&damaged).unwrap();
+    assert!(unchecked.structure_findings().is_empty());
+}
+
+#[test]
+fn xfa_datasets_are_extracted_as_pseudo_attachment() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let datasets = r#"<xfa:datasets xmlns:xfa="http://www.xfa.org/schema/xfa-data/1.0/"><xfa:data><invoice total="42.00"/></xfa:data></xfa:datasets>"#;
+    let with_xfa = |xfa: fn(&mut lopdf::Document, &str) -> Object| {
+        let bytes = build_pdf(|doc, catalog| {
+            let xfa = xfa(doc, datasets);
+            catalog.set("AcroForm", dictionary! { "Fields" => vec![], "XFA" => xfa });
+        });
+        PdfAnalyzer::from_bytes(&bytes).unwrap()
+    };
+
+    let packets = with_xfa(|doc, datasets| {
+        let template = doc.add_object(Stream::new(dictionary! {}, b"<template/>".to_vec()));
+        let mut data = Stream::new(dictionary! {}, datasets.as_bytes().to_vec());
+        data.compress().unwrap();
+        let data = doc.add_object(data);
+        Object::Array(vec![
+            Object::string_literal("template"),
+            template.into(),
+            Object::string_literal("datasets"),
+            data.into(),
+        ])
+    });
+    assert!(packets.has_xfa());
+    let file = packets.xfa_datasets().unwrap().unwrap();
+    assert_eq!(file.filename, "xfa-datasets.xml");
+    assert_eq!(file.data, datasets.as_bytes());
+    assert_eq!(file.metadata.mime_type.as_deref(), Some("application/xml"));
+
+    let xdp = with_xfa(|doc, datasets| {
+        let xdp = format!(
+            r#"<xdp:xdp xmlns:xdp="http://ns.adobe.com/xdp/"><template/>{datasets}</xdp:xdp>"#
+        );
+        doc.add_object(Stream::new(dictionary! {}, xdp.into_bytes()))
+            .into()
+    });
+    assert_eq!(
+        xdp.xfa_datasets().unwrap().unwrap().data,
+        datasets.as_bytes()
+    );
+
+    let plain = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {})).unwrap();
+    assert!(!plain.has_xfa());
+    assert!(plain.xfa_datasets().unwrap().is_none());
+}
+
+#[test]
+fn fdf_and_xfdf_attachments_expose_form_fields() {
+    use extractembedfilepdf::{AttachmentKind, FormDataFormat, FormField, PdfAnalyzer};
+
+    let fdf = b"%FDF-1.2\n%\xe2\xe3\xcf\xd3\n1 0 obj\n<< /FDF << /F (order.pdf) /Fields [\
+        << /T (name) /V <FEFF004A0061006E0065> >>\
+        << /T (address) /Kids [ << /T (city) /V (Bern) >> << /T (zip) >> ] >>\
+        << /T (options) /V [ (a) (b) ] >> ] >> >>\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n";
+    let bytes = pdf_with_attachment("data.fdf", fdf, lopdf::Dictionary::new());
+    let file = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap()
+        .remove(0);
+    assert_eq!(file.classify(), AttachmentKind::FormData);
+    let form = file.form_data().unwrap();
+    assert_eq!(form.format, FormDataFormat::Fdf);
+    assert_eq!(form.target.as_deref(), Some("order.pdf"));
+    let field = |name: &str, values: &[&str]| FormField {
+        name: name.into(),
+        values: values.iter().map(|v| v.to_string()).collect(),
+    };
+    assert_eq!(
+        form.fields,
+        [
+            field("name", &["Jane"]),
+            field("address.city", &["Bern"]),
+            field("address.zip", &[]),
+            field("options", &["a", "b"]),
+        ]
+    );
+
+    let xfdf = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <xfdf xmlns="http://ns.adobe.com/xfdf/" xml:space="preserve">
+            <f href="order.pdf"/>
+            <fields>
+                <field name="address">
+                    <field name="city"><value>Z&#252;rich</value></field>
+                    <field name="zip"/>
+                </field>
+                <field name="total"><value>42.00</value></field>
+            </fields>
+        </xfdf>"#;
+    let bytes = pdf_with_attachment("data.xfdf", xfdf, lopdf::Dictionary::new());
+    let file = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap()
+        .remove(0);
+    assert_eq!(file.classify(), AttachmentKind::FormData);
+    let form = file.form_data().unwrap();
+    assert_eq!(form.format, FormDataFormat::Xfdf);
+    assert_eq!(form.target.as_deref(), Some("order.pdf"));
+    assert_eq!(form.value("address.city"), Some("Zürich"));
+    assert_eq!(form.value("total"), Some("42.00"));
+    assert_eq!(form.fields.len(), 3);
+    assert_eq!(form.value("address.zip"), None);
+
+    // A field that lists itself twice among its kids is visited once.
+    let fdf = b"%FDF-1.2\n1 0 obj\n<< /FDF << /Fields [ 2 0 R << /T (ok) /V (1) >> ] >> >>\nendobj\n\
+        2 0 obj\n<< /T (loop) /Kids [ 2 0 R 2 0 R ] >>\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n";
+    let file = make_file("loop.fdf", fdf);
+    let form = file.form_data().unwrap();
+    assert_eq!(form.value("ok"), Some("1"));
+    assert_eq!(form.fields.len(), 1);
+}
+
+#[test]
+fn extract_text_returns_page_and_document_text() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, _| {
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 72 700 Td (Invoice 4711) Tj ET BT /F1 12 Tf 72 680 Td [(Total ) -250 (42.00 EUR)] TJ ET".to_vec(),
+        ));
+        for object in doc.objects.values_mut() {
+            if let Object::Dictionary(page) = object {
+                if page.get(b"Type").and_then(Object::as_name).ok() == Some(b"Page") {
+                    page.set("Contents", content_id);
+                    page.set(
+                        "Resources",
+                        dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                    );
+                }
+            }
+        }
+    });
+
+    let text = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_text()
+        .unwrap();
+    assert_eq!(text.pages.len(), 1);
+    assert_eq!(text.pages[0].page, 1);
+    let page = text.page(1).unwrap();
+    assert!(page.contains("Invoice 4711"), "{page:?}");
+    assert!(
+        page.contains("Total") && page.contains("42.00 EUR"),
+        "{page:?}"
+    );
+    assert_eq!(text.full_text(), page);
+    assert_eq!(text.page(2), None);
+
+    let text = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {}))
+        .unwrap()
+        .extract_text()
+        .unwrap();
+    assert_eq!(text.full_text(), "");
+
+    // Page content is decoded within the configured limits.
+    let bytes = build_pdf(|doc, _| {
+        let mut content = vec![b' '; 100_000];
+        content.extend_from_slice(b"BT /F1 12 Tf (padded) Tj ET");
+        let mut stream = Stream::new(dictionary! {}, content);
+        stream.compress().unwrap();
+        let content_id = doc.add_object(stream);
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        for object in doc.objects.values_mut() {
+            if let Object::Dictionary(page) = object {
+                if page.get(b"Type").and_then(Object::as_name).ok() == Some(b"Page") {
+                    page.set("Contents", content_id);
+                    page.set(
+                        "Resources",
+                        dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                    );
+                }
+            }
+        }
+    });
+    let text = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_text()
+        .unwrap();
+    assert_eq!(text.full_text(), "padded\n");
+    let limited = ExtractorConfig {
+        max_embedded_file_size: Some(1_000),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, limited).unwrap();
+    assert!(matches!(
+        analyzer.extract_text(),
+        Err(ExtractError::FileSizeExceeded)
+    ));
+    let tight = ExtractorConfig {
+        max_memory_bytes: Some(bytes.len() + 1_000),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, tight).unwrap();
+    assert!(matches!(
+        analyzer.extract_text(),
+        Err(ExtractError::MemoryLimit(_))
+    ));
+}
+
+#[test]
+fn peppol_bis_ubl_attachments_expose_participants() {
+    use extractembedfilepdf::{ParticipantId, PdfAnalyzer, PeppolDocumentType};
+
+    let ubl = br#"<?xml version="1.0" encoding="UTF-8"?>
+<ubl:CreditNote xmlns:ubl="urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2"
+    xmlns:b="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2"
+    xmlns:a="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2">
+  <b:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</b:CustomizationID>
+  <b:ProfileID>urn:fdc:peppol.eu:2017:poacc:billing:01:1.0</b:ProfileID>
+  <a:AccountingSupplierParty>
+    <a:Party><b:EndpointID schemeID="9930">DE123456789</b:EndpointID></a:Party>
+  </a:AccountingSupplierParty>
+  <a:AccountingCustomerParty>
+    <a:Party><b:EndpointID>buyer&amp;co</b:EndpointID></a:Party>
+  </a:AccountingCustomerParty>
+</ubl:CreditNote>"#;
+    let bytes = pdf_with_attachment("invoice.xml", ubl, lopdf::Dictionary::new());
+    let file = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap()
+        .remove(0);
+    let peppol = file.peppol().unwrap();
+    assert_eq!(peppol.document_type, PeppolDocumentType::CreditNote);
+    assert_eq!(
+        peppol.profile_id.as_deref(),
+        Some("urn:fdc:peppol.eu:2017:poacc:billing:01:1.0")
+    );
+    assert_eq!(peppol.sender.unwrap().to_string(), "9930:DE123456789");
+    assert_eq!(
+        peppol.receiver,
+        Some(ParticipantId {
+            scheme: None,
+            id: "buyer&co".into()
+        })
+    );
+
+    // Plain EN 16931 UBL outside the Peppol network.
+    let xrechnung = String::from_utf8_lossy(ubl)
+        .replace("#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0", "")
+        .replace(
+            "urn:fdc:peppol.eu:2017:poacc:billing:01:1.0",
+            "urn:example:process",
+        );
+    let bytes = pdf_with_attachment(
+        "invoice.xml",
+        xrechnung.as_bytes(),
+        lopdf::Dictionary::new(),
+    );
+    let file = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap()
+        .remove(0);
+    assert_eq!(file.peppol(), None);
+}
+
+#[test]
+fn swiss_qr_bill_attachments_expose_payment_part() {
+    use extractembedfilepdf::{AttachmentKind, PdfAnalyzer, QrReferenceType};
+
+    let payload = [
+        "SPC",
+        "0200",
+        "1",
+        "CH58 0079 1123 0008 8901 2",
+        "K",
+        "Pia-Maria Rutschmann-Schnyder",
+        "Grosse Marktgasse 28",
+        "9400 Rorschach",
+        "",
+        "",
+        "CH",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "EUR",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "SCOR",
+        "RF18539007547034",
+        "",
+        "EPD",
+        "//S1/10/10201409/11/200701/30/106017086",
+        "eBill/B/peter@sample.ch",
+    ]
+    .join("\r\n");
+    let xml = format!("<?xml version=\"1.0\"?><qrbill><payload>{payload}</payload></qrbill>");
+
+    for (name, data) in [("qr-bill.txt", payload.clone()), ("qr-bill.xml", xml)] {
+        let bytes = pdf_with_attachment(name, data.as_bytes(), lopdf::Dictionary::new());
+        let file = PdfAnalyzer::from_bytes(&bytes)
+            .unwrap()
+            .extract_embedded_files()
+            .unwrap()
+            .remove(0);
+        assert_eq!(file.classify(), AttachmentKind::QrBill, "{name}");
+        let bill = file.qr_bill().unwrap();
+        assert_eq!(bill.iban, "CH5800791123000889012");
+        assert!(!bill.creditor.structured);
+        assert_eq!(
+            bill.creditor.building_number.as_deref(),
+            Some("9400 Rorschach")
+        );
+        assert_eq!(bill.creditor.country, "CH");
+        assert_eq!(bill.debtor, None);
+        assert_eq!(bill.amount, None);
+        assert_eq!(bill.currency, "EUR");
+        assert_eq!(bill.reference_type, QrReferenceType::CreditorReference);
+        assert_eq!(bill.reference.as_deref(), Some("RF18539007547034"));
+        assert_eq!(bill.message, None);
+        assert_eq!(bill.invoice_number(), Some("10201409"));
+        assert_eq!(bill.swico_field("30"), Some("106017086"));
+        assert_eq!(bill.alternative_procedures, ["eBill/B/peter@sample.ch"]);
+    }
+
+    let truncated = payload.replace("EPD", "END");
+    assert_eq!(make_file("qr.txt", truncated.as_bytes()).qr_bill(), None);
+}
+
+#[test]
+fn validate_hybrid_invoice_checks_packaging_rules() {
+    use extractembedfilepdf::{HybridInvoiceRule, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let factur_x = |name: &str, relationship: &str, guideline: &str, level: &str| {
+        let xmp = format!(
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/" pdfaid:part="3" pdfaid:conformance="B"/>
+<rdf:Description rdf:about="" xmlns:fx="urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#">
+<fx:DocumentType>INVOICE</fx:DocumentType><fx:DocumentFileName>factur-x.xml</fx:DocumentFileName>
+<fx:Version>1.0</fx:Version><fx:ConformanceLevel>{level}</fx:ConformanceLevel>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#
+        );
+        let invoice = format!(
+            r#"<?xml version="1.0"?><rsm:CrossIndustryInvoice xmlns:rsm="urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100" xmlns:ram="urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100">
+<rsm:ExchangedDocumentContext><ram:BusinessProcessSpecifiedDocumentContextParameter><ram:ID>A1</ram:ID></ram:BusinessProcessSpecifiedDocumentContextParameter>
+<ram:GuidelineSpecifiedDocumentContextParameter><ram:ID>{guideline}</ram:ID></ram:GuidelineSpecifiedDocumentContextParameter>
+</rsm:ExchangedDocumentContext></rsm:CrossIndustryInvoice>"#
+        );
+        let bytes = build_pdf(|doc, catalog| {
+            let metadata_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                xmp.into_bytes(),
+            ));
+            catalog.set("Metadata", metadata_id);
+            let stream_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "EmbeddedFile" },
+                invoice.into_bytes(),
+            ));
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(name),
+                "UF" => Object::string_literal(name),
+                "EF" => dictionary! { "F" => stream_id },
+                "AFRelationship" => Object::Name(relationship.as_bytes().to_vec()),
+            });
+            let tree_id = doc.add_object(dictionary! {
+                "Names" => vec![Object::string_literal(name), spec_id.into()],
+            });
+            catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+        });
+        PdfAnalyzer::from_bytes(&bytes)
+            .unwrap()
+            .validate_hybrid_invoice()
+            .unwrap()
+    };
+
+    let en16931 = "urn:cen.eu:en16931:2017";
+    let validation = factur_x("factur-x.xml", "Alternative", en16931, "EN 16931");
+    assert!(validation.is_valid(), "{:?}", validation.findings);
+    assert_eq!(validation.profile.as_deref(), Some(en16931));
+    assert_eq!(validation.conformance_level.as_deref(), Some("EN 16931"));
+
+    let rules = |validation: extractembedfilepdf::HybridInvoiceValidation| {
+        validation
+            .findings
+            .iter()
+            .map(|f| f.rule)
+            .collect::<Vec<_>>()
+    };
+    let basic_wl = "urn:factur-x.eu:1p0:basicwl";
+    assert!(factur_x("factur-x.xml", "Data", basic_wl, "BASIC WL").is_valid());
+    assert_eq!(
+        rules(factur_x("invoice.xml", "Alternative", basic_wl, "EN16931")),
+        [
+            HybridInvoiceRule::FileName,
+            HybridInvoiceRule::AfRelationship,
+            HybridInvoiceRule::XmpDescriptor,
+        ]
+    );
+
+    let plain = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {}))
+        .unwrap()
+        .validate_hybrid_invoice()
+        .unwrap();
+    assert_eq!(
+        rules(plain),
+        [
+            HybridInvoiceRule::PdfA3,
+            HybridInvoiceRule::InvoiceCount,
+            HybridInvoiceRule::XmpDescriptor,
+        ]
+    );
+}
+
+#[test]
+fn registered_classifiers_label_extracted_files() {
+    use extractembedfilepdf::{Classifier, EmbeddedFile, PdfAnalyzer};
+
+    struct Prefix(&'static str, &'static [u8]);
+
+    impl Classifier for Prefix {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn classify(&self, file: &EmbeddedFile) -> Option<String> {
+            file.data
+                .starts_with(self.1)
+                .then(|| format!("{}:{}", file.filename, file.data.len()))
+        }
+    }
+
+    let bytes = pdf_with_attachment("order.acme", b"ACME-PO 17", lopdf::Dictionary::new());
+    let mut analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert!(analyzer.extract_embedded_files().unwrap()[0]
+        .metadata
+        .labels
+        .is_empty());
+
+    analyzer.register_classifier(Prefix("acme", b"ACME-PO"));
+    analyzer.register_classifier(Prefix("other", b"OTHER"));
+    let files = analyzer.extract_embedded_files().unwrap();
+    let labels: Vec<_> = files[0].metadata.labels.iter().collect();
+    assert_eq!(
+        labels,
+        [(&"acme".to_string(), &"order.acme:10".to_string())]
+    );
+
+    let handle = &analyzer.embedded_file_handles().unwrap()[0];
+    assert_eq!(handle.decode().unwrap().metadata.labels.len(), 1);
+}
+
+#[test]
+fn configured_mime_table_drives_extension_fallback() {
+    use extractembedfilepdf::{AttachmentKind, ExtractorConfig, MimeTable, PdfAnalyzer};
+
+    let table = MimeTable::default();
+    assert_eq!(table.mime_type(".PDF"), Some("application/pdf"));
+    assert_eq!(
+        table.for_filename("invoice.final.XML"),
+        Some("application/xml")
+    );
+    assert_eq!(table.for_filename("README"), None);
+    assert_eq!(
+        table.extensions("image/jpeg").collect::<Vec<_>>(),
+        ["jpeg", "jpg"]
+    );
+    assert_eq!(MimeTable::empty().iter().count(), 0);
+
+    let bytes = pdf_with_attachment("scan.jfif", b"\x00\x01\x02", lopdf::Dictionary::new());
+    let config = ExtractorConfig {
+        mime_types: MimeTable::default().with("JFIF", "Image/JPEG"),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, config).unwrap();
+    let file = analyzer.extract_embedded_files().unwrap().remove(0);
+    assert_eq!(file.classify(), AttachmentKind::Other);
+    assert_eq!(
+        file.classify_with(&analyzer.config().mime_types),
+        AttachmentKind::Image
+    );
+    assert_eq!(
+        analyzer.config().mime_types.mime_type("jfif"),
+        Some("image/jpeg")
+    );
+
+    let mut table = MimeTable::default();
+    assert_eq!(table.remove("zip").as_deref(), Some("application/zip"));
+    assert_eq!(
+        make_file("a.zip", b"").classify_with(&table),
+        AttachmentKind::Other
+    );
+    assert_eq!(make_file("a.zip", b"").classify(), AttachmentKind::Archive);
+}
+
+#[test]
+fn detected_mime_type_falls_back_to_extension() {
+    use extractembedfilepdf::{ExtractorConfig, MimeTable, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let detect = |name: &str, data: &[u8], subtype: Option<&str>, config: ExtractorConfig| {
+        let bytes = build_pdf(|doc, catalog| {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, data.to_vec()));
+            let mut spec = dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(name),
+                "EF" => dictionary! { "F" => stream_id },
+            };
+            if let Some(subtype) = subtype {
+                spec.set("Subtype", Object::Name(subtype.as_bytes().to_vec()));
+            }
+            let spec_id = doc.add_object(spec);
+            let tree_id = doc.add_object(dictionary! {
+                "Names" => vec![Object::string_literal(name), spec_id.into()],
+            });
+            catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+        });
+        let file = PdfAnalyzer::from_bytes_with_config(&bytes, config)
+            .unwrap()
+            .extract_embedded_files()
+            .unwrap()
+            .remove(0);
+        (
+            file.metadata.detected_mime_type.clone(),
+            file.metadata.content_type().map(str::to_owned),
+        )
+    };
+    let some = |mime: &str| Some(mime.to_string());
+
+    assert_eq!(
+        detect("data.csv", b"a;b\n1;2\n", None, Default::default()),
+        (some("text/csv"), some("text/csv"))
+    );
+    assert_eq!(
+        detect(
+            "data.csv",
+            b"a;b\n1;2\n",
+            Some("text/plain"),
+            Default::default()
+        ),
+        (None, some("text/plain"))
+    );
+    assert_eq!(
+        detect(
+            "chart",
+            b"\x89PNG\r\n\x1a\n...",
+            Some("text/plain"),
+            Default::default()
+        )
+        .0,
+        some("image/png")
+    );
+    assert_eq!(
+        detect(
+            "x.dat",
+            b"  <?xml version='1.0'?><a/>",
+            None,
+            Default::default()
+        )
+        .0,
+        some("application/xml")
+    );
+    assert_eq!(
+        detect("x.dat", b"1234", None, Default::default()),
+        (None, None)
+    );
+
+    let config = ExtractorConfig {
+        mime_types: MimeTable::default().with("dat", "application/octet-stream"),
+        ..Default::default()
+    };
+    assert_eq!(
+        detect("x.dat", b"1234", None, config).0,
+        some("application/octet-stream")
+    );
+}
+
+#[test]
+fn is_auxiliary_marks_schemas_profiles_sidecars_and_fonts() {
+    use extractembedfilepdf::{AfRelationship, MimeTable};
+
+    let mut icc = vec![0u8; 128];
+    icc[36..40].copy_from_slice(b"acsp");
+    // One table: numTables 1, searchRange 16, entrySelector 0, rangeShift 0.
+    let mut otf = b"OTTO\x00\x01\x00\x10\x00\x00\x00\x00CFF ".to_vec();
+    otf.extend([0; 12]);
+    let mut ttf = otf.clone();
+    ttf[..4].copy_from_slice(b"true");
+    let auxiliary = [
+        make_file("sRGB.bin", &icc),
+        make_file("invoice.XSD", b"<?xml version=\"1.0\"?><root/>"),
+        make_file(
+            "schema.xml",
+            br#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#,
+        ),
+        make_file(
+            "meta.xml",
+            b"<?xpacket begin=\"\"?><x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>",
+        ),
+        make_file("font.bin", &otf),
+        make_file("mac.bin", &ttf),
+        make_file("Arial.TTF", b""),
+    ];
+    for file in &auxiliary {
+        assert!(file.is_auxiliary(), "{}", file.filename);
+    }
+
+    let payload = [
+        make_file("factur-x.xml", b"<rsm:CrossIndustryInvoice/>"),
+        make_file("report.pdf", b"%PDF-1.7\n"),
+        make_file("data.csv", b"a,b\n"),
+        make_file("flags.json", b"true"),
+        make_file("notes.txt", b"true story, told in sixteen words or more"),
+        make_file("truncated.bin", &ttf[..20]),
+    ];
+    for file in &payload {
+        assert!(!file.is_auxiliary(), "{}", file.filename);
+    }
+
+    // A configured MIME table can add font extensions.
+    let glyphs = make_file("glyphs.fnt", b"\x00\x00");
+    assert!(!glyphs.is_auxiliary());
+    assert!(glyphs.is_auxiliary_with(&MimeTable::default().with("fnt", "font/x-fnt")));
+
+    let mut schema = make_file("rules.sch", b"<schema/>");
+    assert!(!schema.is_auxiliary());
+    schema.metadata.af_relationship = Some(AfRelationship::Schema);
+    assert!(schema.is_auxiliary());
+}
+
+#[test]
+fn legacy_zugferd_1_invoices_are_recognised() {
+    use extractembedfilepdf::{AttachmentKind, ConformanceWarningKind, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/" pdfaid:part="3" pdfaid:conformance="B"/>
+<rdf:Description rdf:about="" xmlns:zf="urn:ferd:pdfa:CrossIndustryDocument:invoice:1p0#">
+<zf:DocumentType>INVOICE</zf:DocumentType><zf:DocumentFileName>ZUGFeRD-invoice.xml</zf:DocumentFileName>
+<zf:Version>1.0</zf:Version><zf:ConformanceLevel>COMFORT</zf:ConformanceLevel>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+    let invoice = r#"<?xml version="1.0"?><rsm:CrossIndustryDocument xmlns:rsm="urn:ferd:CrossIndustryDocument:invoice:1p0" xmlns:ram="urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:12">
+<rsm:SpecifiedExchangedDocumentContext><ram:GuidelineSpecifiedDocumentContextParameter><ram:ID>urn:ferd:CrossIndustryDocument:invoice:1p0:comfort</ram:ID></ram:GuidelineSpecifiedDocumentContextParameter>
+</rsm:SpecifiedExchangedDocumentContext></rsm:CrossIndustryDocument>"#;
+    let bytes = build_pdf(|doc, catalog| {
+        let metadata_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            xmp.as_bytes().to_vec(),
+        ));
+        catalog.set("Metadata", metadata_id);
+        let stream_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            invoice.as_bytes().to_vec(),
+        ));
+        let spec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("ZUGFeRD-invoice.xml"),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        let tree_id = doc.add_object(dictionary! {
+            "Names" => vec![Object::string_literal("ZUGFeRD-invoice.xml"), spec_id.into()],
+        });
+        catalog.set("Names", dictionary! { "EmbeddedFiles" => tree_id });
+    });
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert_eq!(files[0].classify(), AttachmentKind::InvoiceXml);
+
+    let validation = analyzer.validate_hybrid_invoice().unwrap();
+    assert!(validation.is_valid(), "{:?}", validation.findings);
+    assert!(validation.legacy_zugferd);
+    assert_eq!(validation.conformance_level.as_deref(), Some("COMFORT"));
+
+    assert!(!analyzer
+        .conformance_warnings()
+        .iter()
+        .any(|w| w.kind == ConformanceWarningKind::MissingAfRelationship));
+}
+
+#[test]
+fn normalized_xml_ignores_formatting_differences() {
+    use extractembedfilepdf::XmlFormat;
+
+    let compact = make_file(
+        "factur-x.xml",
+        br#"<?xml version="1.0" encoding="UTF-8"?><rsm:Invoice xmlns:ram="urn:ram" xmlns:rsm="urn:rsm"><ram:ID>INV-1</ram:ID><ram:Note><![CDATA[a < b]]></ram:Note></rsm:Invoice>"#,
+    );
+    let pretty = "<?xml version='1.0' encoding='UTF-16'?>\r\n<rsm:Invoice xmlns:rsm='urn:rsm'\r\n    xmlns:ram='urn:ram'>\r\n\t<!-- generated -->\r\n\t<ram:ID>INV-1</ram:ID>\r\n\t<ram:Note><![CDATA[a < b]]></ram:Note>\r\n</rsm:Invoice>\r\n";
+    let mut utf16 = vec![0xff, 0xfe];
+    utf16.extend(pretty.encode_utf16().flat_map(u16::to_le_bytes));
+    let pretty = make_file("factur-x.xml", &utf16);
+
+    let format = XmlFormat {
+        comments: false,
+        ..Default::default()
+    };
+    let normalized = compact.with_normalized_xml(&format);
+    assert_eq!(
+        normalized.sha256_hex(),
+        pretty.with_normalized_xml(&format).sha256_hex()
+    );
+    assert_eq!(
+        String::from_utf8(normalized.data).unwrap(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rsm:Invoice xmlns:ram=\"urn:ram\" xmlns:rsm=\"urn:rsm\">\n  <ram:ID>INV-1</ram:ID>\n  <ram:Note><![CDATA[a < b]]></ram:Note>\n</rsm:Invoice>\n"
+    );
+    assert!(pretty
+        .normalized_xml()
+        .unwrap()
+        .contains("  <!-- generated -->\n"));
+
+    let single_line = XmlFormat {
+        indent: String::new(),
+        declaration: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        compact.normalized_xml_with(&single_line).unwrap(),
+        r#"<rsm:Invoice xmlns:ram="urn:ram" xmlns:rsm="urn:rsm"><ram:ID>INV-1</ram:ID><ram:Note><![CDATA[a < b]]></ram:Note></rsm:Invoice>"#
+    );
+
+    assert_eq!(make_file("a.xml", b"<a><b></a>").normalized_xml(), None);
+    let text = make_file("notes.txt", b"not xml");
+    assert_eq!(text.normalized_xml(), None);
+    assert_eq!(text.with_normalized_xml(&format).data, text.data);
+}
+
+#[test]
+fn test_util_builds_every_attachment_placement() {
+    use extractembedfilepdf::test_util::{Attachment, NameTreeLayout, PdfBuilder};
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let config = ExtractorConfig {
+        discovery_sources: Some(vec![
+            AttachmentSource::NamesTree,
+            AttachmentSource::Annotation,
+            AttachmentSource::AssociatedFiles,
+        ]),
+        ..Default::default()
+    };
+    let invoice = Attachment {
+        mime_type: Some("text/xml".into()),
+        description: Some("Invoice".into()),
+        af_relationship: Some(AfRelationship::Data),
+        compress: true,
+        ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+    };
+    for layout in [
+        NameTreeLayout::Flat,
+        NameTreeLayout::Inline,
+        NameTreeLayout::Nested { leaf_size: 2 },
+    ] {
+        let bytes = PdfBuilder::new()
+            .pdfa(3, "U")
+            .pages(2)
+            .name_tree(layout)
+            .attachment(invoice.clone())
+            .attachment(Attachment::new("a.txt", "a"))
+            .attachment(Attachment::new("b.txt", "b"))
+            .annotation(2, Attachment::new("note.txt", "note"))
+            .associated_file(Attachment {
+                af_relationship: Some(AfRelationship::Source),
+                ..Attachment::new("source.csv", "x;y")
+            })
+            .build();
+
+        let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+        assert!(analyzer.is_pdfa3().unwrap(), "{layout:?}");
+        let names = analyzer.embedded_file_names().unwrap();
+        assert!(!names.contains(&"source.csv".to_string()), "{layout:?}");
+
+        let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, config.clone()).unwrap();
+        let files = analyzer.extract_embedded_files().unwrap();
+        let mut names: Vec<_> = files.iter().map(|f| f.filename.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["a.txt", "b.txt", "factur-x.xml", "note.txt", "source.csv"],
+            "{layout:?}"
+        );
+
+        let xml = files.iter().find(|f| f.filename == "factur-x.xml").unwrap();
+        assert_eq!(xml.data, b"<rsm:CrossIndustryInvoice/>");
+        assert_eq!(xml.verify_checksum(), Some(true));
+        assert_eq!(xml.metadata.mime_type.as_deref(), Some("text/xml"));
+        assert_eq!(xml.metadata.description.as_deref(), Some("Invoice"));
+        assert_eq!(xml.metadata.af_relationship, Some(AfRelationship::Data));
+
+        let note = files.iter().find(|f| f.filename == "note.txt").unwrap();
+        assert_eq!(note.metadata.source, Some(AttachmentSource::Annotation));
+        assert_eq!(note.metadata.page, Some(2));
+    }
+}
+
+#[test]
+fn extraction_runs_on_an_in_memory_object_graph() {
+    use extractembedfilepdf::test_util::{extract_report, ObjectGraph};
+    use extractembedfilepdf::WarningKind;
+    use lopdf::{dictionary, Object, Stream};
+
+    let mut graph = ObjectGraph::new();
+    let note = graph.add(Stream::new(dictionary! {}, b"note".to_vec()));
+    let note_spec = graph.add(dictionary! {
+        "Type" => "Filespec",
+        "UF" => Object::string_literal("note.txt"),
+        "EF" => dictionary! { "F" => note },
+    });
+    let annotation = graph.add(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "FileAttachment",
+        "FS" => note_spec,
+    });
+    let page = graph.add(dictionary! { "Type" => "Page", "Annots" => vec![annotation.into()] });
+    let pages =
+        graph.add(dictionary! { "Type" => "Pages", "Kids" => vec![page.into()], "Count" => 1 });
+
+    // A file specification whose stream is missing and one that refers to
+    // itself: neither can come out of a PDF writer.
+    let dangling = graph.add(dictionary! {
+        "Type" => "Filespec",
+        "UF" => Object::string_literal("missing.bin"),
+        "EF" => dictionary! { "F" => (999, 0) },
+    });
+    let cycle = (500, 0);
+    graph.insert(cycle, Object::Reference(cycle));
+    let catalog = graph.add(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages,
+        "Names" => dictionary! {
+            "EmbeddedFiles" => dictionary! {
+                "Names" => vec![
+                    Object::string_literal("missing.bin"), dangling.into(),
+                    Object::string_literal("loop.bin"), cycle.into(),
+                ],
+            },
+        },
+    });
+    graph.set_root(catalog);
+    assert_eq!(graph.get_pages().into_values().collect::<Vec<_>>(), [page]);
+
+    let report = extract_report(&graph, &ExtractorConfig::default()).unwrap();
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.files[0].filename, "note.txt");
+    assert_eq!(report.files[0].data, b"note");
+    assert_eq!(report.files[0].metadata.page, Some(1));
+    let skipped: Vec<_> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::Skipped)
+        .map(|w| w.name.as_str())
+        .collect();
+    assert_eq!(skipped, ["missing.bin", "loop.bin"]);
+
+    // Streams of a graph reported as still encrypted are never decoded.
+    graph.set_locked(true);
+    let report = extract_report(&graph, &ExtractorConfig::default()).unwrap();
+    assert!(report.files.is_empty());
+    assert!(report
+        .warnings
+        .iter()
+        .any(|w| w.message.contains("encrypted")));
+}
+
+#[test]
+fn assert_attachments_compares_names_sizes_digests_and_mime_types() {
+    use extractembedfilepdf::assert_attachments;
+    use extractembedfilepdf::test_util::{
+        Attachment, ExpectedAttachment, ExpectedManifest, PdfBuilder,
+    };
+
+    let pdf = PdfBuilder::new()
+        .attachment(Attachment {
+            mime_type: Some("text/xml".into()),
+            compress: true,
+            ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+        })
+        .attachment(Attachment::new("notes.txt", "hello"))
+        .build();
+
+    assert_attachments!(&pdf, [
+        "notes.txt",
+        "factur-x.xml" => { size: 27, mime_type: "text/xml" },
+    ]);
+    assert_attachments!(PdfBuilder::new().build(), []);
+
+    let manifest = ExpectedManifest::new([
+        ExpectedAttachment::new("factur-x.xml")
+            .size(26)
+            .mime_type("application/xml"),
+        ExpectedAttachment::new("notes.txt").sha256("00"),
+        ExpectedAttachment::new("logo.png"),
+    ]);
+    let mismatches = manifest.check(&pdf).unwrap();
+    assert_eq!(
+        mismatches,
+        [
+            "'factur-x.xml': expected 26 bytes, found 27",
+            "'factur-x.xml': expected MIME type application/xml, found text/xml",
+            "'notes.txt': expected SHA-256 00, found \
+             2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            "'logo.png': missing",
+        ]
+    );
+
+    let files = extractembedfilepdf::PdfAnalyzer::from_bytes(&pdf)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    let recorded = ExpectedManifest::from_files(&files);
+    recorded.assert_matches(&pdf);
+    assert_eq!(
+        recorded.mismatches(&files[..1]),
+        [format!("'{}': missing", files[1].filename)]
+    );
+
+    let result = std::panic::catch_unwind(|| assert_attachments!(&pdf, ["notes.txt"]));
+    let message = result.unwrap_err().downcast::<String>().unwrap();
+    assert!(
+        message.contains("'factur-x.xml': not expected"),
+        "{message}"
+    );
+}
+
+#[cfg(feature = "fuzzing")]
+#[test]
+fn fuzz_entry_points_survive_mutated_documents() {
+    use extractembedfilepdf::fuzz::{
+        fuzz_attachment, fuzz_load_and_extract, fuzz_with_config, FuzzConfig, FuzzInput,
+    };
+    use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+    use extractembedfilepdf::{AttachmentSource, RiskPolicy};
+
+    let bytes = PdfBuilder::new()
+        .pdfa(3, "B")
+        .attachment(Attachment {
+            compress: true,
+            ..Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>")
+        })
+        .annotation(1, Attachment::new("inner.pdf", pdf_with_xmp("<x/>")))
+        .build();
+
+    let config = FuzzConfig {
+        max_embedded_file_size: Some(8),
+        discovery_sources: Some(vec![AttachmentSource::Annotation]),
+        fail_on_any_error: true,
+        risk_policy: RiskPolicy::Block,
+        ..Default::default()
+    };
+    for len in (0..bytes.len()).step_by(97).chain([bytes.len()]) {
+        let mut data = bytes[..len].to_vec();
+        fuzz_load_and_extract(&data);
+        if let Some(byte) = data.get_mut(len / 2) {
+            *byte ^= 0xff;
+        }
+        fuzz_with_config(&FuzzInput {
+            config: config.clone(),
+            data,
+        });
+    }
+
+    for (name, data) in [
+        ("factur-x.xml", &b"<rsm:CrossIndustryInvoice><ram:ID>"[..]),
+        ("form.fdf", b"%FDF-1.2\n1 0 obj <</FDF <</Fields [<</T (a"),
+        ("qr.txt", b"SPC\n0200\n1\n"),
+        ("x.pdf", b"%PDF-1.7\n%%EOF"),
+        ("", b""),
+    ] {
+        fuzz_attachment(name, data);
+    }
+}
+
+proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn built_attachments_extract_unchanged(
+        attachments in proptest::collection::vec(extractembedfilepdf::test_util::attachment(), 1..4),
+    ) {
+        use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+        use extractembedfilepdf::PdfAnalyzer;
+
+        let bytes = attachments
+            .iter()
+            .enumerate()
+            .fold(PdfBuilder::new(), |builder, (i, attachment)| {
+                builder.attachment(Attachment {
+                    name: format!("{i}-{}", attachment.name),
+                    ..attachment.clone()
+                })
+            })
+            .build();
+        let files = PdfAnalyzer::from_bytes(&bytes).unwrap().extract_embedded_files().unwrap();
+        proptest::prop_assert_eq!(files.len(), attachments.len());
+        for (file, attachment) in files.iter().zip(&attachments) {
+            proptest::prop_assert_eq!(&file.data, &attachment.data);
+            proptest::prop_assert_eq!(&file.metadata.af_relationship, &attachment.af_relationship);
+            if attachment.params {
+                proptest::prop_assert_eq!(file.verify_checksum(), Some(true));
+            }
+        }
+    }
+
+    #[test]
+    fn generated_documents_parse_and_extract(
+        attachments in proptest::collection::vec(extractembedfilepdf::test_util::attachment(), 1..6),
+        placements in proptest::collection::vec(0..3u8, 6),
+        pages in 1..4usize,
+        leaf_size in proptest::option::of(1..4usize),
+        config in extractembedfilepdf::test_util::config(),
+    ) {
+        use extractembedfilepdf::test_util::{Attachment, NameTreeLayout, PdfBuilder};
+        use extractembedfilepdf::{AttachmentSource, PdfAnalyzer};
+
+        let layout = leaf_size.map_or(NameTreeLayout::Flat, |leaf_size| {
+            NameTreeLayout::Nested { leaf_size }
+        });
+        let mut builder = PdfBuilder::new().pages(pages).name_tree(layout);
+        for (i, (attachment, placement)) in attachments.iter().zip(&placements).enumerate() {
+            let attachment = Attachment {
+                name: format!("{i}-{}", attachment.name),
+                ..attachment.clone()
+            };
+            builder = match placement {
+                0 => builder.attachment(attachment),
+                1 => builder.annotation(i % pages + 1, attachment),
+                _ => builder.associated_file(attachment),
+            };
+        }
+        let bytes = builder.build();
+
+        // Every generated document parses and gives back every attachment.
+        let everywhere = ExtractorConfig {
+            discovery_sources: Some(vec![
+                AttachmentSource::NamesTree,
+                AttachmentSource::Annotation,
+                AttachmentSource::AssociatedFiles,
+            ]),
+            ..Default::default()
+        };
+        let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, everywhere).unwrap();
+        let mut extracted: Vec<_> = analyzer
+            .extract_embedded_files()
+            .unwrap()
+            .into_iter()
+            .map(|file| (file.filename, file.data))
+            .collect();
+        let mut expected: Vec<_> = attachments
+            .iter()
+            .enumerate()
+            .map(|(i, attachment)| (format!("{i}-{}", attachment.name), attachment.data.clone()))
+            .collect();
+        extracted.sort();
+        expected.sort();
+        proptest::prop_assert_eq!(extracted, expected);
+
+        // Any generated configuration is handled without panicking.
+        if let Ok(analyzer) = PdfAnalyzer::from_bytes_with_config(&bytes, config) {
+            let _ = analyzer.extract_with_report();
+        }
+    }
+
+    #[test]
+    fn generated_files_and_hostile_names_are_handled(
+        file in extractembedfilepdf::test_util::embedded_file(),
+        name in extractembedfilepdf::test_util::hostile_filename(),
+    ) {
+        let _ = (file.classify(), file.summary(), file.extension(), file.is_auxiliary());
+        let renamed = EmbeddedFile { filename: name, ..file };
+        let _ = (renamed.extension(), renamed.preview(16));
+    }
+}
+
+#[test]
+fn require_conformance_reports_found_and_required() {
+    use extractembedfilepdf::{ConformanceLevel, ErrorCode, PdfAConformance, PdfAnalyzer};
+
+    let xmp = r#"<rdf:Description pdfaid:part="3" pdfaid:conformance="A"/>"#;
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+    let declared = PdfAConformance::new(3, ConformanceLevel::A);
+    assert_eq!(analyzer.conformance(), Some(declared));
+    analyzer
+        .require_conformance(PdfAConformance::PDFA_3B)
+        .unwrap();
+    analyzer.require_conformance(declared).unwrap();
+
+    let error = analyzer
+        .require_conformance(PdfAConformance::new(2, ConformanceLevel::B))
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::ConformanceMismatch);
+    assert_eq!(
+        error.to_string(),
+        "Document requires PDF/A-2B but declares PDF/A-3A"
+    );
+
+    let xmp = "<pdfaid:part>3</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance>";
+    let level_b = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+    let error = level_b
+        .require_conformance(PdfAConformance::new(3, ConformanceLevel::U))
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ExtractError::ConformanceMismatch { found: Some(found), .. } if found == PdfAConformance::PDFA_3B
+    ));
+
+    let plain = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {})).unwrap();
+    assert_eq!(
+        plain
+            .require_conformance(PdfAConformance::PDFA_3B)
+            .unwrap_err()
+            .to_string(),
+        "Document requires PDF/A-3B but declares no PDF/A conformance"
+    );
+}
+
+#[test]
+fn xmp_raw_returns_the_packet() {
+    use extractembedfilepdf::PdfAnalyzer;
+    let xmp = r#"<rdf:Description pdfaid:part="3" dc:creator="Alice"/>"#;
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_xmp(xmp)).unwrap();
+    assert_eq!(analyzer.xmp_raw().unwrap(), xmp);
+
+    let without = PdfAnalyzer::from_bytes(&build_pdf(|_, _| {})).unwrap();
+    assert!(matches!(without.xmp_raw(), Err(ExtractError::NotPdfA3(_))));
+}
+
+/// Write `bytes` to a temporary file and open it with `config`.
+fn analyzer_with_config(bytes: &[u8], config: ExtractorConfig) -> extractembedfilepdf::PdfAnalyzer {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), bytes).unwrap();
+    extractembedfilepdf::PdfAnalyzer::with_config(file.path(), config).unwrap()
+}
+
+#[test]
+fn declared_size_is_screened_before_decoding() {
+    use lopdf::dictionary;
+
+    let bytes = pdf_with_attachment("a.xml", b"<a/>", dictionary! { "Size" => 1_000_000 });
+
+    let unlimited = analyzer_with_config(&bytes, ExtractorConfig::default());
+    assert_eq!(
+        unlimited.extract_embedded_file("a.xml").unwrap().data,
+        b"<a/>"
+    );
+
+    let limited = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_embedded_file_size: Some(100),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        limited.extract_embedded_file("a.xml"),
+        Err(ExtractError::FileSizeExceeded)
+    ));
+}
+
+#[test]
+fn unfiltered_stream_length_is_screened() {
+    let bytes = pdf_with_attachment("big.bin", &[0u8; 512], lopdf::Dictionary::new());
+    let analyzer = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_embedded_file_size: Some(100),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        analyzer.embedded_file_handles().unwrap()[0].read(),
+        Err(ExtractError::FileSizeExceeded)
+    ));
+}
+
+#[test]
+fn flate_decoding_stops_at_the_size_limit() {
+    use lopdf::{dictionary, Stream};
+
+    let mut stream = Stream::new(dictionary! {}, vec![b'x'; 10_000]);
+    stream.compress().unwrap();
+    assert!(stream.content.len() < 100, "test data must compress well");
+    let bytes = pdf_with_stream("big.txt", stream);
+
+    let limited = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_embedded_file_size: Some(100),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        limited.extract_embedded_file("big.txt"),
+        Err(ExtractError::FileSizeExceeded)
+    ));
+
+    let roomy = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_embedded_file_size: Some(10_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        roomy.extract_embedded_file("big.txt").unwrap().data,
+        vec![b'x'; 10_000]
+    );
+}
+
+// ── Fixture-based tests (ignored without real PDFs) ───────────────────────────
+
+/// To run: place a valid PDF/A-3 with embedded files at
+/// `tests/fixtures/sample_pdfa3.pdf` and run with `--include-ignored`.
+#[test]
+#[ignore]
+fn fixture_pdfa3_roundtrip() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let bytes = std::fs::read("tests/fixtures/sample_pdfa3.pdf")
+        .expect("place tests/fixtures/sample_pdfa3.pdf to run this test");
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert!(analyzer.is_pdf().unwrap());
+    assert!(analyzer.is_pdfa3().unwrap());
+    assert!(analyzer.has_embedded_files().unwrap());
+
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert!(!files.is_empty());
+    for f in &files {
+        assert!(!f.filename.is_empty());
+        assert!(!f.data.is_empty());
+    }
+}
+
+#[test]
+fn identity_crypt_filter_is_skipped_when_decoding() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::{dictionary, Object, Stream};
+    use std::io::Write;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"crypt filtered").unwrap();
+    let mut stream = Stream::new(dictionary! {}, encoder.finish().unwrap());
+    stream.dict.set(
+        "Filter",
+        vec![
+            Object::Name(b"Crypt".to_vec()),
+            Object::Name(b"FlateDecode".to_vec()),
+        ],
+    );
+    stream.dict.set(
+        "DecodeParms",
+        vec![
+            Object::Dictionary(dictionary! { "Name" => "Identity" }),
+            Object::Null,
+        ],
+    );
+    let bytes = pdf_with_stream("c.txt", stream);
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert_eq!(files[0].data, b"crypt filtered");
+}
+
+#[test]
+fn fallback_decoders_handle_filters_lopdf_does_not() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let filtered = |filter: &str, content: &[u8]| {
+        Stream::new(dictionary! { "Filter" => filter }, content.to_vec())
+    };
+    let cases = [
+        ("hex.txt", filtered("ASCIIHexDecode", b"68 65 6C 6C 6F>")),
+        // Run-length: copy 2 literal bytes, then repeat 'o' 3 times.
+        (
+            "rle.txt",
+            filtered("RunLengthDecode", &[1, b'h', b'e', 254, b'o', 128]),
+        ),
+        (
+            "chain.txt",
+            Stream::new(
+                dictionary! {
+                    "Filter" => vec![
+                        Object::Name(b"AHx".to_vec()),
+                        Object::Name(b"A85".to_vec()),
+                    ],
+                },
+                b"424F752172445A7E3E".to_vec(), // hex of the ASCII85 text "BOu!rDZ~>"
+            ),
+        ),
+        // The LZW example of ISO 32000-1 §7.4.4.2, under its abbreviated name.
+        (
+            "lzw.txt",
+            filtered(
+                "LZW",
+                &[0x80, 0x0B, 0x60, 0x50, 0x22, 0x0C, 0x0C, 0x85, 0x01],
+            ),
+        ),
+    ];
+    let expected: [&[u8]; 4] = [b"hello", b"heooo", b"hello", b"-----A---B"];
+
+    for ((name, stream), expected) in cases.into_iter().zip(expected) {
+        let bytes = pdf_with_stream(name, stream);
+        let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+        let files = analyzer.extract_embedded_files().unwrap();
+        assert_eq!(files[0].data, expected, "{name}");
+        assert!(files[0].is_fully_decoded(), "{name}");
+    }
+}
+
+#[test]
+fn undecodable_filters_are_reported() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let stream = Stream::new(
+        dictionary! {
+            "Filter" => vec![
+                Object::Name(b"ASCIIHexDecode".to_vec()),
+                Object::Name(b"DCTDecode".to_vec()),
+            ],
+        },
+        b"FFD8FFE0>".to_vec(),
+    );
+    let bytes = pdf_with_stream("photo.jpg", stream);
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    assert!(!files[0].is_fully_decoded());
+    assert_eq!(files[0].metadata.undecoded_filters, ["DCTDecode"]);
+    assert_eq!(files[0].data, [0xFF, 0xD8, 0xFF, 0xE0]);
+}
+
+#[test]
+fn malformed_filter_entries_are_reported_as_undecoded() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let cases = [
+        (Object::Integer(7), vec!["<Integer>"]),
+        (
+            Object::Array(vec![
+                Object::Name(b"FlateDecode".to_vec()),
+                Object::Boolean(true),
+            ]),
+            vec!["FlateDecode", "<Boolean>"],
+        ),
+    ];
+    for (filter, expected) in cases {
+        let stream = Stream::new(dictionary! { "Filter" => filter }, b"raw".to_vec());
+        let bytes = pdf_with_stream("odd.bin", stream);
+
+        let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+        let files = analyzer.extract_embedded_files().unwrap();
+        assert!(!files[0].is_fully_decoded());
+        assert_eq!(files[0].metadata.undecoded_filters, expected);
+        assert_eq!(files[0].data, b"raw");
+    }
+}
+
+#[test]
+fn oversized_predictor_rows_are_rejected_before_allocating() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::{dictionary, Stream};
+    use std::io::Write;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&[2, 0, 0, 0]).unwrap();
+    let deflated = encoder.finish().unwrap();
+
+    // 4 × 16 × 2³¹ bits is a 16 GiB row; the second one overflows usize.
+    for columns in [2_147_483_647, i64::MAX] {
+        let stream = Stream::new(
+            dictionary! {
+                "Filter" => "FlateDecode",
+                "DecodeParms" => dictionary! {
+                    "Predictor" => 12,
+                    "Columns" => columns,
+                    "Colors" => 4,
+                    "BitsPerComponent" => 16,
+                },
+            },
+            deflated.clone(),
+        );
+        let bytes = pdf_with_stream("rows.bin", stream);
+        let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+        let file = &analyzer.extract_embedded_files().unwrap()[0];
+        assert_eq!(file.metadata.undecoded_filters, ["FlateDecode"]);
+        assert_eq!(file.data, deflated);
+    }
+}
+
+#[test]
+fn truncated_streams_are_recovered_on_request() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::{dictionary, Stream};
+    use std::io::Write;
+
+    let xml: String = (0..200)
+        .map(|i| format!("<line n=\"{i}\">invoice data</line>\n"))
+        .collect();
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::none());
+    encoder.write_all(xml.as_bytes()).unwrap();
+    let mut deflated = encoder.finish().unwrap();
+    deflated.truncate(deflated.len() / 2);
+
+    let stream = Stream::new(dictionary! { "Filter" => "FlateDecode" }, deflated);
+    let bytes = pdf_with_stream("factur-x.xml", stream);
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let file = &analyzer.extract_embedded_files().unwrap()[0];
+    assert!(!file.metadata.truncated);
+    assert_eq!(file.metadata.undecoded_filters, ["FlateDecode"]);
+
+    let config = ExtractorConfig {
+        recover_truncated_streams: true,
+        ..Default::default()
+    };
+    let analyzer = analyzer_with_config(&bytes, config);
+    let file = &analyzer.extract_embedded_files().unwrap()[0];
+    assert!(file.metadata.truncated);
+    assert!(!file.is_fully_decoded());
+    assert!(!file.data.is_empty());
+    assert!(xml.as_bytes().starts_with(&file.data));
+}
+
+/// A PDF with a valid `good.txt` attachment and a `broken.txt` file
+/// specification whose `/EF` entry points at a dictionary instead of a stream.
+/// Build a PDF whose only attachment `name` has `/EF /F` pointing at `id`.
+fn pdf_with_stream_ref(name: &str, id: lopdf::ObjectId) -> Vec<u8> {
+    use lopdf::{dictionary, Object};
+
+    build_pdf(|doc, catalog| {
+        let spec = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(name),
+            "EF" => dictionary! { "F" => id },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![Object::string_literal(name), spec.into()],
+            }},
+        );
+    })
+}
+
+fn pdf_with_broken_attachment() -> Vec<u8> {
+    use lopdf::{dictionary, Object, Stream};
+
+    build_pdf(|doc, catalog| {
+        let stream_id = doc.add_object(Stream::new(dictionary! {}, b"ok".to_vec()));
+        let good = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("good.txt"),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        let broken = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("broken.txt"),
+            "EF" => dictionary! { "F" => good },
+        });
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! {
+                "Names" => vec![
+                    Object::string_literal("broken.txt"), broken.into(),
+                    Object::string_literal("good.txt"), good.into(),
+                ],
+            }},
+        );
+    })
+}
+
+#[test]
+fn fail_on_any_error_rejects_skipped_files() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let bytes = pdf_with_broken_attachment();
+    let lenient = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = lenient.extract_embedded_files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].filename, "good.txt");
+
+    let config = ExtractorConfig {
+        fail_on_any_error: true,
+        ..Default::default()
+    };
+    let strict = analyzer_with_config(&bytes, config);
+    assert!(matches!(
+        strict.extract_embedded_files(),
+        Err(ExtractError::ExtractionError { name, .. }) if name == "broken.txt"
+    ));
+}
+
+#[test]
+fn extraction_report_lists_skipped_and_damaged_files() {
+    use extractembedfilepdf::{PdfAnalyzer, WarningKind};
+    use lopdf::{dictionary, Object, Stream};
+
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_broken_attachment()).unwrap();
+    let report = analyzer.extract_with_report().unwrap();
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].name, "broken.txt");
+    assert_eq!(report.warnings[0].kind, WarningKind::Skipped);
+
+    let mut stream = Stream::new(dictionary! {}, b"tampered".to_vec());
+    stream.dict.set(
+        "Params",
+        dictionary! { "CheckSum" => Object::string_literal(vec![0u8; 16]) },
+    );
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_stream("a.txt", stream)).unwrap();
+    let report = analyzer.extract_with_report().unwrap();
+    assert_eq!(report.files.len(), 1);
+    let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(kinds, [WarningKind::ChecksumMismatch]);
+}
+
+#[test]
+fn duplicate_filenames_with_different_content_are_flagged() {
+    use extractembedfilepdf::{PdfAnalyzer, WarningKind};
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let mut names = Vec::new();
+        for (key, data) in [("1", "<real/>"), ("2", "<spoof/>"), ("3", "<real/>")] {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, data.into()));
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal("invoice.xml"),
+                "EF" => dictionary! { "F" => stream_id },
+            });
+            names.extend([Object::string_literal(key), spec_id.into()]);
+        }
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+        );
+    });
+
+    let report = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_with_report()
+        .unwrap();
+    assert_eq!(report.files.len(), 3);
+    let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(kinds, [WarningKind::DuplicateName]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let writing = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        },
+    );
+    let report = writing.extract_with_report().unwrap();
+    assert!(report.warnings[0].message.contains("written to disk"));
+    // The copy discovered first is kept, with or without `parallel`.
+    let written = std::fs::read(dir.path().join("invoice.xml")).unwrap();
+    assert_eq!(written, report.files[0].data);
+
+    // Later calls know what earlier ones wrote.
+    let spoof = writing.extract_embedded_file("2").unwrap();
+    assert_eq!(spoof.data, b"<spoof/>");
+    let written = std::fs::read(dir.path().join("invoice.xml")).unwrap();
+    assert_eq!(written, b"<real/>");
+
+    let dir = tempfile::tempdir().unwrap();
+    let writing = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        },
+    );
+    writing.extract_embedded_file("2").unwrap();
+    // A later report names the files kept off the disk by what an earlier
+    // call wrote: both copies of the other content, but not the spoof itself.
+    let report = writing.extract_with_report().unwrap();
+    assert_eq!(report.files.len(), 3);
+    let kept: Vec<_> = report
+        .warnings
+        .iter()
+        .map(|w| (w.kind, w.message.contains("written to disk")))
+        .collect();
+    assert_eq!(kept, [(WarningKind::DuplicateName, true); 2]);
+    let written = std::fs::read(dir.path().join("invoice.xml")).unwrap();
+    assert_eq!(written, b"<spoof/>");
+    // The same attachment extracted again is rewritten.
+    std::fs::remove_file(dir.path().join("invoice.xml")).unwrap();
+    writing.extract_embedded_file("1").unwrap();
+    assert!(!dir.path().join("invoice.xml").exists());
+    writing.extract_embedded_file("2").unwrap();
+    let written = std::fs::read(dir.path().join("invoice.xml")).unwrap();
+    assert_eq!(written, b"<spoof/>");
+}
+
+#[test]
+fn sidecars_record_provenance_next_to_written_files() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = example_pdf("format_a3_withFiles.pdf");
+    let config = ExtractorConfig {
+        extract_to_disk: true,
+        output_directory: Some(dir.path().to_string_lossy().into_owned()),
+        write_sidecars: true,
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::with_config(&path, config).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+
+    let sidecar = std::fs::read_to_string(dir.path().join("Excel_embed1.xlsx.meta.json")).unwrap();
+    let meta: serde_json::Value = serde_json::from_str(&sidecar).unwrap();
+    assert_eq!(meta["filename"], "Excel_embed1.xlsx");
+    assert_eq!(meta["size"], 5902);
+    assert_eq!(meta["sha256"], files[0].sha256_hex());
+    assert_eq!(meta["md5"], files[0].md5_hex());
+    assert_eq!(meta["discovered_via"], "names-tree");
+    assert_eq!(meta["source_sha256"], analyzer.source_sha256().unwrap());
+    assert!(meta["source_path"]
+        .as_str()
+        .unwrap()
+        .ends_with("format_a3_withFiles.pdf"));
+    let extracted_at = meta["extracted_at"].as_str().unwrap();
+    assert!(extracted_at.len() == 20 && extracted_at.ends_with('Z'));
+    assert!(dir.path().join("Word_embed.docx.meta.json").exists());
+
+    let bytes = std::fs::read(&path).unwrap();
+    let plain = tempfile::tempdir().unwrap();
+    let from_bytes = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(plain.path().to_string_lossy().into_owned()),
+            hash_source: true,
+            ..Default::default()
+        },
+    );
+    assert_eq!(from_bytes.source_sha256(), analyzer.source_sha256());
+    let unhashed = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert_eq!(unhashed.source_sha256(), None);
+    from_bytes.extract_embedded_files().unwrap();
+    assert!(plain.path().join("Excel_embed1.xlsx").exists());
+    assert!(!plain.path().join("Excel_embed1.xlsx.meta.json").exists());
+}
+
+#[test]
+fn sidecars_and_attachments_never_overwrite_each_other() {
+    use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+    use extractembedfilepdf::{PdfAnalyzer, WarningKind};
+
+    // Returns what ends up in `b.xml.meta.json` when the attachments are
+    // found in the order given, and the kinds of warning reported.
+    let written = |first: &str, second: &str| {
+        let bytes = PdfBuilder::new()
+            .attachment(Attachment::new(first, "first"))
+            .attachment(Attachment::new(second, "second"))
+            .build();
+        let dir = tempfile::tempdir().unwrap();
+        let config = ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(dir.path().to_string_lossy().into_owned()),
+            write_sidecars: true,
+            ..Default::default()
+        };
+        let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, config).unwrap();
+        let report = analyzer.extract_with_report().unwrap();
+        assert_eq!(report.files.len(), 2);
+        let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+        let sidecar = std::fs::read_to_string(dir.path().join("b.xml.meta.json")).unwrap();
+        (sidecar, kinds)
+    };
+
+    // The attachment comes first: its name is kept for the sidecar of b.xml,
+    // and the attachment named like it is reported as not written.
+    let (sidecar, kinds) = written("b.xml", "b.xml.meta.json");
+    assert!(sidecar.contains("\"filename\": \"b.xml\""));
+    assert_eq!(kinds, [WarningKind::DuplicateName]);
+    // The sidecar-named attachment comes first and is not overwritten.
+    let (attachment, kinds) = written("a/b.xml.meta.json", "dir/b.xml");
+    assert_eq!(attachment, "first");
+    assert!(kinds.is_empty());
+}
+
+#[test]
+fn declared_size_mismatch_is_reported() {
+    use extractembedfilepdf::{PdfAnalyzer, SizeMismatch, WarningKind};
+    use lopdf::dictionary;
+
+    let bytes = pdf_with_attachment("a.xml", b"<a/>", dictionary! { "Size" => 10 });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let report = analyzer.extract_with_report().unwrap();
+    assert_eq!(
+        report.files[0].metadata.size_mismatch,
+        Some(SizeMismatch {
+            declared: 10,
+            actual: 4
+        })
+    );
+    let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(kinds, [WarningKind::SizeMismatch]);
+
+    let strict = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            fail_on_any_error: true,
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        strict.extract_embedded_files(),
+        Err(ExtractError::ExtractionError { reason, .. }) if reason.contains("/Params/Size")
+    ));
+
+    let matching = pdf_with_attachment("a.xml", b"<a/>", dictionary! { "Size" => 4 });
+    let files = PdfAnalyzer::from_bytes(&matching)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    assert_eq!(files[0].metadata.size_mismatch, None);
+
+    // A negative size is no declaration at all, even in strict mode.
+    let negative = pdf_with_attachment("a.xml", b"<a/>", dictionary! { "Size" => -1 });
+    let files = analyzer_with_config(
+        &negative,
+        ExtractorConfig {
+            fail_on_any_error: true,
+            ..Default::default()
+        },
+    )
+    .extract_embedded_files()
+    .unwrap();
+    assert_eq!(files[0].metadata.size, None);
+    assert_eq!(files[0].metadata.size_mismatch, None);
+}
+
+#[test]
+fn strict_mode_applies_to_single_file_extraction() {
+    use extractembedfilepdf::LazyDocument;
+    use lopdf::dictionary;
+
+    let bytes = pdf_with_attachment("a.xml", b"<a/>", dictionary! { "Size" => 10 });
+    let strict = ExtractorConfig {
+        fail_on_any_error: true,
+        ..Default::default()
+    };
+    fn mismatch<T>(result: Result<T, ExtractError>) -> bool {
+        matches!(
+            result,
+            Err(ExtractError::ExtractionError { reason, .. }) if reason.contains("/Params/Size")
+        )
+    }
+
+    let analyzer = analyzer_with_config(&bytes, strict.clone());
+    assert!(mismatch(analyzer.extract_embedded_file("a.xml")));
+    let handles = analyzer.embedded_file_handles().unwrap();
+    assert!(mismatch(handles[0].decode()));
+    assert!(mismatch(handles[0].read()));
+
+    let lazy = LazyDocument::from_bytes_with_config(&bytes, strict).unwrap();
+    assert!(mismatch(lazy.extract_embedded_file("a.xml")));
+
+    // Without strict mode, the mismatch is only recorded.
+    let lenient = analyzer_with_config(&bytes, ExtractorConfig::default());
+    let file = lenient.extract_embedded_file("a.xml").unwrap();
+    assert!(file.metadata.size_mismatch.is_some());
+}
+
+#[test]
+fn errors_carry_stable_codes() {
+    use extractembedfilepdf::{ErrorCode, PdfAnalyzer};
+
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_broken_attachment()).unwrap();
+    let error = analyzer.extract_embedded_file("missing.txt").unwrap_err();
+    assert_eq!(error.code(), ErrorCode::FileNotFound);
+    assert_eq!(error.code().to_string(), "E_FILE_NOT_FOUND");
+
+    let error = analyzer.extract_embedded_file("broken.txt").unwrap_err();
+    assert_eq!(error.code(), ErrorCode::BadFilespec);
+
+    let error = PdfAnalyzer::from_bytes(b"not a pdf").err().unwrap();
+    assert_eq!(error.code(), ErrorCode::Parse);
+    // The parser's own error is only reachable as the source.
+    assert!(matches!(error, ExtractError::ParseError(_)));
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn extraction_errors_carry_typed_details() {
+    use extractembedfilepdf::{ExtractionStage, PdfAnalyzer};
+
+    let analyzer = PdfAnalyzer::from_bytes(&pdf_with_broken_attachment()).unwrap();
+    match analyzer.extract_embedded_file("broken.txt") {
+        Err(ExtractError::ExtractionError {
+            name,
+            spec_id,
+            stage,
+            reason,
+            cause,
+        }) => {
+            assert_eq!(name, "broken.txt");
+            assert!(spec_id.is_some());
+            assert_eq!(stage, ExtractionStage::EmbeddedStream);
+            assert_eq!(reason, "embedded stream object is not a stream");
+            assert!(cause.is_none());
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A dangling stream reference keeps the parser error as its source.
+    let bytes = pdf_with_stream_ref("dangling.txt", (999, 0));
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let error = analyzer.extract_embedded_file("dangling.txt").unwrap_err();
+    let ExtractError::ExtractionError { cause, .. } = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert!(cause.is_some());
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.to_string(), cause.as_ref().unwrap().to_string());
+}
+
+#[test]
+fn metrics_sink_sees_every_stage() {
+    use extractembedfilepdf::{MetricsSink, PdfAnalyzer};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl MetricsSink for Recorder {
+        fn record_load(&self, _: Duration, bytes: usize) {
+            self.0.lock().unwrap().push(format!("load {}", bytes > 0));
+        }
+        fn record_discovery(&self, _: Duration, files: usize) {
+            self.0.lock().unwrap().push(format!("discovery {files}"));
+        }
+        fn record_decode(&self, name: &str, _: Duration, bytes: usize) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("decode {name} {bytes}"));
+        }
+        fn record_write(&self, path: &Path, _: Duration, bytes: usize) {
+            let name = path.file_name().unwrap().to_string_lossy();
+            self.0.lock().unwrap().push(format!("write {name} {bytes}"));
+        }
+    }
+
+    let out = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let config = ExtractorConfig {
+        metrics: Some(recorder.clone()),
+        extract_to_disk: true,
+        output_directory: Some(out.path().to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let analyzer =
+        PdfAnalyzer::with_config(example_pdf("format_a3_withFiles.pdf"), config).unwrap();
+    analyzer.extract_embedded_files().unwrap();
+
+    // With the `parallel` feature, files are decoded in any order.
+    let mut events = recorder.0.lock().unwrap().clone();
+    events.sort();
+    assert_eq!(
+        events,
+        [
+            "decode Excel_embed1.xlsx 5902",
+            "decode Word_embed.docx 5308",
+            "discovery 2",
+            "load true",
+            "write Excel_embed1.xlsx 5902",
+            "write Word_embed.docx 5308",
+        ]
+    );
+}
+
+#[test]
+fn max_duration_aborts_with_timeout() {
+    use extractembedfilepdf::{ErrorCode, PdfAnalyzer};
+    use std::time::Duration;
+
+    let bytes = pdf_with_attachment("a.xml", b"<a/>", lopdf::Dictionary::new());
+    let zero = ExtractorConfig {
+        max_duration: Some(Duration::ZERO),
+        ..Default::default()
+    };
+
+    // Loading has a budget of its own.
+    assert!(matches!(
+        PdfAnalyzer::from_bytes_with_config(&bytes, zero.clone()),
+        Err(ExtractError::Timeout(_))
+    ));
+
+    let mut expired = analyzer_with_config(&bytes, ExtractorConfig::default());
+    expired.set_config(zero);
+    let error = expired.extract_embedded_files().unwrap_err();
+    assert!(matches!(error, ExtractError::Timeout(limit) if limit == Duration::ZERO));
+    assert_eq!(error.code(), ErrorCode::Timeout);
+    assert!(matches!(
+        expired.count_embedded_files(),
+        Err(ExtractError::Timeout(_))
+    ));
+
+    let generous = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_duration: Some(Duration::from_secs(60)),
+            ..Default::default()
+        },
+    );
+    assert_eq!(generous.extract_embedded_files().unwrap()[0].data, b"<a/>");
+}
+
+#[test]
+fn max_memory_bytes_bounds_document_and_results() {
+    use extractembedfilepdf::{ErrorCode, PdfAnalyzer};
+
+    let data = vec![b'x'; 4096];
+    let bytes = pdf_with_attachment("a.txt", &data, lopdf::Dictionary::new());
+    let budget = |max| ExtractorConfig {
+        max_memory_bytes: Some(max),
+        ..Default::default()
+    };
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &bytes).unwrap();
+    let error = PdfAnalyzer::with_config(file.path(), budget(bytes.len() - 1))
+        .err()
+        .unwrap();
+    assert!(matches!(error, ExtractError::MemoryLimit(_)));
+
+    let tight = analyzer_with_config(&bytes, budget(bytes.len() + 1024));
+    let error = tight.extract_embedded_files().unwrap_err();
+    assert!(matches!(error, ExtractError::MemoryLimit(max) if max == bytes.len() + 1024));
+    assert_eq!(error.code(), ErrorCode::MemoryLimit);
+
+    // Handles decode within the budget too, even without any filter.
+    let handles = tight.embedded_file_handles().unwrap();
+    let handle = &handles[0];
+    assert!(matches!(handle.read(), Err(ExtractError::MemoryLimit(_))));
+    assert!(matches!(handle.decode(), Err(ExtractError::MemoryLimit(_))));
+
+    let generous = analyzer_with_config(&bytes, budget(bytes.len() + 8192));
+    assert_eq!(generous.extract_embedded_files().unwrap()[0].data, data);
+    assert_eq!(generous.extract_embedded_files().unwrap().len(), 1);
+    let handles = generous.embedded_file_handles().unwrap();
+    assert_eq!(handles[0].read().unwrap(), data);
+}
+
+#[test]
+fn config_can_change_without_reloading() {
+    use extractembedfilepdf::{AttachmentSource, PdfAnalyzer};
+
+    let bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let mut analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert_eq!(analyzer.embedded_file_names().unwrap().len(), 2);
+
+    analyzer.set_config(ExtractorConfig {
+        max_embedded_file_size: Some(100),
+        ..Default::default()
+    });
+    let report = analyzer.extract_with_report().unwrap();
+    assert!(report.files.is_empty());
+    assert_eq!(report.warnings.len(), 2);
+
+    analyzer.config_mut().max_embedded_file_size = None;
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 2);
+
+    // A different set of discovery sources is searched afresh.
+    analyzer.config_mut().discovery_sources = Some(vec![AttachmentSource::Annotation]);
+    assert!(analyzer.embedded_file_names().unwrap().is_empty());
+}
+
+#[test]
+fn into_document_hands_over_the_parsed_pdf() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let names = analyzer.embedded_file_names().unwrap();
+    let pages = analyzer.document().get_pages().len();
+
+    let mut document = analyzer.into_document();
+    assert_eq!(document.get_pages().len(), pages);
+
+    let mut saved = Vec::new();
+    document.save_to(&mut saved).unwrap();
+    let reloaded = PdfAnalyzer::from_bytes(&saved).unwrap();
+    assert_eq!(reloaded.embedded_file_names().unwrap(), names);
+}
+
+#[test]
+fn analyzer_serves_concurrent_queries() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use std::sync::Arc;
+
+    let analyzer =
+        Arc::new(PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap());
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let analyzer = Arc::clone(&analyzer);
+            std::thread::spawn(move || {
+                let files = analyzer.extract_embedded_files().unwrap();
+                (
+                    analyzer.embedded_file_names().unwrap(),
+                    files.iter().map(|f| f.sha256_hex()).collect::<Vec<_>>(),
+                    analyzer.conformance_level(),
+                )
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(results[0].0.len(), 2);
+    assert!(results.iter().all(|result| *result == results[0]));
+}
+
+#[test]
+fn clones_share_the_document_but_not_the_config() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let original = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let mut clone = original.clone();
+    assert!(std::ptr::eq(original.document(), clone.document()));
+
+    clone.config_mut().max_embedded_file_size = Some(100);
+    assert!(clone.extract_with_report().unwrap().files.is_empty());
+    assert_eq!(original.extract_embedded_files().unwrap().len(), 2);
+
+    let worker = std::thread::spawn(move || clone.into_document().get_pages().len());
+    assert_eq!(
+        worker.join().unwrap(),
+        original.document().get_pages().len()
+    );
+}
+
+#[test]
+fn name_tree_walk_visits_leaves_in_order_and_survives_cycles() {
+    use extractembedfilepdf::name_tree;
+    use lopdf::{dictionary, Document, Object};
+
+    let mut doc = Document::with_version("1.7");
+    let root_id = doc.new_object_id();
+    let first = doc.add_object(dictionary! {
+        "Names" => vec![
+            Object::string_literal("a"), 1.into(),
+            Object::string_literal("b"), 2.into(),
+        ],
+    });
+    let second = doc.add_object(dictionary! {
+        "Names" => vec![Object::string_literal("c"), 3.into(), 4.into(), 5.into()],
+        "Kids" => vec![root_id.into()],
+    });
+    doc.objects.insert(
+        root_id,
+        Object::Dictionary(dictionary! { "Kids" => vec![first.into(), second.into()] }),
+    );
+
+    let entries: Vec<_> = name_tree::walk(&doc, root_id)
+        .into_iter()
+        .map(|(key, value)| (key.to_vec(), value.as_i64().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        [(b"a".to_vec(), 1), (b"b".to_vec(), 2), (b"c".to_vec(), 3)]
+    );
+}
+
+#[test]
+fn name_tree_discovery_visits_leaves_in_order_and_survives_cycles() {
+    use extractembedfilepdf::PdfAnalyzer;
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        let mut spec = |name: &str| -> Object {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, name.as_bytes().to_vec()));
+            doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(name),
+                "EF" => dictionary! { "F" => stream_id },
+            })
+            .into()
+        };
+        let (a, b, c) = (spec("a.txt"), spec("b.txt"), spec("c.txt"));
+
+        let root_id = doc.new_object_id();
+        let first = doc.add_object(dictionary! {
+            "Names" => vec![
+                Object::string_literal("a.txt"), a,
+                Object::string_literal("b.txt"), b,
+            ],
+        });
+        let second = doc.add_object(dictionary! {
+            "Names" => vec![Object::string_literal("c.txt"), c, 4.into(), 5.into()],
+            "Kids" => vec![root_id.into()],
+        });
+        doc.objects.insert(
+            root_id,
+            Object::Dictionary(dictionary! { "Kids" => vec![first.into(), second.into()] }),
+        );
+        catalog.set("Names", dictionary! { "EmbeddedFiles" => root_id });
+    });
+
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        analyzer.embedded_file_names().unwrap(),
+        ["a.txt", "b.txt", "c.txt"]
+    );
+}
+
+#[test]
+fn from_bytes_with_config_applies_the_config() {
+    use extractembedfilepdf::PdfAnalyzer;
+
+    let bytes = pdf_with_attachment("a.txt", &[b'x'; 64], lopdf::Dictionary::new());
+    let config = ExtractorConfig {
+        max_embedded_file_size: Some(16),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, config).unwrap();
+    assert_eq!(analyzer.config().max_embedded_file_size, Some(16));
+    assert!(analyzer.extract_embedded_files().is_err());
+
+    let too_small = ExtractorConfig {
+        max_memory_bytes: Some(bytes.len() - 1),
+        ..Default::default()
+    };
+    let error = PdfAnalyzer::from_bytes_with_config(&bytes, too_small)
+        .err()
+        .unwrap();
+    assert!(matches!(error, ExtractError::MemoryLimit(_)));
+}
+
+#[test]
+fn progress_callback_reports_every_file() {
+    use extractembedfilepdf::{PdfAnalyzer, Progress, ProgressCallback};
+    use std::sync::{Arc, Mutex};
+
+    let updates = Arc::new(Mutex::new(Vec::<Progress>::new()));
+    let sink = Arc::clone(&updates);
+    let config = ExtractorConfig {
+        progress: Some(ProgressCallback::new(move |p| sink.lock().unwrap().push(p))),
+        ..Default::default()
+    };
+    let analyzer =
+        PdfAnalyzer::with_config(example_pdf("format_a3_withFiles.pdf"), config).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+
+    // Updates arrive in order, each with the bytes of exactly the files
+    // counted so far; with `parallel`, either file may finish first.
+    let updates = updates.lock().unwrap().clone();
+    assert_eq!(
+        updates.iter().map(|p| p.current).collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(updates.iter().all(|p| p.total == 2));
+    let sizes: Vec<u64> = files.iter().map(|f| f.data.len() as u64).collect();
+    assert!(sizes.contains(&updates[0].bytes_decoded));
+    assert_eq!(updates[1].bytes_decoded, 5902 + 5308);
+}
+
+#[cfg(all(feature = "parallel", feature = "fs"))]
+#[test]
+fn parallel_progress_is_reported_as_files_are_decoded() {
+    use extractembedfilepdf::test_util::{Attachment, PdfBuilder};
+    use extractembedfilepdf::{PdfAnalyzer, ProgressCallback};
+    use std::sync::{Arc, Mutex};
+
+    let mut builder = PdfBuilder::new();
+    for i in 0..8 {
+        builder = builder.attachment(Attachment::new(format!("file{i}.txt"), "data"));
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_owned();
+    // Files are written one after another once all are decoded, so every
+    // update must arrive before the first file is on disk.
+    let on_disk = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&on_disk);
+    let config = ExtractorConfig {
+        extract_to_disk: true,
+        output_directory: Some(path.to_string_lossy().into_owned()),
+        progress: Some(ProgressCallback::new(move |_| {
+            let written = std::fs::read_dir(&path).unwrap().count();
+            sink.lock().unwrap().push(written);
+        })),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&builder.build(), config).unwrap();
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 8);
+    assert_eq!(*on_disk.lock().unwrap(), [0; 8]);
+}
+
+#[test]
+fn audit_sink_records_extraction_outcomes() {
+    use extractembedfilepdf::{AuditEvent, AuditSink, ErrorCode};
+    use lopdf::{dictionary, Object};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Trail(Mutex<Vec<AuditEvent>>);
+
+    impl AuditSink for Trail {
+        fn record(&self, event: AuditEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    let bytes = pdf_with_attachment(
+        "a.xml",
+        b"<a/>",
+        dictionary! { "CheckSum" => Object::string_literal(vec![0u8; 16]) },
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let trail = Arc::new(Trail::default());
+    let analyzer = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            extract_to_disk: true,
+            output_directory: Some(dir.path().to_string_lossy().into_owned()),
+            audit: Some(trail.clone()),
+            ..Default::default()
+        },
+    );
+    let file = analyzer.extract_embedded_file("a.xml").unwrap();
+
+    let events = trail.0.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        [
+            AuditEvent::ChecksumFailed {
+                name: "a.xml".into(),
+                declared: "0".repeat(32),
+                actual: file.md5_hex(),
+            },
+            AuditEvent::Extracted {
+                name: "a.xml".into(),
+                size: 4,
+                sha256: file.sha256_hex(),
+            },
+            AuditEvent::Written {
+                name: "a.xml".into(),
+                path: dir.path().join("a.xml"),
+                sha256: file.sha256_hex(),
+            },
+        ]
+    );
+
+    let trail = Arc::new(Trail::default());
+    let analyzer = analyzer_with_config(
+        &bytes,
+        ExtractorConfig {
+            max_embedded_file_size: Some(2),
+            audit: Some(trail.clone()),
+            ..Default::default()
+        },
+    );
+    assert!(analyzer.extract_embedded_files().is_err());
+    assert!(matches!(
+        trail.0.lock().unwrap().as_slice(),
+        [AuditEvent::Blocked { name, code: ErrorCode::SizeLimit, .. }] if name == "a.xml"
+    ));
+}
+
+#[test]
+fn content_scanner_policies() {
+    use extractembedfilepdf::{ContentScanner, ScanPolicy, ScanVerdict, WarningKind};
+    use std::sync::Arc;
+
+    struct FlagEvil;
+
+    impl ContentScanner for FlagEvil {
+        fn scan(&self, _name: &str, data: &[u8]) -> std::io::Result<ScanVerdict> {
+            Ok(if data == b"EVIL" {
+                ScanVerdict::Infected("Test.Evil".into())
+            } else {
+                ScanVerdict::Clean
+            })
+        }
+    }
+
+    let bytes = pdf_with_attachment("evil.bin", b"EVIL", lopdf::Dictionary::new());
+    let with_policy = |scan_policy| {
+        analyzer_with_config(
+            &bytes,
+            ExtractorConfig {
+                scanner: Some(Arc::new(FlagEvil)),
+                scan_policy,
+                ..Default::default()
+            },
+        )
+    };
+
+    let report = with_policy(ScanPolicy::Skip).extract_with_report().unwrap();
+    assert!(report.files.is_empty());
+    assert_eq!(report.warnings[0].kind, WarningKind::Skipped);
+    assert!(report.warnings[0].message.contains("Test.Evil"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let analyzer = with_policy(ScanPolicy::Quarantine(dir.path().to_path_buf()));
+    assert!(matches!(
+        analyzer.extract_embedded_file("evil.bin"),
+        Err(ExtractError::Infected { threat, .. }) if threat == "Test.Evil"
+    ));
+    assert_eq!(std::fs::read(dir.path().join("evil.bin")).unwrap(), b"EVIL");
+
+    // Quarantining under a taken name keeps the file already there.
+    assert!(analyzer.extract_embedded_file("evil.bin").is_err());
+    let evil = pdf_with_attachment("evil.bin", b"EVIL", lopdf::Dictionary::new());
+    let second = analyzer_with_config(
+        &evil,
+        ExtractorConfig {
+            scanner: Some(Arc::new(FlagEvil)),
+            scan_policy: ScanPolicy::Quarantine(dir.path().to_path_buf()),
+            ..Default::default()
+        },
+    );
+    assert!(second.extract_with_report().unwrap().files.is_empty());
+    for name in ["evil.bin", "evil-1.bin", "evil-2.bin"] {
+        assert_eq!(
+            std::fs::read(dir.path().join(name)).unwrap(),
+            b"EVIL",
+            "{name}"
+        );
+    }
+
+    // A hostile name is reduced to its last component, inside the directory.
+    let quarantine = dir.path().join("q").join("inner");
+    let escaping = pdf_with_attachment("../../escape.bin", b"EVIL", lopdf::Dictionary::new());
+    let analyzer = analyzer_with_config(
+        &escaping,
+        ExtractorConfig {
+            scanner: Some(Arc::new(FlagEvil)),
+            scan_policy: ScanPolicy::Quarantine(quarantine.clone()),
+            ..Default::default()
+        },
+    );
+    assert!(analyzer.extract_with_report().unwrap().files.is_empty());
+    let quarantined = std::fs::read(quarantine.join("escape.bin")).unwrap();
+    assert_eq!(quarantined, b"EVIL");
+    assert!(!dir.path().join("escape.bin").exists());
+
+    let error = with_policy(ScanPolicy::Fail)
+        .extract_with_report()
+        .unwrap_err();
+    assert_eq!(error.code().as_str(), "E_INFECTED");
+
+    let clean = pdf_with_attachment("a.xml", b"<a/>", lopdf::Dictionary::new());
+    let analyzer = analyzer_with_config(
+        &clean,
+        ExtractorConfig {
+            scanner: Some(Arc::new(FlagEvil)),
+            scan_policy: ScanPolicy::Fail,
+            ..Default::default()
+        },
+    );
+    assert_eq!(analyzer.extract_embedded_files().unwrap().len(), 1);
+}
+
+#[test]
+fn security_findings_report_active_content() {
+    use extractembedfilepdf::{FindingKind, PdfAnalyzer};
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        catalog.set(
+            "OpenAction",
+            dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert('hi');") },
+        );
+        let launch_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "A" => dictionary! { "S" => "Launch", "Win" => dictionary! { "F" => Object::string_literal("cmd.exe") } },
+        });
+        let submit_id = doc.add_object(dictionary! {
+            "S" => "SubmitForm",
+            "F" => dictionary! { "FS" => "URL", "F" => Object::string_literal("https://example.com/collect") },
+        });
+        catalog.set("Extra", vec![launch_id.into(), submit_id.into()]);
+    });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let findings = analyzer.security_findings();
+
+    let summary: Vec<_> = findings
+        .iter()
+        .map(|f| (f.kind, f.detail.as_deref()))
+        .collect();
+    assert_eq!(summary.len(), 4, "{summary:?}");
+    assert_eq!(summary[0], (FindingKind::OpenAction, Some("JavaScript")));
+    assert!(summary.contains(&(FindingKind::JavaScript, Some("app.alert('hi');"))));
+    assert!(summary.contains(&(FindingKind::Launch, Some("cmd.exe"))));
+    assert!(summary.contains(&(FindingKind::SubmitForm, Some("https://example.com/collect"))));
+
+    let clean = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    assert!(clean.security_findings().is_empty());
+
+    // Script streams are decoded for an excerpt only up to a small size.
+    let bytes = build_pdf(|doc, catalog| {
+        let mut short = Stream::new(dictionary! {}, b"app.alert('short');".to_vec());
+        short.compress().unwrap();
+        let mut long = Stream::new(dictionary! {}, vec![b' '; 1_000_000]);
+        long.compress().unwrap();
+        let short_id = doc.add_object(short);
+        let long_id = doc.add_object(long);
+        let actions = vec![
+            dictionary! { "S" => "JavaScript", "JS" => short_id }.into(),
+            dictionary! { "S" => "JavaScript", "JS" => long_id }.into(),
+        ];
+        catalog.set("Extra", Object::Array(actions));
+    });
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let details: Vec<_> = analyzer
+        .security_findings()
+        .into_iter()
+        .map(|f| f.detail)
+        .collect();
+    assert_eq!(details, [Some("app.alert('short');".to_string()), None]);
+}
+
+#[test]
+fn attachments_are_classified_by_risk() {
+    use extractembedfilepdf::{EmbeddedFile, PdfAnalyzer, RiskCategory};
+
+    let classify = |filename: &str, data: &[u8]| {
+        EmbeddedFile {
+            filename: filename.into(),
+            data: data.to_vec(),
+            metadata: Default::default(),
+        }
+        .risk_category()
+    };
+    assert_eq!(
+        classify("a.pdf", b"MZ\x90\x00\x03"),
+        Some(RiskCategory::Executable)
+    );
+    assert_eq!(
+        classify("a", b"\x7fELF\x02\x01"),
+        Some(RiskCategory::Executable)
+    );
+    assert_eq!(
+        classify("run", b"#!/bin/sh\nrm -rf ~"),
+        Some(RiskCategory::Script)
+    );
+    assert_eq!(classify("Run.VBS", b"MsgBox 1"), Some(RiskCategory::Script));
+    assert_eq!(
+        classify("a.zip", b"PK\x03\x04....a.txt"),
+        Some(RiskCategory::Archive)
+    );
+    assert_eq!(
+        classify("a.gz", b"\x1f\x8b\x08\x00"),
+        Some(RiskCategory::Archive)
+    );
+    assert_eq!(
+        classify(
+            "a.xlsm",
+            b"PK\x03\x04[Content_Types].xml...xl/vbaProject.bin"
+        ),
+        Some(RiskCategory::MacroDocument)
+    );
+    assert_eq!(classify("a.xml", b"<invoice/>"), None);
+
+    let analyzer = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    for file in analyzer.extract_embedded_files().unwrap() {
+        assert_eq!(file.risk_category(), None, "{}", file.filename);
+    }
+}
+
+#[test]
+fn risk_policy_flags_or_blocks_dangerous_files() {
+    use extractembedfilepdf::{RiskCategory, RiskPolicy, WarningKind};
+
+    let bytes = pdf_with_attachment("tool.exe", b"MZ\x90\x00\x03", lopdf::Dictionary::new());
+    let with_policy = |risk_policy| {
+        analyzer_with_config(
+            &bytes,
+            ExtractorConfig {
+                risk_policy,
+                ..Default::default()
+            },
+        )
+    };
+
+    let file = &with_policy(RiskPolicy::Allow)
+        .extract_embedded_files()
+        .unwrap()[0];
+    assert_eq!(file.metadata.risk, None);
+
+    let report = with_policy(RiskPolicy::Flag).extract_with_report().unwrap();
+    assert_eq!(
+        report.files[0].metadata.risk,
+        Some(RiskCategory::Executable)
+    );
+    assert_eq!(report.warnings[0].kind, WarningKind::Dangerous);
+
+    let analyzer = with_policy(RiskPolicy::Block);
+    assert!(matches!(
+        analyzer.extract_embedded_file("tool.exe"),
+        Err(ExtractError::DangerousFile {
+            category: RiskCategory::Executable,
+            ..
+        })
+    ));
+    let report = analyzer.extract_with_report().unwrap();
+    assert!(report.files.is_empty());
+    assert_eq!(report.warnings[0].kind, WarningKind::Skipped);
+}
+
+#[test]
+fn risk_report_combines_signals_into_one_score() {
+    use extractembedfilepdf::{
+        AttachmentSource, FindingKind, PdfAnalyzer, RiskCategory, RiskReport, RiskSignal,
+    };
+    use lopdf::{dictionary, Object, Stream};
+
+    let bytes = build_pdf(|doc, catalog| {
+        catalog.set(
+            "OpenAction",
+            dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("this.print();") },
+        );
+        let stream_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"MZ\x90\x00\x03".to_vec(),
+        ));
+        doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("invoice.pdf.exe"),
+            "EF" => dictionary! { "F" => stream_id },
+        });
+    });
+    let deep = ExtractorConfig {
+        discovery_sources: Some(vec![
+            AttachmentSource::NamesTree,
+            AttachmentSource::DeepScan,
+        ]),
+        ..Default::default()
+    };
+    let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, deep).unwrap();
+    let report = analyzer.risk_report().unwrap();
+
+    assert_eq!(
+        report.signals,
+        [
+            RiskSignal::ActiveContent(FindingKind::OpenAction),
+            RiskSignal::ActiveContent(FindingKind::JavaScript),
+            RiskSignal::HiddenAttachment("invoice.pdf.exe".into()),
+            RiskSignal::DangerousAttachment {
+                name: "invoice.pdf.exe".into(),
+                category: RiskCategory::Executable,
+            },
+        ]
+    );
+    assert_eq!(report.score, RiskReport::MAX_SCORE);
+
+    // The default discovery sources do not include a deep scan.
+    let report = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .risk_report()
+        .unwrap();
+    assert_eq!(report.signals.len(), 2);
+
+    // A flood of dangerous attachments is cut off at the cap.
+    let flood = build_pdf(|doc, catalog| {
+        let mut names = Vec::new();
+        for i in 0..RiskReport::MAX_SIGNALS + 20 {
+            let stream_id = doc.add_object(Stream::new(dictionary! {}, b"MZ\x90\x00".to_vec()));
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(format!("{i}.exe")),
+                "EF" => dictionary! { "F" => stream_id },
+            });
+            names.extend([Object::string_literal(format!("{i:04}")), spec_id.into()]);
+        }
+        catalog.set(
+            "Names",
+            dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } },
+        );
+    });
+    let report = PdfAnalyzer::from_bytes(&flood)
+        .unwrap()
+        .risk_report()
+        .unwrap();
+    assert_eq!(report.signals.len(), RiskReport::MAX_SIGNALS);
+    assert_eq!(report.score, RiskReport::MAX_SCORE);
+
+    let clean = PdfAnalyzer::from_path(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let report = clean.risk_report().unwrap();
+    assert_eq!((report.score, report.signals.len()), (0, 0));
+}
+
+#[test]
+fn encrypted_containers_are_flagged() {
+    use extractembedfilepdf::{ExtractionReport, PdfAnalyzer, RiskPolicy, RiskSignal};
+
+    let flag = ExtractorConfig {
+        risk_policy: RiskPolicy::Flag,
+        ..Default::default()
+    };
+    let encrypted = |name: &str, data: &[u8]| {
+        let bytes = pdf_with_attachment(name, data, lopdf::Dictionary::new());
+        let analyzer = PdfAnalyzer::from_bytes_with_config(&bytes, flag.clone()).unwrap();
+        analyzer.extract_embedded_files().unwrap()[0]
+            .metadata
+            .encrypted_container
+    };
+    assert!(encrypted(
+        "secret.zip",
+        b"PK\x03\x04\x14\x00\x01\x00\x08\x00"
+    ));
+    assert!(!encrypted(
+        "plain.zip",
+        b"PK\x03\x04\x14\x00\x00\x00\x08\x00"
+    ));
+    assert!(encrypted(
+        "locked.pdf",
+        b"%PDF-1.7\ntrailer << /Root 1 0 R /Encrypt 5 0 R >>"
+    ));
+    // Only the trailer counts, not text or keys elsewhere that mention it.
+    assert!(!encrypted(
+        "notes.pdf",
+        b"%PDF-1.7\n4 0 obj << /Length 9 >> stream\n(/Encrypt)\nendstream endobj\n\
+          trailer << /Root 1 0 R /Info << /Encrypt (no) >> /ID [<ab> <cd>] >>"
+    ));
+    assert!(encrypted(
+        "nested.pdf",
+        b"%PDF-1.7\ntrailer << /Info << /Title <ab>>> /Note (a >> b) /Encrypt 5 0 R >>"
+    ));
+
+    // An archive whose first entry is plain but a later one is encrypted.
+    let mut zip = ExtractionReport {
+        files: vec![make_file("a.txt", b"plain"), make_file("b.txt", b"secret")],
+        ..Default::default()
+    }
+    .to_zip_bytes()
+    .unwrap();
+    let central: Vec<_> = zip
+        .windows(4)
+        .enumerate()
+        .filter(|(_, w)| *w == b"PK\x01\x02")
+        .map(|(at, _)| at)
+        .collect();
+    zip[central[1] + 8] |= 1;
+    assert!(encrypted("mixed.zip", &zip));
+    zip[central[1] + 8] &= !1;
+    assert!(!encrypted("mixed.zip", &zip));
+    let mut ole = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1".to_vec();
+    ole.extend("EncryptedPackage".bytes().flat_map(|b| [b, 0]));
+    assert!(encrypted("locked.docx", &ole));
+
+    let path = example_pdf("format_a3_withFiles.pdf");
+    let analyzer = PdfAnalyzer::with_config(path, flag.clone()).unwrap();
+    for file in analyzer.extract_embedded_files().unwrap() {
+        assert!(!file.metadata.encrypted_container, "{}", file.filename);
+    }
+
+    // Without a risk policy that needs it, nothing is checked.
+    let bytes = pdf_with_attachment(
+        "secret.zip",
+        b"PK\x03\x04\x14\x00\x01\x00",
+        lopdf::Dictionary::new(),
+    );
+    let files = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    assert!(!files[0].metadata.encrypted_container);
+
+    let report = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .risk_report()
+        .unwrap();
+    assert!(report
+        .signals
+        .contains(&RiskSignal::EncryptedAttachment("secret.zip".into())));
+}
+
+#[test]
+fn extraction_manifest_records_source_and_files() {
+    use extractembedfilepdf::{ExtractionManifest, PdfAnalyzer};
+
+    let bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let files = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    let manifest = ExtractionManifest::new(&bytes, &files);
+
+    assert_eq!(manifest.source_sha256.len(), 64);
+    assert_eq!(manifest.files.len(), files.len());
+    assert_eq!(manifest.files[0].filename, "Excel_embed1.xlsx");
+    assert_eq!(manifest.files[0].size, 5902);
+    assert_eq!(manifest.files[0].sha256, files[0].sha256_hex());
+
+    let text = String::from_utf8(manifest.canonical_bytes()).unwrap();
+    assert!(text.starts_with("extractembedfilepdf-manifest/1\nsource "));
+    assert!(text.contains(" 5902 17:Excel_embed1.xlsx\n"));
+}
+
+#[test]
+fn checksum_verification_reports_missing_unexpected_and_mismatched() {
+    use extractembedfilepdf::{ExtractionManifest, PdfAnalyzer};
+
+    let bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let analyzer = PdfAnalyzer::from_bytes(&bytes).unwrap();
+    let files = analyzer.extract_embedded_files().unwrap();
+    let mut expected = ExtractionManifest::new(&bytes, &files).checksums();
+    assert!(analyzer.verify_checksums(&expected).unwrap().is_ok());
+
+    let word = expected.remove("Word_embed.docx").unwrap();
+    expected.insert("factur-x.xml".into(), word.clone());
+    expected.insert("Excel_embed1.xlsx".into(), word.to_uppercase());
+    let result = analyzer.verify_checksums(&expected).unwrap();
+    assert!(!result.is_ok());
+    assert_eq!(result.missing, ["factur-x.xml"]);
+    assert_eq!(result.unexpected, ["Word_embed.docx"]);
+    assert_eq!(result.mismatched[0].filename, "Excel_embed1.xlsx");
+    assert_eq!(result.mismatched[0].expected_sha256, word);
+    assert_eq!(result.mismatched[0].actual_sha256, files[0].sha256_hex());
+    assert!(result.verified.is_empty());
+}
+
+#[cfg(feature = "signing")]
+#[test]
+fn signed_manifest_verifies_only_unchanged_content() {
+    use ed25519_dalek::SigningKey;
+    use extractembedfilepdf::{ExtractionManifest, PdfAnalyzer};
+
+    let bytes = std::fs::read(example_pdf("format_a3_withFiles.pdf")).unwrap();
+    let files = PdfAnalyzer::from_bytes(&bytes)
+        .unwrap()
+        .extract_embedded_files()
+        .unwrap();
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let mut signed = ExtractionManifest::new(&bytes, &files).sign(&key);
+
+    assert!(signed.verify(&key.verifying_key()));
+    assert_eq!(signed.signature_hex().len(), 128);
+    let other = SigningKey::from_bytes(&[8; 32]);
+    assert!(!signed.verify(&other.verifying_key()));
+
+    signed.manifest.files[0].size += 1;
+    assert!(!signed.verify(&key.verifying_key()));
 }